@@ -10,6 +10,8 @@ use std::fs;
 use askama::Template;
 use chrono::{Utc, Duration};
 use crate::state::{AppState, BotState, BacktestState, MyDialogue};
+use crate::services::backtest_service;
+use crate::services::strategy_engine::StrategyConfig;
 use crate::i18n;
 
 /// Helper function to HTML escape
@@ -552,10 +554,10 @@ async fn update_html_report_with_ai_analysis(
     Ok(())
 }
 
-/// Calculate timerange string for Freqtrade CLI (format: YYYYMMDD-)
-fn calculate_timerange(range: &str) -> String {
+/// Start of a backtest time range ("1day", "1week", ...) ending now
+fn timerange_start(range: &str) -> chrono::DateTime<Utc> {
     let now = Utc::now();
-    let start_date = match range {
+    match range {
         "1day" => now - Duration::days(1),
         "1week" => now - Duration::days(7),
         "1month" => now - Duration::days(30),
@@ -564,10 +566,12 @@ fn calculate_timerange(range: &str) -> String {
         "9months" => now - Duration::days(270),
         "1year" => now - Duration::days(365),
         _ => now - Duration::days(7),
-    };
-    
-    // Format as YYYYMMDD- for Freqtrade CLI
-    format!("{}-", start_date.format("%Y%m%d"))
+    }
+}
+
+/// Calculate timerange string for Freqtrade CLI (format: YYYYMMDD-)
+fn calculate_timerange(range: &str) -> String {
+    format!("{}-", timerange_start(range).format("%Y%m%d"))
 }
 
 /// Parse strategy description to extract parameters (returns algorithm, buy, sell, timeframe, pair)
@@ -909,6 +913,459 @@ fn generate_strategy_file(
     Ok(filepath)
 }
 
+/// What a backtest ran on, shown in its result message and HTML report
+struct BacktestRun {
+    strategy_name: String,
+    exchange: String,
+    pair: String,
+    timeframe: String,
+    timerange: String,
+    /// Engine label ("Native" or "Freqtrade")
+    engine: &'static str,
+}
+
+/// Send the result of a backtest: summary message, HTML report (AI analysis is added in
+/// the background) and the report tables
+async fn send_backtest_report(
+    bot: &Bot,
+    state: &AppState,
+    user: Option<&shared::entity::users::Model>,
+    (chat_id, message_id): (ChatId, teloxide::types::MessageId),
+    run: &BacktestRun,
+    result: &shared::BacktestResult,
+    filepath: Option<&Path>,
+) -> Result<(), anyhow::Error> {
+    let locale = user
+        .and_then(|u| u.language.as_ref())
+        .map(|l| i18n::get_user_language(Some(l)))
+        .unwrap_or("en");
+    let BacktestRun { strategy_name, exchange, pair: freqtrade_pair, timeframe, timerange, engine } = run;
+    
+    tracing::info!(
+        "Backtest succeeded: strategy={} exchange={} trades={} profit_pct={:.2}",
+        strategy_name,
+        exchange,
+        result.trades,
+        result.profit_pct
+    );
+    
+    // Build result message with detailed report table
+    let mut result_msg = i18n::translate(
+        locale,
+        "backtest_result_header",
+        Some(&[
+            ("strategy", &escape_html(strategy_name)),
+            ("exchange", &escape_html(exchange)),
+            ("pair", freqtrade_pair),
+            ("timerange", timerange),
+            ("timeframe", timeframe),
+        ]),
+    );
+    
+    result_msg.push_str(&i18n::translate(locale, "backtest_engine_used", Some(&[("engine", engine)])));
+    result_msg.push_str("\n\n");
+    
+    // Add detailed results table
+    result_msg.push_str(&i18n::translate(locale, "backtest_report_title", None));
+    result_msg.push_str("\n");
+    result_msg.push_str("━━━━━━━━━━\n");
+    
+    // Total trades
+    result_msg.push_str(&i18n::translate(
+        locale,
+        "backtest_total_trades",
+        Some(&[("count", &result.trades.to_string())]),
+    ));
+    result_msg.push_str("\n");
+    
+    // Profit with color indication
+    let profit_key = if result.profit_pct >= 0.0 {
+        "backtest_profit"
+    } else {
+        "backtest_profit_negative"
+    };
+    result_msg.push_str(&i18n::translate(
+        locale,
+        profit_key,
+        Some(&[("profit", &format!("{:.2}", result.profit_pct))]),
+    ));
+    result_msg.push_str("\n");
+    
+    // Additional metrics if available
+    if let Some(win_rate) = result.win_rate {
+        result_msg.push_str(&i18n::translate(
+            locale,
+            "backtest_win_rate",
+            Some(&[("rate", &format!("{:.2}", win_rate))]),
+        ));
+        result_msg.push_str("\n");
+    }
+    if let Some(drawdown) = result.max_drawdown {
+        result_msg.push_str(&i18n::translate(
+            locale,
+            "backtest_max_drawdown",
+            Some(&[("drawdown", &format!("{:.2}", drawdown))]),
+        ));
+        result_msg.push_str("\n");
+    }
+    if let (Some(start), Some(final_bal)) = (result.starting_balance, result.final_balance) {
+        result_msg.push_str(&i18n::translate(
+            locale,
+            "backtest_balance",
+            Some(&[
+                ("start", &format!("{:.2}", start)),
+                ("final", &format!("{:.2}", final_bal)),
+            ]),
+        ));
+        result_msg.push_str("\n");
+    }
+    
+    result_msg.push_str("━━━━━━━━━━━━━━━\n\n");
+    
+    // Add timing info
+    result_msg.push_str(&i18n::translate(locale, "backtest_performance_title", None));
+    result_msg.push_str("\n");
+    if let Some(dl_time) = result.download_time_secs {
+        result_msg.push_str(&i18n::translate(
+            locale,
+            "backtest_data_download",
+            Some(&[("time", &dl_time.to_string())]),
+        ));
+    } else {
+        result_msg.push_str(&i18n::translate(locale, "backtest_data_download_skipped", None));
+    }
+    result_msg.push_str("\n");
+    result_msg.push_str(&i18n::translate(
+        locale,
+        "backtest_execution_time",
+        Some(&[("time", &result.backtest_time_secs.to_string())]),
+    ));
+    result_msg.push_str("\n");
+    
+    let total_time = result.download_time_secs.unwrap_or(0) + result.backtest_time_secs;
+    result_msg.push_str(&i18n::translate(
+        locale,
+        "backtest_total_time",
+        Some(&[("time", &total_time.to_string())]),
+    ));
+    result_msg.push_str("\n\n");
+    
+    if let Some(filepath) = filepath {
+        result_msg.push_str(&i18n::translate(
+            locale,
+            "backtest_strategy_file",
+            Some(&[("filepath", &filepath.display().to_string())]),
+        ));
+    }
+    
+    // Extract tables for HTML report and Telegram messages
+    let tables = if let Some(ref stdout) = result.stdout {
+        extract_all_tables(stdout)
+    } else {
+        Vec::new()
+    };
+    
+    // Generate HTML report if enabled - use config from AppState
+    let config = state.config.as_ref();
+    
+    // Get user fullname for HTML report
+    let user_fullname = user.as_ref().and_then(|u| u.fullname.clone());
+    
+    // Generate HTML report immediately (without AI analysis)
+    let (html_report_url, html_report_filepath) = if config.generate_html_reports {
+        match generate_html_report(
+            config,
+            strategy_name,
+            exchange,
+            freqtrade_pair,
+            timeframe,
+            timerange,
+            user_fullname.clone(),
+            result,
+            &tables,
+            None, // No AI analysis initially
+        ).await {
+            Ok(Some((url, filepath))) => {
+                tracing::info!("HTML report generated: {}", url);
+                (Some(url), Some(filepath))
+            }
+            Ok(None) => (None, None),
+            Err(e) => {
+                tracing::error!("Failed to generate HTML report: {}", e);
+                (None, None)
+            }
+        }
+    } else {
+        (None, None)
+    };
+    
+    // Spawn background task to generate AI analysis and update HTML report
+    tracing::info!(
+        "🔍 Checking Gemini analysis conditions: enable_gemini_analysis={}, html_report_filepath.is_some()={}, gemini_api_key.is_some()={}",
+        config.enable_gemini_analysis,
+        html_report_filepath.is_some(),
+        config.gemini_api_key.is_some()
+    );
+    
+    if config.enable_gemini_analysis && html_report_filepath.is_some() {
+        if let Some(ref api_key) = config.gemini_api_key {
+            tracing::info!("✅ Starting Gemini AI analysis background task...");
+            use crate::services::gemini::GeminiService;
+    
+            // Determine language based on user locale (before moving into task)
+            let locale = user.as_ref()
+                .and_then(|u| u.language.as_ref())
+                .map(|l| l.as_str())
+                .unwrap_or("en")
+                .to_string();
+    
+            // Clone data needed for background task
+            let gemini = GeminiService::with_config(
+                api_key.clone(),
+                config.gemini_model_name.clone(),
+                config.gemini_base_url.clone(),
+                config.gemini_timeout_secs,
+            );
+    
+            let filepath = html_report_filepath.clone().unwrap();
+            let strategy_name_clone = strategy_name.clone();
+            let exchange_clone = exchange.clone();
+            let freqtrade_pair_clone = freqtrade_pair.clone();
+            let timeframe_clone = timeframe.clone();
+            let timerange_clone = timerange.clone();
+            let user_fullname_clone = user_fullname.clone();
+            let result_clone = result.clone();
+            let tables_clone = tables.clone();
+    
+            // Spawn background task
+            tokio::spawn(async move {
+                tracing::info!("🔄 Starting background Gemini AI analysis...");
+    
+                let analysis_result = if locale.as_str() == "vi" {
+                gemini.analyze_backtest(
+                        &strategy_name_clone,
+                        &exchange_clone,
+                        &freqtrade_pair_clone,
+                        &timeframe_clone,
+                        &timerange_clone,
+                        result_clone.trades,
+                        result_clone.profit_pct,
+                        result_clone.win_rate,
+                        result_clone.max_drawdown,
+                        result_clone.starting_balance,
+                        result_clone.final_balance,
+                        &tables_clone,
+                        result_clone.stdout.as_deref(),
+                ).await
+            } else {
+                gemini.analyze_backtest_en(
+                        &strategy_name_clone,
+                        &exchange_clone,
+                        &freqtrade_pair_clone,
+                        &timeframe_clone,
+                        &timerange_clone,
+                        result_clone.trades,
+                        result_clone.profit_pct,
+                        result_clone.win_rate,
+                        result_clone.max_drawdown,
+                        result_clone.starting_balance,
+                        result_clone.final_balance,
+                        &tables_clone,
+                        result_clone.stdout.as_deref(),
+                ).await
+            };
+    
+            match analysis_result {
+                Ok(analysis) => {
+                        tracing::info!("✅ Gemini AI analysis generated successfully (length: {} chars)", analysis.len());
+    
+                        // Update HTML report with AI analysis
+                        if let Err(e) = update_html_report_with_ai_analysis(
+                            &filepath,
+                            &strategy_name_clone,
+                            &exchange_clone,
+                            &freqtrade_pair_clone,
+                            &timeframe_clone,
+                            &timerange_clone,
+                            user_fullname_clone,
+                            &result_clone,
+                            &tables_clone,
+                            analysis,
+                        ).await {
+                            tracing::error!("⚠️ Failed to update HTML report with AI analysis: {}", e);
+                        } else {
+                            tracing::info!("✅ HTML report successfully updated with AI analysis at: {}", filepath.display());
+                        }
+                }
+                Err(e) => {
+                    tracing::warn!("⚠️ Failed to generate Gemini AI analysis: {}", e);
+                }
+            }
+            });
+        } else {
+            tracing::warn!("⚠️ Gemini API key not configured, skipping AI analysis");
+        }
+    } else {
+        if !config.enable_gemini_analysis {
+            tracing::debug!("Gemini analysis is disabled in config");
+        }
+        if html_report_filepath.is_none() {
+            tracing::debug!("HTML report filepath is None, skipping AI analysis");
+        }
+    }
+    
+    tracing::info!("html_report_url: {:?}", html_report_url);
+    // Add HTML report link to summary message if available
+    if let Some(ref html_url) = html_report_url {
+        // Telegram HTML link format: <a href="URL">text</a>
+        // Đảm bảo URL không có spaces và format đúng
+        let clean_url = html_url.trim();
+    
+        result_msg.push_str("\n\n✅🌐 <b>View Full Report:</b>\n");
+        result_msg.push_str(&format!("<code>{}</code>\n", clean_url));
+    
+        // Warning nếu URL là localhost
+        if clean_url.contains("localhost") || clean_url.contains("127.0.0.1") {
+            tracing::warn!("URL contains localhost, Telegram users won't be able to access it. URL: {}", clean_url);
+            result_msg.push_str("\n⚠️ <i>Note: This is a localhost URL. Use a public domain for remote access.</i>");
+        }
+    
+        // Debug: log full message để kiểm tra
+        tracing::info!("Added HTML link to message. Full message length: {}, URL: {}", result_msg.len(), clean_url);
+        tracing::debug!("Link HTML format: <a href=\"{}\">Open HTML Report</a>", clean_url);
+    } else {
+        tracing::warn!("html_report_url is None, not adding link to message");
+    }
+    
+    bot.edit_message_text(
+        chat_id,
+        message_id,
+        result_msg
+    )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+    
+    // Log full output to console for debugging
+    tracing::info!("=== Backtest Full Output ===");
+    if let Some(ref stdout) = result.stdout {
+        tracing::info!("STDOUT:\n{}", stdout);
+    }
+    if let Some(ref stderr) = result.stderr {
+        if !stderr.is_empty() {
+            tracing::info!("STDERR:\n{}", stderr);
+        }
+    }
+    tracing::info!("=== End Backtest Output ===");
+    
+    // Extract and send all tables from backtest output
+    if !tables.is_empty() {
+        // Check if mobile-friendly format is enabled
+        let use_mobile_format = config.mobile_friendly_tables;
+    
+        // Send each table as a separate message for better readability
+        for (idx, (title, table_content)) in tables.iter().enumerate() {
+            let table_num = idx + 1;
+            let total_tables = tables.len();
+    
+            // Format title nicely with emoji based on content
+            let emoji = if title.contains("SUMMARY") {
+                "📊"
+            } else if title.contains("REPORT") {
+                "📈"
+            } else if title.contains("STATS") {
+                "📉"
+            } else {
+                "📋"
+            };
+    
+            let formatted_title = format!(
+                "{} <b>{}</b> ({}/{})\n",
+                emoji,
+                escape_html(title),
+                table_num,
+                total_tables
+            );
+    
+            // Format table content based on mobile-friendly flag
+            let formatted_content = if use_mobile_format {
+                format_table_mobile_friendly(table_content)
+            } else {
+                escape_html(table_content)
+            };
+    
+            // Split table content into chunks if needed
+            // Use larger chunk size for mobile format (it's more compact)
+            let chunk_size = if use_mobile_format { 3500 } else { 3200 };
+            let chunks = split_into_chunks(&formatted_content, chunk_size);
+    
+            for (chunk_idx, chunk) in chunks.iter().enumerate() {
+                let mut table_msg = if chunk_idx == 0 {
+                    formatted_title.clone()
+                } else {
+                    format!("{} <b>{} (cont.)</b>\n", emoji, escape_html(title))
+                };
+    
+                if use_mobile_format {
+                    // Mobile-friendly format: no <pre> tag, just formatted text
+                    table_msg.push_str(&chunk);
+                } else {
+                    // Original format: use <pre> for monospace
+                    table_msg.push_str("<pre>");
+                    table_msg.push_str(chunk);
+                    table_msg.push_str("</pre>");
+                }
+    
+                bot.send_message(chat_id, table_msg)
+                    .parse_mode(teloxide::types::ParseMode::Html)
+                    .await?;
+    
+                // Small delay between messages to avoid rate limiting
+                if chunk_idx < chunks.len() - 1 || idx < total_tables - 1 {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+                }
+            }
+        }
+    } else if let Some(ref stdout) = result.stdout {
+        // Fallback: if no tables found, send full output in chunks
+        let chunks = split_into_chunks(stdout, 3500);
+        let total_chunks = chunks.len();
+    
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let chunk_num = idx + 1;
+            let mut chunk_msg = format!(
+                "📋 <b>Backtest Output ({}/{})</b>\n\n",
+                chunk_num,
+                total_chunks
+            );
+            chunk_msg.push_str("<pre>");
+            chunk_msg.push_str(&escape_html(chunk));
+            chunk_msg.push_str("</pre>");
+    
+            bot.send_message(chat_id, chunk_msg)
+                .parse_mode(teloxide::types::ParseMode::Html)
+                .await?;
+    
+            if idx < chunks.len() - 1 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Truncate an error to fit a Telegram message
+/// Use character-based truncation to avoid UTF-8 boundary errors
+fn truncate_error(locale: &str, error: &str) -> String {
+    if error.chars().count() > 1500 {
+        let truncated: String = error.chars().take(1500).collect();
+        let truncated_msg = i18n::translate(locale, "backtest_error_truncated", None);
+        format!("{}{}", truncated, truncated_msg)
+    } else {
+        error.to_string()
+    }
+}
+
 /// Handler to start backtest wizard
 pub async fn handle_backtest(
     bot: Bot,
@@ -1136,6 +1593,47 @@ pub async fn handle_backtest_callback(
                     let timerange = data.replace("backtest_timerange_", "");
 
                     if let Ok(Some(BotState::Backtest(BacktestState::WaitingForTimeRange { strategy_id, strategy_name, exchange }))) = dialogue.get().await {
+                        // Show engine selection
+                        let engine_buttons = vec![
+                            vec![
+                                InlineKeyboardButton::callback(
+                                    i18n::get_button_text(locale, "backtest_engine_native"),
+                                    "backtest_engine_native"
+                                ),
+                            ],
+                            vec![
+                                InlineKeyboardButton::callback(
+                                    i18n::get_button_text(locale, "backtest_engine_freqtrade"),
+                                    "backtest_engine_freqtrade"
+                                ),
+                            ],
+                            vec![
+                                InlineKeyboardButton::callback(
+                                    i18n::get_button_text(locale, "backtest_button_cancel"),
+                                    "backtest_cancel"
+                                ),
+                            ],
+                        ];
+
+                        let timerange_selected = i18n::translate(locale, "backtest_timerange_selected", Some(&[("timerange", &timerange)]));
+                        bot.edit_message_text(chat_id, message_id, timerange_selected)
+                            .parse_mode(teloxide::types::ParseMode::Html)
+                            .reply_markup(teloxide::types::InlineKeyboardMarkup::new(engine_buttons))
+                            .await?;
+
+                        dialogue.update(BotState::Backtest(BacktestState::WaitingForEngine {
+                            strategy_id,
+                            strategy_name,
+                            exchange,
+                            timerange,
+                        })).await?;
+                    }
+                }
+                _ if data.starts_with("backtest_engine_") => {
+                    bot.answer_callback_query(q.id).await?;
+                    let engine = data.replace("backtest_engine_", "");
+
+                    if let Ok(Some(BotState::Backtest(BacktestState::WaitingForEngine { strategy_id, strategy_name, exchange, timerange }))) = dialogue.get().await {
                         // Get strategy details
                         let strategy = strategies::Entity::find_by_id(strategy_id)
                             .one(state.db.as_ref())
@@ -1190,6 +1688,60 @@ pub async fn handle_backtest_callback(
                             .parse_mode(teloxide::types::ParseMode::Html)
                             .await?;
 
+                        let run = BacktestRun {
+                            strategy_name: strategy_name.clone(),
+                            exchange: exchange.clone(),
+                            pair: freqtrade_pair.clone(),
+                            timeframe: timeframe.clone(),
+                            timerange: timerange.clone(),
+                            engine: if engine == "native" { "Native" } else { "Freqtrade" },
+                        };
+                        let checking_msg = i18n::translate(
+                            locale,
+                            "backtest_checking_data",
+                            Some(&[
+                                ("strategy", &escape_html(&strategy_name)),
+                                ("exchange", &escape_html(&exchange)),
+                                ("pair", &freqtrade_pair),
+                                ("timeframe", &timeframe),
+                                ("timerange", &timerange),
+                            ]),
+                        );
+
+                        if engine == "native" {
+                            // Strategies without a stored config run the config parsed from their description
+                            let config = strategy_config.unwrap_or_else(|| StrategyConfig {
+                                strategy_type: algorithm.clone(),
+                                parameters: serde_json::json!({}),
+                                pair: freqtrade_pair.clone(),
+                                timeframe: timeframe.clone(),
+                                buy_condition: buy_condition.clone(),
+                                sell_condition: sell_condition.clone(),
+                            });
+                            bot.edit_message_text(chat_id, message_id, checking_msg)
+                                .parse_mode(teloxide::types::ParseMode::Html)
+                                .await?;
+
+                            match backtest_service::run_backtest(&config, &exchange, timerange_start(&timerange)).await {
+                                Ok(result) => {
+                                    send_backtest_report(&bot, &state, user.as_ref(), (chat_id, message_id), &run, &result, None).await?;
+                                }
+                                Err(e) => {
+                                    tracing::error!("Native backtest of strategy {} failed: {}", strategy_id, e);
+                                    let failed_msg = i18n::translate(
+                                        locale,
+                                        "backtest_native_failed",
+                                        Some(&[("error", &escape_html(&truncate_error(locale, &e.to_string())))]),
+                                    );
+                                    bot.edit_message_text(chat_id, message_id, failed_msg)
+                                        .parse_mode(teloxide::types::ParseMode::Html)
+                                        .await?;
+                                }
+                            }
+                            dialogue.exit().await?;
+                            return Ok(());
+                        }
+
                         // Generate strategy file
                         // Use env var for Docker path, fallback to local path
                         let strategies_path_str = std::env::var("STRATEGIES_PATH")
@@ -1242,17 +1794,6 @@ pub async fn handle_backtest_callback(
                                 // }
 
                                 // Update message - checking/downloading data
-                                let checking_msg = i18n::translate(
-                                    locale,
-                                    "backtest_checking_data",
-                                    Some(&[
-                                        ("strategy", &escape_html(&strategy_name)),
-                                        ("exchange", &escape_html(&exchange)),
-                                        ("pair", &freqtrade_pair),
-                                        ("timeframe", &timeframe),
-                                        ("timerange", &timerange),
-                                    ]),
-                                );
                                 bot.edit_message_text(chat_id, message_id, checking_msg)
                                     .parse_mode(teloxide::types::ParseMode::Html)
                                     .await?;
@@ -1269,15 +1810,7 @@ pub async fn handle_backtest_callback(
                                     Ok(result) => Ok(result),
                                     Err(e) => {
                                         // Truncate error message to avoid Telegram MESSAGE_TOO_LONG error
-                                        // Use character-based truncation to avoid UTF-8 boundary errors
-                                        let error_str = e.to_string();
-                                        let truncated_error = if error_str.chars().count() > 1500 {
-                                            let truncated: String = error_str.chars().take(1500).collect();
-                                            let truncated_msg = i18n::translate(locale, "backtest_error_truncated", None);
-                                            format!("{}{}", truncated, truncated_msg)
-                                        } else {
-                                            error_str.clone()
-                                        };
+                                        let truncated_error = truncate_error(locale, &e.to_string());
                                         
                                         let failed_msg = i18n::translate(
                                             locale,
@@ -1294,416 +1827,8 @@ pub async fn handle_backtest_callback(
                                     }
                                 };
 
-                                match result {
-                                    Ok(result) => {
-                                        tracing::info!(
-                                            "Backtest succeeded: strategy={} exchange={} trades={} profit_pct={:.2}",
-                                            strategy_name,
-                                            exchange,
-                                            result.trades,
-                                            result.profit_pct
-                                        );
-                                        
-                                        // Build result message with detailed report table
-                                        let mut result_msg = i18n::translate(
-                                            locale,
-                                            "backtest_result_header",
-                                            Some(&[
-                                                ("strategy", &escape_html(&strategy_name)),
-                                                ("exchange", &escape_html(&exchange)),
-                                                ("pair", &freqtrade_pair),
-                                                ("timerange", &timerange),
-                                                ("timeframe", &timeframe),
-                                            ]),
-                                        );
-                                        
-                                        // Add detailed results table
-                                        result_msg.push_str(&i18n::translate(locale, "backtest_report_title", None));
-                                        result_msg.push_str("\n");
-                                        result_msg.push_str("━━━━━━━━━━\n");
-                                        
-                                        // Total trades
-                                        result_msg.push_str(&i18n::translate(
-                                            locale,
-                                            "backtest_total_trades",
-                                            Some(&[("count", &result.trades.to_string())]),
-                                        ));
-                                        result_msg.push_str("\n");
-                                        
-                                        // Profit with color indication
-                                        let profit_key = if result.profit_pct >= 0.0 {
-                                            "backtest_profit"
-                                        } else {
-                                            "backtest_profit_negative"
-                                        };
-                                        result_msg.push_str(&i18n::translate(
-                                            locale,
-                                            profit_key,
-                                            Some(&[("profit", &format!("{:.2}", result.profit_pct))]),
-                                        ));
-                                        result_msg.push_str("\n");
-                                        
-                                        // Additional metrics if available
-                                        if let Some(win_rate) = result.win_rate {
-                                            result_msg.push_str(&i18n::translate(
-                                                locale,
-                                                "backtest_win_rate",
-                                                Some(&[("rate", &format!("{:.2}", win_rate))]),
-                                            ));
-                                            result_msg.push_str("\n");
-                                        }
-                                        if let Some(drawdown) = result.max_drawdown {
-                                            result_msg.push_str(&i18n::translate(
-                                                locale,
-                                                "backtest_max_drawdown",
-                                                Some(&[("drawdown", &format!("{:.2}", drawdown))]),
-                                            ));
-                                            result_msg.push_str("\n");
-                                        }
-                                        if let (Some(start), Some(final_bal)) = (result.starting_balance, result.final_balance) {
-                                            result_msg.push_str(&i18n::translate(
-                                                locale,
-                                                "backtest_balance",
-                                                Some(&[
-                                                    ("start", &format!("{:.2}", start)),
-                                                    ("final", &format!("{:.2}", final_bal)),
-                                                ]),
-                                            ));
-                                            result_msg.push_str("\n");
-                                        }
-                                        
-                                        result_msg.push_str("━━━━━━━━━━━━━━━\n\n");
-                                        
-                                        // Add timing info
-                                        result_msg.push_str(&i18n::translate(locale, "backtest_performance_title", None));
-                                        result_msg.push_str("\n");
-                                        if let Some(dl_time) = result.download_time_secs {
-                                            result_msg.push_str(&i18n::translate(
-                                                locale,
-                                                "backtest_data_download",
-                                                Some(&[("time", &dl_time.to_string())]),
-                                            ));
-                                        } else {
-                                            result_msg.push_str(&i18n::translate(locale, "backtest_data_download_skipped", None));
-                                        }
-                                        result_msg.push_str("\n");
-                                        result_msg.push_str(&i18n::translate(
-                                            locale,
-                                            "backtest_execution_time",
-                                            Some(&[("time", &result.backtest_time_secs.to_string())]),
-                                        ));
-                                        result_msg.push_str("\n");
-                                        
-                                        let total_time = result.download_time_secs.unwrap_or(0) + result.backtest_time_secs;
-                                        result_msg.push_str(&i18n::translate(
-                                            locale,
-                                            "backtest_total_time",
-                                            Some(&[("time", &total_time.to_string())]),
-                                        ));
-                                        result_msg.push_str("\n\n");
-                                        
-                                        result_msg.push_str(&i18n::translate(
-                                            locale,
-                                            "backtest_strategy_file",
-                                            Some(&[("filepath", &filepath.display().to_string())]),
-                                        ));
-                                        
-                                        // Extract tables for HTML report and Telegram messages
-                                        let tables = if let Some(ref stdout) = result.stdout {
-                                            extract_all_tables(stdout)
-                                        } else {
-                                            Vec::new()
-                                        };
-                                        
-                                        // Generate HTML report if enabled - use config from AppState
-                                        let config = state.config.as_ref();
-                                        
-                                        // Get user fullname for HTML report
-                                        let user_fullname = user.as_ref().and_then(|u| u.fullname.clone());
-                                        
-                                        // Generate HTML report immediately (without AI analysis)
-                                        let (html_report_url, html_report_filepath) = if config.generate_html_reports {
-                                            match generate_html_report(
-                                                &config,
-                                                &strategy_name,
-                                                &exchange,
-                                                &freqtrade_pair,
-                                                &timeframe,
-                                                &timerange,
-                                                user_fullname.clone(),
-                                                &result,
-                                                &tables,
-                                                None, // No AI analysis initially
-                                            ).await {
-                                                Ok(Some((url, filepath))) => {
-                                                    tracing::info!("HTML report generated: {}", url);
-                                                    (Some(url), Some(filepath))
-                                                }
-                                                Ok(None) => (None, None),
-                                                Err(e) => {
-                                                    tracing::error!("Failed to generate HTML report: {}", e);
-                                                    (None, None)
-                                                }
-                                            }
-                                        } else {
-                                            (None, None)
-                                        };
-                                        
-                                        // Spawn background task to generate AI analysis and update HTML report
-                                        tracing::info!(
-                                            "🔍 Checking Gemini analysis conditions: enable_gemini_analysis={}, html_report_filepath.is_some()={}, gemini_api_key.is_some()={}",
-                                            config.enable_gemini_analysis,
-                                            html_report_filepath.is_some(),
-                                            config.gemini_api_key.is_some()
-                                        );
-                                        
-                                        if config.enable_gemini_analysis && html_report_filepath.is_some() {
-                                            if let Some(ref api_key) = config.gemini_api_key {
-                                                tracing::info!("✅ Starting Gemini AI analysis background task...");
-                                                use crate::services::gemini::GeminiService;
-                                                
-                                                // Determine language based on user locale (before moving into task)
-                                                let locale = user.as_ref()
-                                                    .and_then(|u| u.language.as_ref())
-                                                    .map(|l| l.as_str())
-                                                    .unwrap_or("en")
-                                                    .to_string();
-                                                
-                                                // Clone data needed for background task
-                                                let gemini = GeminiService::with_config(
-                                                    api_key.clone(),
-                                                    config.gemini_model_name.clone(),
-                                                    config.gemini_base_url.clone(),
-                                                    config.gemini_timeout_secs,
-                                                );
-                                                
-                                                let filepath = html_report_filepath.clone().unwrap();
-                                                let strategy_name_clone = strategy_name.clone();
-                                                let exchange_clone = exchange.clone();
-                                                let freqtrade_pair_clone = freqtrade_pair.clone();
-                                                let timeframe_clone = timeframe.clone();
-                                                let timerange_clone = timerange.clone();
-                                                let user_fullname_clone = user_fullname.clone();
-                                                let result_clone = result.clone();
-                                                let tables_clone = tables.clone();
-                                                
-                                                // Spawn background task
-                                                tokio::spawn(async move {
-                                                    tracing::info!("🔄 Starting background Gemini AI analysis...");
-                                                    
-                                                    let analysis_result = if locale.as_str() == "vi" {
-                                                    gemini.analyze_backtest(
-                                                            &strategy_name_clone,
-                                                            &exchange_clone,
-                                                            &freqtrade_pair_clone,
-                                                            &timeframe_clone,
-                                                            &timerange_clone,
-                                                            result_clone.trades,
-                                                            result_clone.profit_pct,
-                                                            result_clone.win_rate,
-                                                            result_clone.max_drawdown,
-                                                            result_clone.starting_balance,
-                                                            result_clone.final_balance,
-                                                            &tables_clone,
-                                                            result_clone.stdout.as_deref(),
-                                                    ).await
-                                                } else {
-                                                    gemini.analyze_backtest_en(
-                                                            &strategy_name_clone,
-                                                            &exchange_clone,
-                                                            &freqtrade_pair_clone,
-                                                            &timeframe_clone,
-                                                            &timerange_clone,
-                                                            result_clone.trades,
-                                                            result_clone.profit_pct,
-                                                            result_clone.win_rate,
-                                                            result_clone.max_drawdown,
-                                                            result_clone.starting_balance,
-                                                            result_clone.final_balance,
-                                                            &tables_clone,
-                                                            result_clone.stdout.as_deref(),
-                                                    ).await
-                                                };
-                                                
-                                                match analysis_result {
-                                                    Ok(analysis) => {
-                                                            tracing::info!("✅ Gemini AI analysis generated successfully (length: {} chars)", analysis.len());
-                                                            
-                                                            // Update HTML report with AI analysis
-                                                            if let Err(e) = update_html_report_with_ai_analysis(
-                                                                &filepath,
-                                                                &strategy_name_clone,
-                                                                &exchange_clone,
-                                                                &freqtrade_pair_clone,
-                                                                &timeframe_clone,
-                                                                &timerange_clone,
-                                                                user_fullname_clone,
-                                                                &result_clone,
-                                                                &tables_clone,
-                                                                analysis,
-                                                            ).await {
-                                                                tracing::error!("⚠️ Failed to update HTML report with AI analysis: {}", e);
-                                                            } else {
-                                                                tracing::info!("✅ HTML report successfully updated with AI analysis at: {}", filepath.display());
-                                                            }
-                                                    }
-                                                    Err(e) => {
-                                                        tracing::warn!("⚠️ Failed to generate Gemini AI analysis: {}", e);
-                                                    }
-                                                }
-                                                });
-                                            } else {
-                                                tracing::warn!("⚠️ Gemini API key not configured, skipping AI analysis");
-                                            }
-                                        } else {
-                                            if !config.enable_gemini_analysis {
-                                                tracing::debug!("Gemini analysis is disabled in config");
-                                            }
-                                            if html_report_filepath.is_none() {
-                                                tracing::debug!("HTML report filepath is None, skipping AI analysis");
-                                            }
-                                        }
-                                        
-                                        tracing::info!("html_report_url: {:?}", html_report_url);
-                                        // Add HTML report link to summary message if available
-                                        if let Some(ref html_url) = html_report_url {
-                                            // Telegram HTML link format: <a href="URL">text</a>
-                                            // Đảm bảo URL không có spaces và format đúng
-                                            let clean_url = html_url.trim();
-                                            
-                                            result_msg.push_str("\n\n✅🌐 <b>View Full Report:</b>\n");
-                                            result_msg.push_str(&format!("<code>{}</code>\n", clean_url));
-                                            
-                                            // Warning nếu URL là localhost
-                                            if clean_url.contains("localhost") || clean_url.contains("127.0.0.1") {
-                                                tracing::warn!("URL contains localhost, Telegram users won't be able to access it. URL: {}", clean_url);
-                                                result_msg.push_str("\n⚠️ <i>Note: This is a localhost URL. Use a public domain for remote access.</i>");
-                                            }
-                                            
-                                            // Debug: log full message để kiểm tra
-                                            tracing::info!("Added HTML link to message. Full message length: {}, URL: {}", result_msg.len(), clean_url);
-                                            tracing::debug!("Link HTML format: <a href=\"{}\">Open HTML Report</a>", clean_url);
-                                        } else {
-                                            tracing::warn!("html_report_url is None, not adding link to message");
-                                        }
-                                        
-                                        bot.edit_message_text(
-                                            chat_id,
-                                            message_id,
-                                            result_msg
-                                        )
-                                            .parse_mode(teloxide::types::ParseMode::Html)
-                                            .await?;
-                                        
-                                        // Log full output to console for debugging
-                                        tracing::info!("=== Backtest Full Output ===");
-                                        if let Some(ref stdout) = result.stdout {
-                                            tracing::info!("STDOUT:\n{}", stdout);
-                                        }
-                                        if let Some(ref stderr) = result.stderr {
-                                            if !stderr.is_empty() {
-                                                tracing::info!("STDERR:\n{}", stderr);
-                                            }
-                                        }
-                                        tracing::info!("=== End Backtest Output ===");
-                                        
-                                        // Extract and send all tables from backtest output
-                                        if !tables.is_empty() {
-                                            // Check if mobile-friendly format is enabled
-                                            let use_mobile_format = config.mobile_friendly_tables;
-                                            
-                                            // Send each table as a separate message for better readability
-                                            for (idx, (title, table_content)) in tables.iter().enumerate() {
-                                                let table_num = idx + 1;
-                                                let total_tables = tables.len();
-                                                
-                                                // Format title nicely with emoji based on content
-                                                let emoji = if title.contains("SUMMARY") {
-                                                    "📊"
-                                                } else if title.contains("REPORT") {
-                                                    "📈"
-                                                } else if title.contains("STATS") {
-                                                    "📉"
-                                                } else {
-                                                    "📋"
-                                                };
-                                                
-                                                let formatted_title = format!(
-                                                    "{} <b>{}</b> ({}/{})\n",
-                                                    emoji,
-                                                    escape_html(title),
-                                                    table_num,
-                                                    total_tables
-                                                );
-                                                
-                                                // Format table content based on mobile-friendly flag
-                                                let formatted_content = if use_mobile_format {
-                                                    format_table_mobile_friendly(table_content)
-                                                } else {
-                                                    escape_html(table_content)
-                                                };
-                                                
-                                                // Split table content into chunks if needed
-                                                // Use larger chunk size for mobile format (it's more compact)
-                                                let chunk_size = if use_mobile_format { 3500 } else { 3200 };
-                                                let chunks = split_into_chunks(&formatted_content, chunk_size);
-                                                
-                                                for (chunk_idx, chunk) in chunks.iter().enumerate() {
-                                                    let mut table_msg = if chunk_idx == 0 {
-                                                        formatted_title.clone()
-                                                    } else {
-                                                        format!("{} <b>{} (cont.)</b>\n", emoji, escape_html(title))
-                                                    };
-                                                    
-                                                    if use_mobile_format {
-                                                        // Mobile-friendly format: no <pre> tag, just formatted text
-                                                        table_msg.push_str(&chunk);
-                                                    } else {
-                                                        // Original format: use <pre> for monospace
-                                                        table_msg.push_str("<pre>");
-                                                        table_msg.push_str(chunk);
-                                                        table_msg.push_str("</pre>");
-                                                    }
-                                                    
-                                                    bot.send_message(chat_id, table_msg)
-                                                        .parse_mode(teloxide::types::ParseMode::Html)
-                                                        .await?;
-                                                    
-                                                    // Small delay between messages to avoid rate limiting
-                                                    if chunk_idx < chunks.len() - 1 || idx < total_tables - 1 {
-                                                        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-                                                    }
-                                                }
-                                            }
-                                        } else if let Some(ref stdout) = result.stdout {
-                                            // Fallback: if no tables found, send full output in chunks
-                                            let chunks = split_into_chunks(stdout, 3500);
-                                            let total_chunks = chunks.len();
-                                            
-                                            for (idx, chunk) in chunks.iter().enumerate() {
-                                                let chunk_num = idx + 1;
-                                                let mut chunk_msg = format!(
-                                                    "📋 <b>Backtest Output ({}/{})</b>\n\n",
-                                                    chunk_num,
-                                                    total_chunks
-                                                );
-                                                chunk_msg.push_str("<pre>");
-                                                chunk_msg.push_str(&escape_html(chunk));
-                                                chunk_msg.push_str("</pre>");
-                                                
-                                                bot.send_message(chat_id, chunk_msg)
-                                                    .parse_mode(teloxide::types::ParseMode::Html)
-                                                    .await?;
-                                                
-                                                if idx < chunks.len() - 1 {
-                                                    tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-                                                }
-                                            }
-                                        }
-                                    }
-                                    Err(_) => {
-                                        // Error already handled above
-                                    }
+                                if let Ok(result) = result {
+                                    send_backtest_report(&bot, &state, user.as_ref(), (chat_id, message_id), &run, &result, Some(&filepath)).await?;
                                 }
 
                                 dialogue.exit().await?;
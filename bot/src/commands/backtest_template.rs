@@ -4,6 +4,7 @@
 //! using the indicator config registry pattern.
 
 use serde_json::Value;
+use freqtrade_rs::strategy::Condition;
 use crate::services::strategy_engine::indicator_configs::IndicatorConfigRegistry;
 
 /// Template data for generating Python strategy file
@@ -27,7 +28,10 @@ pub struct StrategyTemplateData {
 }
 
 impl StrategyTemplateData {
-    /// Create template data from algorithm and conditions using indicator config registry
+    /// Create template data from algorithm and conditions
+    ///
+    /// Conditions written in the expression language are translated as a whole; others
+    /// fall back to the indicator config registry of the algorithm.
     pub fn from_config(
         algorithm: &str,
         buy_condition: &str,
@@ -36,6 +40,10 @@ impl StrategyTemplateData {
         parameters: &Value,
         strategy_name: &str,
     ) -> Self {
+        if algorithm.eq_ignore_ascii_case("MIX") {
+            return Self::from_mix(timeframe, parameters, strategy_name);
+        }
+        
         let registry = IndicatorConfigRegistry::new();
         
         // Find the indicator config for this algorithm
@@ -44,8 +52,22 @@ impl StrategyTemplateData {
         let mut indicator_code_blocks = Vec::new();
         let mut entry_conditions = Vec::new();
         let mut exit_conditions = Vec::new();
+        let mut startup_candle_count = 200;
         
-        if let Some(config) = indicator_config {
+        let expressions = Condition::parse(buy_condition, parameters)
+            .and_then(|buy| Ok((buy, Condition::parse(sell_condition, parameters)?)));
+        if let Ok((buy, sell)) = expressions {
+            let mut calls = buy.indicators();
+            for call in sell.indicators() {
+                if !calls.contains(&call) {
+                    calls.push(call);
+                }
+            }
+            indicator_code_blocks.extend(calls.iter().map(|call| call.to_python()));
+            entry_conditions.push(buy.to_python());
+            exit_conditions.push(sell.to_python());
+            startup_candle_count = startup_candle_count.max(buy.lookback().max(sell.lookback()) as i32);
+        } else if let Some(config) = indicator_config {
             // Extract parameters
             let params = config.extract_parameters(parameters);
             
@@ -70,6 +92,17 @@ impl StrategyTemplateData {
             }
         }
         
+        Self {
+            startup_candle_count,
+            indicator_code_blocks,
+            entry_conditions,
+            exit_conditions,
+            ..Self::with_defaults(timeframe, strategy_name)
+        }
+    }
+    
+    /// Template data with the default ROI and stop loss and no indicators or conditions
+    fn with_defaults(timeframe: &str, strategy_name: &str) -> Self {
         Self {
             strategy_name: strategy_name.to_string(),
             minimal_roi_60: "0.05".to_string(),
@@ -81,9 +114,95 @@ impl StrategyTemplateData {
             trailing_stop_offset: "0.01".to_string(),
             timeframe: timeframe.to_string(),
             startup_candle_count: 200,
+            indicator_code_blocks: Vec::new(),
+            entry_conditions: Vec::new(),
+            exit_conditions: Vec::new(),
+        }
+    }
+    
+    /// Create template data of a Custom Mix from its member configs
+    ///
+    /// Each member's entry and exit become 0 / 1 columns (`mix_entry_0`, `mix_exit_0`, ...)
+    /// combined by the mix mode. Members count with confidence 1, and sequence mode
+    /// checks the earlier members within the window without their order.
+    fn from_mix(timeframe: &str, parameters: &Value, strategy_name: &str) -> Self {
+        let members = parameters.get("strategies")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let mode = parameters.get("mode").and_then(|v| v.as_str()).unwrap_or("unanimous");
+        let threshold = parameters.get("threshold").and_then(|v| v.as_f64()).unwrap_or(0.5);
+        let confirm_window = parameters.get("confirm_window").and_then(|v| v.as_u64()).unwrap_or(3);
+        
+        let mut indicator_code_blocks: Vec<String> = Vec::new();
+        let mut signal_columns = Vec::new();
+        let mut startup_candle_count = 200;
+        let mut weights = Vec::new();
+        
+        for (i, member) in members.iter().enumerate() {
+            let field = |key: &str| member.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+            let data = Self::from_config(
+                field("strategy_type"),
+                field("buy_condition"),
+                field("sell_condition"),
+                timeframe,
+                member.get("parameters").unwrap_or(&Value::Null),
+                strategy_name,
+            );
+            for block in data.indicator_code_blocks {
+                if !indicator_code_blocks.contains(&block) {
+                    indicator_code_blocks.push(block);
+                }
+            }
+            for (column, conditions) in [("entry", &data.entry_conditions), ("exit", &data.exit_conditions)] {
+                let signal = if conditions.is_empty() {
+                    "0".to_string()
+                } else {
+                    let conditions: Vec<String> = conditions.iter().map(|c| format!("({})", c)).collect();
+                    format!("({}).astype(int)", conditions.join(" & "))
+                };
+                signal_columns.push(format!("dataframe['mix_{}_{}'] = {}", column, i, signal));
+            }
+            startup_candle_count = startup_candle_count.max(data.startup_candle_count);
+            weights.push(member.get("weight").and_then(|v| v.as_f64()).unwrap_or(1.0));
+        }
+        indicator_code_blocks.extend(signal_columns);
+        
+        let combined = |column: &str| {
+            let signal = |i: usize| format!("dataframe['mix_{}_{}']", column, i);
+            let count = members.len();
+            let sum = (0..count).map(signal).collect::<Vec<_>>().join(" + ");
+            match mode {
+                "majority" => format!("(({}) * 2 > {})", sum, count),
+                "weighted" => {
+                    let weighted = (0..count)
+                        .map(|i| format!("{} * {}", weights[i], signal(i)))
+                        .collect::<Vec<_>>()
+                        .join(" + ");
+                    format!("(({}) >= {})", weighted, threshold * weights.iter().sum::<f64>())
+                }
+                "sequence" if count > 1 => {
+                    let mut conditions: Vec<String> = (0..count - 1)
+                        .map(|i| format!("({}.rolling({}, min_periods=1).max() == 1)", signal(i), confirm_window + 1))
+                        .collect();
+                    conditions.push(format!("({} == 1)", signal(count - 1)));
+                    format!("({})", conditions.join(" & "))
+                }
+                _ => format!("(({}) == {})", sum, count),
+            }
+        };
+        let (entry_conditions, exit_conditions) = if members.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            (vec![combined("entry")], vec![combined("exit")])
+        };
+        
+        Self {
             indicator_code_blocks,
             entry_conditions,
             exit_conditions,
+            startup_candle_count,
+            ..Self::with_defaults(timeframe, strategy_name)
         }
     }
     
@@ -110,6 +229,7 @@ impl StrategyTemplateData {
         
         format!(
             r#"import talib.abstract as ta
+import freqtrade.vendor.qtpylib.indicators as qtpylib
 import pandas as pd
 from functools import reduce
 from pandas import DataFrame
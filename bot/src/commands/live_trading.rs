@@ -8,7 +8,8 @@ use teloxide::types::InlineKeyboardButton;
 use sea_orm::{EntityTrait, ActiveValue, ColumnTrait, QueryFilter, QueryOrder, Order};
 use crate::state::{AppState, MyDialogue, BotState, LiveTradingState};
 use crate::i18n;
-use shared::entity::{users, exchange_tokens, live_trading_sessions};
+use crate::services::session_service;
+use shared::entity::{users, exchange_tokens, live_trading_sessions, live_trading_signals};
 use chrono::Utc;
 
 /// Handler for /livetrading command
@@ -32,16 +33,12 @@ pub async fn handle_live_trading(
         .map(|l| i18n::get_user_language(Some(l)))
         .unwrap_or("en");
     
-    // Check if user already has an active live trading session
-    let active_session = live_trading_sessions::Entity::find()
-        .filter(live_trading_sessions::Column::UserId.eq(telegram_id))
-        .filter(live_trading_sessions::Column::Status.eq("active"))
-        .one(state.db.as_ref())
-        .await?;
-    
-    if active_session.is_some() {
-        let msg_text = i18n::translate(locale, "trading_already_active", None);
-        bot.send_message(msg.chat.id, msg_text).await?;
+    // Check if user can run another live trading session on their tier
+    if let Some(limit) = session_service::session_limit_reached(state.db.as_ref(), telegram_id).await? {
+        let msg_text = i18n::translate(locale, "trading_session_limit", Some(&[("limit", &limit.to_string())]));
+        bot.send_message(msg.chat.id, msg_text)
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await?;
         return Ok(());
     }
     
@@ -836,45 +833,16 @@ async fn start_live_trading_with_exchange(
         strategy_config.strategy_type
     );
     
-    // Start strategy executor (registers user's strategy)
-    state.strategy_executor.start_trading(user_id, strategy_config.clone(), Some(token.exchange.clone())).await?;
-    
-    // Save live trading session to database
-    let session = live_trading_sessions::ActiveModel {
-        user_id: ActiveValue::Set(user_id),
-        strategy_id: ActiveValue::Set(strategy_id),
-        strategy_name: ActiveValue::Set(Some(strategy_config.strategy_type.clone())),
-        exchange: ActiveValue::Set(token.exchange.clone()),
-        pair: ActiveValue::Set(strategy_config.pair.clone()),
-        timeframe: ActiveValue::Set(Some(strategy_config.timeframe.clone())),
-        status: ActiveValue::Set("active".to_string()),
-        started_at: ActiveValue::Set(Some(Utc::now())),
-        stopped_at: ActiveValue::NotSet,
-        created_at: ActiveValue::Set(Some(Utc::now())),
-        updated_at: ActiveValue::Set(Some(Utc::now())),
-        ..Default::default()
-    };
-    
-    let session_result = live_trading_sessions::Entity::insert(session)
-        .exec(state.db.as_ref())
-        .await?;
-    
-    tracing::info!("✅ Created live trading session {} for user {} with strategy {}", 
-        session_result.last_insert_id, user_id, 
-        strategy_id.map(|id| id.to_string()).unwrap_or_else(|| "N/A".to_string()));
-    
-    // Start user-specific trading service (monitors market and sends signals)
-    use crate::services::trading_signal::start_user_trading_service;
-    
-    start_user_trading_service(
-        state,
+    // Create the session and start its strategy and trading services
+    session_service::start_session(
+        &state,
         bot,
         user_id,
         user_chat_id,
-        strategy_config.clone(),
-        token.exchange.clone(),
-        strategy_config.pair.clone(),
-    );
+        &token.exchange,
+        strategy_config,
+        strategy_id,
+    ).await?;
     Ok(())
 }
 
@@ -899,54 +867,112 @@ pub async fn handle_my_trading(
         .unwrap_or("en");
     
     // Get all active live trading sessions from database
-    let active_sessions = live_trading_sessions::Entity::find()
-        .filter(live_trading_sessions::Column::UserId.eq(telegram_id))
-        .filter(live_trading_sessions::Column::Status.eq("active"))
-        .order_by(live_trading_sessions::Column::StartedAt, Order::Desc)
-        .all(state.db.as_ref())
-        .await?;
+    let active_sessions = session_service::active_sessions(state.db.as_ref(), telegram_id).await?;
     
     if !active_sessions.is_empty() {
-        // Get the first (most recent) session for display
-        let session = &active_sessions[0];
-        let exchange_name = match session.exchange.as_str() {
-            "binance" => "🔵 Binance",
-            "okx" => "🟢 OKX",
-            _ => &session.exchange,
-        };
-        
-        let strategy_name = session.strategy_name.as_ref()
-            .unwrap_or(&format!("Strategy #{}", session.strategy_id.map(|id| id.to_string()).unwrap_or_else(|| "N/A".to_string())))
-            .clone();
-        
-        let pair = session.pair.clone();
-        let timeframe = session.timeframe.as_ref()
-            .map(|t| t.clone())
-            .unwrap_or_else(|| "N/A".to_string());
-        
-        let started_at = session.started_at
-            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-            .unwrap_or_else(|| "N/A".to_string());
-        
-        let status_msg = format!(
-            "{}\n{}\n\n{}\n{}\n{}\n{}\n{}\n\n{}",
+        let limit = user.as_ref()
+            .map(session_service::max_sessions)
+            .unwrap_or(session_service::FREE_MAX_SESSIONS);
+        let mut status_msg = format!(
+            "{}\n{}\n{}\n",
             i18n::translate(locale, "mytrading_status_title", None),
             i18n::translate(locale, "mytrading_status_running", None),
-            i18n::translate(locale, "mytrading_strategy", Some(&[("strategy", &strategy_name)])),
-            i18n::translate(locale, "mytrading_pair", Some(&[("pair", &pair)])),
-            i18n::translate(locale, "mytrading_timeframe", Some(&[("timeframe", &timeframe)])),
-            i18n::translate(locale, "mytrading_exchange", Some(&[("exchange", exchange_name)])),
-            i18n::translate(locale, "mytrading_started", Some(&[("started_at", &started_at)])),
-            i18n::translate(locale, "mytrading_monitoring", None),
+            i18n::translate(locale, "mytrading_sessions", Some(&[
+                ("count", &active_sessions.len().to_string()),
+                ("limit", &limit.to_string()),
+            ])),
         );
+        let mut buttons = Vec::new();
         
-        // Create stop button - will show session list if multiple sessions
-        let stop_button = InlineKeyboardButton::callback(
-            i18n::get_button_text(locale, "mytrading_stop_button"),
-            format!("stop_live_trading_{}", telegram_id)
-        );
-        
-        let buttons = vec![vec![stop_button]];
+        // Oldest first, so session numbers read in the order they were started
+        for session in active_sessions.iter().rev() {
+            let exchange_name = match session.exchange.as_str() {
+                "binance" => "🔵 Binance",
+                "okx" => "🟢 OKX",
+                _ => &session.exchange,
+            };
+            
+            let strategy_name = session.strategy_name.as_ref()
+                .unwrap_or(&format!("Strategy #{}", session.strategy_id.map(|id| id.to_string()).unwrap_or_else(|| "N/A".to_string())))
+                .clone();
+            
+            let pair = session.pair.clone();
+            let timeframe = session.timeframe.clone()
+                .unwrap_or_else(|| "N/A".to_string());
+            
+            let started_at = session.started_at
+                .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "N/A".to_string());
+            
+            // Strategies still collecting their lookback cannot emit signals yet
+            let warmup_progress = state.strategy_executor.get_warmup_progress(session.id).await;
+            let warming_up: Vec<String> = warmup_progress.iter()
+                .filter(|(_, progress)| !progress.is_complete())
+                .map(|(pair, progress)| i18n::translate(locale, "mytrading_warmup", Some(&[
+                    ("pair", pair.as_str()),
+                    ("candles", &progress.candles.to_string()),
+                    ("required", &progress.required.to_string()),
+                    ("percent", &format!("{:.0}", progress.percent())),
+                ])))
+                .collect();
+            let warmup_text = if warming_up.is_empty() {
+                i18n::translate(locale, "mytrading_warmup_complete", None)
+            } else {
+                warming_up.join("\n")
+            };
+            
+            status_msg.push_str(&format!(
+                "\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n",
+                i18n::translate(locale, "mytrading_session_title", Some(&[("id", &session.id.to_string())])),
+                i18n::translate(locale, "mytrading_strategy", Some(&[("strategy", &strategy_name)])),
+                i18n::translate(locale, "mytrading_pair", Some(&[("pair", &pair)])),
+                i18n::translate(locale, "mytrading_timeframe", Some(&[("timeframe", &timeframe)])),
+                i18n::translate(locale, "mytrading_exchange", Some(&[("exchange", exchange_name)])),
+                i18n::translate(locale, "mytrading_started", Some(&[("started_at", &started_at)])),
+                warmup_text,
+            ));
+            if let Some(paused_at) = session.paused_at {
+                status_msg.push_str(&format!("{}\n", i18n::translate(locale, "mytrading_paused", Some(&[
+                    ("paused_at", &paused_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+                ]))));
+            }
+            // Last hot reload recorded on the session
+            let last_reload = session.config_changes.as_deref()
+                .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(json).ok())
+                .and_then(|history| history.last().cloned());
+            if let Some(reload) = last_reload {
+                let at = reload["at"].as_str()
+                    .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                    .map(|at| at.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| "N/A".to_string());
+                let changes = reload["changes"].as_object()
+                    .map(|changes| changes.keys().cloned().collect::<Vec<_>>().join(", "))
+                    .unwrap_or_default();
+                status_msg.push_str(&format!("{}\n", i18n::translate(locale, "mytrading_last_reload", Some(&[
+                    ("at", &at),
+                    ("changes", &changes),
+                ]))));
+            }
+            
+            // Each session has its own pause / resume, reload and stop buttons
+            let (pause_key, pause_data) = if session.paused_at.is_some() {
+                ("mytrading_resume_session", format!("resume_session_{}", session.id))
+            } else {
+                ("mytrading_pause_session", format!("pause_session_{}", session.id))
+            };
+            buttons.push(vec![
+                InlineKeyboardButton::callback(i18n::get_button_text(locale, pause_key), pause_data),
+                InlineKeyboardButton::callback(
+                    i18n::get_button_text(locale, "mytrading_reload_session"),
+                    format!("reload_session_{}", session.id)
+                ),
+                InlineKeyboardButton::callback(
+                    format!("{} #{} · {}", i18n::get_button_text(locale, "mytrading_stop_session"), session.id, pair),
+                    format!("stop_session_{}", session.id)
+                ),
+            ]);
+        }
+        status_msg.push_str(&format!("\n{}", i18n::translate(locale, "mytrading_monitoring", None)));
         
         bot.send_message(msg.chat.id, status_msg)
             .parse_mode(teloxide::types::ParseMode::Html)
@@ -1264,26 +1290,9 @@ pub async fn handle_stop_trading_callback(
                         .text(&confirm_text)
                         .await?;
                     
-                    let user_id = session.user_id;
-                    
-                    // Stop trading and unsubscribe from stream
-                    match state.strategy_executor.stop_trading(user_id).await {
-                        Ok(Some((exchange_stream, pair_stream))) => {
-                            // Unsubscribe from stream
-                            state.stream_manager.unsubscribe(&exchange_stream, &pair_stream, user_id).await;
-                            
-                            // Update live trading session status to stopped
-                            let mut session_update: live_trading_sessions::ActiveModel = session.into();
-                            session_update.status = ActiveValue::Set("stopped".to_string());
-                            session_update.stopped_at = ActiveValue::Set(Some(Utc::now()));
-                            session_update.updated_at = ActiveValue::Set(Some(Utc::now()));
-                            
-                            live_trading_sessions::Entity::update(session_update)
-                                .exec(state.db.as_ref())
-                                .await?;
-                            
-                            tracing::info!("✅ Updated live trading session {} status to stopped for user {}", session_id, user_id);
-                            
+                    // Stop the session's strategy, stream subscriptions and snapshots
+                    match session_service::stop_session(&state, &session).await {
+                        Ok(()) => {
                             // Update message and remove buttons to prevent further clicks
                             if let Some(msg) = q.message {
                                 let success_msg = i18n::translate(locale, "stop_trading_success", None);
@@ -1302,35 +1311,6 @@ pub async fn handle_stop_trading_callback(
                                 }
                             }
                         }
-                        Ok(None) => {
-                            // User was not trading in executor, but session exists - just update session
-                            let mut session_update: live_trading_sessions::ActiveModel = session.into();
-                            session_update.status = ActiveValue::Set("stopped".to_string());
-                            session_update.stopped_at = ActiveValue::Set(Some(Utc::now()));
-                            session_update.updated_at = ActiveValue::Set(Some(Utc::now()));
-                            
-                            live_trading_sessions::Entity::update(session_update)
-                                .exec(state.db.as_ref())
-                                .await?;
-                            
-                            // Update message and remove buttons
-                            if let Some(msg) = q.message {
-                                let success_msg = i18n::translate(locale, "stop_trading_success", None);
-                                
-                                // Edit message and remove buttons
-                                if let Err(e) = bot.edit_message_text(msg.chat().id, msg.id(), &success_msg)
-                                    .parse_mode(teloxide::types::ParseMode::Html)
-                                    .reply_markup(teloxide::types::InlineKeyboardMarkup::new::<Vec<Vec<teloxide::types::InlineKeyboardButton>>>(vec![])) // Empty buttons
-                                    .await
-                                {
-                                    // Ignore "message is not modified" error - message already has correct content
-                                    let error_str = e.to_string();
-                                    if !error_str.contains("message is not modified") {
-                                        tracing::warn!("Failed to edit message: {}", e);
-                                    }
-                                }
-                            }
-                        }
                         Err(e) => {
                             let error_msg = i18n::translate(locale, "stop_trading_error", Some(&[("error", &e.to_string())]));
                             
@@ -1387,3 +1367,276 @@ pub async fn handle_stop_trading_callback(
     
     Ok(())
 }
+
+/// Handler for the pause, resume and reload buttons of a session in /mytrading
+pub async fn handle_session_control_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    state: Arc<AppState>,
+) -> Result<(), anyhow::Error> {
+    let Some(data) = q.data.clone() else {
+        return Ok(());
+    };
+    let callback_user_id = q.from.id.0 as i64;
+    
+    // Get user locale
+    let user = users::Entity::find_by_id(callback_user_id)
+        .one(state.db.as_ref())
+        .await?;
+    let locale = user
+        .as_ref()
+        .and_then(|u| u.language.as_ref())
+        .map(|l| i18n::get_user_language(Some(l)))
+        .unwrap_or("en");
+    
+    let Some((action, session_id)) = data.split_once("_session_")
+        .and_then(|(action, id)| id.parse::<u64>().ok().map(|id| (action, id)))
+    else {
+        return Ok(());
+    };
+    let session = live_trading_sessions::Entity::find_by_id(session_id)
+        .one(state.db.as_ref())
+        .await?;
+    
+    // Only the owner can control an active session
+    let error_key = match &session {
+        None => Some("stop_trading_session_not_found"),
+        Some(session) if session.user_id != callback_user_id => Some("stop_trading_not_yours"),
+        Some(session) if session.status != "active" => Some("stop_trading_session_not_active"),
+        Some(_) => None,
+    };
+    let (Some(session), None) = (session, error_key) else {
+        bot.answer_callback_query(q.id)
+            .text(i18n::translate(locale, error_key.unwrap_or("stop_trading_session_not_found"), None))
+            .await?;
+        return Ok(());
+    };
+    let id = session.id.to_string();
+    
+    let result = match action {
+        "pause" => session_service::pause_session(&state, &session).await
+            .map(|_| i18n::translate(locale, "session_paused", Some(&[("id", &id)]))),
+        "resume" => session_service::resume_session(&state, &session).await
+            .map(|_| i18n::translate(locale, "session_resumed", Some(&[("id", &id)]))),
+        "reload" => match session_service::reload_session(&state, &session).await {
+            Ok(changes) if changes.is_empty() => Ok(i18n::translate(locale, "session_reload_no_changes", Some(&[("id", &id)]))),
+            Ok(changes) => {
+                // The full list of changes goes to the chat, the callback answer is kept short
+                if let Some(msg) = &q.message {
+                    let reloaded_msg = i18n::translate(locale, "session_reloaded", Some(&[
+                        ("id", &id),
+                        ("changes", &crate::services::trading_signal::escape_html(&changes.join(", "))),
+                    ]));
+                    bot.send_message(msg.chat().id, reloaded_msg)
+                        .parse_mode(teloxide::types::ParseMode::Html)
+                        .await?;
+                }
+                Ok(i18n::translate(locale, "session_reload_done", Some(&[("id", &id)])))
+            }
+            Err(e) => Err(e),
+        },
+        _ => return Ok(()),
+    };
+    
+    let answer = result.unwrap_or_else(|e| {
+        tracing::warn!("Session control '{}' failed for session {}: {}", action, session.id, e);
+        i18n::translate(locale, "session_control_error", Some(&[("id", &id), ("error", &e.to_string())]))
+    });
+    bot.answer_callback_query(q.id)
+        .text(answer)
+        .show_alert(true)
+        .await?;
+    
+    Ok(())
+}
+
+/// Handler for the "why" button of a signal message: shows the candle, condition tree
+/// and indicator values the signal was raised on
+pub async fn handle_signal_why_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    state: Arc<AppState>,
+) -> Result<(), anyhow::Error> {
+    use crate::services::strategy_engine::SignalExplanation;
+    use crate::services::trading_signal::escape_html;
+    
+    let Some(signal_id) = q.data.as_deref()
+        .and_then(|d| d.strip_prefix("why_signal_"))
+        .and_then(|id| id.parse::<u64>().ok())
+    else {
+        return Ok(());
+    };
+    let callback_user_id = q.from.id.0 as i64;
+    
+    // Get user locale
+    let user = users::Entity::find_by_id(callback_user_id)
+        .one(state.db.as_ref())
+        .await?;
+    let locale = user
+        .as_ref()
+        .and_then(|u| u.language.as_ref())
+        .map(|l| i18n::get_user_language(Some(l)))
+        .unwrap_or("en");
+    
+    // Only the owner can see why their signal fired
+    let signal = live_trading_signals::Entity::find_by_id(signal_id)
+        .one(state.db.as_ref())
+        .await?
+        .filter(|s| s.user_id == callback_user_id);
+    let Some(signal) = signal else {
+        bot.answer_callback_query(q.id)
+            .text(i18n::translate(locale, "signal_why_not_found", None))
+            .await?;
+        return Ok(());
+    };
+    let Some(explanation) = signal.indicator_values.as_deref()
+        .and_then(|v| serde_json::from_str::<SignalExplanation>(v).ok())
+    else {
+        bot.answer_callback_query(q.id)
+            .text(i18n::translate(locale, "signal_why_unavailable", None))
+            .await?;
+        return Ok(());
+    };
+    bot.answer_callback_query(q.id).await?;
+    
+    let candle = &explanation.candle;
+    let mut text = i18n::translate(locale, "signal_why_title", Some(&[
+        ("id", &signal.id.to_string()),
+        ("side", &signal.side.to_uppercase()),
+        ("pair", &signal.pair),
+        ("timeframe", signal.timeframe.as_deref().unwrap_or("-")),
+        ("strategy", &escape_html(signal.strategy_name.as_deref().unwrap_or("-"))),
+    ]));
+    text.push_str("\n\n");
+    text.push_str(&i18n::translate(locale, "signal_why_candle", Some(&[
+        ("time", &chrono::DateTime::from_timestamp(candle.timestamp, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default()),
+        ("open", &format!("{:.4}", candle.open)),
+        ("high", &format!("{:.4}", candle.high)),
+        ("low", &format!("{:.4}", candle.low)),
+        ("close", &format!("{:.4}", candle.close)),
+        ("volume", &format!("{:.4}", candle.volume)),
+    ])));
+    
+    text.push_str("\n\n");
+    if explanation.conditions.is_empty() {
+        text.push_str(&i18n::translate(locale, "signal_why_no_conditions", Some(&[
+            ("reason", &escape_html(signal.reason.as_deref().unwrap_or("-"))),
+        ])));
+    } else {
+        text.push_str(&i18n::translate(locale, "signal_why_conditions", None));
+        for (label, clause) in &explanation.conditions {
+            text.push_str(&format!("\n<i>{}</i>", escape_html(label)));
+            push_clause(&mut text, clause, 1);
+        }
+    }
+    
+    if !explanation.indicators.is_empty() {
+        text.push_str("\n\n");
+        text.push_str(&i18n::translate(locale, "signal_why_indicators", None));
+        for (name, value) in &explanation.indicators {
+            text.push_str(&format!("\n• {} = <code>{:.4}</code>", escape_html(name), value));
+        }
+    }
+    
+    // Deep condition trees would exceed Telegram's limit and the message would not be sent
+    let text = fit_message(&text, TELEGRAM_MESSAGE_LIMIT, |count| {
+        i18n::translate(locale, "signal_why_truncated", Some(&[("count", &count.to_string())]))
+    });
+    if let Some(msg) = &q.message {
+        bot.send_message(msg.chat().id, text)
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await?;
+    }
+    
+    Ok(())
+}
+
+/// Longest Telegram message, in characters
+const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
+
+/// Cut an HTML message at a line break to fit `max_chars`, so no tag is left open, and end
+/// it with the `marker` of the number of lines left out
+fn fit_message(text: &str, max_chars: usize, marker: impl Fn(usize) -> String) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut kept = String::new();
+    let mut kept_chars = 0;
+    let mut kept_lines = 0;
+    for line in &lines {
+        let line_chars = line.chars().count() + usize::from(kept_lines > 0);
+        // Room is left for the marker of the lines after this one
+        let marker_chars = marker(lines.len() - kept_lines - 1).chars().count() + 1;
+        if kept_chars + line_chars + marker_chars > max_chars {
+            break;
+        }
+        if kept_lines > 0 {
+            kept.push('\n');
+        }
+        kept.push_str(line);
+        kept_chars += line_chars;
+        kept_lines += 1;
+    }
+    if kept_lines > 0 {
+        kept.push('\n');
+    }
+    kept.push_str(&marker(lines.len() - kept_lines));
+    kept
+}
+
+/// Append a clause and its children, one indented line each
+fn push_clause(text: &mut String, clause: &freqtrade_rs::strategy::ClauseResult, depth: usize) {
+    let icon = match clause.holds {
+        Some(true) => "✅",
+        Some(false) => "❌",
+        None => "⏳",
+    };
+    text.push_str(&format!("\n{}{} <code>{}</code>",
+        "    ".repeat(depth), icon, crate::services::trading_signal::escape_html(&clause.clause)));
+    if let (Some(left), Some(right)) = (clause.left, clause.right) {
+        text.push_str(&format!(" ({:.4} vs {:.4})", left, right));
+    }
+    for child in &clause.children {
+        push_clause(text, child, depth + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freqtrade_rs::strategy::ClauseResult;
+
+    fn clause(text: &str, holds: Option<bool>, children: Vec<ClauseResult>) -> ClauseResult {
+        ClauseResult { clause: text.to_string(), holds, left: Some(1.0), right: Some(2.0), children }
+    }
+
+    #[test]
+    fn test_deep_condition_tree_fits_a_message() {
+        // A chain of nested ANDs, each with an RSI comparison beside the next level
+        let mut tree = clause("RSI(14) < 30", Some(true), Vec::new());
+        for depth in 0..200 {
+            tree = clause("AND", Some(depth % 2 == 0), vec![clause("EMA(9) > EMA(21)", None, Vec::new()), tree]);
+        }
+        let mut text = "🔍 <b>Why signal #1:</b> BUY BTC/USDT (1m, <i>deep</i>)\n\n🧮 <b>Conditions</b>\n<i>buy</i>".to_string();
+        push_clause(&mut text, &tree, 1);
+        assert!(text.chars().count() > TELEGRAM_MESSAGE_LIMIT);
+
+        let message = fit_message(&text, TELEGRAM_MESSAGE_LIMIT, |count| format!("<i>… {} more lines</i>", count));
+        assert!(message.chars().count() <= TELEGRAM_MESSAGE_LIMIT);
+        assert!(message.starts_with("🔍 <b>Why signal #1:</b>"));
+        let omitted = text.lines().count() - (message.lines().count() - 1);
+        assert!(message.ends_with(&format!("<i>… {} more lines</i>", omitted)));
+        // Only whole lines are kept, so every tag is closed
+        assert_eq!(message.matches("<code>").count(), message.matches("</code>").count());
+        assert!(message.lines().all(|line| text.lines().any(|l| l == line) || line.starts_with("<i>…")));
+    }
+
+    #[test]
+    fn test_short_message_is_not_cut() {
+        let text = "🔍 <b>Why</b>\n✅ <code>RSI(14) &lt; 30</code>";
+        assert_eq!(fit_message(text, TELEGRAM_MESSAGE_LIMIT, |_| unreachable!()), text);
+    }
+}
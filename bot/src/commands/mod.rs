@@ -17,7 +17,7 @@ pub mod payment;
 
 pub use admin::handle_version;
 pub use me::handle_me;
-pub use strategy::{handle_create_strategy, handle_strategy_callback, handle_strategy_input_callback, handle_my_strategies, handle_delete_strategy_callback};
+pub use strategy::{handle_create_strategy, handle_strategy_callback, handle_strategy_input_callback, handle_my_strategies, handle_delete_strategy_callback, handle_position_sizing_callback};
 pub use backtest::{handle_backtest as handle_backtest_wizard, handle_backtest_callback};
 pub use start::{handle_start, handle_language_selection, handle_language_callback};
 pub use me::handle_profile_callback;
@@ -25,7 +25,7 @@ pub use payment::{handle_deposit, handle_balance, handle_deposit_callback};
 pub mod start_trading;
 pub use start_trading::{handle_start_trading, handle_start_trading_callback};
 pub mod live_trading;
-pub use live_trading::{handle_live_trading, handle_live_trading_callback, handle_live_trading_input, handle_my_trading, handle_stop_trading_callback};
+pub use live_trading::{handle_live_trading, handle_live_trading_callback, handle_live_trading_input, handle_my_trading, handle_session_control_callback, handle_signal_why_callback, handle_stop_trading_callback};
 pub mod tokens;
 pub use tokens::{handle_tokens, handle_tokens_callback};
 pub mod ai;
@@ -6,6 +6,7 @@ use teloxide::prelude::*;
 use teloxide::types::InlineKeyboardButton;
 use crate::state::{AppState, MyDialogue};
 use crate::services::strategy_service::StrategyService;
+use crate::services::session_service;
 use crate::i18n;
 use shared::entity::users;
 use sea_orm::EntityTrait;
@@ -30,10 +31,12 @@ pub async fn handle_start_trading(
         .map(|l| i18n::get_user_language(Some(l)))
         .unwrap_or("en");
     
-    // Check if user already has a strategy running
-    if state.strategy_executor.is_user_trading(telegram_id).await {
-        let msg_text = i18n::translate(locale, "trading_already_active", None);
-        bot.send_message(msg.chat.id, msg_text).await?;
+    // Check if user can run another session on their tier
+    if let Some(limit) = session_service::session_limit_reached(state.db.as_ref(), telegram_id).await? {
+        let msg_text = i18n::translate(locale, "trading_session_limit", Some(&[("limit", &limit.to_string())]));
+        bot.send_message(msg.chat.id, msg_text)
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await?;
         return Ok(());
     }
     
@@ -103,8 +106,16 @@ pub async fn handle_start_trading_callback(
                     // Convert to StrategyConfig
                     match state.strategy_service.strategy_to_config(&strategy) {
                         Ok(config) => {
-                            // Start trading
-                            if let Err(e) = state.strategy_executor.start_trading(telegram_id, config, None).await {
+                            // Start a session on Binance market data, signals go to the user's private chat
+                            if let Err(e) = session_service::start_session(
+                                &state,
+                                bot.clone(),
+                                telegram_id,
+                                telegram_id,
+                                "binance",
+                                config,
+                                Some(strategy_id),
+                            ).await {
                                 let error_msg = i18n::translate(locale, "trading_start_error", Some(&[
                                     ("error", &e.to_string())
                                 ]));
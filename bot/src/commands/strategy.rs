@@ -7,6 +7,7 @@ use sea_orm::{QueryFilter, QueryOrder};
 use crate::state::{AppState, BotState, CreateStrategyState, MyDialogue};
 use crate::i18n;
 use crate::services::preset_strategies;
+use freqtrade_rs::strategy::{Condition, ExprError};
 
 // Helper function to HTML escape (must escape & first!)
 fn escape_html(text: &str) -> String {
@@ -112,6 +113,12 @@ pub async fn handle_strategy_callback(
                                 i18n::get_button_text(&locale, "algorithm_adx"),
                                 "algorithm_adx"
                             ),
+                            InlineKeyboardButton::callback(
+                                i18n::get_button_text(&locale, "algorithm_expression"),
+                                "algorithm_expression"
+                            ),
+                        ],
+                        vec![
                             InlineKeyboardButton::callback(
                                 i18n::get_button_text(&locale, "strategy_cancel_button"),
                                 "cancel_strategy"
@@ -330,7 +337,7 @@ pub async fn handle_strategy_callback(
                     bot.answer_callback_query(q.id).await?;
                     let algorithm_msg = i18n::translate(&locale, "strategy_algorithm_selected", Some(&[("algorithm", "EMA")]));
                     let info_msg = i18n::translate(&locale, "strategy_algorithm_ema_info", None);
-                    let step2_msg = i18n::translate(&locale, "strategy_step2_enter_buy", Some(&[("example", "EMA(12) > EMA(26)")]));
+                    let step2_msg = i18n::translate(&locale, "strategy_step2_enter_buy", Some(&[("example", "crosses_above(EMA(12), EMA(26))")]));
                     let instruction = format!("{}\n\n{}\n\n{}", algorithm_msg, info_msg, step2_msg);
                     
                     bot.edit_message_text(chat_id, message_id, instruction)
@@ -345,7 +352,7 @@ pub async fn handle_strategy_callback(
                     bot.answer_callback_query(q.id).await?;
                     let algorithm_msg = i18n::translate(&locale, "strategy_algorithm_selected", Some(&[("algorithm", "MACD")]));
                     let info_msg = i18n::translate(&locale, "strategy_algorithm_macd_info", None);
-                    let step2_msg = i18n::translate(&locale, "strategy_step2_enter_buy", Some(&[("example", "MACD > Signal")]));
+                    let step2_msg = i18n::translate(&locale, "strategy_step2_enter_buy", Some(&[("example", "crosses_above(MACD, MACD_SIGNAL)")]));
                     let instruction = format!("{}\n\n{}\n\n{}", algorithm_msg, info_msg, step2_msg);
                     
                     bot.edit_message_text(chat_id, message_id, instruction)
@@ -360,7 +367,7 @@ pub async fn handle_strategy_callback(
                     bot.answer_callback_query(q.id).await?;
                     let algorithm_msg = i18n::translate(&locale, "strategy_algorithm_selected", Some(&[("algorithm", "MA")]));
                     let info_msg = i18n::translate(&locale, "strategy_algorithm_ma_info", None);
-                    let step2_msg = i18n::translate(&locale, "strategy_step2_enter_buy", Some(&[("example", "MA(9) > MA(21)")]));
+                    let step2_msg = i18n::translate(&locale, "strategy_step2_enter_buy", Some(&[("example", "crosses_above(MA(9), MA(21))")]));
                     let instruction = format!("{}\n\n{}\n\n{}", algorithm_msg, info_msg, step2_msg);
                     
                     bot.edit_message_text(chat_id, message_id, instruction)
@@ -401,6 +408,21 @@ pub async fn handle_strategy_callback(
                         algorithm: "ADX".to_string(),
                     })).await?;
                 }
+                "algorithm_expression" => {
+                    bot.answer_callback_query(q.id).await?;
+                    let algorithm_msg = i18n::translate(&locale, "strategy_algorithm_selected", Some(&[("algorithm", "Expression")]));
+                    let info_msg = i18n::translate(&locale, "strategy_algorithm_expression_info", None);
+                    let step2_msg = i18n::translate(&locale, "strategy_step2_enter_buy", Some(&[("example", "RSI < 30 AND close > EMA(200)")]));
+                    let instruction = format!("{}\n\n{}\n\n{}", algorithm_msg, info_msg, step2_msg);
+                    
+                    bot.edit_message_text(chat_id, message_id, instruction)
+                        .parse_mode(teloxide::types::ParseMode::Html)
+                        .await?;
+                    
+                    dialogue.update(BotState::CreateStrategy(CreateStrategyState::WaitingForBuyCondition {
+                        algorithm: "Expression".to_string(),
+                    })).await?;
+                }
                 _ if data.starts_with("mix_select_") => {
                     let callback_query_id = q.id.clone();
                     bot.answer_callback_query(callback_query_id).await?;
@@ -511,6 +533,48 @@ pub async fn handle_strategy_callback(
                             return Ok(());
                         }
                         
+                        // Ask how the signals of the selected strategies combine
+                        let mut mode_buttons: Vec<Vec<InlineKeyboardButton>> = ["unanimous", "majority", "weighted", "sequence"]
+                            .iter()
+                            .map(|mode| vec![InlineKeyboardButton::callback(
+                                i18n::get_button_text(&locale, &format!("strategy_mix_mode_{}", mode)),
+                                format!("mix_mode_{}", mode)
+                            )])
+                            .collect();
+                        mode_buttons.push(vec![
+                            InlineKeyboardButton::callback(
+                                i18n::get_button_text(&locale, "strategy_cancel_button"),
+                                "cancel_strategy"
+                            ),
+                        ]);
+                        
+                        let mode_msg = i18n::translate(&locale, "strategy_choose_mix_mode", None);
+                        bot.edit_message_text(chat_id, message_id, mode_msg)
+                            .parse_mode(teloxide::types::ParseMode::Html)
+                            .reply_markup(teloxide::types::InlineKeyboardMarkup::new(mode_buttons))
+                            .await?;
+                        
+                        dialogue.update(BotState::CreateStrategy(CreateStrategyState::WaitingForMixMode {
+                            selected_strategy_ids: selected_strategy_ids.clone(),
+                        })).await?;
+                    }
+                }
+                _ if data.starts_with("mix_mode_") => {
+                    let callback_query_id = q.id.clone();
+                    bot.answer_callback_query(callback_query_id).await?;
+                    let mix_mode = data.replace("mix_mode_", "");
+                    
+                    if let Ok(Some(BotState::CreateStrategy(CreateStrategyState::WaitingForMixMode { selected_strategy_ids }))) = dialogue.get().await {
+                        // The first strategies confirm the last one
+                        if mix_mode == "sequence" && selected_strategy_ids.len() < 2 {
+                            let error_msg = i18n::translate(&locale, "strategy_mix_sequence_needs_two", None);
+                            bot.answer_callback_query(q.id)
+                                .text(&error_msg)
+                                .show_alert(true)
+                                .await?;
+                            return Ok(());
+                        }
+                        
                         // Ask for strategy name
                         let name_msg = i18n::translate(&locale, "strategy_enter_name", None);
                         bot.edit_message_text(chat_id, message_id, name_msg)
@@ -519,7 +583,8 @@ pub async fn handle_strategy_callback(
                         
                         // Update state to wait for name
                         dialogue.update(BotState::CreateStrategy(CreateStrategyState::WaitingForMixStrategyName {
-                            selected_strategy_ids: selected_strategy_ids.clone(),
+                            selected_strategy_ids,
+                            mix_mode,
                         })).await?;
                     }
                 }
@@ -626,7 +691,7 @@ pub async fn handle_strategy_callback(
                             };
 
                             match strategies::Entity::insert(new_strategy).exec(state.db.as_ref()).await {
-                                Ok(_) => {
+                                Ok(insert_result) => {
                                     // Use short message for preset strategies (when strategy_name is not empty)
                                     // Use long message for custom strategies (when strategy_name is empty)
                                     let success_msg = if !strategy_name.is_empty() {
@@ -647,6 +712,7 @@ pub async fn handle_strategy_callback(
                                     bot.send_message(chat_id, success_msg)
                                         .parse_mode(teloxide::types::ParseMode::Html)
                                         .await?;
+                                    send_position_sizing_prompt(&bot, chat_id, &locale, insert_result.last_insert_id).await?;
                                     dialogue.exit().await?;
                                 }
                                 Err(e) => {
@@ -775,6 +841,10 @@ pub async fn handle_strategy_input_callback(
             BotState::CreateStrategy(CreateStrategyState::WaitingForBuyCondition { algorithm }) => {
                 if let Some(text) = msg.text() {
                     let buy_condition = text.trim().to_string();
+                    if let Err(e) = Condition::parse(&buy_condition, &serde_json::json!({})) {
+                        send_condition_error(&bot, msg.chat.id, locale, &e).await?;
+                        return Ok(());
+                    }
                     // Don't escape here - translate() will handle HTML escaping
                     let step1_complete = i18n::translate(locale, "strategy_step1_complete", Some(&[
                         ("algorithm", &algorithm),
@@ -846,7 +916,7 @@ pub async fn handle_strategy_input_callback(
                     })).await?;
                 }
             }
-            BotState::CreateStrategy(CreateStrategyState::WaitingForMixStrategyName { selected_strategy_ids }) => {
+            BotState::CreateStrategy(CreateStrategyState::WaitingForMixStrategyName { selected_strategy_ids, mix_mode }) => {
                 if let Some(text) = msg.text() {
                     let strategy_name = text.trim().to_string();
                     
@@ -874,7 +944,8 @@ pub async fn handle_strategy_input_callback(
                                 let strategy_json = serde_json::json!({
                                     "id": strategy.id,
                                     "name": strategy.name,
-                                    "algorithm": config.strategy_type,
+                                    "weight": 1.0,
+                                    "strategy_type": config.strategy_type,
                                     "buy_condition": config.buy_condition,
                                     "sell_condition": config.sell_condition,
                                     "timeframe": config.timeframe,
@@ -908,12 +979,13 @@ pub async fn handle_strategy_input_callback(
                     let mixed_config = json!({
                         "type": "mixed",
                         "strategies": mixed_strategies,
-                        "mix_mode": "all" // Could be "all", "any", "majority" etc.
+                        "mix_mode": mix_mode,
                     });
                     
                     // Create a combined description
                     let description = format!(
-                        "Mixed Strategy combining {} strategies:{}",
+                        "Mixed Strategy ({}) combining {} strategies:{}",
+                        mix_mode,
                         mixed_strategies.len(),
                         strategy_summary
                     );
@@ -937,6 +1009,7 @@ pub async fn handle_strategy_input_callback(
                             let success_msg = i18n::translate(locale, "strategy_mix_saved", Some(&[
                                 ("name", &escape_html(&strategy_name)),
                                 ("count", &mixed_strategies.len().to_string()),
+                                ("mode", &i18n::get_button_text(locale, &format!("strategy_mix_mode_{}", mix_mode))),
                             ]));
                             bot.send_message(msg.chat.id, success_msg)
                                 .parse_mode(teloxide::types::ParseMode::Html)
@@ -958,6 +1031,10 @@ pub async fn handle_strategy_input_callback(
             BotState::CreateStrategy(CreateStrategyState::WaitingForSellCondition { algorithm, buy_condition }) => {
                 if let Some(text) = msg.text() {
                     let sell_condition = text.trim().to_string();
+                    if let Err(e) = Condition::parse(&sell_condition, &serde_json::json!({})) {
+                        send_condition_error(&bot, msg.chat.id, locale, &e).await?;
+                        return Ok(());
+                    }
                     let mut timeframe_buttons = vec![
                         vec![
                             InlineKeyboardButton::callback(i18n::get_button_text(locale, "timeframe_1m"), "timeframe_1m"),
@@ -1041,11 +1118,12 @@ pub async fn handle_strategy_input_callback(
                     };
 
                     match strategies::Entity::insert(new_strategy).exec(state.db.as_ref()).await {
-                        Ok(_) => {
+                        Ok(insert_result) => {
                             let success_msg = i18n::translate(locale, "strategy_saved_success", None);
                             bot.send_message(msg.chat.id, success_msg)
                                 .parse_mode(teloxide::types::ParseMode::Html)
                                 .await?;
+                            send_position_sizing_prompt(&bot, msg.chat.id, locale, insert_result.last_insert_id).await?;
                             dialogue.exit().await?;
                         }
                         Err(e) => {
@@ -1061,6 +1139,125 @@ pub async fn handle_strategy_input_callback(
     Ok(())
 }
 
+/// Position sizing presets offered in the bot, keyed by callback suffix
+fn position_sizing_preset(key: &str) -> Option<freqtrade_rs::portfolio::PositionSizing> {
+    use freqtrade_rs::portfolio::PositionSizing;
+    match key {
+        "percent" => Some(PositionSizing::default()),
+        "fixed" => Some(PositionSizing::FixedStake { stake: 50.0 }),
+        "risk" => Some(PositionSizing::RiskBased { risk_per_trade: 0.01 }),
+        "kelly" => Some(PositionSizing::default_kelly()),
+        "volatility" => Some(PositionSizing::default_volatility_target()),
+        _ => None,
+    }
+}
+
+/// Ask the user to pick a position sizing model for a newly saved strategy
+async fn send_position_sizing_prompt(
+    bot: &Bot,
+    chat_id: ChatId,
+    locale: &str,
+    strategy_id: u64,
+) -> Result<(), anyhow::Error> {
+    let buttons: Vec<Vec<InlineKeyboardButton>> = ["percent", "fixed", "risk", "kelly", "volatility"]
+        .iter()
+        .map(|key| vec![InlineKeyboardButton::callback(
+            i18n::get_button_text(locale, &format!("sizing_{}", key)),
+            format!("sizing_{}_{}", strategy_id, key),
+        )])
+        .collect();
+    
+    let prompt = i18n::translate(locale, "strategy_sizing_choose", None);
+    bot.send_message(chat_id, prompt)
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .reply_markup(teloxide::types::InlineKeyboardMarkup::new(buttons))
+        .await?;
+    Ok(())
+}
+
+/// Tell the user why a buy / sell condition does not parse; the dialogue stays on the same step
+async fn send_condition_error(
+    bot: &Bot,
+    chat_id: ChatId,
+    locale: &str,
+    error: &ExprError,
+) -> Result<(), anyhow::Error> {
+    let message = i18n::translate(locale, "strategy_condition_invalid", Some(&[
+        ("error", &error.message),
+        ("pointer", &error.pointer()),
+    ]));
+    bot.send_message(chat_id, message)
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+/// Handler for position sizing selection (sizing_<strategy_id>_<model>)
+pub async fn handle_position_sizing_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    state: Arc<AppState>,
+) -> Result<(), anyhow::Error> {
+    use sea_orm::ColumnTrait;
+    
+    let user_id = q.from.id.0 as i64;
+    let user = users::Entity::find_by_id(user_id)
+        .one(state.db.as_ref())
+        .await?;
+    let locale = user
+        .as_ref()
+        .and_then(|u| u.language.as_ref())
+        .map(|l| i18n::get_user_language(Some(l)))
+        .unwrap_or("en");
+    
+    let Some(data) = q.data.as_ref() else {
+        return Ok(());
+    };
+    let parsed = data.strip_prefix("sizing_")
+        .and_then(|rest| rest.split_once('_'))
+        .and_then(|(id, key)| Some((id.parse::<u64>().ok()?, position_sizing_preset(key)?)));
+    let Some((strategy_id, sizing)) = parsed else {
+        let error_msg = i18n::translate(locale, "error_invalid_strategy_id", None);
+        bot.answer_callback_query(q.id).text(&error_msg).await?;
+        return Ok(());
+    };
+    
+    // Only the owner can change the strategy
+    let strategy = strategies::Entity::find_by_id(strategy_id)
+        .filter(strategies::Column::TelegramId.eq(user_id.to_string()))
+        .one(state.db.as_ref())
+        .await?;
+    let Some(strategy) = strategy else {
+        let error_msg = i18n::translate(locale, "strategy_delete_not_found", None);
+        bot.answer_callback_query(q.id).text(&error_msg).show_alert(true).await?;
+        return Ok(());
+    };
+    
+    let mut content: serde_json::Value = strategy.content.as_deref()
+        .and_then(|c| serde_json::from_str(c).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    if !content["parameters"].is_object() {
+        content["parameters"] = serde_json::json!({});
+    }
+    content["parameters"]["position_sizing"] = serde_json::to_value(&sizing)?;
+    
+    let mut active: strategies::ActiveModel = strategy.into();
+    active.content = ActiveValue::Set(Some(content.to_string()));
+    sea_orm::ActiveModelTrait::update(active, state.db.as_ref()).await?;
+    
+    let success_msg = i18n::translate(locale, "strategy_sizing_saved", Some(&[
+        ("sizing", &sizing.describe()),
+    ]));
+    bot.answer_callback_query(q.id).await?;
+    if let Some(msg) = q.message {
+        bot.edit_message_text(msg.chat().id, msg.id(), &success_msg)
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await?;
+    }
+    
+    Ok(())
+}
+
 /// Helper function to build strategies message with pagination
 /// Returns (message_text, buttons, current_page, total_pages)
 fn build_strategies_message_paginated(
@@ -42,7 +42,7 @@ pub async fn handle_streams(
     let mut msg_text = i18n::translate(locale, "streams_title", None);
     msg_text.push_str("\n━━━━━━━━━━\n\n");
     
-    for (idx, (exchange, pair, subscriber_count, subscriber_ids)) in active_streams.iter().enumerate() {
+    for (idx, (exchange, pair, subscriber_count, subscriber_ids, health)) in active_streams.iter().enumerate() {
         let exchange_name = match exchange.as_str() {
             "binance" => "🔵 Binance",
             "okx" => "🟢 OKX",
@@ -65,6 +65,22 @@ pub async fn handle_streams(
         ));
         msg_text.push_str("\n");
         
+        let status_key = if health.connected { "streams_connected" } else { "streams_reconnecting" };
+        let last_event = match health.last_event {
+            Some(at) => format!("{}s", (chrono::Utc::now() - at).num_seconds().max(0)),
+            None => "-".to_string(),
+        };
+        msg_text.push_str(&i18n::translate(
+            locale,
+            "streams_health",
+            Some(&[
+                ("status", &i18n::translate(locale, status_key, None)),
+                ("last_event", &last_event),
+                ("reconnects", &health.reconnects.to_string()),
+            ]),
+        ));
+        msg_text.push('\n');
+        
         // Show subscriber IDs (first 5, then "... and X more" if more)
         if !subscriber_ids.is_empty() {
             let display_count = subscriber_ids.len().min(5);
@@ -216,6 +216,10 @@ pub fn get_button_text(locale: &str, key: &str) -> String {
         ("en", "backtest_exchange_binance") => "🔵 Binance".to_string(),
         ("vi", "backtest_exchange_okx") => "🟠 OKX".to_string(),
         ("en", "backtest_exchange_okx") => "🟠 OKX".to_string(),
+        ("vi", "backtest_engine_native") => "⚡ Native (nhanh)".to_string(),
+        ("en", "backtest_engine_native") => "⚡ Native (fast)".to_string(),
+        ("vi", "backtest_engine_freqtrade") => "🐍 Freqtrade (tham chiếu)".to_string(),
+        ("en", "backtest_engine_freqtrade") => "🐍 Freqtrade (reference)".to_string(),
         ("vi", "period_1day") => "📅 1 Ngày".to_string(),
         ("en", "period_1day") => "📅 1 Day".to_string(),
         ("vi", "period_1week") => "📅 1 Tuần".to_string(),
@@ -246,6 +250,8 @@ pub fn get_button_text(locale: &str, key: &str) -> String {
         ("en", "algorithm_stochastic") => "📊 Stochastic".to_string(),
         ("vi", "algorithm_adx") => "📊 ADX".to_string(),
         ("en", "algorithm_adx") => "📊 ADX".to_string(),
+        ("vi", "algorithm_expression") => "🧮 Biểu thức".to_string(),
+        ("en", "algorithm_expression") => "🧮 Expression".to_string(),
         ("vi", "strategy_cancel_button") => "❌ Hủy".to_string(),
         ("en", "strategy_cancel_button") => "❌ Cancel".to_string(),
         ("vi", "strategy_type_custom") => "🛠️ Tùy Chỉnh".to_string(),
@@ -256,6 +262,14 @@ pub fn get_button_text(locale: &str, key: &str) -> String {
         ("en", "strategy_type_custom_mix") => "🔀 Mix Strategies".to_string(),
         ("vi", "strategy_mix_done") => "✅ Hoàn thành".to_string(),
         ("en", "strategy_mix_done") => "✅ Done".to_string(),
+        ("vi", "strategy_mix_mode_unanimous") => "🤝 Đồng thuận".to_string(),
+        ("en", "strategy_mix_mode_unanimous") => "🤝 Unanimous".to_string(),
+        ("vi", "strategy_mix_mode_majority") => "🗳 Đa số".to_string(),
+        ("en", "strategy_mix_mode_majority") => "🗳 Majority vote".to_string(),
+        ("vi", "strategy_mix_mode_weighted") => "⚖️ Trọng số".to_string(),
+        ("en", "strategy_mix_mode_weighted") => "⚖️ Weighted".to_string(),
+        ("vi", "strategy_mix_mode_sequence") => "⛓ Xác nhận tuần tự".to_string(),
+        ("en", "strategy_mix_mode_sequence") => "⛓ A confirms B".to_string(),
         
         // Timeframe buttons
         ("vi", "timeframe_1m") => "1 phút".to_string(),
@@ -291,6 +305,18 @@ pub fn get_button_text(locale: &str, key: &str) -> String {
         ("vi", "pair_manual") => "✏️ Khác".to_string(),
         ("en", "pair_manual") => "✏️ Other".to_string(),
         
+        // Position sizing buttons
+        ("vi", "sizing_percent") => "📊 10% vốn".to_string(),
+        ("en", "sizing_percent") => "📊 10% of equity".to_string(),
+        ("vi", "sizing_fixed") => "💵 Cố định 50 USDT".to_string(),
+        ("en", "sizing_fixed") => "💵 Fixed 50 USDT".to_string(),
+        ("vi", "sizing_risk") => "🛡️ Rủi ro 1% theo stop".to_string(),
+        ("en", "sizing_risk") => "🛡️ 1% risk to stop".to_string(),
+        ("vi", "sizing_kelly") => "🎯 Half Kelly".to_string(),
+        ("en", "sizing_kelly") => "🎯 Half Kelly".to_string(),
+        ("vi", "sizing_volatility") => "🌊 Theo biến động (ATR)".to_string(),
+        ("en", "sizing_volatility") => "🌊 Volatility target (ATR)".to_string(),
+        
         // Payment buttons
         ("vi", "payment_deposit_100") => "💵 Nạp 100 điểm".to_string(),
         ("en", "payment_deposit_100") => "💵 Deposit 100 points".to_string(),
@@ -364,8 +390,18 @@ pub fn get_button_text(locale: &str, key: &str) -> String {
         ("en", "live_trading_cancelled") => "❌ Cancelled".to_string(),
         
         // My trading buttons
-        ("vi", "mytrading_stop_button") => "🛑 Dừng Live Trading".to_string(),
-        ("en", "mytrading_stop_button") => "🛑 Stop Live Trading".to_string(),
+        ("vi", "mytrading_stop_session") => "🛑 Dừng".to_string(),
+        ("en", "mytrading_stop_session") => "🛑 Stop".to_string(),
+        ("vi", "mytrading_pause_session") => "⏸️ Tạm dừng".to_string(),
+        ("en", "mytrading_pause_session") => "⏸️ Pause".to_string(),
+        ("vi", "mytrading_resume_session") => "▶️ Tiếp tục".to_string(),
+        ("en", "mytrading_resume_session") => "▶️ Resume".to_string(),
+        ("vi", "mytrading_reload_session") => "🔄 Tải lại".to_string(),
+        ("en", "mytrading_reload_session") => "🔄 Reload".to_string(),
+        
+        // Signal message buttons
+        ("vi", "signal_why") => "🔍 Vì sao?".to_string(),
+        ("en", "signal_why") => "🔍 Why?".to_string(),
         
 
         ("vi", "tokens_cancel") => "❌ Hủy".to_string(),
@@ -21,12 +21,13 @@ use crate::{commands::{handle_invalid, handle_version,
     handle_create_strategy, handle_strategy_callback, 
     handle_strategy_input_callback, handle_my_strategies,
     handle_delete_strategy_callback,
+    handle_position_sizing_callback,
     handle_start, handle_language_selection, handle_language_callback, handle_profile_callback,
     handle_start_trading, handle_start_trading_callback,
     handle_live_trading, handle_live_trading_callback, handle_live_trading_input,
     handle_tokens, handle_tokens_callback,
     handle_back, handle_deposit, handle_balance, handle_deposit_callback,
-    handle_ai, handle_my_trading, handle_stop_trading_callback,
+    handle_ai, handle_my_trading, handle_stop_trading_callback, handle_session_control_callback, handle_signal_why_callback,
     handle_pnl, handle_streams, handle_command_invalid,
     Command},  state::AppState};
 use state::{BotState, BacktestState};
@@ -88,6 +89,13 @@ fn schema() -> UpdateHandler<anyhow::Error> {
             })
             .endpoint(handle_delete_strategy_callback)
         )
+        // Handle position sizing selection for saved strategies from any state
+        .branch(
+            dptree::filter(|q: CallbackQuery| {
+                q.data.as_ref().map(|d| d.starts_with("sizing_")).unwrap_or(false)
+            })
+            .endpoint(handle_position_sizing_callback)
+        )
         // Handle language selection callbacks (lang_select_vi, lang_select_en, cancel_language) from any state
         .branch(
             dptree::filter(|q: CallbackQuery| {
@@ -124,6 +132,24 @@ fn schema() -> UpdateHandler<anyhow::Error> {
                 ).unwrap_or(false)
             })
             .endpoint(handle_stop_trading_callback)
+        )
+        // Handle pause / resume / reload buttons of live sessions from any state
+        .branch(
+            dptree::filter(|q: CallbackQuery| {
+                q.data.as_ref().map(|d| 
+                    d.starts_with("pause_session_") ||
+                    d.starts_with("resume_session_") ||
+                    d.starts_with("reload_session_")
+                ).unwrap_or(false)
+            })
+            .endpoint(handle_session_control_callback)
+        )
+        // Handle the "why" button of signal messages from any state
+        .branch(
+            dptree::filter(|q: CallbackQuery| {
+                q.data.as_ref().map(|d| d.starts_with("why_signal_")).unwrap_or(false)
+            })
+            .endpoint(handle_signal_why_callback)
         )
             // Handle live trading callbacks from any state
         .branch(
@@ -213,6 +239,17 @@ async fn main() -> Result<(), anyhow::Error> {
     let bot = Bot::new(&app_state.bot_token);
     tracing::info!("Bot created");
 
+    // Report stored strategies whose conditions no longer carry over as written
+    match app_state.strategy_service.check_stored_strategies().await {
+        Ok(strategies) => {
+            for (strategy, issues) in strategies {
+                warn!("⚠️ Strategy {} ({}) does not translate as stored: {}",
+                    strategy.id, strategy.name.as_deref().unwrap_or("unnamed"), issues.join("; "));
+            }
+        }
+        Err(e) => error!("❌ Failed to check stored strategies: {}", e),
+    }
+
     // Restore active live trading sessions from database
     info!("🔄 Restoring active live trading sessions...");
     if let Err(e) = trading_signal::restore_active_sessions(app_state.clone(), bot.clone()).await {
@@ -231,25 +268,6 @@ async fn main() -> Result<(), anyhow::Error> {
         .enable_ctrlc_handler()
         .build();
 
-    // Start trading signal service if channel ID is configured
-    // if let Ok(channel_id_str) = std::env::var("TRADING_SIGNAL_CHANNEL_ID") {
-    //     if let Ok(channel_id) = channel_id_str.parse::<i64>() {
-    //         let app_state_signal = app_state.clone();
-    //         let bot_signal = bot.clone();
-    //         trading_signal::start_trading_signal_service(
-    //             app_state_signal,
-    //             bot_signal,
-    //             channel_id,
-    //             "BNB/USDT".to_string(),
-    //         );
-    //         info!("✅ Trading Signal Service started for channel: {}", channel_id);
-    //     } else {
-    //         warn!("⚠️  Invalid TRADING_SIGNAL_CHANNEL_ID, trading signals disabled");
-    //     }
-    // } else {
-    //     info!("ℹ️  TRADING_SIGNAL_CHANNEL_ID not set, trading signals disabled");
-    // }
-
     // Check if webhook mode is enabled
     if let Some(webhook_url) = &app_state.config.webhook_url {
         // WEBHOOK MODE
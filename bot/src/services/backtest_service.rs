@@ -0,0 +1,455 @@
+//! Native Backtest Service
+//!
+//! Runs a user's strategy with the freqtrade-rs backtesting engine in process, on
+//! cached historical klines. The strategy is the same implementation live trading
+//! runs, so no strategy file or freqtrade container is involved and results come
+//! back in seconds. The report mimics freqtrade's tables so both engines share the
+//! same result message, HTML report and AI analysis. The strategy is also checked
+//! for lookahead bias and warm-up dependence, which is reported as its own table.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use freqtrade_rs::backtest::{AnalysisReport, BacktestEngine, GroupStats, MetricsCalculator, StrategyAnalyzer};
+use freqtrade_rs::data::{timeframe_duration, CandleSeries};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use crate::services::market_recorder::recorded_candles;
+use crate::services::strategy_engine::{Candle, StrategyConfig, StrategyRegistry};
+use crate::services::warmup_service::fetch_klines_since;
+
+/// Starting balance, the same as the freqtrade dry-run wallet
+const STARTING_BALANCE: f64 = 1000.0;
+
+/// Candles re-checked for lookahead bias, each check replays the series up to it
+const LOOKAHEAD_CHECKS: usize = 10;
+
+/// Cache file of one pair's klines
+fn cache_path(exchange: &str, pair: &str, timeframe: &str) -> PathBuf {
+    let data_path = std::env::var("BACKTEST_DATA_PATH")
+        .unwrap_or_else(|_| "./data/backtest".to_string());
+    PathBuf::from(data_path)
+        .join(exchange)
+        .join(format!("{}-{}.json", pair.replace('/', "_").to_uppercase(), timeframe))
+}
+
+/// Load the closed candles of a pair opened at or after `since` (unix seconds)
+/// Candles are cached on disk and extended with the ones the market recorder saved
+/// (`MARKET_RECORD_DIR`); only the ones still missing are downloaded.
+/// Returns the candles and whether any had to be downloaded.
+pub async fn load_candles(
+    exchange: &str,
+    pair: &str,
+    timeframe: &str,
+    since: i64,
+) -> Result<(Vec<Candle>, bool)> {
+    if exchange != "binance" {
+        bail!("Native backtests only have Binance data, use the freqtrade engine for {}", exchange);
+    }
+
+    let path = cache_path(exchange, pair, timeframe);
+    let mut candles: Vec<Candle> = match tokio::fs::read(&path).await {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+
+    let mut recorded = 0;
+    if let (Ok(dir), Some(period)) = (std::env::var("MARKET_RECORD_DIR"), timeframe_duration(timeframe)) {
+        let from = candles.last().map_or(since, |last| last.timestamp + 1);
+        let candles_recorded = recorded_candles(Path::new(&dir), exchange, pair, timeframe, from).await;
+        recorded = append_recorded(&mut candles, candles_recorded, period.num_seconds());
+        if recorded > 0 {
+            info!("⏺️ Added {} recorded {} candles of {} to the cache", recorded, timeframe, pair);
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()?;
+    let downloaded = update_cache(&mut candles, since, |from| {
+        fetch_klines_since(&client, pair, timeframe, from)
+    }).await?;
+
+    if downloaded || recorded > 0 {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&path, serde_json::to_vec(&candles)?).await?;
+        info!("📥 Cached {} {} candles of {} at {}", candles.len(), timeframe, pair, path.display());
+    }
+
+    candles.retain(|c| c.timestamp >= since);
+    Ok((candles, downloaded))
+}
+
+/// Append the recorded candles continuing the cache without a gap
+/// Returns how many were appended.
+fn append_recorded(candles: &mut Vec<Candle>, recorded: Vec<Candle>, period_secs: i64) -> usize {
+    let before = candles.len();
+    for candle in recorded {
+        match candles.last() {
+            Some(last) if candle.timestamp <= last.timestamp => continue,
+            Some(last) if candle.timestamp != last.timestamp + period_secs => break,
+            _ => candles.push(candle),
+        }
+    }
+    candles.len() - before
+}
+
+/// Bring the cached candles up to date for a range opened at or after `since`
+/// `fetch` downloads the candles opened at or after its argument. The cache is
+/// contiguous: newer candles are appended, a range starting before it is downloaded
+/// again. Returns whether any candles were downloaded.
+async fn update_cache<F, Fut>(candles: &mut Vec<Candle>, since: i64, fetch: F) -> Result<bool>
+where
+    F: FnOnce(i64) -> Fut,
+    Fut: Future<Output = Result<Vec<Candle>>>,
+{
+    match candles.last() {
+        Some(last) if candles[0].timestamp <= since => {
+            let newer = fetch(last.timestamp + 1).await?;
+            let downloaded = !newer.is_empty();
+            candles.extend(newer);
+            Ok(downloaded)
+        }
+        _ => {
+            *candles = fetch(since).await?;
+            Ok(true)
+        }
+    }
+}
+
+/// Backtest a strategy from `start` until now
+/// The strategy's startup candles before `start` are loaded too, so it trades from `start` on.
+pub async fn run_backtest(
+    config: &StrategyConfig,
+    exchange: &str,
+    start: DateTime<Utc>,
+) -> Result<shared::BacktestResult> {
+    let mut strategy = StrategyRegistry::new().create_strategy_impl(config)?;
+    let duration = timeframe_duration(&config.timeframe)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported timeframe: {}", config.timeframe))?;
+    let startup = strategy.startup_candle_count() as i64;
+    let since = start.timestamp() - startup * duration.num_seconds();
+
+    let download_started = Instant::now();
+    let (candles, downloaded) = load_candles(exchange, &config.pair, &config.timeframe, since).await?;
+    let download_time = download_started.elapsed();
+    if candles.is_empty() {
+        bail!("No {} candles of {} since {}", config.timeframe, config.pair, start.format("%Y-%m-%d"));
+    }
+    let series = CandleSeries::from_vec(candles.iter()
+        .map(|c| c.to_freqtrade(&config.pair, &config.timeframe))
+        .collect());
+
+    let backtest_started = Instant::now();
+    let sizing = config.position_sizing()?;
+    let protections = config.protections();
+    let analysis_config = config.clone();
+    let (result, analysis, series) = tokio::task::spawn_blocking(move || {
+        let mut engine = BacktestEngine::new(STARTING_BALANCE)
+            .with_position_sizing(&sizing)?
+            .with_protections(protections);
+        let result = engine.run(strategy.as_mut(), &series)?;
+
+        // Bias analysis is informational, a failure must not cost the user the backtest
+        let registry = StrategyRegistry::new();
+        let analysis = StrategyAnalyzer::new(startup as usize)
+            .with_max_checks(LOOKAHEAD_CHECKS)
+            .analyze(|| registry.create_strategy_impl(&analysis_config), &series)
+            .map_err(|e| warn!("Bias analysis of {} failed: {}", analysis_config.strategy_type, e))
+            .ok();
+        anyhow::Ok((result, analysis, series))
+    }).await??;
+    let backtest_time = backtest_started.elapsed();
+
+    info!("⚡ Native backtest of {} on {} {}: {} candles, {} trades, {:.2}%",
+        config.strategy_type, config.pair, config.timeframe, series.len(), result.num_trades, result.total_return_percent);
+
+    // Startup candles before `start` only warm the strategy up
+    let from = series.candles().first().map_or(start, |c| c.timestamp.max(start));
+    let to = series.candles().last().map(|c| c.timestamp).unwrap_or(start);
+    let mut report = format_report(&result, from, to);
+    if let Some(analysis) = &analysis {
+        report.push('\n');
+        report.push_str(&format_analysis(analysis));
+    }
+    Ok(shared::BacktestResult {
+        strategy: config.strategy_type.clone(),
+        trades: result.num_trades as i32,
+        profit_pct: result.total_return_percent,
+        download_time_secs: downloaded.then_some(download_time.as_secs()),
+        backtest_time_secs: backtest_time.as_secs(),
+        stdout: Some(report),
+        stderr: None,
+        win_rate: Some(result.win_rate),
+        max_drawdown: Some(result.max_drawdown * 100.0),
+        starting_balance: Some(result.start_balance),
+        final_balance: Some(result.end_balance),
+    })
+}
+
+/// Report in freqtrade's table layout, so the report parsing of both engines is shared
+fn format_report(result: &freqtrade_rs::backtest::BacktestResult, from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    let group_rows = |stats: &[GroupStats]| -> Vec<Vec<String>> {
+        stats.iter().map(|group| {
+            let draws = group.trades - group.wins - group.losses;
+            vec![
+                group.key.clone(),
+                group.trades.to_string(),
+                format!("{:.2}", group.avg_pnl_percent),
+                format!("{:.3}", group.total_pnl),
+                format!("{:.2}", group.total_pnl / result.start_balance * 100.0),
+                format!("{} {} {} {:.1}", group.wins, draws, group.losses,
+                    group.wins as f64 / group.trades.max(1) as f64 * 100.0),
+            ]
+        }).collect()
+    };
+    let columns = |key: &'static str, count: &'static str| {
+        [key, count, "Avg Profit %", "Tot Profit USDT", "Tot Profit %", "Win  Draw  Loss  Win%"]
+    };
+
+    let mut pair_rows = group_rows(&result.pair_stats);
+    pair_rows.push(vec![
+        "TOTAL".to_string(),
+        result.num_trades.to_string(),
+        format!("{:.2}", result.pair_stats.iter().map(|g| g.avg_pnl_percent * g.trades as f64).sum::<f64>()
+            / result.num_trades.max(1) as f64),
+        format!("{:.3}", result.total_return),
+        format!("{:.2}", result.total_return_percent),
+        format!("{} {} {} {:.1}", result.winning_trades,
+            result.num_trades - result.winning_trades - result.losing_trades, result.losing_trades, result.win_rate),
+    ]);
+
+    let days = ((to - from).num_seconds() as f64 / 86400.0).max(1.0);
+    let summary_rows: Vec<Vec<String>> = [
+        ("Backtesting from", from.format("%Y-%m-%d %H:%M:%S").to_string()),
+        ("Backtesting to", to.format("%Y-%m-%d %H:%M:%S").to_string()),
+        ("Total/Daily Avg Trades", format!("{} / {:.2}", result.num_trades, result.num_trades as f64 / days)),
+        ("Starting balance", format!("{:.3} USDT", result.start_balance)),
+        ("Final balance", format!("{:.3} USDT", result.end_balance)),
+        ("Absolute profit", format!("{:.3} USDT", result.total_return)),
+        ("Total profit %", format!("{:.2}%", result.total_return_percent)),
+        ("Profit factor", format!("{:.2}", MetricsCalculator::profit_factor(result))),
+        ("Expectancy", format!("{:.3} USDT", MetricsCalculator::expectancy(result))),
+        ("Sharpe", format!("{:.2}", result.sharpe_ratio)),
+        ("Win rate", format!("{:.2}%", result.win_rate)),
+        ("Avg. profit / loss", format!("{:.3} / {:.3} USDT", result.avg_profit, result.avg_loss)),
+        ("Max % of account underwater", format!("{:.2}%", result.max_drawdown * 100.0)),
+        ("Entries rejected by risk limits", result.rejected_entries.to_string()),
+        ("Entries blocked by protections", result.locked_entries.to_string()),
+    ].into_iter().map(|(metric, value)| vec![metric.to_string(), value]).collect();
+
+    [
+        render_table("BACKTESTING REPORT", &columns("Pair", "Trades"), &pair_rows),
+        render_table("ENTER TAG STATS", &columns("Enter Tag", "Entries"), &group_rows(&result.enter_tag_stats)),
+        render_table("EXIT REASON STATS", &columns("Exit Reason", "Exits"), &group_rows(&result.exit_reason_stats)),
+        render_table("SUMMARY METRICS", &["Metric", "Value"], &summary_rows),
+    ].join("\n")
+}
+
+/// Lookahead and recursive analysis findings as a report table
+fn format_analysis(analysis: &AnalysisReport) -> String {
+    let label = |passed: bool| if passed { "PASS" } else { "FAIL" }.to_string();
+    let lookahead = &analysis.lookahead;
+    let recursive = &analysis.recursive;
+    let biased_indicators = lookahead.biased_indicator_names();
+    let rows: Vec<Vec<String>> = [
+        ("Lookahead checked candles", lookahead.checked.to_string()),
+        ("Signals changed by future candles", lookahead.signals.len().to_string()),
+        ("Indicators using future candles", if biased_indicators.is_empty() {
+            "none".to_string()
+        } else {
+            biased_indicators.join(", ")
+        }),
+        ("Lookahead analysis", label(lookahead.passed())),
+        ("Startup candle count", recursive.startup_candle_count.to_string()),
+        ("Minimum stable startup", recursive.min_stable_startup()
+            .map_or_else(|| "none of the tested counts".to_string(), |count| count.to_string())),
+        ("Recursive analysis", label(recursive.passed())),
+    ].into_iter().map(|(metric, value)| vec![metric.to_string(), value]).collect();
+
+    render_table("STRATEGY ANALYSIS REPORT", &["Check", "Result"], &rows)
+}
+
+/// Box-drawn table with a centered title, as freqtrade prints them
+fn render_table(title: &str, headers: &[&str], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = headers.iter().enumerate()
+        .map(|(i, header)| rows.iter()
+            .filter_map(|row| row.get(i))
+            .map(|cell| cell.chars().count())
+            .chain(std::iter::once(header.chars().count()))
+            .max()
+            .unwrap_or(0))
+        .collect();
+    let border = |left: &str, fill: &str, join: &str, right: &str| {
+        let cells: Vec<String> = widths.iter().map(|w| fill.repeat(w + 2)).collect();
+        format!("{}{}{}", left, cells.join(join), right)
+    };
+    let line = |separator: &str, cells: Vec<&str>| {
+        let cells: Vec<String> = cells.iter().zip(&widths)
+            .map(|(cell, w)| format!(" {:>w$} ", cell, w = w))
+            .collect();
+        format!("{}{}{}", separator, cells.join(separator), separator)
+    };
+
+    let top = border("┏", "━", "┳", "┓");
+    let mut lines = vec![
+        format!("{:^w$}", title, w = top.chars().count()).trim_end().to_string(),
+        top,
+        line("┃", headers.to_vec()),
+        border("┡", "━", "╇", "┩"),
+    ];
+    lines.extend(rows.iter().map(|row| line("│", row.iter().map(String::as_str).collect())));
+    lines.push(border("└", "─", "┴", "┘"));
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use freqtrade_rs::backtest::BacktestResult;
+    use std::cell::RefCell;
+
+    fn candle(timestamp: i64) -> Candle {
+        Candle { open: 1.0, high: 1.0, low: 1.0, close: 1.0, volume: 1.0, timestamp }
+    }
+
+    /// Klines of the exchange, one a minute up to 180
+    fn klines_since(from: i64) -> Vec<Candle> {
+        (0..=180).step_by(60).filter(|t| *t >= from).map(candle).collect()
+    }
+
+    fn timestamps(candles: &[Candle]) -> Vec<i64> {
+        candles.iter().map(|c| c.timestamp).collect()
+    }
+
+    fn group(key: &str, trades: usize, wins: usize, losses: usize, total_pnl: f64, avg_pnl_percent: f64) -> GroupStats {
+        GroupStats { key: key.to_string(), trades, wins, losses, total_pnl, avg_pnl_percent }
+    }
+
+    #[test]
+    fn test_render_table() {
+        let rows = vec![
+            vec!["BTC/USDT".to_string(), "3".to_string()],
+            vec!["TOTAL".to_string(), "12".to_string()],
+        ];
+        assert_eq!(render_table("REPORT", &["Pair", "Trades"], &rows), [
+            "       REPORT",
+            "┏━━━━━━━━━━┳━━━━━━━━┓",
+            "┃     Pair ┃ Trades ┃",
+            "┡━━━━━━━━━━╇━━━━━━━━┩",
+            "│ BTC/USDT │      3 │",
+            "│    TOTAL │     12 │",
+            "└──────────┴────────┘",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn test_format_report() {
+        let result = BacktestResult {
+            start_balance: 1000.0,
+            end_balance: 1015.0,
+            total_return: 15.0,
+            total_return_percent: 1.5,
+            num_trades: 3,
+            winning_trades: 2,
+            losing_trades: 1,
+            win_rate: 200.0 / 3.0,
+            avg_profit: 10.0,
+            avg_loss: -5.0,
+            max_drawdown: 0.004,
+            sharpe_ratio: 1.25,
+            rejected_entries: 1,
+            circuit_breakers: Vec::new(),
+            locked_entries: 2,
+            protection_locks: Vec::new(),
+            pair_stats: vec![group("BTC/USDT", 3, 2, 1, 15.0, 0.5)],
+            enter_tag_stats: vec![group("oversold", 3, 2, 1, 15.0, 0.5)],
+            exit_reason_stats: vec![group("roi", 2, 2, 0, 20.0, 1.0), group("stop_loss", 1, 0, 1, -5.0, -0.5)],
+        };
+        let from = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2026, 1, 3, 0, 0, 0).unwrap();
+        let report = format_report(&result, from, to);
+
+        let titles: Vec<&str> = report.lines()
+            .filter(|line| !line.contains(['┃', '│', '━', '─']) && !line.trim().is_empty())
+            .map(str::trim)
+            .collect();
+        assert_eq!(titles, ["BACKTESTING REPORT", "ENTER TAG STATS", "EXIT REASON STATS", "SUMMARY METRICS"]);
+        // Cells of the first row starting with `key`
+        let row = |key: &str| report.lines()
+            .map(|line| line.split('│').map(str::trim).filter(|cell| !cell.is_empty()).collect::<Vec<_>>())
+            .find(|cells| cells.first() == Some(&key))
+            .unwrap_or_default();
+        assert_eq!(row("BTC/USDT"), ["BTC/USDT", "3", "0.50", "15.000", "1.50", "2 0 1 66.7"]);
+        assert_eq!(row("TOTAL"), ["TOTAL", "3", "0.50", "15.000", "1.50", "2 0 1 66.7"]);
+        assert_eq!(row("stop_loss"), ["stop_loss", "1", "-0.50", "-5.000", "-0.50", "0 0 1 0.0"]);
+        let metric = |name: &str| row(name).get(1).map(|value| value.to_string());
+        assert_eq!(metric("Backtesting from").as_deref(), Some("2026-01-01 00:00:00"));
+        assert_eq!(metric("Total/Daily Avg Trades").as_deref(), Some("3 / 1.50"));
+        assert_eq!(metric("Profit factor").as_deref(), Some("4.00"));
+        assert_eq!(metric("Max % of account underwater").as_deref(), Some("0.40%"));
+        assert_eq!(metric("Entries blocked by protections").as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_appends_newer_candles() {
+        let requested = RefCell::new(Vec::new());
+        let fetch = |from: i64| {
+            requested.borrow_mut().push(from);
+            async move { Ok(klines_since(from)) }
+        };
+
+        // Covered from its first candle on, only candles after the last cached one are fetched
+        let mut candles = vec![candle(0), candle(60)];
+        assert!(update_cache(&mut candles, 0, fetch).await.unwrap());
+        assert_eq!(*requested.borrow(), [61]);
+        assert_eq!(timestamps(&candles), [0, 60, 120, 180]);
+
+        // Nothing newer: the cache is left as it is
+        let mut candles = vec![candle(0), candle(60)];
+        let up_to_date = update_cache(&mut candles, 30, |_| async { Ok(Vec::new()) }).await.unwrap();
+        assert!(!up_to_date);
+        assert_eq!(timestamps(&candles), [0, 60]);
+    }
+
+    #[tokio::test]
+    async fn test_update_cache_downloads_ranges_before_the_cache_again() {
+        let requested = RefCell::new(Vec::new());
+        let fetch = |from: i64| {
+            requested.borrow_mut().push(from);
+            async move { Ok(klines_since(from)) }
+        };
+        let mut candles = vec![candle(120), candle(180)];
+        assert!(update_cache(&mut candles, 0, fetch).await.unwrap());
+        assert_eq!(*requested.borrow(), [0]);
+        assert_eq!(timestamps(&candles), [0, 60, 120, 180]);
+
+        // An empty cache is downloaded from `since`
+        let mut candles = Vec::new();
+        assert!(update_cache(&mut candles, 60, |from| async move { Ok(vec![candle(from)]) }).await.unwrap());
+        assert_eq!(timestamps(&candles), [60]);
+    }
+
+    #[test]
+    fn test_append_recorded_continues_the_cache() {
+        // Recorded candles already cached are skipped, the cache stays contiguous at a gap
+        let mut candles = vec![candle(0), candle(60)];
+        let recorded = vec![candle(60), candle(120), candle(180), candle(300), candle(360)];
+        assert_eq!(append_recorded(&mut candles, recorded, 60), 2);
+        assert_eq!(timestamps(&candles), [0, 60, 120, 180]);
+
+        // An empty cache starts at the first recorded candle
+        let mut candles = Vec::new();
+        assert_eq!(append_recorded(&mut candles, vec![candle(120), candle(180)], 60), 2);
+        assert_eq!(timestamps(&candles), [120, 180]);
+
+        let mut candles = vec![candle(0)];
+        assert_eq!(append_recorded(&mut candles, vec![candle(120)], 60), 0);
+        assert_eq!(timestamps(&candles), [0]);
+    }
+}
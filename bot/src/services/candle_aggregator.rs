@@ -0,0 +1,299 @@
+//! Candle Aggregator
+//!
+//! Builds OHLCV candles of one timeframe from public trades. Periods are aligned to the
+//! exchange's trade time, and each period stays open for a short grace window after it
+//! ends so trades delivered late still count. Trades for a period closed already are dropped,
+//! and periods without trades close as flat zero-volume candles at the last close.
+
+use std::collections::BTreeMap;
+use freqtrade_rs::exchange::OrderSide;
+use crate::services::strategy_engine::Candle;
+
+/// Milliseconds a period stays open after its end for trades delivered late
+pub const LATE_TRADE_GRACE_MS: i64 = 2_000;
+
+/// Public trade of a stream
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub price: f64,
+    /// Quantity in base currency
+    pub quantity: f64,
+    /// Aggressor side (Buy when a buyer took the ask)
+    pub side: OrderSide,
+    /// Exchange time of the trade in milliseconds
+    pub time_ms: i64,
+}
+
+impl Trade {
+    /// Exchange time of the trade in seconds
+    pub fn timestamp(&self) -> i64 {
+        self.time_ms.div_euclid(1000)
+    }
+}
+
+/// Candles of one timeframe built from a pair's trades
+#[derive(Debug)]
+pub struct CandleAggregator {
+    period_ms: i64,
+    /// Open periods by start time (ms): the forming one and those still in their grace window
+    open: BTreeMap<i64, Candle>,
+    /// End (ms) of the last closed period, earlier trades are late
+    closed_until: i64,
+    /// Whether the forming candle changed since the last `take_forming_update`
+    updated: bool,
+    /// Trades dropped because their period was closed already
+    late_trades: u64,
+    /// Close of the last closed candle, the price of periods without trades
+    last_close: Option<f64>,
+}
+
+impl CandleAggregator {
+    /// Create an aggregator for a timeframe like "1m", "15m" or "4h" (unknown timeframes use 1m)
+    pub fn new(timeframe: &str) -> Self {
+        let period_ms = freqtrade_rs::data::timeframe_duration(timeframe)
+            .map(|d| d.num_milliseconds())
+            .filter(|ms| *ms > 0)
+            .unwrap_or(60_000);
+        Self {
+            period_ms,
+            open: BTreeMap::new(),
+            closed_until: i64::MIN,
+            updated: false,
+            late_trades: 0,
+            last_close: None,
+        }
+    }
+
+    /// Start (ms) of the period containing `time_ms`
+    fn period_start(&self, time_ms: i64) -> i64 {
+        time_ms.div_euclid(self.period_ms) * self.period_ms
+    }
+
+    /// Add a trade to the candle of its period
+    /// Returns false if the period was closed already and the trade is dropped.
+    pub fn add_trade(&mut self, trade: &Trade) -> bool {
+        if trade.time_ms < self.closed_until {
+            self.late_trades += 1;
+            return false;
+        }
+        let start = self.period_start(trade.time_ms);
+        self.open.entry(start)
+            .and_modify(|candle| {
+                candle.high = candle.high.max(trade.price);
+                candle.low = candle.low.min(trade.price);
+                candle.close = trade.price;
+                candle.volume += trade.quantity;
+            })
+            .or_insert_with(|| Candle {
+                open: trade.price,
+                high: trade.price,
+                low: trade.price,
+                close: trade.price,
+                volume: trade.quantity,
+                timestamp: start.div_euclid(1000),
+            });
+        self.updated = true;
+        true
+    }
+
+    /// Merge a candle built before this aggregator started (e.g. checkpointed before a restart)
+    /// into its period, as the earlier part of it
+    pub fn merge_earlier(&mut self, earlier: Candle) {
+        let start = self.period_start(earlier.timestamp * 1000);
+        if start + self.period_ms <= self.closed_until {
+            return;
+        }
+        self.open.entry(start)
+            .and_modify(|candle| {
+                candle.open = earlier.open;
+                candle.high = candle.high.max(earlier.high);
+                candle.low = candle.low.min(earlier.low);
+                candle.volume += earlier.volume;
+            })
+            .or_insert(earlier);
+        self.updated = true;
+    }
+
+    /// Close the periods whose grace window ended by `now_ms` (exchange time)
+    /// Returns the closed candles, oldest first, with a flat zero-volume candle for each
+    /// period without trades after the first closed one.
+    pub fn close_until(&mut self, now_ms: i64) -> Vec<Candle> {
+        let mut closed = Vec::new();
+        let mut start = match self.open.keys().next() {
+            _ if self.closed_until != i64::MIN => self.closed_until,
+            Some(first) => *first,
+            None => return closed,
+        };
+        while start + self.period_ms + LATE_TRADE_GRACE_MS <= now_ms {
+            let candle = self.open.remove(&start).or_else(|| {
+                self.last_close.map(|close| Candle {
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 0.0,
+                    timestamp: start.div_euclid(1000),
+                })
+            });
+            if let Some(candle) = candle {
+                self.last_close = Some(candle.close);
+                closed.push(candle);
+            }
+            start += self.period_ms;
+            self.closed_until = start;
+        }
+        closed
+    }
+
+    /// The forming candle if a trade changed it since the last call
+    pub fn take_forming_update(&mut self) -> Option<Candle> {
+        if !std::mem::take(&mut self.updated) {
+            return None;
+        }
+        self.open.last_key_value().map(|(_, candle)| candle.clone())
+    }
+
+    /// Start (unix seconds) of the first period an outage may have left incomplete:
+    /// the oldest open period, or the one after the last closed period
+    pub fn backfill_since(&self) -> Option<i64> {
+        self.open.keys().next().copied()
+            .or((self.closed_until != i64::MIN).then_some(self.closed_until))
+            .map(|ms| ms.div_euclid(1000))
+    }
+
+    /// Take closed candles fetched from the exchange after an outage in place of the
+    /// incomplete ones; every period up to the last backfilled candle is closed
+    pub fn apply_backfill(&mut self, candles: &[Candle]) {
+        let Some(last) = candles.last() else {
+            return;
+        };
+        let end = last.timestamp * 1000 + self.period_ms;
+        self.open.retain(|start, _| *start >= end);
+        if end >= self.closed_until {
+            self.closed_until = end;
+            self.last_close = Some(last.close);
+        }
+    }
+
+    /// Trades dropped so far because they arrived after their period closed
+    pub fn late_trades(&self) -> u64 {
+        self.late_trades
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: i64 = 60_000;
+
+    fn trade(price: f64, quantity: f64, time_ms: i64) -> Trade {
+        Trade { price, quantity, side: OrderSide::Buy, time_ms }
+    }
+
+    fn candle(open: f64, high: f64, low: f64, close: f64, volume: f64, timestamp: i64) -> Candle {
+        Candle { open, high, low, close, volume, timestamp }
+    }
+
+    fn ohlcv(candle: &Candle) -> (f64, f64, f64, f64, f64, i64) {
+        (candle.open, candle.high, candle.low, candle.close, candle.volume, candle.timestamp)
+    }
+
+    #[test]
+    fn test_periods_close_after_the_grace_window() {
+        let mut aggregator = CandleAggregator::new("1m");
+        assert!(aggregator.add_trade(&trade(100.0, 1.0, 1_000)));
+        assert!(aggregator.add_trade(&trade(104.0, 0.5, 20_000)));
+        assert!(aggregator.add_trade(&trade(99.0, 0.5, 59_999)));
+
+        // Still open during the grace window, a trade delivered late counts
+        assert!(aggregator.close_until(MINUTE_MS + LATE_TRADE_GRACE_MS - 1).is_empty());
+        assert!(aggregator.add_trade(&trade(101.0, 1.0, 59_000)));
+        assert!(aggregator.add_trade(&trade(102.0, 1.0, MINUTE_MS + 500)));
+
+        let closed = aggregator.close_until(MINUTE_MS + LATE_TRADE_GRACE_MS);
+        assert_eq!(closed.iter().map(ohlcv).collect::<Vec<_>>(), [(100.0, 104.0, 99.0, 101.0, 3.0, 0)]);
+        assert_eq!(aggregator.take_forming_update().map(|c| ohlcv(&c)), Some((102.0, 102.0, 102.0, 102.0, 1.0, 60)));
+        assert_eq!(aggregator.take_forming_update().map(|c| c.timestamp), None, "unchanged since the last update");
+    }
+
+    #[test]
+    fn test_late_trades_are_dropped() {
+        let mut aggregator = CandleAggregator::new("1m");
+        aggregator.add_trade(&trade(100.0, 1.0, 1_000));
+        assert_eq!(aggregator.close_until(MINUTE_MS + LATE_TRADE_GRACE_MS).len(), 1);
+
+        assert!(!aggregator.add_trade(&trade(90.0, 1.0, 59_000)));
+        assert!(aggregator.add_trade(&trade(101.0, 1.0, MINUTE_MS)));
+        assert_eq!(aggregator.late_trades(), 1);
+        let closed = aggregator.close_until(2 * MINUTE_MS + LATE_TRADE_GRACE_MS);
+        assert_eq!(closed.iter().map(ohlcv).collect::<Vec<_>>(), [(101.0, 101.0, 101.0, 101.0, 1.0, 60)]);
+    }
+
+    #[test]
+    fn test_empty_periods_close_flat() {
+        let mut aggregator = CandleAggregator::new("1m");
+        assert!(aggregator.close_until(10 * MINUTE_MS).is_empty(), "nothing to close before the first trade");
+
+        aggregator.add_trade(&trade(100.0, 1.0, 1_000));
+        aggregator.add_trade(&trade(103.0, 1.0, 3 * MINUTE_MS + 1_000));
+        let closed = aggregator.close_until(4 * MINUTE_MS + LATE_TRADE_GRACE_MS);
+        assert_eq!(closed.iter().map(ohlcv).collect::<Vec<_>>(), [
+            (100.0, 100.0, 100.0, 100.0, 1.0, 0),
+            (100.0, 100.0, 100.0, 100.0, 0.0, 60),
+            (100.0, 100.0, 100.0, 100.0, 0.0, 120),
+            (103.0, 103.0, 103.0, 103.0, 1.0, 180),
+        ]);
+
+        // Quiet periods keep closing without trades
+        let closed = aggregator.close_until(6 * MINUTE_MS + LATE_TRADE_GRACE_MS);
+        assert_eq!(closed.iter().map(ohlcv).collect::<Vec<_>>(), [
+            (103.0, 103.0, 103.0, 103.0, 0.0, 240),
+            (103.0, 103.0, 103.0, 103.0, 0.0, 300),
+        ]);
+    }
+
+    #[test]
+    fn test_merge_earlier_part_of_a_period() {
+        let mut aggregator = CandleAggregator::new("1m");
+        aggregator.add_trade(&trade(102.0, 1.0, 40_000));
+        aggregator.add_trade(&trade(101.0, 1.0, 50_000));
+        // Checkpointed before a restart: opened the period and saw its high
+        aggregator.merge_earlier(candle(98.0, 110.0, 99.0, 100.0, 2.0, 0));
+        assert_eq!(aggregator.take_forming_update().map(|c| ohlcv(&c)), Some((98.0, 110.0, 99.0, 101.0, 4.0, 0)));
+
+        // A period without trades yet takes the checkpoint as it is
+        aggregator.merge_earlier(candle(101.0, 102.0, 100.0, 100.5, 1.0, 60));
+        let closed = aggregator.close_until(2 * MINUTE_MS + LATE_TRADE_GRACE_MS);
+        assert_eq!(closed.iter().map(ohlcv).collect::<Vec<_>>(), [
+            (98.0, 110.0, 99.0, 101.0, 4.0, 0),
+            (101.0, 102.0, 100.0, 100.5, 1.0, 60),
+        ]);
+
+        // Closed periods ignore checkpoints
+        aggregator.merge_earlier(candle(1.0, 1.0, 1.0, 1.0, 1.0, 60));
+        assert_eq!(aggregator.take_forming_update().map(|c| c.timestamp), None);
+    }
+
+    #[test]
+    fn test_apply_backfill_replaces_incomplete_periods() {
+        let mut aggregator = CandleAggregator::new("1m");
+        aggregator.add_trade(&trade(100.0, 1.0, 1_000));
+        assert_eq!(aggregator.close_until(MINUTE_MS + LATE_TRADE_GRACE_MS).len(), 1);
+        assert_eq!(aggregator.backfill_since(), Some(60));
+
+        // Outage: the period at 60 saw one trade before the connection dropped
+        aggregator.add_trade(&trade(101.0, 1.0, MINUTE_MS + 1_000));
+        assert_eq!(aggregator.backfill_since(), Some(60));
+        aggregator.apply_backfill(&[
+            candle(100.0, 105.0, 99.0, 104.0, 10.0, 60),
+            candle(104.0, 106.0, 103.0, 105.0, 8.0, 120),
+        ]);
+        assert_eq!(aggregator.backfill_since(), Some(180));
+        assert!(!aggregator.add_trade(&trade(103.0, 1.0, 2 * MINUTE_MS + 30_000)), "backfilled periods are closed");
+
+        // The next quiet period closes flat at the backfilled close
+        let closed = aggregator.close_until(4 * MINUTE_MS + LATE_TRADE_GRACE_MS);
+        assert_eq!(closed.iter().map(ohlcv).collect::<Vec<_>>(), [(105.0, 105.0, 105.0, 105.0, 0.0, 180)]);
+    }
+}
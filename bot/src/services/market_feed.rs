@@ -0,0 +1,496 @@
+//! Market Feed
+//!
+//! Sources of the public trades the market streams are built from. The live source is
+//! a set of Binance WebSocket connections (see `market_replay` for recorded data).
+//! Connections run as tasks on the shared runtime and carry up to
+//! `MAX_PAIRS_PER_CONNECTION` pairs each; pairs are subscribed and unsubscribed on the
+//! open connection. Dropped or stale connections reconnect with backoff and resubscribe
+//! their pairs; a stream without trades on a live connection is resubscribed on its own.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+use freqtrade_rs::exchange::OrderSide;
+use crate::services::candle_aggregator::Trade;
+
+const BINANCE_WS_URL: &str = "wss://stream.binance.com:9443/stream";
+
+/// Binance allows 1024 streams per connection
+const MAX_PAIRS_PER_CONNECTION: usize = 1024;
+
+/// Seconds without any frame (Binance pings every 20s) after which a connection is
+/// considered stale and reconnected
+const STALE_CONNECTION_SECS: u64 = 60;
+
+/// Seconds without trades after which a stream is considered stale and resubscribed,
+/// while the connection itself may still be alive
+const STALE_STREAM_SECS: u64 = 60;
+
+/// Longest wait between two reconnect attempts
+const MAX_RECONNECT_BACKOFF_SECS: u64 = 60;
+
+/// Event of a market data source
+#[derive(Debug)]
+pub enum FeedEvent {
+    /// Trade of a subscribed pair ("BTC/USDT")
+    Trade {
+        pair: String,
+        trade: Trade,
+        /// Local time the trade was received, in milliseconds
+        received_ms: i64,
+    },
+    /// Pairs are delivered (subscribed on a live connection); `reconnect` after an outage
+    Connected { pairs: Vec<String>, reconnect: bool },
+    /// A connection dropped, its pairs get no trades until it reconnects
+    Disconnected { pairs: Vec<String>, reason: String },
+}
+
+/// Source of the market data of one exchange's pairs, delivered as [`FeedEvent`]s
+/// to the channel the source was created with
+pub trait MarketDataSource: Send + Sync {
+    /// Exchange whose pairs the source delivers
+    fn exchange(&self) -> &str;
+
+    /// Start delivering a pair ("BTC/USDT")
+    fn add(&self, pair: &str);
+
+    /// Stop delivering a pair
+    fn remove(&self, pair: &str);
+}
+
+/// Subscription change sent to a connection task
+enum Command {
+    Subscribe(String),
+    Unsubscribe(String),
+}
+
+/// Handle of a connection task, which stops once the handle is dropped
+struct Connection {
+    pairs: HashSet<String>,
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+/// Public trades of Binance spot pairs, multiplexed over as few connections as possible
+pub struct BinanceTradeFeed {
+    connections: Mutex<Vec<Connection>>,
+    events: mpsc::UnboundedSender<FeedEvent>,
+}
+
+impl BinanceTradeFeed {
+    /// Create a feed sending its events to `events`
+    pub fn new(events: mpsc::UnboundedSender<FeedEvent>) -> Self {
+        Self {
+            connections: Mutex::new(Vec::new()),
+            events,
+        }
+    }
+}
+
+impl MarketDataSource for BinanceTradeFeed {
+    fn exchange(&self) -> &str {
+        "binance"
+    }
+
+    /// Subscribe the trades of a pair, opening a connection if all are full
+    fn add(&self, pair: &str) {
+        let mut connections = self.connections.lock().unwrap();
+        if connections.iter().any(|c| c.pairs.contains(pair)) {
+            return;
+        }
+        let index = match connections.iter().position(|c| c.pairs.len() < MAX_PAIRS_PER_CONNECTION) {
+            Some(index) => index,
+            None => {
+                let (commands, receiver) = mpsc::unbounded_channel();
+                tokio::spawn(run_connection(receiver, self.events.clone()));
+                connections.push(Connection { pairs: HashSet::new(), commands });
+                info!("Opened Binance trade connection #{}", connections.len());
+                connections.len() - 1
+            }
+        };
+        connections[index].pairs.insert(pair.to_string());
+        let _ = connections[index].commands.send(Command::Subscribe(pair.to_string()));
+    }
+
+    /// Unsubscribe the trades of a pair, closing its connection if no pair is left on it
+    fn remove(&self, pair: &str) {
+        let mut connections = self.connections.lock().unwrap();
+        let Some(index) = connections.iter().position(|c| c.pairs.contains(pair)) else {
+            return;
+        };
+        connections[index].pairs.remove(pair);
+        let _ = connections[index].commands.send(Command::Unsubscribe(pair.to_string()));
+        if connections[index].pairs.is_empty() {
+            connections.remove(index);
+            info!("Closed Binance trade connection, {} left", connections.len());
+        }
+    }
+}
+
+/// Binance stream name of a pair's trades ("BTC/USDT" -> "btcusdt@trade")
+fn trade_stream(pair: &str) -> String {
+    format!("{}@trade", pair.replace('/', "").to_lowercase())
+}
+
+/// SUBSCRIBE / UNSUBSCRIBE request for some streams
+fn request(method: &str, streams: Vec<String>, id: u64) -> Message {
+    Message::text(serde_json::json!({ "method": method, "params": streams, "id": id }).to_string())
+}
+
+/// Combined stream payload
+#[derive(Deserialize)]
+struct StreamMessage {
+    stream: String,
+    data: TradeData,
+}
+
+/// Trade of the `<symbol>@trade` stream
+#[derive(Deserialize)]
+struct TradeData {
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    /// Trade time (ms)
+    #[serde(rename = "T")]
+    time_ms: i64,
+    /// Whether the buyer was the maker (the aggressor sold)
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,
+}
+
+impl TradeData {
+    fn into_trade(self) -> Option<Trade> {
+        Some(Trade {
+            price: self.price.parse().ok()?,
+            quantity: self.quantity.parse().ok()?,
+            side: if self.buyer_is_maker { OrderSide::Sell } else { OrderSide::Buy },
+            time_ms: self.time_ms,
+        })
+    }
+}
+
+/// Subscription changes of a live connection not sent yet
+/// They are batched into one SUBSCRIBE and one UNSUBSCRIBE request at a time.
+#[derive(Debug, Default)]
+struct PendingChanges {
+    subscribe: HashSet<String>,
+    unsubscribe: HashSet<String>,
+}
+
+impl PendingChanges {
+    /// Apply a subscription change to the connection's pairs (by stream name) and queue
+    /// the request it needs; a pair subscribed and unsubscribed before it was sent needs none
+    fn apply(&mut self, pairs: &mut HashMap<String, String>, command: Command) {
+        match command {
+            Command::Subscribe(pair) => {
+                let stream = trade_stream(&pair);
+                self.unsubscribe.remove(&stream);
+                if pairs.insert(stream.clone(), pair).is_none() {
+                    self.subscribe.insert(stream);
+                }
+            }
+            Command::Unsubscribe(pair) => {
+                let stream = trade_stream(&pair);
+                if !self.subscribe.remove(&stream) && pairs.contains_key(&stream) {
+                    self.unsubscribe.insert(stream.clone());
+                }
+                pairs.remove(&stream);
+            }
+        }
+    }
+
+    /// Take the streams to subscribe and to unsubscribe, each sorted
+    fn take(&mut self) -> (Vec<String>, Vec<String>) {
+        let mut subscribe: Vec<String> = self.subscribe.drain().collect();
+        let mut unsubscribe: Vec<String> = self.unsubscribe.drain().collect();
+        subscribe.sort();
+        unsubscribe.sort();
+        (subscribe, unsubscribe)
+    }
+}
+
+/// Streams without a trade for `STALE_STREAM_SECS`, sorted; their clocks restart so they
+/// are resubscribed once per quiet period. Streams subscribed since the last check start
+/// their clock, unsubscribed ones are dropped.
+fn stale_streams(last_trade: &mut HashMap<String, Instant>, pairs: &HashMap<String, String>, now: Instant) -> Vec<String> {
+    last_trade.retain(|stream, _| pairs.contains_key(stream));
+    let mut stale = Vec::new();
+    for stream in pairs.keys() {
+        let last = last_trade.entry(stream.clone()).or_insert(now);
+        if now.duration_since(*last).as_secs() >= STALE_STREAM_SECS {
+            *last = now;
+            stale.push(stream.clone());
+        }
+    }
+    stale.sort();
+    stale
+}
+
+/// Keep a connection subscribed to its pairs until its handle is dropped
+/// Subscription changes are batched into one request per second (Binance allows 5).
+async fn run_connection(
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    events: mpsc::UnboundedSender<FeedEvent>,
+) {
+    // Pair of each subscribed stream name
+    let mut pairs: HashMap<String, String> = HashMap::new();
+    let mut backoff_secs = 1;
+    let mut was_connected = false;
+    let mut request_id = 0u64;
+
+    loop {
+        let started = Instant::now();
+        let (reason, was_live) = match tokio_tungstenite::connect_async(BINANCE_WS_URL).await {
+            Ok((socket, _)) => {
+                let (mut write, mut read) = socket.split();
+                request_id += 1;
+                if !pairs.is_empty() {
+                    if let Err(e) = write.send(request("SUBSCRIBE", pairs.keys().cloned().collect(), request_id)).await {
+                        warn!("Failed to subscribe {} Binance trade streams: {}", pairs.len(), e);
+                    }
+                }
+                let _ = events.send(FeedEvent::Connected {
+                    pairs: pairs.values().cloned().collect(),
+                    reconnect: was_connected,
+                });
+                was_connected = true;
+
+                let mut pending = PendingChanges::default();
+                let mut ticker = interval(Duration::from_secs(1));
+                let mut last_frame = Instant::now();
+                // Time of each stream's last trade, the connection's frames include pings
+                let mut last_trade: HashMap<String, Instant> = HashMap::new();
+                let reason = loop {
+                    tokio::select! {
+                        command = commands.recv() => match command {
+                            Some(command) => pending.apply(&mut pairs, command),
+                            None => {
+                                let _ = write.send(Message::Close(None)).await;
+                                return;
+                            }
+                        },
+                        frame = read.next() => match frame {
+                            Some(Ok(Message::Text(text))) => {
+                                last_frame = Instant::now();
+                                match serde_json::from_str::<StreamMessage>(&text) {
+                                    Ok(message) => {
+                                        let pair = pairs.get(&message.stream).cloned();
+                                        if pair.is_some() {
+                                            last_trade.insert(message.stream, last_frame);
+                                        }
+                                        if let (Some(pair), Some(trade)) = (pair, message.data.into_trade()) {
+                                            let _ = events.send(FeedEvent::Trade {
+                                                pair,
+                                                trade,
+                                                received_ms: chrono::Utc::now().timestamp_millis(),
+                                            });
+                                        }
+                                    }
+                                    // Replies to (un)subscribe requests
+                                    Err(_) => debug!("Binance trade connection: {}", text.as_str()),
+                                }
+                            }
+                            Some(Ok(Message::Close(frame))) => break format!("closed by exchange ({:?})", frame),
+                            Some(Ok(_)) => last_frame = Instant::now(),
+                            Some(Err(e)) => break e.to_string(),
+                            None => break "connection ended".to_string(),
+                        },
+                        _ = ticker.tick() => {
+                            if last_frame.elapsed().as_secs() >= STALE_CONNECTION_SECS {
+                                break format!("no data for {}s", last_frame.elapsed().as_secs());
+                            }
+                            let stale = stale_streams(&mut last_trade, &pairs, Instant::now());
+                            if !stale.is_empty() {
+                                // Subscribers are told the pairs were down so the resubscription backfills them
+                                let stale_pairs: Vec<String> = stale.iter().filter_map(|s| pairs.get(s).cloned()).collect();
+                                warn!("No trades of {} Binance streams for {}s, resubscribing", stale.len(), STALE_STREAM_SECS);
+                                let _ = events.send(FeedEvent::Disconnected {
+                                    pairs: stale_pairs.clone(),
+                                    reason: format!("no trades for {}s", STALE_STREAM_SECS),
+                                });
+                                request_id += 1;
+                                if let Err(e) = write.send(request("UNSUBSCRIBE", stale.clone(), request_id)).await {
+                                    break e.to_string();
+                                }
+                                request_id += 1;
+                                if let Err(e) = write.send(request("SUBSCRIBE", stale, request_id)).await {
+                                    break e.to_string();
+                                }
+                                let _ = events.send(FeedEvent::Connected { pairs: stale_pairs, reconnect: true });
+                            }
+                            let (subscribe, unsubscribe) = pending.take();
+                            if !subscribe.is_empty() {
+                                request_id += 1;
+                                let added = subscribe.iter().filter_map(|s| pairs.get(s).cloned()).collect();
+                                if let Err(e) = write.send(request("SUBSCRIBE", subscribe, request_id)).await {
+                                    break e.to_string();
+                                }
+                                let _ = events.send(FeedEvent::Connected { pairs: added, reconnect: false });
+                            }
+                            if !unsubscribe.is_empty() {
+                                request_id += 1;
+                                if let Err(e) = write.send(request("UNSUBSCRIBE", unsubscribe, request_id)).await {
+                                    break e.to_string();
+                                }
+                            }
+                        }
+                    }
+                };
+                (reason, true)
+            }
+            Err(e) => (e.to_string(), false),
+        };
+
+        // Subscribers are told once per outage, not on every failed attempt
+        if was_live {
+            let _ = events.send(FeedEvent::Disconnected {
+                pairs: pairs.values().cloned().collect(),
+                reason: reason.clone(),
+            });
+        }
+        // A connection that ran for a while starts over with a short backoff
+        if started.elapsed().as_secs() > MAX_RECONNECT_BACKOFF_SECS {
+            backoff_secs = 1;
+        }
+        warn!("Binance trade connection with {} pairs is down ({}), reconnecting in {}s", pairs.len(), reason, backoff_secs);
+
+        // Keep track of subscription changes while waiting
+        let reconnect_at = tokio::time::sleep(Duration::from_secs(backoff_secs));
+        tokio::pin!(reconnect_at);
+        loop {
+            tokio::select! {
+                _ = &mut reconnect_at => break,
+                command = commands.recv() => match command {
+                    Some(Command::Subscribe(pair)) => {
+                        pairs.insert(trade_stream(&pair), pair);
+                    }
+                    Some(Command::Unsubscribe(pair)) => {
+                        pairs.remove(&trade_stream(&pair));
+                    }
+                    None => return,
+                },
+            }
+        }
+        backoff_secs = (backoff_secs * 2).min(MAX_RECONNECT_BACKOFF_SECS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscribe(pair: &str) -> Command {
+        Command::Subscribe(pair.to_string())
+    }
+
+    fn unsubscribe(pair: &str) -> Command {
+        Command::Unsubscribe(pair.to_string())
+    }
+
+    #[test]
+    fn test_trade_stream_names() {
+        assert_eq!(trade_stream("BTC/USDT"), "btcusdt@trade");
+        assert_eq!(trade_stream("ETHBTC"), "ethbtc@trade");
+    }
+
+    #[test]
+    fn test_request_payload() {
+        let Message::Text(text) = request("SUBSCRIBE", vec!["btcusdt@trade".to_string()], 3) else {
+            panic!("requests are text frames");
+        };
+        let payload: serde_json::Value = serde_json::from_str(text.as_str()).unwrap();
+        assert_eq!(payload, serde_json::json!({ "method": "SUBSCRIBE", "params": ["btcusdt@trade"], "id": 3 }));
+    }
+
+    #[test]
+    fn test_trade_data_parsing() {
+        let text = r#"{"stream":"btcusdt@trade","data":{"e":"trade","E":1700000000123,"s":"BTCUSDT","t":42,
+            "p":"37000.50","q":"0.0125","T":1700000000100,"m":true,"M":true}}"#;
+        let message: StreamMessage = serde_json::from_str(text).unwrap();
+        assert_eq!(message.stream, "btcusdt@trade");
+        let trade = message.data.into_trade().unwrap();
+        assert_eq!((trade.price, trade.quantity, trade.time_ms), (37000.5, 0.0125, 1_700_000_000_100));
+        assert_eq!(trade.side, OrderSide::Sell, "the buyer was the maker, so the aggressor sold");
+
+        let taker_buy = r#"{"p":"1.5","q":"2","T":5,"m":false}"#;
+        let trade = serde_json::from_str::<TradeData>(taker_buy).unwrap().into_trade().unwrap();
+        assert_eq!(trade.side, OrderSide::Buy);
+
+        let malformed = r#"{"p":"n/a","q":"2","T":5,"m":false}"#;
+        assert!(serde_json::from_str::<TradeData>(malformed).unwrap().into_trade().is_none());
+        // Replies to subscription requests are no stream messages
+        assert!(serde_json::from_str::<StreamMessage>(r#"{"result":null,"id":1}"#).is_err());
+    }
+
+    #[test]
+    fn test_pending_changes_are_batched() {
+        let mut pairs = HashMap::new();
+        let mut pending = PendingChanges::default();
+        for command in [subscribe("BTC/USDT"), subscribe("ETH/USDT"), subscribe("BTC/USDT"), subscribe("SOL/USDT")] {
+            pending.apply(&mut pairs, command);
+        }
+        assert_eq!(pending.take(), (
+            vec!["btcusdt@trade".to_string(), "ethusdt@trade".to_string(), "solusdt@trade".to_string()],
+            Vec::new(),
+        ));
+        assert_eq!(pairs.get("ethusdt@trade").map(String::as_str), Some("ETH/USDT"));
+        assert_eq!(pending.take(), (Vec::new(), Vec::new()), "sent changes are not repeated");
+
+        pending.apply(&mut pairs, unsubscribe("ETH/USDT"));
+        pending.apply(&mut pairs, unsubscribe("XRP/USDT"));
+        assert_eq!(pending.take(), (Vec::new(), vec!["ethusdt@trade".to_string()]), "only subscribed streams are unsubscribed");
+        assert!(!pairs.contains_key("ethusdt@trade"));
+    }
+
+    #[test]
+    fn test_quiet_streams_are_stale() {
+        let pairs = HashMap::from([
+            ("btcusdt@trade".to_string(), "BTC/USDT".to_string()),
+            ("ethusdt@trade".to_string(), "ETH/USDT".to_string()),
+        ]);
+        let start = Instant::now();
+        let later = |secs| start + Duration::from_secs(secs);
+        let mut last_trade = HashMap::from([("xrpusdt@trade".to_string(), start)]);
+
+        assert!(stale_streams(&mut last_trade, &pairs, start).is_empty(), "new streams start their clock");
+        assert!(!last_trade.contains_key("xrpusdt@trade"), "unsubscribed streams are dropped");
+
+        // BTC keeps trading while ETH goes quiet
+        last_trade.insert("btcusdt@trade".to_string(), later(30));
+        assert!(stale_streams(&mut last_trade, &pairs, later(STALE_STREAM_SECS - 1)).is_empty());
+        assert_eq!(stale_streams(&mut last_trade, &pairs, later(STALE_STREAM_SECS)), vec!["ethusdt@trade".to_string()]);
+        assert!(
+            stale_streams(&mut last_trade, &pairs, later(STALE_STREAM_SECS + 1)).is_empty(),
+            "a resubscribed stream gets another quiet period",
+        );
+        assert_eq!(
+            stale_streams(&mut last_trade, &pairs, later(2 * STALE_STREAM_SECS + 30)),
+            vec!["btcusdt@trade".to_string(), "ethusdt@trade".to_string()],
+        );
+    }
+
+    #[test]
+    fn test_pending_changes_cancel_out() {
+        let mut pairs = HashMap::from([("btcusdt@trade".to_string(), "BTC/USDT".to_string())]);
+        let mut pending = PendingChanges::default();
+
+        // Subscribed and unsubscribed before the next request: nothing to send
+        pending.apply(&mut pairs, subscribe("ETH/USDT"));
+        pending.apply(&mut pairs, unsubscribe("ETH/USDT"));
+        assert_eq!(pending.take(), (Vec::new(), Vec::new()));
+        assert_eq!(pairs.len(), 1);
+
+        // Unsubscribed and subscribed again: the stream stays, and is subscribed again so the
+        // pair is reported connected to its new stream
+        pending.apply(&mut pairs, unsubscribe("BTC/USDT"));
+        pending.apply(&mut pairs, subscribe("BTC/USDT"));
+        assert_eq!(pending.take(), (vec!["btcusdt@trade".to_string()], Vec::new()));
+        assert_eq!(pairs.len(), 1);
+        assert!(pairs.contains_key("btcusdt@trade"));
+    }
+}
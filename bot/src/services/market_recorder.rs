@@ -0,0 +1,352 @@
+//! Market Recorder
+//!
+//! Optionally persists what the market streams see: every trade and every candle the
+//! streams close, as JSONL in the format `market_replay` reads. Files rotate daily into
+//! `<dir>/<exchange>/<YYYY-MM-DD>/` (UTC, by event time), so a day can be replayed by
+//! pointing `MARKET_REPLAY_DIR` at it. Days older than the retention are deleted.
+//! Native backtests read the recorded candles too, so they download less.
+//!
+//! Records are queued without waiting for the disk: when the writer falls behind,
+//! records are dropped and counted rather than slowing down the streams.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use chrono::{DateTime, NaiveDate, Utc};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::time::{interval, Duration};
+use tracing::{info, warn};
+use freqtrade_rs::exchange::OrderSide;
+use crate::services::candle_aggregator::Trade;
+use crate::services::strategy_engine::Candle;
+
+/// Seconds between flushes of the open files
+const FLUSH_INTERVAL_SECS: u64 = 5;
+
+/// Records queued for the writer before new ones are dropped
+const RECORD_QUEUE_SIZE: usize = 10_000;
+
+/// Market data to record
+enum Record {
+    Trade {
+        exchange: String,
+        pair: String,
+        trade: Trade,
+    },
+    Candle {
+        exchange: String,
+        pair: String,
+        timeframe: String,
+        candle: Candle,
+    },
+}
+
+impl Record {
+    /// File of the record (relative to the recording directory) and its line
+    fn to_line(&self) -> (PathBuf, String) {
+        let (exchange, pair, time_ms, suffix, line) = match self {
+            Record::Trade { exchange, pair, trade } => {
+                let side = match trade.side {
+                    OrderSide::Buy => "buy",
+                    OrderSide::Sell => "sell",
+                };
+                let line = serde_json::json!({
+                    "time_ms": trade.time_ms,
+                    "price": trade.price,
+                    "quantity": trade.quantity,
+                    "side": side,
+                });
+                (exchange, pair, trade.time_ms, "trades", line)
+            }
+            Record::Candle { exchange, pair, timeframe, candle } => {
+                let line = serde_json::to_value(candle).unwrap_or_default();
+                (exchange, pair, candle.timestamp * 1000, timeframe.as_str(), line)
+            }
+        };
+        let day = DateTime::from_timestamp_millis(time_ms)
+            .unwrap_or_else(Utc::now)
+            .format("%Y-%m-%d")
+            .to_string();
+        (PathBuf::from(exchange).join(day).join(file_name(pair, suffix)), line.to_string())
+    }
+}
+
+/// Recording file of a pair's trades ("trades") or candles (their timeframe) within a day
+fn file_name(pair: &str, suffix: &str) -> String {
+    format!("{}-{}.jsonl", pair.replace('/', "_").to_uppercase(), suffix)
+}
+
+/// Handle of the task writing the recordings
+#[derive(Clone)]
+pub struct MarketRecorder {
+    records: mpsc::Sender<Record>,
+    /// Records dropped because the writer fell behind
+    dropped: Arc<AtomicU64>,
+}
+
+impl MarketRecorder {
+    /// Start recording into `dir`, keeping `retention_days` days
+    /// Must be called within the tokio runtime.
+    pub fn start(dir: impl Into<PathBuf>, retention_days: u32) -> Self {
+        let (records, receiver) = mpsc::channel(RECORD_QUEUE_SIZE);
+        let dropped = Arc::new(AtomicU64::new(0));
+        tokio::spawn(write_records(dir.into(), retention_days, receiver, dropped.clone()));
+        Self { records, dropped }
+    }
+
+    /// Queue a record for the writer, or drop it if the queue is full
+    fn send(&self, record: Record) {
+        if let Err(TrySendError::Full(_)) = self.records.try_send(record) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Record a trade of a pair ("BTC/USDT")
+    pub fn record_trade(&self, exchange: &str, pair: &str, trade: &Trade) {
+        self.send(Record::Trade {
+            exchange: exchange.to_string(),
+            pair: pair.to_string(),
+            trade: trade.clone(),
+        });
+    }
+
+    /// Record a closed candle of a pair
+    pub fn record_candle(&self, exchange: &str, pair: &str, timeframe: &str, candle: &Candle) {
+        self.send(Record::Candle {
+            exchange: exchange.to_string(),
+            pair: pair.to_string(),
+            timeframe: timeframe.to_string(),
+            candle: candle.clone(),
+        });
+    }
+}
+
+/// Append records to their files until the recorder is dropped
+async fn write_records(
+    dir: PathBuf,
+    retention_days: u32,
+    mut records: mpsc::Receiver<Record>,
+    dropped: Arc<AtomicU64>,
+) {
+    info!("⏺️ Recording market data to {} ({} days retention)", dir.display(), retention_days);
+    let mut files: HashMap<PathBuf, BufWriter<File>> = HashMap::new();
+    let mut today: Option<NaiveDate> = None;
+    let mut reported_drops = 0;
+    let mut flush_timer = interval(Duration::from_secs(FLUSH_INTERVAL_SECS));
+
+    loop {
+        tokio::select! {
+            record = records.recv() => {
+                let Some(record) = record else {
+                    flush_all(&mut files).await;
+                    return;
+                };
+                // Rotate: close the previous day's files and drop expired days
+                let date = Utc::now().date_naive();
+                if today != Some(date) {
+                    flush_all(&mut files).await;
+                    files.clear();
+                    today = Some(date);
+                    remove_expired(&dir, date, retention_days).await;
+                }
+
+                let (path, line) = record.to_line();
+                let path = dir.join(path);
+                if !files.contains_key(&path) {
+                    match open_append(&path).await {
+                        Ok(file) => {
+                            files.insert(path.clone(), BufWriter::new(file));
+                        }
+                        Err(e) => {
+                            warn!("Failed to open recording {}: {}", path.display(), e);
+                            continue;
+                        }
+                    }
+                }
+                if let Some(file) = files.get_mut(&path) {
+                    if let Err(e) = file.write_all(format!("{}\n", line).as_bytes()).await {
+                        warn!("Failed to write recording {}: {}", path.display(), e);
+                        files.remove(&path);
+                    }
+                }
+            }
+            _ = flush_timer.tick() => {
+                flush_all(&mut files).await;
+                let drops = dropped.load(Ordering::Relaxed);
+                if drops > reported_drops {
+                    warn!("Recorder fell behind, dropped {} records ({} in total)", drops - reported_drops, drops);
+                    reported_drops = drops;
+                }
+            }
+        }
+    }
+}
+
+/// Open a recording for appending, creating it and its directory if needed
+async fn open_append(path: &Path) -> std::io::Result<File> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    OpenOptions::new().create(true).append(true).open(path).await
+}
+
+async fn flush_all(files: &mut HashMap<PathBuf, BufWriter<File>>) {
+    for (path, file) in files.iter_mut() {
+        if let Err(e) = file.flush().await {
+            warn!("Failed to flush recording {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Delete the day directories of every exchange older than the retention
+async fn remove_expired(dir: &Path, today: NaiveDate, retention_days: u32) {
+    let Some(oldest) = today.checked_sub_days(chrono::Days::new(retention_days as u64)) else {
+        return;
+    };
+    let Ok(mut exchanges) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    while let Ok(Some(exchange)) = exchanges.next_entry().await {
+        let Ok(mut days) = tokio::fs::read_dir(exchange.path()).await else {
+            continue;
+        };
+        while let Ok(Some(day)) = days.next_entry().await {
+            let expired = day.file_name().to_str()
+                .and_then(|name| NaiveDate::parse_from_str(name, "%Y-%m-%d").ok())
+                .is_some_and(|date| date < oldest);
+            if expired {
+                match tokio::fs::remove_dir_all(day.path()).await {
+                    Ok(()) => info!("🗑️ Removed expired market recording {}", day.path().display()),
+                    Err(e) => warn!("Failed to remove market recording {}: {}", day.path().display(), e),
+                }
+            }
+        }
+    }
+}
+
+/// Recorded closed candles of a pair at or after `since` (unix seconds), oldest first
+pub async fn recorded_candles(dir: &Path, exchange: &str, pair: &str, timeframe: &str, since: i64) -> Vec<Candle> {
+    let file = file_name(pair, timeframe);
+    let Ok(mut days) = tokio::fs::read_dir(dir.join(exchange)).await else {
+        return Vec::new();
+    };
+    let mut candles = Vec::new();
+    while let Ok(Some(day)) = days.next_entry().await {
+        let Ok(content) = tokio::fs::read_to_string(day.path().join(&file)).await else {
+            continue;
+        };
+        candles.extend(
+            content.lines()
+                .filter_map(|line| serde_json::from_str::<Candle>(line).ok())
+                .filter(|candle| candle.timestamp >= since),
+        );
+    }
+    candles.sort_by_key(|candle| candle.timestamp);
+    candles.dedup_by_key(|candle| candle.timestamp);
+    candles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Empty directory of a test, removed by the test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("market-recorder-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn candle(timestamp: i64, close: f64) -> Candle {
+        Candle { open: close, high: close, low: close, close, volume: 1.0, timestamp }
+    }
+
+    #[test]
+    fn test_record_to_line() {
+        // 2024-01-02 03:04:05.678 UTC
+        let trade = Record::Trade {
+            exchange: "binance".to_string(),
+            pair: "btc/usdt".to_string(),
+            trade: Trade { price: 42000.5, quantity: 0.25, side: OrderSide::Sell, time_ms: 1_704_164_645_678 },
+        };
+        let (path, line) = trade.to_line();
+        assert_eq!(path, PathBuf::from("binance/2024-01-02/BTC_USDT-trades.jsonl"));
+        let line: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(line, serde_json::json!({ "time_ms": 1_704_164_645_678i64, "price": 42000.5, "quantity": 0.25, "side": "sell" }));
+
+        // Candles go to the day they opened in, in the format replays and backtests read
+        let record = Record::Candle {
+            exchange: "binance".to_string(),
+            pair: "ETH/USDT".to_string(),
+            timeframe: "1h".to_string(),
+            candle: candle(1_704_153_600 - 3_600, 2300.0),
+        };
+        let (path, line) = record.to_line();
+        assert_eq!(path, PathBuf::from("binance/2024-01-01/ETH_USDT-1h.jsonl"));
+        let parsed: Candle = serde_json::from_str(&line).unwrap();
+        assert_eq!((parsed.timestamp, parsed.close), (1_704_150_000, 2300.0));
+    }
+
+    #[tokio::test]
+    async fn test_remove_expired() {
+        let dir = temp_dir("expired");
+        for day in ["2024-01-01", "2024-01-07", "2024-01-08", "2024-01-10", "notes"] {
+            std::fs::create_dir_all(dir.join("binance").join(day)).unwrap();
+        }
+        std::fs::create_dir_all(dir.join("bybit").join("2023-12-31")).unwrap();
+        std::fs::write(dir.join("README"), "not an exchange").unwrap();
+
+        let today = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        remove_expired(&dir, today, 2).await;
+
+        let mut kept: Vec<String> = std::fs::read_dir(dir.join("binance")).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        kept.sort();
+        let bybit_days = std::fs::read_dir(dir.join("bybit")).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(kept, ["2024-01-08", "2024-01-10", "notes"]);
+        assert_eq!(bybit_days, 0);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_records() {
+        let (records, mut receiver) = mpsc::channel(1);
+        let recorder = MarketRecorder { records, dropped: Arc::new(AtomicU64::new(0)) };
+        let trade = Trade { price: 1.0, quantity: 1.0, side: OrderSide::Buy, time_ms: 0 };
+        recorder.record_trade("binance", "BTC/USDT", &trade);
+        recorder.record_candle("binance", "BTC/USDT", "1m", &candle(0, 1.0));
+        recorder.record_trade("binance", "BTC/USDT", &trade);
+        assert_eq!(recorder.dropped.load(Ordering::Relaxed), 2);
+        assert!(matches!(receiver.recv().await, Some(Record::Trade { .. })));
+
+        // A stopped writer is not counted as falling behind
+        drop(receiver);
+        recorder.record_trade("binance", "BTC/USDT", &trade);
+        assert_eq!(recorder.dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_candles() {
+        let dir = temp_dir("candles");
+        let write = |day: &str, candles: &[Candle]| {
+            let path = dir.join("binance").join(day);
+            std::fs::create_dir_all(&path).unwrap();
+            let lines: Vec<String> = candles.iter().map(|c| serde_json::to_string(c).unwrap()).collect();
+            std::fs::write(path.join("BTC_USDT-1m.jsonl"), lines.join("\n")).unwrap();
+        };
+        write("2024-01-02", &[candle(180, 3.0), candle(240, 4.0)]);
+        write("2024-01-01", &[candle(60, 1.0), candle(120, 2.0), candle(180, 3.0)]);
+        std::fs::write(dir.join("binance").join("2024-01-02").join("BTC_USDT-trades.jsonl"), "{}").unwrap();
+
+        let candles = recorded_candles(&dir, "binance", "BTC/USDT", "1m", 120).await;
+        let missing = recorded_candles(&dir, "binance", "ETH/USDT", "1m", 0).await;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(candles.iter().map(|c| c.timestamp).collect::<Vec<_>>(), [120, 180, 240]);
+        assert!(missing.is_empty());
+    }
+}
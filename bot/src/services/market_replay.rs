@@ -0,0 +1,340 @@
+//! Market Replay
+//!
+//! Market data source replaying recorded trades or candles from files, so the live
+//! pipeline (candle aggregation, strategies, signals, messages) runs without a network.
+//! A pair's recording is looked up in the replay directory as `BTC_USDT-trades.jsonl`
+//! (or `.csv`), else as candles of some timeframe like `BTC_USDT-1m.csv`.
+//!
+//! Formats, one record per line (CSV headers are skipped):
+//! - trades: `time_ms,price,quantity,side` or `{"time_ms", "price", "quantity", "side"}`
+//! - candles: `timestamp,open,high,low,close,volume` (seconds) or the same keys as JSON
+//!
+//! Candles are replayed as four trades (open, high and low, close) within their period,
+//! which the stream's aggregator turns back into the same candle.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use anyhow::Result;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+use freqtrade_rs::exchange::OrderSide;
+use crate::services::candle_aggregator::Trade;
+use crate::services::market_feed::{FeedEvent, MarketDataSource};
+use crate::services::strategy_engine::Candle;
+
+/// Replays the recordings in a directory as if they were live
+pub struct ReplaySource {
+    exchange: String,
+    dir: PathBuf,
+    /// Recorded time per real time, e.g. 60 replays a minute per second
+    speed: f64,
+    events: mpsc::UnboundedSender<FeedEvent>,
+    /// Running replay of each pair
+    replays: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl ReplaySource {
+    /// Create a source replaying the recordings in `dir` as `exchange`'s market data
+    pub fn new(events: mpsc::UnboundedSender<FeedEvent>, exchange: &str, dir: impl Into<PathBuf>, speed: f64) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            dir: dir.into(),
+            speed: if speed > 0.0 { speed } else { 1.0 },
+            events,
+            replays: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl MarketDataSource for ReplaySource {
+    fn exchange(&self) -> &str {
+        &self.exchange
+    }
+
+    fn add(&self, pair: &str) {
+        let mut replays = self.replays.lock().unwrap();
+        if replays.contains_key(pair) {
+            return;
+        }
+        let Some(path) = find_recording(&self.dir, pair) else {
+            warn!("No recording of {} in {}", pair, self.dir.display());
+            return;
+        };
+        info!("▶️ Replaying {} from {} at {}x", pair, path.display(), self.speed);
+        let task = tokio::spawn(replay(pair.to_string(), path, self.speed, self.events.clone()));
+        replays.insert(pair.to_string(), task);
+    }
+
+    fn remove(&self, pair: &str) {
+        if let Some(task) = self.replays.lock().unwrap().remove(pair) {
+            task.abort();
+        }
+    }
+}
+
+/// Recording of a pair: its trades if recorded, else its candles of any timeframe
+fn find_recording(dir: &Path, pair: &str) -> Option<PathBuf> {
+    let prefix = format!("{}-", pair.replace('/', "_").to_uppercase());
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir).ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name().and_then(|n| n.to_str()).is_some_and(|name| name.starts_with(&prefix))
+                && matches!(path.extension().and_then(|e| e.to_str()), Some("csv" | "jsonl"))
+        })
+        .collect();
+    files.sort();
+    files.iter()
+        .find(|path| recording_timeframe(path).as_deref() == Some("trades"))
+        .or(files.first())
+        .cloned()
+}
+
+/// "trades" or the candle timeframe of a recording, from its file name
+fn recording_timeframe(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    stem.rsplit_once('-').map(|(_, timeframe)| timeframe.to_string())
+}
+
+/// Recorded trade (JSONL)
+#[derive(Deserialize)]
+struct TradeRecord {
+    time_ms: i64,
+    price: f64,
+    quantity: f64,
+    #[serde(default)]
+    side: Option<String>,
+}
+
+impl TradeRecord {
+    fn into_trade(self) -> Trade {
+        Trade {
+            price: self.price,
+            quantity: self.quantity,
+            side: match self.side.as_deref() {
+                Some("sell") | Some("SELL") => OrderSide::Sell,
+                _ => OrderSide::Buy,
+            },
+            time_ms: self.time_ms,
+        }
+    }
+}
+
+/// Parse one line of a trades recording, `None` for headers and empty lines
+fn parse_trade(line: &str) -> Option<Trade> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        return serde_json::from_str::<TradeRecord>(line).ok().map(TradeRecord::into_trade);
+    }
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    Some(TradeRecord {
+        time_ms: fields.first()?.parse().ok()?,
+        price: fields.get(1)?.parse().ok()?,
+        quantity: fields.get(2)?.parse().ok()?,
+        side: fields.get(3).map(|s| s.to_string()),
+    }.into_trade())
+}
+
+/// Parse one line of a candles recording, `None` for headers and empty lines
+fn parse_candle(line: &str) -> Option<Candle> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        return serde_json::from_str(line).ok();
+    }
+    let fields: Vec<f64> = line.split(',').map(|f| f.trim().parse().ok()).collect::<Option<_>>()?;
+    match fields[..] {
+        [timestamp, open, high, low, close, volume, ..] => Some(Candle {
+            open,
+            high,
+            low,
+            close,
+            volume,
+            timestamp: timestamp as i64,
+        }),
+        _ => None,
+    }
+}
+
+/// Trades rebuilding a candle: open, the nearer extreme first, then the other and the close
+fn candle_trades(candle: &Candle, period_ms: i64) -> Vec<Trade> {
+    let bullish = candle.close >= candle.open;
+    let (first, second) = if bullish { (candle.low, candle.high) } else { (candle.high, candle.low) };
+    let side = if bullish { OrderSide::Buy } else { OrderSide::Sell };
+    [candle.open, first, second, candle.close].iter()
+        .enumerate()
+        .map(|(i, price)| Trade {
+            price: *price,
+            quantity: candle.volume / 4.0,
+            side,
+            time_ms: candle.timestamp * 1000 + period_ms * i as i64 / 4,
+        })
+        .collect()
+}
+
+/// Load a recording as trades, oldest first
+fn load_trades(path: &Path) -> Result<Vec<Trade>> {
+    let content = std::fs::read_to_string(path)?;
+    let timeframe = recording_timeframe(path).unwrap_or_default();
+    let mut trades: Vec<Trade> = if timeframe == "trades" {
+        content.lines().filter_map(parse_trade).collect()
+    } else {
+        let period_ms = freqtrade_rs::data::timeframe_duration(&timeframe)
+            .map(|d| d.num_milliseconds())
+            .ok_or_else(|| anyhow::anyhow!("Unknown timeframe {} of {}", timeframe, path.display()))?;
+        content.lines()
+            .filter_map(parse_candle)
+            .flat_map(|candle| candle_trades(&candle, period_ms))
+            .collect()
+    };
+    trades.sort_by_key(|trade| trade.time_ms);
+    Ok(trades)
+}
+
+/// Send a recording's trades at `speed` times their recorded pace
+async fn replay(pair: String, path: PathBuf, speed: f64, events: mpsc::UnboundedSender<FeedEvent>) {
+    let trades = match load_trades(&path) {
+        Ok(trades) => trades,
+        Err(e) => {
+            warn!("Failed to load recording {}: {}", path.display(), e);
+            return;
+        }
+    };
+    let Some(first_ms) = trades.first().map(|trade| trade.time_ms) else {
+        warn!("Recording {} is empty", path.display());
+        return;
+    };
+    let _ = events.send(FeedEvent::Connected { pairs: vec![pair.clone()], reconnect: false });
+
+    let started = Instant::now();
+    let count = trades.len();
+    for trade in trades {
+        let offset_ms = ((trade.time_ms - first_ms) as f64 / speed) as u64;
+        tokio::time::sleep_until(started + Duration::from_millis(offset_ms)).await;
+        let _ = events.send(FeedEvent::Trade {
+            pair: pair.clone(),
+            trade,
+            received_ms: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+    info!("⏹️ Replay of {} finished ({} trades)", pair, count);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::candle_aggregator::{CandleAggregator, LATE_TRADE_GRACE_MS};
+    use crate::services::strategy_engine::{StrategyConfig, StrategyExecutor, StrategySignal};
+
+    /// 1m candles falling for six minutes, then jumping above their average
+    const FIXTURE: &str = "timestamp,open,high,low,close,volume
+60,101,102,99.5,100,4
+120,100,100.5,98,99,8
+180,99,99,97.5,98,4
+240,98,98.5,96,97,8
+300,97,97.5,95.5,96,4
+360,96,96,94,95,8
+420,95,111,95,110,12
+";
+
+    fn prices(trades: &[Trade]) -> Vec<f64> {
+        trades.iter().map(|trade| trade.price).collect()
+    }
+
+    #[test]
+    fn test_parse_trade() {
+        let trade = parse_trade("1700000000123, 42000.5, 0.25, sell").unwrap();
+        assert_eq!((trade.time_ms, trade.price, trade.quantity), (1_700_000_000_123, 42000.5, 0.25));
+        assert!(matches!(trade.side, OrderSide::Sell));
+        // The side is optional and anything but a sell is a buy
+        assert!(matches!(parse_trade("1,2,3").unwrap().side, OrderSide::Buy));
+        assert!(matches!(parse_trade("1,2,3,BUY").unwrap().side, OrderSide::Buy));
+
+        let trade = parse_trade(r#"{"time_ms": 5, "price": 1.5, "quantity": 2, "side": "SELL"}"#).unwrap();
+        assert_eq!((trade.time_ms, trade.price, trade.quantity), (5, 1.5, 2.0));
+        assert!(matches!(trade.side, OrderSide::Sell));
+
+        assert!(parse_trade("time_ms,price,quantity,side").is_none());
+        assert!(parse_trade("").is_none());
+        assert!(parse_trade("1,2").is_none());
+        assert!(parse_trade(r#"{"price": 1.5}"#).is_none());
+    }
+
+    #[test]
+    fn test_parse_candle() {
+        let candle = parse_candle("60,1,3,0.5,2,10").unwrap();
+        assert_eq!(candle.timestamp, 60);
+        assert_eq!((candle.open, candle.high, candle.low, candle.close, candle.volume), (1.0, 3.0, 0.5, 2.0, 10.0));
+
+        let candle = parse_candle(r#"{"timestamp": 120, "open": 2, "high": 4, "low": 1, "close": 3, "volume": 5}"#).unwrap();
+        assert_eq!(candle.timestamp, 120);
+        assert_eq!((candle.open, candle.close), (2.0, 3.0));
+
+        assert!(parse_candle("timestamp,open,high,low,close,volume").is_none());
+        assert!(parse_candle("").is_none());
+        assert!(parse_candle("60,1,3,0.5,2").is_none());
+    }
+
+    #[test]
+    fn test_candle_trades() {
+        let bullish = Candle { open: 10.0, high: 12.0, low: 9.0, close: 11.0, volume: 8.0, timestamp: 60 };
+        let trades = candle_trades(&bullish, 60_000);
+        assert_eq!(prices(&trades), [10.0, 9.0, 12.0, 11.0]);
+        assert_eq!(trades.iter().map(|t| t.time_ms).collect::<Vec<_>>(), [60_000, 75_000, 90_000, 105_000]);
+        assert!(trades.iter().all(|t| t.quantity == 2.0 && matches!(t.side, OrderSide::Buy)));
+
+        let bearish = Candle { open: 11.0, high: 12.0, low: 9.0, close: 10.0, volume: 8.0, timestamp: 60 };
+        let trades = candle_trades(&bearish, 60_000);
+        assert_eq!(prices(&trades), [11.0, 12.0, 9.0, 10.0]);
+        assert!(trades.iter().all(|t| matches!(t.side, OrderSide::Sell)));
+    }
+
+    #[tokio::test]
+    async fn test_replayed_fixture_reaches_the_strategy() {
+        let dir = std::env::temp_dir().join(format!("market-replay-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("BTC_USDT-1m.csv"), FIXTURE).unwrap();
+        let path = find_recording(&dir, "BTC/USDT").unwrap();
+        let trades = load_trades(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let trades = trades.unwrap();
+        assert_eq!(trades.len(), 28);
+
+        // The stream's aggregator rebuilds the recorded candles
+        let mut aggregator = CandleAggregator::new("1m");
+        for trade in &trades {
+            aggregator.add_trade(trade);
+        }
+        // Up to the end of the last recorded minute
+        let closed = aggregator.close_until(480_000 + LATE_TRADE_GRACE_MS);
+        let recorded: Vec<Candle> = FIXTURE.lines().filter_map(parse_candle).collect();
+        assert_eq!(closed.len(), recorded.len());
+        for (closed, recorded) in closed.iter().zip(&recorded) {
+            assert_eq!(
+                (closed.timestamp, closed.open, closed.high, closed.low, closed.close, closed.volume),
+                (recorded.timestamp, recorded.open, recorded.high, recorded.low, recorded.close, recorded.volume),
+            );
+        }
+
+        // and the strategy trades on them
+        let executor = StrategyExecutor::new();
+        let config = StrategyConfig {
+            strategy_type: "EXPRESSION".to_string(),
+            parameters: serde_json::json!({}),
+            pair: "BTC/USDT".to_string(),
+            timeframe: "1m".to_string(),
+            buy_condition: "close > SMA(4)".to_string(),
+            sell_condition: "close < 0".to_string(),
+        };
+        executor.start_trading(1, 7, config, None).await.unwrap();
+        let mut signals = Vec::new();
+        for candle in &closed {
+            signals.push(executor.process_candle(1, "BTC/USDT", candle).await);
+        }
+        let (last, earlier) = signals.split_last().unwrap();
+        assert!(earlier.iter().all(Option::is_none), "{:?}", earlier);
+        assert!(matches!(last, Some(StrategySignal::Buy { price, .. }) if *price == 110.0), "{:?}", last);
+    }
+}
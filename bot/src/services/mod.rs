@@ -5,4 +5,14 @@ pub mod strategy_engine;
 pub mod strategy_service;
 pub mod gemini;
 pub mod position_service;
+pub mod protection_service;
+pub mod pairlist_service;
+pub mod snapshot_service;
+pub mod candle_aggregator;
+pub mod market_feed;
+pub mod market_replay;
+pub mod market_recorder;
+pub mod session_service;
+pub mod warmup_service;
+pub mod backtest_service;
 
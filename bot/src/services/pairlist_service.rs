@@ -0,0 +1,211 @@
+//! Pairlist Service
+//!
+//! Builds the pairs of a live session from Binance 24h tickers with the strategy's
+//! pairlist (see `freqtrade_rs::data::Pairlist`) and keeps the session's per-pair
+//! trading services in sync as the list refreshes.
+
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tokio::time::interval;
+use tracing::{info, warn, error};
+use freqtrade_rs::data::{same_pair, PairStats, Pairlist, PairlistConfig, PairlistSource};
+use crate::services::strategy_engine::StrategyConfig;
+use crate::services::trading_signal::Subscriber;
+use crate::state::AppState;
+
+const BINANCE_API_URL: &str = "https://api.binance.com/api/v3";
+
+/// Binance 24h ticker (numbers are sent as strings)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker24h {
+    symbol: String,
+    last_price: String,
+    bid_price: String,
+    ask_price: String,
+    quote_volume: String,
+}
+
+/// Pair name used by the session for an exchange symbol, `None` if the source does not list it
+fn pair_name(config: &PairlistConfig, symbol: &str) -> Option<String> {
+    match &config.source {
+        PairlistSource::Static { pairs } => pairs.iter().find(|p| same_pair(p, symbol)).cloned(),
+        PairlistSource::VolumeTop { quote_asset, .. } => {
+            let quote = quote_asset.to_uppercase();
+            symbol.strip_suffix(&quote)
+                .filter(|base| !base.is_empty())
+                .map(|base| format!("{}/{}", base, quote))
+        }
+    }
+}
+
+/// Fetch market snapshots for the pairlist, with daily history for the candidates if filters need it
+pub async fn fetch_pair_stats(
+    client: &reqwest::Client,
+    config: &PairlistConfig,
+) -> Result<Vec<PairStats>, anyhow::Error> {
+    let tickers: Vec<Ticker24h> = client
+        .get(format!("{}/ticker/24hr", BINANCE_API_URL))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let parse = |value: &str| value.parse::<f64>().ok().filter(|v| *v > 0.0);
+    let mut stats: Vec<PairStats> = tickers.iter()
+        .filter_map(|ticker| {
+            let pair = pair_name(config, &ticker.symbol)?;
+            let mut stats = PairStats::new(&pair, parse(&ticker.last_price)?, parse(&ticker.quote_volume).unwrap_or(0.0));
+            stats.bid = parse(&ticker.bid_price);
+            stats.ask = parse(&ticker.ask_price);
+            Some(stats)
+        })
+        .collect();
+
+    let history_days = config.history_days();
+    if history_days > 0 {
+        let candidates: Vec<String> = config.candidates(&stats).iter().map(|s| s.symbol.clone()).collect();
+        for stats in stats.iter_mut().filter(|s| candidates.contains(&s.symbol)) {
+            match fetch_daily_history(client, &stats.symbol, history_days).await {
+                Ok((listed_since, closes)) => {
+                    stats.listed_since = listed_since;
+                    stats.daily_closes = closes;
+                }
+                Err(e) => warn!("Failed to fetch daily candles for {}: {}", stats.symbol, e),
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Fetch up to `days` daily candles, returns (first candle time, closes oldest first)
+async fn fetch_daily_history(
+    client: &reqwest::Client,
+    pair: &str,
+    days: usize,
+) -> Result<(Option<DateTime<Utc>>, Vec<f64>), anyhow::Error> {
+    let symbol = pair.replace('/', "").to_uppercase();
+    let klines: Vec<Vec<serde_json::Value>> = client
+        .get(format!("{}/klines", BINANCE_API_URL))
+        .query(&[("symbol", symbol.as_str()), ("interval", "1d"), ("limit", &days.min(1000).to_string())])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // Kline layout: [open time (ms), open, high, low, close, ...]
+    let listed_since = klines.first()
+        .and_then(|k| k.first())
+        .and_then(|t| t.as_i64())
+        .and_then(DateTime::from_timestamp_millis);
+    let closes = klines.iter()
+        .filter_map(|k| k.get(4).and_then(|c| c.as_str()).and_then(|c| c.parse().ok()))
+        .collect();
+
+    Ok((listed_since, closes))
+}
+
+/// Run a user's session across the pairs of the strategy's pairlist
+/// Refreshes the list on its interval and starts / stops per-pair trading services as pairs change.
+/// Pairs with an open position keep trading until the position is closed.
+pub fn start_pairlist_service(
+    app_state: Arc<AppState>,
+    bot: teloxide::Bot,
+    session_id: u64,
+    user_id: i64,
+    user_chat_id: i64,
+    strategy_config: StrategyConfig,
+    exchange: String,
+) {
+    let Some(pairlist_config) = strategy_config.pairlist() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        use crate::services::position_service;
+        use crate::services::trading_signal::start_user_trading_service;
+
+        if exchange != "binance" {
+            warn!("Pairlists only support Binance, user {} trades {} on {}", user_id, strategy_config.pair, exchange);
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client");
+        let refresh_secs = pairlist_config.refresh_period_secs.max(60) as u64;
+        let mut pairlist = Pairlist::new(pairlist_config);
+        let mut timer = interval(Duration::from_secs(refresh_secs));
+
+        info!("📋 [Session {}] Pairlist service started (refresh every {} seconds)", session_id, refresh_secs);
+
+        loop {
+            timer.tick().await;
+            if !app_state.strategy_executor.is_session_trading(session_id).await {
+                break;
+            }
+
+            let mut pairs = if exchange == "binance" {
+                match fetch_pair_stats(&client, pairlist.config()).await {
+                    Ok(stats) => pairlist.refresh(&stats, Utc::now()).to_vec(),
+                    Err(e) => {
+                        error!("Failed to refresh pairlist for user {}: {}", user_id, e);
+                        continue;
+                    }
+                }
+            } else {
+                vec![strategy_config.pair.clone()]
+            };
+
+            // Open positions keep being managed after their pair drops out
+            // (set_pairs skips those another session of the user trades)
+            match position_service::get_open_positions(app_state.db.as_ref(), user_id).await {
+                Ok(positions) => {
+                    for position in positions {
+                        if !pairs.iter().any(|p| same_pair(p, &position.pair)) {
+                            pairs.push(position.pair);
+                        }
+                    }
+                }
+                Err(e) => error!("Failed to load open positions for user {}: {}", user_id, e),
+            }
+
+            let Some((added, removed)) = app_state.strategy_executor.set_pairs(session_id, &pairs).await else {
+                break;
+            };
+            if added.is_empty() && removed.is_empty() {
+                continue;
+            }
+            info!("📋 [Session {}] Pairlist refreshed: {} (added: {:?}, removed: {:?})",
+                session_id, pairs.join(", "), added, removed);
+
+            // Per-pair services stop once their pair is no longer traded
+            for pair in removed {
+                app_state.stream_manager.unsubscribe(&exchange, &pair, &Subscriber::Session(session_id)).await;
+            }
+            for pair in added {
+                start_user_trading_service(
+                    app_state.clone(),
+                    bot.clone(),
+                    session_id,
+                    user_id,
+                    user_chat_id,
+                    StrategyConfig {
+                        pair: pair.clone(),
+                        ..strategy_config.clone()
+                    },
+                    exchange.clone(),
+                    pair,
+                );
+            }
+        }
+
+        info!("🛑 [Session {}] Pairlist service stopped", session_id);
+    });
+}
@@ -2,15 +2,15 @@
 
 use anyhow::Result;
 use std::sync::Arc;
-use sea_orm::{EntityTrait, ActiveValue, ColumnTrait, QueryFilter, QueryOrder, Order};
-use shared::entity::{positions, trades, live_trading_signals};
+use sea_orm::{EntityTrait, ActiveValue, ColumnTrait, ConnectionTrait, QueryFilter, QueryOrder, Order, TransactionTrait};
+use shared::entity::{positions, position_orders, trades};
 use chrono::Utc;
 use rust_decimal::Decimal;
 use std::str::FromStr;
 
 /// Create a new position when buy signal is executed
-pub async fn create_position(
-    db: &sea_orm::DatabaseConnection,
+pub async fn create_position<C: ConnectionTrait + TransactionTrait>(
+    db: &C,
     user_id: i64,
     order_id: Option<u64>,
     strategy_id: Option<u64>,
@@ -41,6 +41,7 @@ pub async fn create_position(
         current_price: ActiveValue::Set(Some(entry_price_decimal)),
         unrealized_pnl: ActiveValue::Set(Decimal::ZERO),
         unrealized_pnl_percent: ActiveValue::Set(Decimal::ZERO),
+        realized_pnl: ActiveValue::Set(Decimal::ZERO),
         status: ActiveValue::Set("open".to_string()),
         entry_time: ActiveValue::Set(Some(Utc::now())),
         close_time: ActiveValue::NotSet,
@@ -49,36 +50,196 @@ pub async fn create_position(
         ..Default::default()
     };
     
+    // The position and its entry fill are written together or not at all
+    let txn = db.begin().await?;
     let result = positions::Entity::insert(position)
-        .exec(db)
+        .exec(&txn)
+        .await?;
+    
+    position_orders::Entity::insert(new_position_order(result.last_insert_id, user_id, order_id, "entry", entry_price, quantity, 0.0))
+        .exec(&txn)
         .await?;
+    txn.commit().await?;
     
     Ok(result.last_insert_id)
 }
 
-/// Close a position and create a trade record when sell signal is executed
-pub async fn close_position_and_create_trade(
-    db: &sea_orm::DatabaseConnection,
+/// Build an entry or exit fill record of a position
+fn new_position_order(
+    position_id: u64,
     user_id: i64,
+    signal_id: Option<u64>,
+    kind: &str,
+    price: f64,
+    quantity: f64,
+    realized_pnl: f64,
+) -> position_orders::ActiveModel {
+    let to_decimal = |value: f64| Decimal::from_str(&value.to_string()).unwrap_or(Decimal::ZERO);
+    
+    position_orders::ActiveModel {
+        position_id: ActiveValue::Set(position_id),
+        user_id: ActiveValue::Set(user_id),
+        signal_id: ActiveValue::Set(signal_id),
+        kind: ActiveValue::Set(kind.to_string()),
+        price: ActiveValue::Set(to_decimal(price)),
+        quantity: ActiveValue::Set(to_decimal(quantity)),
+        value: ActiveValue::Set(to_decimal(price * quantity)),
+        realized_pnl: ActiveValue::Set(to_decimal(realized_pnl)),
+        created_at: ActiveValue::Set(Some(Utc::now())),
+        ..Default::default()
+    }
+}
+
+/// Get the entry and exit fills of a position, oldest first
+pub async fn get_position_orders<C: ConnectionTrait>(
+    db: &C,
     position_id: u64,
-    sell_order_id: Option<u64>,
-    exit_price: f64,
-) -> Result<u64, anyhow::Error> {
-    // Get the position
-    let position = positions::Entity::find_by_id(position_id)
+) -> Result<Vec<position_orders::Model>, anyhow::Error> {
+    let orders = position_orders::Entity::find()
+        .filter(position_orders::Column::PositionId.eq(position_id))
+        .order_by(position_orders::Column::Id, Order::Asc)
+        .all(db)
+        .await?;
+    
+    Ok(orders)
+}
+
+/// Find an open position owned by the user
+async fn find_open_position<C: ConnectionTrait>(
+    db: &C,
+    user_id: i64,
+    position_id: u64,
+) -> Result<positions::Model, anyhow::Error> {
+    positions::Entity::find_by_id(position_id)
         .filter(positions::Column::UserId.eq(user_id))
         .filter(positions::Column::Status.eq("open"))
         .one(db)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Position not found or already closed"))?;
+        .ok_or_else(|| anyhow::anyhow!("Position not found or already closed"))
+}
+
+/// Add to an open position (DCA entry), averaging its entry price
+pub async fn increase_position<C: ConnectionTrait + TransactionTrait>(
+    db: &C,
+    user_id: i64,
+    position_id: u64,
+    signal_id: Option<u64>,
+    price: f64,
+    quantity: f64,
+) -> Result<(), anyhow::Error> {
+    let txn = db.begin().await?;
+    let position = find_open_position(&txn, user_id, position_id).await?;
+    
+    let current_quantity: f64 = f64::from_str(&position.quantity.to_string()).unwrap_or(0.0);
+    let entry_value: f64 = f64::from_str(&position.entry_value.to_string()).unwrap_or(0.0);
+    let new_quantity = current_quantity + quantity;
+    let new_entry_value = entry_value + price * quantity;
+    let to_decimal = |value: f64| Decimal::from_str(&value.to_string()).unwrap_or(Decimal::ZERO);
+    
+    position_orders::Entity::insert(new_position_order(position_id, user_id, signal_id, "entry", price, quantity, 0.0))
+        .exec(&txn)
+        .await?;
+    
+    let mut position_update: positions::ActiveModel = position.into();
+    position_update.entry_price = ActiveValue::Set(to_decimal(new_entry_value / new_quantity));
+    position_update.quantity = ActiveValue::Set(to_decimal(new_quantity));
+    position_update.entry_value = ActiveValue::Set(to_decimal(new_entry_value));
+    position_update.updated_at = ActiveValue::Set(Some(Utc::now()));
+    
+    positions::Entity::update(position_update)
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+    
+    Ok(())
+}
+
+/// Exit part of an open position, realizing its P&L
+/// Returns the realized P&L; reducing by the full quantity closes the position instead
+pub async fn reduce_position<C: ConnectionTrait + TransactionTrait>(
+    db: &C,
+    user_id: i64,
+    position_id: u64,
+    signal_id: Option<u64>,
+    price: f64,
+    quantity: f64,
+) -> Result<f64, anyhow::Error> {
+    let txn = db.begin().await?;
+    let position = find_open_position(&txn, user_id, position_id).await?;
+    
+    let entry_price: f64 = f64::from_str(&position.entry_price.to_string()).unwrap_or(0.0);
+    let current_quantity: f64 = f64::from_str(&position.quantity.to_string()).unwrap_or(0.0);
+    let realized_pnl: f64 = f64::from_str(&position.realized_pnl.to_string()).unwrap_or(0.0);
+    if quantity >= current_quantity {
+        close_position_and_create_trade(&txn, user_id, position_id, signal_id, price, Some("partial_exit")).await?;
+        txn.commit().await?;
+        return Ok((price - entry_price) * current_quantity);
+    }
+    
+    let pnl = (price - entry_price) * quantity;
+    let new_quantity = current_quantity - quantity;
+    let to_decimal = |value: f64| Decimal::from_str(&value.to_string()).unwrap_or(Decimal::ZERO);
+    
+    position_orders::Entity::insert(new_position_order(position_id, user_id, signal_id, "exit", price, quantity, pnl))
+        .exec(&txn)
+        .await?;
+    
+    let mut position_update: positions::ActiveModel = position.into();
+    position_update.quantity = ActiveValue::Set(to_decimal(new_quantity));
+    position_update.entry_value = ActiveValue::Set(to_decimal(entry_price * new_quantity));
+    position_update.realized_pnl = ActiveValue::Set(to_decimal(realized_pnl + pnl));
+    position_update.updated_at = ActiveValue::Set(Some(Utc::now()));
+    
+    positions::Entity::update(position_update)
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+    
+    Ok(pnl)
+}
+
+/// Close a position and create a trade record when sell signal is executed
+pub async fn close_position_and_create_trade<C: ConnectionTrait + TransactionTrait>(
+    db: &C,
+    user_id: i64,
+    position_id: u64,
+    sell_order_id: Option<u64>,
+    exit_price: f64,
+    exit_reason: Option<&str>,
+) -> Result<u64, anyhow::Error> {
+    // The exit fill, trade record and closed position are written together or not at all
+    let txn = db.begin().await?;
+    let position = find_open_position(&txn, user_id, position_id).await?;
     
     // Convert Decimal to f64 for calculations
     let entry_price: f64 = f64::from_str(&position.entry_price.to_string()).unwrap_or(0.0);
-    let quantity: f64 = f64::from_str(&position.quantity.to_string()).unwrap_or(0.0);
-    let entry_value: f64 = f64::from_str(&position.entry_value.to_string()).unwrap_or(0.0);
+    let remaining_quantity: f64 = f64::from_str(&position.quantity.to_string()).unwrap_or(0.0);
+    let position_entry_value: f64 = f64::from_str(&position.entry_value.to_string()).unwrap_or(0.0);
+    let realized_pnl: f64 = f64::from_str(&position.realized_pnl.to_string()).unwrap_or(0.0);
+    let final_pnl = (exit_price - entry_price) * remaining_quantity;
+    
+    position_orders::Entity::insert(new_position_order(position_id, user_id, sell_order_id, "exit", exit_price, remaining_quantity, final_pnl))
+        .exec(&txn)
+        .await?;
     
-    let exit_value = exit_price * quantity;
-    let pnl = exit_value - entry_value;
+    // Trade totals span every fill, so DCA entries and partial exits are averaged in
+    let orders = get_position_orders(&txn, position_id).await?;
+    let sum = |kind: &str| orders.iter()
+        .filter(|o| o.kind == kind)
+        .fold((0.0, 0.0), |(quantity, value), o| {
+            (
+                quantity + f64::from_str(&o.quantity.to_string()).unwrap_or(0.0),
+                value + f64::from_str(&o.value.to_string()).unwrap_or(0.0),
+            )
+        });
+    let (quantity, entry_value, exit_value) = match (sum("entry"), sum("exit")) {
+        ((quantity, entry_value), (_, exit_value)) if quantity > 0.0 => (quantity, entry_value, exit_value),
+        // Positions opened before fills were recorded have a single entry
+        _ => (remaining_quantity, position_entry_value, exit_price * remaining_quantity),
+    };
+    let pnl = realized_pnl + final_pnl;
+    let entry_price = entry_value / quantity;
+    let trade_exit_price = exit_value / quantity;
     let pnl_percent = if entry_value > 0.0 {
         (pnl / entry_value) * 100.0
     } else {
@@ -107,7 +268,7 @@ pub async fn close_position_and_create_trade(
         exchange: ActiveValue::Set(position_exchange),
         pair: ActiveValue::Set(position_pair),
         entry_price: ActiveValue::Set(entry_price.to_string()),
-        exit_price: ActiveValue::Set(exit_price.to_string()),
+        exit_price: ActiveValue::Set(trade_exit_price.to_string()),
         quantity: ActiveValue::Set(quantity.to_string()),
         entry_value: ActiveValue::Set(entry_value.to_string()),
         exit_value: ActiveValue::Set(exit_value.to_string()),
@@ -116,12 +277,13 @@ pub async fn close_position_and_create_trade(
         entry_time: ActiveValue::Set(Some(entry_time)),
         exit_time: ActiveValue::Set(Some(exit_time)),
         duration: ActiveValue::Set(Some(duration)),
+        exit_reason: ActiveValue::Set(exit_reason.map(str::to_string)),
         created_at: ActiveValue::Set(Some(Utc::now())),
         ..Default::default()
     };
     
     let trade_result = trades::Entity::insert(trade)
-        .exec(db)
+        .exec(&txn)
         .await?;
     
     // Convert f64 to Decimal for database
@@ -139,11 +301,13 @@ pub async fn close_position_and_create_trade(
     position_update.current_price = ActiveValue::Set(Some(exit_price_decimal));
     position_update.unrealized_pnl = ActiveValue::Set(pnl_decimal);
     position_update.unrealized_pnl_percent = ActiveValue::Set(pnl_percent_decimal);
+    position_update.realized_pnl = ActiveValue::Set(pnl_decimal);
     position_update.updated_at = ActiveValue::Set(Some(Utc::now()));
     
     positions::Entity::update(position_update)
-        .exec(db)
+        .exec(&txn)
         .await?;
+    txn.commit().await?;
     
     Ok(trade_result.last_insert_id)
 }
@@ -243,6 +407,52 @@ pub async fn get_user_trades(
     Ok(trades_list)
 }
 
+/// Starting paper-trading equity used for position sizing (USDT)
+pub const PAPER_STARTING_EQUITY: f64 = 1000.0;
+
+/// Paper account state used for position sizing
+#[derive(Debug, Clone)]
+pub struct SizingAccount {
+    /// Starting equity plus realized P&L
+    pub equity: f64,
+    /// Equity not locked in open positions
+    pub available: f64,
+    /// Fractional returns of closed trades, oldest first
+    pub recent_returns: Vec<f64>,
+}
+
+/// Load the paper account state a position sizer needs
+pub async fn get_sizing_account(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i64,
+) -> Result<SizingAccount, anyhow::Error> {
+    let closed_trades = get_user_trades(db, user_id, None).await?;
+    let realized_pnl: f64 = closed_trades.iter()
+        .map(|t| t.pnl.parse::<f64>().unwrap_or(0.0))
+        .sum();
+    // Trades come newest first
+    let recent_returns = closed_trades.iter()
+        .rev()
+        .map(|t| t.pnl_percent.parse::<f64>().unwrap_or(0.0) / 100.0)
+        .collect();
+    
+    let open_positions = get_open_positions(db, user_id).await?;
+    let in_positions: f64 = open_positions.iter()
+        .map(|p| f64::from_str(&p.entry_value.to_string()).unwrap_or(0.0))
+        .sum();
+    // Partial exits of open positions are already realized
+    let partial_pnl: f64 = open_positions.iter()
+        .map(|p| f64::from_str(&p.realized_pnl.to_string()).unwrap_or(0.0))
+        .sum();
+    
+    let equity = PAPER_STARTING_EQUITY + realized_pnl + partial_pnl;
+    Ok(SizingAccount {
+        equity,
+        available: (equity - in_positions).max(0.0),
+        recent_returns,
+    })
+}
+
 /// Calculate P&L summary for a user
 pub async fn calculate_pnl_summary(
     db: &sea_orm::DatabaseConnection,
@@ -290,6 +500,11 @@ pub async fn calculate_pnl_summary(
         }
     }
     
+    // Partial exits of open positions are already realized
+    total_realized_pnl += open_positions.iter()
+        .map(|p| f64::from_str(&p.realized_pnl.to_string()).unwrap_or(0.0))
+        .sum::<f64>();
+    
     if total_entry_value > 0.0 {
         total_realized_pnl_percent = (total_realized_pnl / total_entry_value) * 100.0;
     }
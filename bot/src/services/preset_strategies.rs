@@ -76,11 +76,6 @@ pub async fn load_strategy_from_local(strategy_name: &str) -> Result<PresetStrat
     Ok(strategy)
 }
 
-/// Fetch strategy file from GitHub and parse it (kept for backward compatibility)
-pub async fn fetch_strategy_from_github(strategy_name: &str) -> Result<PresetStrategy> {
-    load_strategy_from_local(strategy_name).await
-}
-
 /// Parse Python strategy file to extract relevant information
 fn parse_strategy_file(content: &str, strategy_name: &str) -> Result<PresetStrategy> {
     
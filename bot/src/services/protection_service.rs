@@ -0,0 +1,106 @@
+//! Protection Lock Service
+//!
+//! Evaluates the strategy's protections after a trade closes and persists the
+//! resulting locks, so entries stay blocked across bot restarts.
+
+use anyhow::Result;
+use std::sync::Arc;
+use std::str::FromStr;
+use sea_orm::{EntityTrait, ActiveValue, ColumnTrait, QueryFilter};
+use shared::entity::{protection_locks, trades};
+use chrono::{DateTime, Utc};
+use freqtrade_rs::portfolio::{ProtectionLock, ProtectionTrade};
+use freqtrade_rs::strategy::ExitReason;
+use tracing::info;
+use crate::services::position_service;
+use crate::state::AppState;
+
+/// Load a user's locks that have not expired yet
+pub async fn get_active_locks(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i64,
+) -> Result<Vec<ProtectionLock>, anyhow::Error> {
+    let locks = protection_locks::Entity::find()
+        .filter(protection_locks::Column::UserId.eq(user_id))
+        .filter(protection_locks::Column::LockUntil.gt(Utc::now()))
+        .all(db)
+        .await?;
+    
+    Ok(locks.into_iter()
+        .map(|lock| ProtectionLock {
+            symbol: lock.pair,
+            until: lock.lock_until,
+            protection: lock.protection,
+            reason: lock.reason,
+        })
+        .collect())
+}
+
+/// Persist new locks for a user
+pub async fn save_locks(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i64,
+    locks: &[ProtectionLock],
+) -> Result<(), anyhow::Error> {
+    if locks.is_empty() {
+        return Ok(());
+    }
+    
+    let models = locks.iter().map(|lock| protection_locks::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        pair: ActiveValue::Set(lock.symbol.clone()),
+        protection: ActiveValue::Set(lock.protection.clone()),
+        reason: ActiveValue::Set(lock.reason.clone()),
+        lock_until: ActiveValue::Set(lock.until),
+        created_at: ActiveValue::Set(Some(Utc::now())),
+        ..Default::default()
+    });
+    protection_locks::Entity::insert_many(models)
+        .exec(db)
+        .await?;
+    
+    Ok(())
+}
+
+/// Closed trade as seen by protections
+fn to_protection_trade(trade: &trades::Model) -> Option<ProtectionTrade> {
+    Some(ProtectionTrade {
+        symbol: trade.pair.clone(),
+        exit_time: trade.exit_time?,
+        pnl: trade.pnl.parse().unwrap_or(0.0),
+        pnl_ratio: trade.pnl_percent.parse::<f64>().unwrap_or(0.0) / 100.0,
+        // Trades closed before exit reasons were recorded count as signal exits
+        exit_reason: trade.exit_reason.as_deref()
+            .and_then(|r| ExitReason::from_str(r).ok())
+            .unwrap_or(ExitReason::Signal),
+    })
+}
+
+/// Evaluate the session's protections after a trade of the user closed at `time` and persist any new locks
+pub async fn apply_protections(
+    app_state: &Arc<AppState>,
+    session_id: u64,
+    user_id: i64,
+    pair: &str,
+    time: DateTime<Utc>,
+) -> Result<(), anyhow::Error> {
+    if !app_state.strategy_executor.has_protections(session_id).await {
+        return Ok(());
+    }
+    
+    let db = app_state.db.as_ref();
+    let trades: Vec<ProtectionTrade> = position_service::get_user_trades(db, user_id, None).await?
+        .iter()
+        .filter_map(to_protection_trade)
+        .collect();
+    let account = position_service::get_sizing_account(db, user_id).await?;
+    
+    let locks = app_state.strategy_executor
+        .evaluate_protections(session_id, pair, &trades, account.equity, time)
+        .await;
+    for lock in &locks {
+        info!("🔒 [User {}] {} locked {} until {}: {}", user_id, lock.protection,
+            lock.symbol.as_deref().unwrap_or("all pairs"), lock.until.format("%Y-%m-%d %H:%M"), lock.reason);
+    }
+    save_locks(db, user_id, &locks).await
+}
@@ -0,0 +1,367 @@
+//! Live Session Service
+//!
+//! Starts and stops live trading sessions. Each session runs one strategy in the
+//! executor under the id of its `live_trading_sessions` row, so a user can run several
+//! strategies and pairs at once, up to the concurrent session limit of their tier.
+//! Running sessions can be paused, resumed and reloaded with edited parameters
+//! without losing their streams or warm-up.
+
+use anyhow::Result;
+use std::sync::Arc;
+use chrono::Utc;
+use sea_orm::{ActiveValue, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Order};
+use shared::entity::{live_trading_sessions, users};
+use tracing::{info, error};
+use crate::services::strategy_engine::{StrategyConfig, StrategyExecutor};
+use crate::services::trading_signal::Subscriber;
+use crate::state::AppState;
+
+/// Concurrent sessions on the free trial (or without a subscription)
+pub const FREE_MAX_SESSIONS: usize = 1;
+
+/// Concurrent sessions with an active paid subscription
+pub const PAID_MAX_SESSIONS: usize = 5;
+
+/// Concurrent sessions a user may run
+pub fn max_sessions(user: &users::Model) -> usize {
+    let is_trial = user.subscription_tier.as_deref().is_none_or(|tier| tier.contains("free_trial"));
+    let is_subscribed = user.subscription_expires.is_some_and(|exp| exp > Utc::now());
+    if is_subscribed && !is_trial {
+        PAID_MAX_SESSIONS
+    } else {
+        FREE_MAX_SESSIONS
+    }
+}
+
+/// Active sessions of a user, most recent first
+pub async fn active_sessions(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i64,
+) -> Result<Vec<live_trading_sessions::Model>, anyhow::Error> {
+    Ok(live_trading_sessions::Entity::find()
+        .filter(live_trading_sessions::Column::UserId.eq(user_id))
+        .filter(live_trading_sessions::Column::Status.eq("active"))
+        .order_by(live_trading_sessions::Column::StartedAt, Order::Desc)
+        .all(db)
+        .await?)
+}
+
+/// Check the user's active sessions against their tier
+/// Returns the limit if the user cannot start another session
+pub async fn session_limit_reached(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i64,
+) -> Result<Option<usize>, anyhow::Error> {
+    let limit = users::Entity::find_by_id(user_id)
+        .one(db)
+        .await?
+        .map(|user| max_sessions(&user))
+        .unwrap_or(FREE_MAX_SESSIONS);
+    let active = active_sessions(db, user_id).await?.len();
+
+    Ok((active >= limit).then_some(limit))
+}
+
+/// Check that a user can start a session of `strategy_config`
+/// `limit` is the session limit the user has reached, if any.
+async fn check_can_start(
+    executor: &StrategyExecutor,
+    user_id: i64,
+    limit: Option<usize>,
+    strategy_config: &StrategyConfig,
+) -> Result<()> {
+    if let Some(limit) = limit {
+        return Err(anyhow::anyhow!("Session limit reached ({} active sessions)", limit));
+    }
+    // Positions and snapshots are kept per user and pair, so two sessions cannot share a pair
+    if strategy_config.pairlist().is_none() {
+        if let Some(other) = executor.session_trading_pair(user_id, &strategy_config.pair).await {
+            return Err(anyhow::anyhow!("{} is already traded by session #{}", strategy_config.pair, other));
+        }
+    }
+    Ok(())
+}
+
+/// Start a live session of a user and its trading services
+/// Returns the id of the new session
+pub async fn start_session(
+    state: &Arc<AppState>,
+    bot: teloxide::Bot,
+    user_id: i64,
+    user_chat_id: i64, // Telegram chat ID to send signals to
+    exchange: &str,
+    strategy_config: StrategyConfig,
+    strategy_id: Option<u64>, // Strategy ID from database
+) -> Result<u64> {
+    let db = state.db.as_ref();
+    let limit = session_limit_reached(db, user_id).await?;
+    check_can_start(&state.strategy_executor, user_id, limit, &strategy_config).await?;
+
+    let session = live_trading_sessions::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        strategy_id: ActiveValue::Set(strategy_id),
+        strategy_name: ActiveValue::Set(Some(strategy_config.strategy_type.clone())),
+        exchange: ActiveValue::Set(exchange.to_string()),
+        pair: ActiveValue::Set(strategy_config.pair.clone()),
+        timeframe: ActiveValue::Set(Some(strategy_config.timeframe.clone())),
+        status: ActiveValue::Set("active".to_string()),
+        started_at: ActiveValue::Set(Some(Utc::now())),
+        stopped_at: ActiveValue::NotSet,
+        created_at: ActiveValue::Set(Some(Utc::now())),
+        updated_at: ActiveValue::Set(Some(Utc::now())),
+        ..Default::default()
+    };
+    let session_id = live_trading_sessions::Entity::insert(session)
+        .exec(db)
+        .await?
+        .last_insert_id;
+
+    // Register the session's strategy, the row is kept as "error" if it cannot run
+    if let Err(e) = state.strategy_executor
+        .start_trading(session_id, user_id, strategy_config.clone(), Some(exchange.to_string()))
+        .await
+    {
+        set_status(db, session_id, "error").await?;
+        return Err(e);
+    }
+
+    info!("✅ Created live trading session {} for user {} with strategy {}",
+        session_id, user_id,
+        strategy_id.map(|id| id.to_string()).unwrap_or_else(|| "N/A".to_string()));
+
+    use crate::services::trading_signal::start_user_trading_service;
+    use crate::services::pairlist_service::start_pairlist_service;
+
+    if strategy_config.pairlist().is_some() {
+        // The pairlist service starts one trading service per selected pair
+        start_pairlist_service(state.clone(), bot, session_id, user_id, user_chat_id, strategy_config, exchange.to_string());
+    } else {
+        let pair = strategy_config.pair.clone();
+        start_user_trading_service(
+            state.clone(),
+            bot,
+            session_id,
+            user_id,
+            user_chat_id,
+            strategy_config,
+            exchange.to_string(),
+            pair,
+        );
+    }
+    Ok(session_id)
+}
+
+/// Stop a live session: its strategy, stream subscriptions and snapshots
+pub async fn stop_session(
+    state: &Arc<AppState>,
+    session: &live_trading_sessions::Model,
+) -> Result<()> {
+    if let Some((exchange, pairs)) = state.strategy_executor.stop_trading(session.id).await? {
+        // Unsubscribe from the streams of every traded pair
+        for pair in &pairs {
+            state.stream_manager.unsubscribe(&exchange, pair, &Subscriber::Session(session.id)).await;
+        }
+
+        // The next session on these pairs starts fresh instead of resuming this one's strategy state
+        if let Err(e) = crate::services::snapshot_service::delete_snapshots(state.db.as_ref(), session.user_id, &pairs).await {
+            error!("Failed to delete strategy snapshots for session {}: {}", session.id, e);
+        }
+    }
+
+    set_status(state.db.as_ref(), session.id, "stopped").await?;
+    info!("✅ Updated live trading session {} status to stopped for user {}", session.id, session.user_id);
+    Ok(())
+}
+
+/// Pause the signals of a session; candles keep feeding its indicators
+pub async fn pause_session(
+    state: &Arc<AppState>,
+    session: &live_trading_sessions::Model,
+) -> Result<()> {
+    if !state.strategy_executor.set_paused(session.id, true).await {
+        return Err(anyhow::anyhow!("Session #{} is not running", session.id));
+    }
+    set_paused_at(state.db.as_ref(), session.id, Some(Utc::now())).await?;
+    info!("⏸️ Paused live trading session {} for user {}", session.id, session.user_id);
+    Ok(())
+}
+
+/// Resume the signals of a paused session
+pub async fn resume_session(
+    state: &Arc<AppState>,
+    session: &live_trading_sessions::Model,
+) -> Result<()> {
+    if !state.strategy_executor.set_paused(session.id, false).await {
+        return Err(anyhow::anyhow!("Session #{} is not running", session.id));
+    }
+    set_paused_at(state.db.as_ref(), session.id, None).await?;
+    info!("▶️ Resumed live trading session {} for user {}", session.id, session.user_id);
+    Ok(())
+}
+
+/// Hot-reload the saved strategy of a session into the running session
+/// Thresholds and parameters may change, the strategy type and indicator periods may not.
+/// The changes are appended to the session's `config_changes`; returns the changed keys.
+pub async fn reload_session(
+    state: &Arc<AppState>,
+    session: &live_trading_sessions::Model,
+) -> Result<Vec<String>> {
+    let strategy_id = session.strategy_id
+        .ok_or_else(|| anyhow::anyhow!("Session #{} has no saved strategy", session.id))?;
+    let strategy = state.strategy_service.get_strategy_by_id(strategy_id).await?
+        .ok_or_else(|| anyhow::anyhow!("Strategy {} not found", strategy_id))?;
+    let config = state.strategy_service.strategy_to_config(&strategy)?;
+
+    let previous = state.strategy_executor.reload_session(session.id, config.clone()).await?;
+    let changes = config_changes(&previous, &config);
+    if changes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut history: Vec<serde_json::Value> = session.config_changes.as_deref()
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+    history.push(serde_json::json!({
+        "at": Utc::now().to_rfc3339(),
+        "changes": changes,
+    }));
+    let update = live_trading_sessions::ActiveModel {
+        id: ActiveValue::Unchanged(session.id),
+        config_changes: ActiveValue::Set(Some(serde_json::to_string(&history)?)),
+        updated_at: ActiveValue::Set(Some(Utc::now())),
+        ..Default::default()
+    };
+    live_trading_sessions::Entity::update(update)
+        .exec(state.db.as_ref())
+        .await?;
+
+    info!("🔄 Reloaded live trading session {} for user {}: {}",
+        session.id, session.user_id, changes.keys().cloned().collect::<Vec<_>>().join(", "));
+    Ok(changes.keys().cloned().collect())
+}
+
+/// Changed conditions and top-level parameters as `{key: {"from", "to"}}`
+fn config_changes(previous: &StrategyConfig, config: &StrategyConfig) -> serde_json::Map<String, serde_json::Value> {
+    let mut changes = serde_json::Map::new();
+    let mut record = |key: String, from: serde_json::Value, to: serde_json::Value| {
+        if from != to {
+            changes.insert(key, serde_json::json!({ "from": from, "to": to }));
+        }
+    };
+    record("buy_condition".to_string(), previous.buy_condition.clone().into(), config.buy_condition.clone().into());
+    record("sell_condition".to_string(), previous.sell_condition.clone().into(), config.sell_condition.clone().into());
+
+    let empty = serde_json::Map::new();
+    let old_params = previous.parameters.as_object().unwrap_or(&empty);
+    let new_params = config.parameters.as_object().unwrap_or(&empty);
+    let mut keys: Vec<&String> = old_params.keys().chain(new_params.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        record(
+            format!("parameters.{}", key),
+            old_params.get(key).cloned().unwrap_or_default(),
+            new_params.get(key).cloned().unwrap_or_default(),
+        );
+    }
+    changes
+}
+
+/// Set or clear the pause time of a session row
+async fn set_paused_at(
+    db: &sea_orm::DatabaseConnection,
+    session_id: u64,
+    paused_at: Option<chrono::DateTime<Utc>>,
+) -> Result<(), anyhow::Error> {
+    let session = live_trading_sessions::ActiveModel {
+        id: ActiveValue::Unchanged(session_id),
+        paused_at: ActiveValue::Set(paused_at),
+        updated_at: ActiveValue::Set(Some(Utc::now())),
+        ..Default::default()
+    };
+    live_trading_sessions::Entity::update(session)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Update the status of a session row
+async fn set_status(
+    db: &sea_orm::DatabaseConnection,
+    session_id: u64,
+    status: &str,
+) -> Result<(), anyhow::Error> {
+    let session = live_trading_sessions::ActiveModel {
+        id: ActiveValue::Unchanged(session_id),
+        status: ActiveValue::Set(status.to_string()),
+        stopped_at: ActiveValue::Set(Some(Utc::now())),
+        updated_at: ActiveValue::Set(Some(Utc::now())),
+        ..Default::default()
+    };
+    live_trading_sessions::Entity::update(session)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pair: &str) -> StrategyConfig {
+        StrategyConfig {
+            strategy_type: "EXPRESSION".to_string(),
+            parameters: serde_json::json!({}),
+            pair: pair.to_string(),
+            timeframe: "1m".to_string(),
+            buy_condition: "close > 100".to_string(),
+            sell_condition: "close < 0".to_string(),
+        }
+    }
+
+    fn user(tier: Option<&str>, expires_in_days: i64) -> users::Model {
+        users::Model {
+            id: 1,
+            username: None,
+            language: None,
+            created_at: None,
+            subscription_tier: tier.map(str::to_string),
+            subscription_expires: Some(Utc::now() + chrono::Duration::days(expires_in_days)),
+            live_trading_enabled: None,
+            telegram_id: None,
+            fullname: None,
+            points: 0,
+        }
+    }
+
+    #[test]
+    fn test_max_sessions_by_tier() {
+        assert_eq!(max_sessions(&user(Some("pro"), 30)), PAID_MAX_SESSIONS);
+        assert_eq!(max_sessions(&user(Some("pro"), -1)), FREE_MAX_SESSIONS, "expired subscription");
+        assert_eq!(max_sessions(&user(Some("free_trial"), 30)), FREE_MAX_SESSIONS);
+        assert_eq!(max_sessions(&user(None, 30)), FREE_MAX_SESSIONS);
+    }
+
+    #[tokio::test]
+    async fn test_check_can_start_rejects_reached_limit() {
+        let executor = StrategyExecutor::new();
+        let error = check_can_start(&executor, 1, Some(FREE_MAX_SESSIONS), &config("BTC/USDT")).await.unwrap_err();
+        assert_eq!(error.to_string(), "Session limit reached (1 active sessions)");
+        assert!(check_can_start(&executor, 1, None, &config("BTC/USDT")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_check_can_start_rejects_a_pair_traded_by_another_session() {
+        let executor = StrategyExecutor::new();
+        executor.start_trading(7, 1, config("BTC/USDT"), None).await.unwrap();
+
+        for pair in ["BTC/USDT", "BTCUSDT"] {
+            let error = check_can_start(&executor, 1, None, &config(pair)).await.unwrap_err();
+            assert_eq!(error.to_string(), format!("{} is already traded by session #7", pair));
+        }
+        assert!(check_can_start(&executor, 1, None, &config("ETH/USDT")).await.is_ok());
+        assert!(check_can_start(&executor, 2, None, &config("BTC/USDT")).await.is_ok(), "other users trade it too");
+    }
+}
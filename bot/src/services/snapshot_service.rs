@@ -0,0 +1,178 @@
+//! Strategy Snapshot Service
+//!
+//! Checkpoints the state of running strategies (indicators, price buffers, last signal
+//! and the forming candle) so a restarted bot resumes them instead of re-warming
+//! from live candles.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use shared::entity::strategy_snapshots;
+use crate::services::strategy_engine::executor::PairSnapshot;
+
+/// Seconds between checkpoints of a running pair
+pub const SNAPSHOT_INTERVAL_SECS: u64 = 60;
+
+/// Snapshots older than this many candles of the strategy's timeframe are discarded;
+/// the gap would leave the indicators out of step with the market
+pub const MAX_MISSED_CANDLES: i32 = 10;
+
+/// Save (or replace) the snapshot of one of a user's pairs
+pub async fn save_snapshot(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i64,
+    pair: &str,
+    snapshot: &PairSnapshot,
+) -> Result<(), anyhow::Error> {
+    let state = serde_json::to_string(snapshot)?;
+    let existing = strategy_snapshots::Entity::find()
+        .filter(strategy_snapshots::Column::UserId.eq(user_id))
+        .filter(strategy_snapshots::Column::Pair.eq(pair))
+        .one(db)
+        .await?;
+    
+    let model = strategy_snapshots::ActiveModel {
+        id: existing.map(|s| ActiveValue::Unchanged(s.id)).unwrap_or(ActiveValue::NotSet),
+        user_id: ActiveValue::Set(user_id),
+        pair: ActiveValue::Set(pair.to_string()),
+        strategy_type: ActiveValue::Set(snapshot.strategy_type.clone()),
+        state: ActiveValue::Set(state),
+        updated_at: ActiveValue::Set(Utc::now()),
+    };
+    if model.id.is_not_set() {
+        strategy_snapshots::Entity::insert(model).exec(db).await?;
+    } else {
+        strategy_snapshots::Entity::update(model).exec(db).await?;
+    }
+    
+    Ok(())
+}
+
+/// Load the snapshot of one of a user's pairs with the time it was taken
+pub async fn load_snapshot(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i64,
+    pair: &str,
+) -> Result<Option<(PairSnapshot, DateTime<Utc>)>, anyhow::Error> {
+    let Some(row) = strategy_snapshots::Entity::find()
+        .filter(strategy_snapshots::Column::UserId.eq(user_id))
+        .filter(strategy_snapshots::Column::Pair.eq(pair))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    
+    Ok(Some((serde_json::from_str(&row.state)?, row.updated_at)))
+}
+
+/// Delete the snapshots of a user's pairs (their session was stopped, the next one starts fresh)
+pub async fn delete_snapshots(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i64,
+    pairs: &[String],
+) -> Result<(), anyhow::Error> {
+    strategy_snapshots::Entity::delete_many()
+        .filter(strategy_snapshots::Column::UserId.eq(user_id))
+        .filter(strategy_snapshots::Column::Pair.is_in(pairs.iter().cloned()))
+        .exec(db)
+        .await?;
+    
+    Ok(())
+}
+
+/// Check if a snapshot taken at `taken_at` is recent enough to resume a strategy on `timeframe`
+pub fn is_resumable(taken_at: DateTime<Utc>, timeframe: &str, now: DateTime<Utc>) -> bool {
+    let candle = freqtrade_rs::data::timeframe_duration(timeframe)
+        .unwrap_or_else(|| chrono::Duration::minutes(1));
+    let max_age = (candle * MAX_MISSED_CANDLES).max(chrono::Duration::minutes(15));
+    now - taken_at <= max_age
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use crate::services::strategy_engine::{Candle, StrategyConfig, StrategyExecutor};
+
+    fn config(buy_condition: &str) -> StrategyConfig {
+        StrategyConfig {
+            strategy_type: "EXPRESSION".to_string(),
+            parameters: serde_json::json!({}),
+            pair: "BTC/USDT".to_string(),
+            timeframe: "1m".to_string(),
+            buy_condition: buy_condition.to_string(),
+            sell_condition: "close < 0".to_string(),
+        }
+    }
+
+    fn candles(count: i64) -> Vec<Candle> {
+        (0..count)
+            .map(|i| {
+                let close = 100.0 + i as f64;
+                Candle { open: close, high: close + 1.0, low: close - 1.0, close, volume: 1.0, timestamp: i * 60 }
+            })
+            .collect()
+    }
+
+    /// Snapshot of a session whose strategy was warmed up on a few candles
+    async fn warm_snapshot(buy_condition: &str) -> PairSnapshot {
+        let executor = StrategyExecutor::new();
+        executor.start_trading(1, 7, config(buy_condition), None).await.unwrap();
+        executor.warm_up(1, "BTC/USDT", &candles(10)).await;
+        executor.snapshot_pair(1, "BTC/USDT").await.unwrap()
+    }
+
+    #[test]
+    fn test_is_resumable_allows_missed_candles_up_to_the_limit() {
+        let taken_at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let limit = Duration::hours(MAX_MISSED_CANDLES as i64);
+
+        assert!(is_resumable(taken_at, "1h", taken_at + limit));
+        assert!(!is_resumable(taken_at, "1h", taken_at + limit + Duration::seconds(1)));
+    }
+
+    #[test]
+    fn test_is_resumable_has_a_15_minute_floor() {
+        let taken_at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        // 10 missed 1m candles would only allow 10 minutes
+        assert!(is_resumable(taken_at, "1m", taken_at + Duration::minutes(15)));
+        assert!(!is_resumable(taken_at, "1m", taken_at + Duration::minutes(16)));
+        // Unknown timeframes count as 1m
+        assert!(is_resumable(taken_at, "?", taken_at + Duration::minutes(15)));
+    }
+
+    #[tokio::test]
+    async fn test_restore_pair_resumes_the_strategy() {
+        let snapshot = warm_snapshot("close > SMA(5)").await;
+
+        let executor = StrategyExecutor::new();
+        executor.start_trading(2, 7, config("close > SMA(5)"), None).await.unwrap();
+        assert!(executor.warmup_needed(2, "BTC/USDT").await > 0);
+
+        executor.restore_pair(2, "BTC/USDT", snapshot.clone()).await.unwrap();
+
+        assert_eq!(executor.warmup_needed(2, "BTC/USDT").await, 0);
+        let restored = executor.snapshot_pair(2, "BTC/USDT").await.unwrap();
+        assert_eq!(restored.strategy, snapshot.strategy);
+        assert_eq!(restored.last_signal, snapshot.last_signal);
+    }
+
+    #[tokio::test]
+    async fn test_restore_pair_rejects_a_changed_config() {
+        let snapshot = warm_snapshot("close > SMA(5)").await;
+
+        let executor = StrategyExecutor::new();
+        executor.start_trading(2, 7, config("close > SMA(8)"), None).await.unwrap();
+
+        let err = executor.restore_pair(2, "BTC/USDT", snapshot.clone()).await.unwrap_err();
+        assert!(err.to_string().contains("different strategy configuration"), "{}", err);
+        assert!(executor.warmup_needed(2, "BTC/USDT").await > 0);
+
+        // Snapshots of another strategy type or pair are rejected as well
+        let other_type = PairSnapshot { strategy_type: "GRID".to_string(), ..snapshot.clone() };
+        assert!(executor.restore_pair(2, "BTC/USDT", other_type).await.is_err());
+        assert!(executor.restore_pair(2, "ETH/USDT", snapshot).await.is_err());
+    }
+}
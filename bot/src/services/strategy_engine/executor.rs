@@ -4,160 +4,947 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use anyhow::Result;
 use std::collections::HashMap;
-use crate::services::strategy_engine::{Strategy, StrategyConfig, Candle, StrategySignal};
+use crate::services::strategy_engine::{LiveStrategy, StrategyConfig, Candle, StrategySignal, SignalExplanation};
+use freqtrade_rs::data::{same_pair, InformativeData};
+use freqtrade_rs::indicators::{Indicator, ATR};
+use freqtrade_rs::portfolio::{Position, PositionSide, ProtectionLock, ProtectionManager, ProtectionTrade};
+use freqtrade_rs::exchange::OrderSide;
+use freqtrade_rs::config::RiskConfig;
+use freqtrade_rs::strategy::{ClauseResult, ExitReason, LimitOrder, TrendFilter};
+use serde::{Deserialize, Serialize};
 
-/// User's trading state with strategy
+/// A user's trading session: one strategy instance per traded pair
 pub struct UserTradingState {
+    /// Id of the session's `live_trading_sessions` row
+    pub session_id: u64,
     pub user_id: i64,
-    pub strategy: Box<dyn Strategy>,
-    pub pair: String,
+    pub config: StrategyConfig,
     pub exchange: String, // Store exchange for stream management
     pub is_active: bool,
+    /// Paused sessions keep their streams and indicators but emit no strategy signals
+    pub is_paused: bool,
+    /// Traded pairs: the configured pair, or the current pairlist plus pairs still holding a position
+    pub pairs: HashMap<String, PairTradingState>,
+    /// Protections and their active locks (restored from the database on restart)
+    pub protections: ProtectionManager,
+}
+
+impl UserTradingState {
+    /// Create the strategy state for one pair of the session
+    fn new_pair(&self, pair: &str) -> Result<PairTradingState> {
+        PairTradingState::new(self.user_id, StrategyConfig {
+            pair: pair.to_string(),
+            ..self.config.clone()
+        })
+    }
+    
+    /// Strategy name shown to the user
+    fn strategy_name(&self) -> &str {
+        self.pairs.values()
+            .next()
+            .map(|p| p.strategy.name())
+            .unwrap_or(&self.config.strategy_type)
+    }
+    
+    /// Traded pairs, sorted
+    fn pair_names(&self) -> Vec<String> {
+        let mut pairs: Vec<String> = self.pairs.keys().cloned().collect();
+        pairs.sort();
+        pairs
+    }
+    
+    /// Feed a completed candle of `pair` and evaluate its strategy
+    fn evaluate_candle(&mut self, pair: &str, candle: &Candle) -> Option<StrategySignal> {
+        let protections = &self.protections;
+        self.pairs.get_mut(pair)?.evaluate_candle(candle, protections, self.is_paused)
+    }
+}
+
+/// Strategy state of one pair in a user's session
+pub struct PairTradingState {
+    pub user_id: i64,
+    pub strategy: LiveStrategy,
+    pub pair: String,
+    /// ATR tracked for volatility-based position sizing
+    pub atr: Option<ATR>,
+    /// Closed higher-timeframe / other-pair candles
+    pub informative: InformativeData,
+    /// Entry filters evaluated on informative candles
+    pub informative_filters: Vec<TrendFilter>,
+    /// Open position mirrored from the database, managed by the strategy's trade callbacks
+    pub position: Option<Position>,
+    /// Limit orders the strategy rests until its next candle
+    pub resting_orders: Vec<LimitOrder>,
+    /// Last BUY / SELL / ADJUST signal the strategy raised
+    pub last_signal: Option<StrategySignal>,
+    /// Candles fed so far against the strategy's lookback
+    pub warmup: WarmupProgress,
 }
 
-/// Strategy Executor - manages multiple users' strategies
+/// Warm-up progress of a pair's strategy: closed candles seen vs. its required lookback
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarmupProgress {
+    pub candles: usize,
+    pub required: usize,
+}
+
+impl WarmupProgress {
+    /// Check if the strategy has seen its full lookback
+    pub fn is_complete(&self) -> bool {
+        self.candles >= self.required
+    }
+    
+    /// Share of the lookback seen, in percent
+    pub fn percent(&self) -> f64 {
+        if self.required == 0 {
+            return 100.0;
+        }
+        (self.candles.min(self.required) as f64 / self.required as f64) * 100.0
+    }
+}
+
+/// Checkpoint of one pair's strategy state, resumed after a bot restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairSnapshot {
+    pub strategy_type: String,
+    /// Strategy state from `LiveStrategy::snapshot`
+    pub strategy: serde_json::Value,
+    pub atr: Option<ATR>,
+    pub last_signal: Option<StrategySignal>,
+    /// Candle still forming when the snapshot was taken
+    pub forming_candle: Option<Candle>,
+}
+
+impl PairTradingState {
+    fn new(user_id: i64, strategy_config: StrategyConfig) -> Result<Self> {
+        use crate::services::strategy_engine::StrategyRegistry;
+        
+        let registry = StrategyRegistry::new();
+        let strategy = registry.create_strategy(strategy_config.clone())?;
+        let atr = strategy_config.position_sizing()?.build().atr_period().map(ATR::new);
+        let informative_filters = strategy_config.informative_filters();
+        let specs: Vec<_> = informative_filters.iter().map(|f| f.spec()).collect();
+        let informative = InformativeData::new(&specs, &strategy_config.pair);
+        let warmup = WarmupProgress {
+            candles: 0,
+            required: strategy.warmup_candles(),
+        };
+        
+        Ok(Self {
+            user_id,
+            strategy,
+            pair: strategy_config.pair,
+            atr,
+            informative,
+            informative_filters,
+            position: None,
+            resting_orders: Vec::new(),
+            last_signal: None,
+            warmup,
+        })
+    }
+    
+    /// Feed a completed candle and evaluate the strategy and informative filters
+    /// While `paused` only the stops and custom exits of the open position are raised.
+    fn evaluate_candle(&mut self, candle: &Candle, protections: &ProtectionManager, paused: bool) -> Option<StrategySignal> {
+        // The strategy sees every candle, even when the position callbacks act on it
+        let signal = self.feed_candle(candle);
+        
+        // Stops and custom exits of the open position take precedence over the strategy signal
+        if let Some(exit) = self.check_position_exits(candle) {
+            return Some(exit);
+        }
+        if paused {
+            return None;
+        }
+        if !matches!(signal, Some(StrategySignal::Sell { .. })) {
+            if let Some(adjustment) = self.check_position_adjustment(candle) {
+                return Some(adjustment);
+            }
+        }
+        
+        let signal = signal?;
+        if !matches!(signal, StrategySignal::Hold) {
+            self.last_signal = Some(signal.clone());
+        }
+        match &signal {
+            StrategySignal::Buy { .. } => {
+                // Locks are checked at candle time, as in backtests, so replays and warm-ups see them too
+                let time = chrono::DateTime::from_timestamp(candle.timestamp, 0).unwrap_or_default();
+                if let Some(lock) = protections.lock_for(&self.pair, time) {
+                    tracing::info!("🔒 [User {}] BUY blocked by {} until {}: {}",
+                        self.user_id, lock.protection, lock.until.format("%Y-%m-%d %H:%M"), lock.reason);
+                    return None;
+                }
+                if let Some(filter) = self.informative_filters.iter()
+                    .find(|f| !f.allows_entry(&self.informative, &self.pair))
+                {
+                    tracing::info!("⏭️ [User {}] BUY blocked by informative filter {} {} (EMA {})",
+                        self.user_id, filter.symbol.as_deref().unwrap_or(&self.pair), filter.timeframe, filter.ema_period);
+                    return None;
+                }
+                if !self.strategy.confirm_trade_entry(&signal, candle) {
+                    tracing::info!("⏭️ [User {}] BUY rejected by confirm_trade_entry", self.user_id);
+                    return None;
+                }
+            }
+            StrategySignal::Sell { .. } => {
+                if let Some(position) = &self.position {
+                    if !self.strategy.confirm_trade_exit(position, ExitReason::Signal, candle) {
+                        tracing::info!("⏭️ [User {}] SELL rejected by confirm_trade_exit", self.user_id);
+                        return None;
+                    }
+                }
+            }
+            StrategySignal::AdjustPosition { .. } | StrategySignal::Hold => {}
+        }
+        Some(signal)
+    }
+    
+    /// Update the indicators, informative candles and strategy with a completed candle
+    /// Returns the raw strategy signal, before position callbacks and entry filters
+    fn feed_candle(&mut self, candle: &Candle) -> Option<StrategySignal> {
+        if let Some(atr) = self.atr.as_mut() {
+            atr.update_hlc(candle.high, candle.low, candle.close);
+        }
+        
+        let timeframe = self.strategy.config().timeframe.clone();
+        let mut base_candle = candle.to_freqtrade(&self.pair, &timeframe);
+        let duration = freqtrade_rs::data::timeframe_duration(&timeframe);
+        if let Some(duration) = duration {
+            // Live candles are stamped with their first trade's minute; align to the period start
+            let secs = duration.num_seconds();
+            let aligned = candle.timestamp.div_euclid(secs) * secs;
+            base_candle.timestamp = chrono::DateTime::from_timestamp(aligned, 0).unwrap_or_default();
+        }
+        self.informative.feed(&base_candle);
+        // Other-pair buckets that ended by this candle's close are complete
+        if let Some(duration) = duration {
+            self.informative.close_until(base_candle.timestamp + duration);
+        }
+        
+        let signal = self.strategy.process_candle_with_informative(candle, &self.informative);
+        self.resting_orders = self.strategy.limit_orders();
+        self.warmup.candles += 1;
+        signal
+    }
+    
+    /// Run `adjust_trade_position` for the open position
+    /// Returns an ADJUST signal for DCA entries and partial exits
+    fn check_position_adjustment(&mut self, candle: &Candle) -> Option<StrategySignal> {
+        let position = self.position.as_ref()?;
+        let stake = self.strategy.adjust_trade_position(position, candle)?;
+        if stake == 0.0 {
+            return None;
+        }
+        if stake < 0.0 && !self.strategy.confirm_trade_exit(position, ExitReason::PartialExit, candle) {
+            tracing::info!("⏭️ [User {}] Partial exit rejected by confirm_trade_exit", self.user_id);
+            return None;
+        }
+        
+        let reason = if stake > 0.0 {
+            format!("DCA entry #{}", position.entry_count() + 1)
+        } else {
+            ExitReason::PartialExit.to_string()
+        };
+        Some(StrategySignal::AdjustPosition {
+            stake,
+            price: candle.close,
+            reason,
+        })
+    }
+    
+    /// Stop loss, take profit and trailing stop of a position entered at `entry_price`:
+    /// the entry signal's levels, else the defaults the backtester uses
+    fn exit_levels(&self, entry_price: f64) -> (f64, f64, Option<f64>) {
+        let levels = self.strategy.entry_levels();
+        let (default_stop, default_take_profit) = RiskConfig::default().default_exit_levels(PositionSide::Long, entry_price);
+        (
+            levels.stop_loss.unwrap_or(default_stop),
+            levels.take_profit.unwrap_or(default_take_profit),
+            levels.trailing_stop,
+        )
+    }
+    
+    /// Check the open position's stop loss, take profit and trailing stop, and run
+    /// `custom_stoploss` / `custom_exit`
+    /// Returns a SELL signal if the position should be closed
+    fn check_position_exits(&mut self, candle: &Candle) -> Option<StrategySignal> {
+        let position = self.position.as_mut()?;
+        
+        position.update_price(candle.low);
+        if position.is_stop_loss_hit() {
+            let reason = position.stop_exit_reason();
+            if self.strategy.confirm_trade_exit(position, reason, candle) {
+                return Some(StrategySignal::Sell {
+                    confidence: 1.0,
+                    price: position.stop_loss.unwrap_or(candle.close),
+                    reason: reason.to_string(),
+                    exit_reason: reason,
+                });
+            }
+        }
+        
+        position.update_price(candle.high);
+        if position.is_take_profit_hit()
+            && self.strategy.confirm_trade_exit(position, ExitReason::Roi, candle)
+        {
+            return Some(StrategySignal::Sell {
+                confidence: 1.0,
+                price: position.take_profit.unwrap_or(candle.close),
+                reason: ExitReason::Roi.to_string(),
+                exit_reason: ExitReason::Roi,
+            });
+        }
+        position.update_trailing_stop(candle.high);
+        
+        position.update_price(candle.close);
+        if let Some(stop) = self.strategy.custom_stoploss(position, candle) {
+            if position.apply_custom_stoploss(stop) {
+                tracing::info!("🛡️ [User {}] Stop loss moved to {:.4} by custom_stoploss", self.user_id, stop);
+            }
+        }
+        if let Some(tag) = self.strategy.custom_exit(position, candle) {
+            if self.strategy.confirm_trade_exit(position, ExitReason::CustomExit, candle) {
+                return Some(StrategySignal::Sell {
+                    confidence: 1.0,
+                    price: candle.close,
+                    reason: format!("{}: {}", ExitReason::CustomExit, tag),
+                    exit_reason: ExitReason::CustomExit,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Strategy Executor - manages the trading sessions of all users
+/// A user can run several sessions at once, each trading its own pairs.
 pub struct StrategyExecutor {
-    /// Map of user_id -> UserTradingState
-    users: Arc<RwLock<HashMap<i64, UserTradingState>>>,
+    /// Map of session_id -> UserTradingState
+    sessions: Arc<RwLock<HashMap<u64, UserTradingState>>>,
 }
 
 impl StrategyExecutor {
     pub fn new() -> Self {
         Self {
-            users: Arc::new(RwLock::new(HashMap::new())),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
         }
     }
     
-    /// Start trading for a user with a specific strategy
+    /// Start a trading session of a user with a specific strategy
+    /// The session trades `strategy_config.pair` until a pairlist sets its pairs.
+    /// Fails if another session of the user already trades that pair.
     pub async fn start_trading(
         &self,
+        session_id: u64,
         user_id: i64,
         strategy_config: StrategyConfig,
         exchange: Option<String>, // Optional exchange for stream management
     ) -> Result<()> {
-        use crate::services::strategy_engine::StrategyRegistry;
-        
-        let registry = StrategyRegistry::new();
-        let strategy = registry.create_strategy(strategy_config.clone())?;
-        
-        let mut users = self.users.write().await;
-        users.insert(user_id, UserTradingState {
+        let protections = ProtectionManager::new(strategy_config.protections());
+        let mut state = UserTradingState {
+            session_id,
             user_id,
-            strategy,
-            pair: strategy_config.pair,
+            config: strategy_config.clone(),
             exchange: exchange.unwrap_or_else(|| "binance".to_string()),
             is_active: true,
-        });
+            is_paused: false,
+            pairs: HashMap::new(),
+            protections,
+        };
+        if strategy_config.pairlist().is_none() {
+            let pair_state = state.new_pair(&strategy_config.pair)?;
+            state.pairs.insert(strategy_config.pair.clone(), pair_state);
+        }
+        
+        let mut sessions = self.sessions.write().await;
+        if let Some(other) = sessions.values()
+            .find(|s| s.user_id == user_id && s.session_id != session_id
+                && s.pairs.keys().any(|p| state.pairs.keys().any(|pair| same_pair(p, pair))))
+        {
+            return Err(anyhow::anyhow!("{} is already traded by session #{}", strategy_config.pair, other.session_id));
+        }
+        sessions.insert(session_id, state);
         
-        tracing::info!("✅ Started trading for user {} with strategy {}", user_id, strategy_config.strategy_type);
+        tracing::info!("✅ Started session {} for user {} with strategy {}", session_id, user_id, strategy_config.strategy_type);
         Ok(())
     }
     
-    /// Stop trading for a user
-    /// Returns (exchange, pair) if user was trading, for stream cleanup
-    pub async fn stop_trading(&self, user_id: i64) -> Result<Option<(String, String)>> {
-        let mut users = self.users.write().await;
-        if let Some(state) = users.get(&user_id) {
-            let exchange = state.exchange.clone();
-            let pair = state.pair.clone();
-        users.remove(&user_id);
-            tracing::info!("🛑 Stopped trading for user {} ({} on {})", user_id, pair, exchange);
-            Ok(Some((exchange, pair)))
+    /// Stop a trading session
+    /// Returns (exchange, pairs) if the session was running, for stream cleanup
+    pub async fn stop_trading(&self, session_id: u64) -> Result<Option<(String, Vec<String>)>> {
+        let mut sessions = self.sessions.write().await;
+        if let Some(state) = sessions.remove(&session_id) {
+            let pairs = state.pair_names();
+            tracing::info!("🛑 Stopped session {} of user {} ({} on {})", session_id, state.user_id, pairs.join(", "), state.exchange);
+            Ok(Some((state.exchange, pairs)))
         } else {
             Ok(None)
         }
     }
     
-    /// Process a candle for a specific user
+    /// Replace the traded pairs of a session (e.g. after a pairlist refresh)
+    /// Pairs dropped from the list keep trading while their position is open,
+    /// pairs another session of the same user trades are skipped.
+    /// Returns the (added, removed) pairs, or None if the session is not running.
+    pub async fn set_pairs(&self, session_id: u64, pairs: &[String]) -> Option<(Vec<String>, Vec<String>)> {
+        let mut sessions = self.sessions.write().await;
+        let user_id = sessions.get(&session_id)?.user_id;
+        let taken: Vec<String> = sessions.values()
+            .filter(|s| s.user_id == user_id && s.session_id != session_id)
+            .flat_map(|s| s.pairs.keys().cloned())
+            .collect();
+        let state = sessions.get_mut(&session_id)?;
+        
+        let removed: Vec<String> = state.pairs.iter()
+            .filter(|(pair, s)| !pairs.iter().any(|p| same_pair(p, pair)) && s.position.is_none())
+            .map(|(pair, _)| pair.clone())
+            .collect();
+        for pair in &removed {
+            state.pairs.remove(pair);
+        }
+        
+        let mut added = Vec::new();
+        for pair in pairs {
+            if state.pairs.keys().chain(&taken).any(|p| same_pair(p, pair)) {
+                continue;
+            }
+            match state.new_pair(pair) {
+                Ok(pair_state) => {
+                    state.pairs.insert(pair.clone(), pair_state);
+                    added.push(pair.clone());
+                }
+                Err(e) => tracing::error!("Failed to create strategy for session {} on {}: {}", session_id, pair, e),
+            }
+        }
+        
+        Some((added, removed))
+    }
+    
+    /// Pause or resume the signals of a session
+    /// Candles keep feeding the indicators while paused, so a resumed session needs no warm-up.
+    /// Returns false if the session is not running.
+    pub async fn set_paused(&self, session_id: u64, paused: bool) -> bool {
+        let mut sessions = self.sessions.write().await;
+        let Some(state) = sessions.get_mut(&session_id) else {
+            return false;
+        };
+        state.is_paused = paused;
+        tracing::info!("{} session {} of user {}", if paused { "⏸️ Paused" } else { "▶️ Resumed" }, session_id, state.user_id);
+        true
+    }
+    
+    /// Apply edited thresholds and parameters to a running session without losing its warm-up
+    /// The strategy type, timeframe, pairs and indicator periods must stay the same; every
+    /// pair's new strategy resumes the running one's indicators, positions and locks are kept.
+    /// Nothing changes if any pair cannot be reloaded. Returns the replaced configuration.
+    pub async fn reload_session(&self, session_id: u64, strategy_config: StrategyConfig) -> Result<StrategyConfig> {
+        use crate::services::strategy_engine::StrategyRegistry;
+        
+        let mut sessions = self.sessions.write().await;
+        let state = sessions.get_mut(&session_id)
+            .ok_or_else(|| anyhow::anyhow!("Session {} is not running", session_id))?;
+        let current = &state.config;
+        if !strategy_config.strategy_type.eq_ignore_ascii_case(&current.strategy_type) {
+            return Err(anyhow::anyhow!("Strategy type changed from {} to {}", current.strategy_type, strategy_config.strategy_type));
+        }
+        if strategy_config.timeframe != current.timeframe {
+            return Err(anyhow::anyhow!("Timeframe changed from {} to {}", current.timeframe, strategy_config.timeframe));
+        }
+        if strategy_config.pair != current.pair
+            || strategy_config.parameters.get("pairlist") != current.parameters.get("pairlist")
+        {
+            return Err(anyhow::anyhow!("Traded pairs changed"));
+        }
+        if strategy_config.parameters.get("informative") != current.parameters.get("informative") {
+            return Err(anyhow::anyhow!("Informative filters changed"));
+        }
+        let atr_period = strategy_config.position_sizing()?.build().atr_period();
+        if current.position_sizing().ok().and_then(|s| s.build().atr_period()) != atr_period {
+            return Err(anyhow::anyhow!("ATR period of the position sizing changed"));
+        }
+        
+        let registry = StrategyRegistry::new();
+        let mut strategies = HashMap::new();
+        for (pair, pair_state) in &state.pairs {
+            let mut strategy = registry.create_strategy(StrategyConfig {
+                pair: pair.clone(),
+                ..strategy_config.clone()
+            })?;
+            strategy.resume_indicators(&pair_state.strategy)
+                .map_err(|e| anyhow::anyhow!("{}: {}", pair, e))?;
+            strategies.insert(pair.clone(), strategy);
+        }
+        
+        for (pair, strategy) in strategies {
+            if let Some(pair_state) = state.pairs.get_mut(&pair) {
+                pair_state.resting_orders = strategy.limit_orders();
+                pair_state.warmup.required = strategy.warmup_candles();
+                pair_state.strategy = strategy;
+            }
+        }
+        let protections = strategy_config.protections();
+        if protections != state.config.protections() {
+            state.protections = ProtectionManager::new(protections)
+                .with_locks(state.protections.locks().to_vec());
+        }
+        let previous = std::mem::replace(&mut state.config, strategy_config);
+        
+        tracing::info!("🔄 Reloaded session {} of user {} ({} pairs)", session_id, state.user_id, state.pairs.len());
+        Ok(previous)
+    }
+    
+    /// Check if a session is trading a pair
+    pub async fn is_pair_trading(&self, session_id: u64, pair: &str) -> bool {
+        let sessions = self.sessions.read().await;
+        sessions.get(&session_id)
+            .is_some_and(|s| s.is_active && s.pairs.contains_key(pair))
+    }
+    
+    /// Process a candle of one of a session's pairs
     pub async fn process_candle(
         &self,
-        user_id: i64,
+        session_id: u64,
+        pair: &str,
         candle: &Candle,
     ) -> Option<StrategySignal> {
-        let mut users = self.users.write().await;
+        let mut sessions = self.sessions.write().await;
         
-        if let Some(state) = users.get_mut(&user_id) {
+        if let Some(state) = sessions.get_mut(&session_id) {
             if state.is_active {
-                // Process candle regardless of pair match (pair matching is done at stream level)
                 // Log strategy evaluation for debugging
-                tracing::debug!("🔍 [User {}] Processing candle for strategy '{}' (pair: {}, timeframe: {})", 
-                    user_id, state.strategy.name(), state.pair, state.strategy.config().timeframe);
-                return state.strategy.process_candle(candle);
+                tracing::debug!("🔍 [Session {}] Processing candle for strategy '{}' (pair: {}, timeframe: {})", 
+                    session_id, state.strategy_name(), pair, state.config.timeframe);
+                return state.evaluate_candle(pair, candle);
             } else {
-                tracing::warn!("⚠️ [User {}] Strategy is not active, skipping candle processing", user_id);
+                tracing::warn!("⚠️ [Session {}] Strategy is not active, skipping candle processing", session_id);
             }
         } else {
-            tracing::warn!("⚠️ [User {}] No trading state found, skipping candle processing", user_id);
+            tracing::warn!("⚠️ [Session {}] No trading state found, skipping candle processing", session_id);
         }
         
         None
     }
     
-    /// Process candle for all active users
-    /// Returns vector of (user_id, signal) pairs
-    pub async fn process_candle_for_all(
+    /// Pairs other than `pair` that the informative filters of the session's `pair` strategy need
+    pub async fn get_informative_pairs(&self, session_id: u64, pair: &str) -> Vec<String> {
+        let sessions = self.sessions.read().await;
+        sessions.get(&session_id)
+            .and_then(|s| s.pairs.get(pair))
+            .map(|p| p.informative.extra_symbols(pair))
+            .unwrap_or_default()
+    }
+    
+    /// Feed a trade of an informative pair to the session's `pair` strategy
+    /// Returns false if the session is no longer trading the pair
+    pub async fn feed_informative_trade(
         &self,
-        candle: &Candle,
-    ) -> Vec<(i64, StrategySignal)> {
-        let mut signals = Vec::new();
-        let mut users = self.users.write().await;
+        session_id: u64,
+        pair: &str,
+        informative_pair: &str,
+        price: f64,
+        timestamp: i64,
+    ) -> bool {
+        let mut sessions = self.sessions.write().await;
+        let Some(state) = sessions.get_mut(&session_id) else {
+            return false;
+        };
+        let is_active = state.is_active;
+        let Some(pair_state) = state.pairs.get_mut(pair) else {
+            return false;
+        };
         
-        for (user_id, state) in users.iter_mut() {
-            if state.is_active {
-                // Process candle for this user's strategy
-                if let Some(signal) = state.strategy.process_candle(candle) {
-                    signals.push((*user_id, signal));
+        let time = chrono::DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
+        // Ticks carry no timeframe, so buckets close once a later trade or base candle arrives
+        let tick = freqtrade_rs::data::Candle::new(price, price, price, price, 0.0, time, informative_pair.to_string(), String::new());
+        pair_state.informative.feed(&tick);
+        is_active
+    }
+    
+    /// Mirror a newly opened position so the strategy's trade callbacks can manage it
+    pub async fn on_position_opened(
+        &self,
+        session_id: u64,
+        pair: &str,
+        position_id: u64,
+        entry_price: f64,
+        quantity: f64,
+        entry_time: chrono::DateTime<chrono::Utc>,
+    ) {
+        let mut sessions = self.sessions.write().await;
+        let Some(state) = sessions.get_mut(&session_id) else {
+            return;
+        };
+        // A position restored after a restart may be on a pair the pairlist dropped
+        if !state.pairs.contains_key(pair) {
+            match state.new_pair(pair) {
+                Ok(pair_state) => {
+                    state.pairs.insert(pair.to_string(), pair_state);
                 }
+                Err(e) => {
+                    tracing::error!("Failed to create strategy for session {} on {}: {}", session_id, pair, e);
+                    return;
+                }
+            }
+        }
+        if let Some(pair_state) = state.pairs.get_mut(pair) {
+            let mut position = Position::new(
+                position_id.to_string(),
+                pair.to_string(),
+                PositionSide::Long,
+                entry_price,
+                quantity,
+            );
+            position.set_entry_time(entry_time);
+            let (stop_loss, take_profit, trailing_stop) = pair_state.exit_levels(entry_price);
+            position.set_stop_loss(stop_loss);
+            position.set_take_profit(take_profit);
+            if let Some(distance) = trailing_stop {
+                position.set_trailing_stop(distance);
+            }
+            pair_state.position = Some(position);
+        }
+    }
+    
+    /// Apply a DCA entry (positive quantity) or partial exit (negative quantity) to the mirrored position
+    pub async fn on_position_adjusted(
+        &self,
+        session_id: u64,
+        pair: &str,
+        price: f64,
+        quantity: f64,
+        time: chrono::DateTime<chrono::Utc>,
+    ) {
+        let mut sessions = self.sessions.write().await;
+        if let Some(position) = sessions.get_mut(&session_id)
+            .and_then(|s| s.pairs.get_mut(pair))
+            .and_then(|p| p.position.as_mut())
+        {
+            if quantity > 0.0 {
+                position.increase(price, quantity, time);
+            } else {
+                position.decrease(price, -quantity, time);
+            }
+        }
+    }
+    
+    /// Forget the mirrored position once it is closed
+    pub async fn on_position_closed(&self, session_id: u64, pair: &str) {
+        let mut sessions = self.sessions.write().await;
+        if let Some(pair_state) = sessions.get_mut(&session_id).and_then(|s| s.pairs.get_mut(pair)) {
+            pair_state.position = None;
+        }
+    }
+    
+    /// Take the session's resting limit orders on `pair` that a trade at `price` and `time` reaches
+    /// Buys are kept resting while the pair is locked by a protection.
+    pub async fn take_limit_fills(
+        &self,
+        session_id: u64,
+        pair: &str,
+        price: f64,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> Vec<LimitOrder> {
+        let mut sessions = self.sessions.write().await;
+        let Some(state) = sessions.get_mut(&session_id) else {
+            return Vec::new();
+        };
+        if !state.is_active || state.is_paused {
+            return Vec::new();
+        }
+        let buys_locked = state.protections.lock_for(pair, time).is_some();
+        let Some(pair_state) = state.pairs.get_mut(pair) else {
+            return Vec::new();
+        };
+        
+        let (hit, resting): (Vec<LimitOrder>, Vec<LimitOrder>) = pair_state.resting_orders
+            .drain(..)
+            .partition(|order| match order.side {
+                OrderSide::Buy => !buys_locked && price <= order.price,
+                OrderSide::Sell => price >= order.price,
+            });
+        pair_state.resting_orders = resting;
+        hit
+    }
+    
+    /// Tell the strategy one of its limit orders was executed
+    pub async fn on_limit_order_filled(
+        &self,
+        session_id: u64,
+        pair: &str,
+        order: &LimitOrder,
+        fill_price: f64,
+        time: chrono::DateTime<chrono::Utc>,
+    ) {
+        let mut sessions = self.sessions.write().await;
+        if let Some(pair_state) = sessions.get_mut(&session_id).and_then(|s| s.pairs.get_mut(pair)) {
+            pair_state.strategy.on_order_filled(order, fill_price, time);
+        }
+    }
+    
+    /// Snapshot the strategy state of one of a session's pairs
+    /// `None` if the pair is not traded or its strategy does not support snapshots
+    pub async fn snapshot_pair(&self, session_id: u64, pair: &str) -> Option<PairSnapshot> {
+        let sessions = self.sessions.read().await;
+        let state = sessions.get(&session_id)?;
+        let pair_state = state.pairs.get(pair)?;
+        
+        Some(PairSnapshot {
+            strategy_type: state.config.strategy_type.clone(),
+            strategy: pair_state.strategy.snapshot()?,
+            atr: pair_state.atr.clone(),
+            last_signal: pair_state.last_signal.clone(),
+            forming_candle: None,
+        })
+    }
+    
+    /// Explain the signal a pair's strategy just emitted on `candle`: its indicators,
+    /// evaluated conditions and the informative filters gating entries
+    pub async fn explain_signal(&self, session_id: u64, pair: &str, candle: &Candle) -> Option<SignalExplanation> {
+        let sessions = self.sessions.read().await;
+        let pair_state = sessions.get(&session_id)?.pairs.get(pair)?;
+        
+        let mut explanation = pair_state.strategy.explain(candle);
+        for filter in &pair_state.informative_filters {
+            explanation.conditions.push(("filter".to_string(), ClauseResult {
+                clause: format!("{} {} close > EMA({})",
+                    filter.symbol.as_deref().unwrap_or(pair), filter.timeframe, filter.ema_period),
+                holds: filter.is_bullish(&pair_state.informative, pair),
+                left: None,
+                right: None,
+                children: Vec::new(),
+            }));
+        }
+        Some(explanation)
+    }
+    
+    /// Resume one of a session's pairs from a snapshot
+    /// Fails (leaving the fresh strategy in place) if the snapshot does not match the strategy
+    pub async fn restore_pair(&self, session_id: u64, pair: &str, snapshot: PairSnapshot) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
+        let state = sessions.get_mut(&session_id)
+            .ok_or_else(|| anyhow::anyhow!("Session {} is not running", session_id))?;
+        if !snapshot.strategy_type.eq_ignore_ascii_case(&state.config.strategy_type) {
+            return Err(anyhow::anyhow!("Snapshot is for strategy {}, session runs {}",
+                snapshot.strategy_type, state.config.strategy_type));
+        }
+        let pair_state = state.pairs.get_mut(pair)
+            .ok_or_else(|| anyhow::anyhow!("Session {} is not trading {}", session_id, pair))?;
+        
+        pair_state.strategy.restore(snapshot.strategy)?;
+        // The sizing model may have changed since; only resume an ATR of the same period
+        if let (Some(atr), Some(saved)) = (pair_state.atr.as_mut(), snapshot.atr) {
+            if saved.period() == atr.period() {
+                *atr = saved;
             }
         }
+        pair_state.last_signal = snapshot.last_signal;
+        pair_state.resting_orders = pair_state.strategy.limit_orders();
+        pair_state.warmup.candles = pair_state.warmup.candles.max(pair_state.warmup.required);
+        Ok(())
+    }
+    
+    /// Candles the strategy of a session's pair still needs before it can emit signals
+    pub async fn warmup_needed(&self, session_id: u64, pair: &str) -> usize {
+        let sessions = self.sessions.read().await;
+        sessions.get(&session_id)
+            .and_then(|s| s.pairs.get(pair))
+            .map(|p| p.warmup.required.saturating_sub(p.warmup.candles))
+            .unwrap_or(0)
+    }
+    
+    /// Feed closed historical candles (oldest first) through the strategy of a session's pair
+    /// Signals raised on history are discarded. Returns the number of candles fed.
+    pub async fn warm_up(&self, session_id: u64, pair: &str, candles: &[Candle]) -> usize {
+        let mut sessions = self.sessions.write().await;
+        let Some(pair_state) = sessions.get_mut(&session_id)
+            .filter(|s| s.is_active)
+            .and_then(|s| s.pairs.get_mut(pair))
+        else {
+            return 0;
+        };
         
-        signals
+        for candle in candles {
+            pair_state.feed_candle(candle);
+        }
+        candles.len()
+    }
+    
+    /// Warm-up progress of each of a session's pairs, sorted by pair
+    pub async fn get_warmup_progress(&self, session_id: u64) -> Vec<(String, WarmupProgress)> {
+        let sessions = self.sessions.read().await;
+        let Some(state) = sessions.get(&session_id) else {
+            return Vec::new();
+        };
+        state.pair_names()
+            .into_iter()
+            .filter_map(|pair| state.pairs.get(&pair).map(|p| (pair, p.warmup)))
+            .collect()
+    }
+    
+    /// Check if the session's strategy configures any protections
+    pub async fn has_protections(&self, session_id: u64) -> bool {
+        let sessions = self.sessions.read().await;
+        sessions.get(&session_id)
+            .map(|s| !s.protections.is_empty())
+            .unwrap_or(false)
+    }
+    
+    /// Evaluate the session's protections after a trade on `pair` closed at `time`
+    /// Returns the newly created locks so they can be persisted
+    pub async fn evaluate_protections(
+        &self,
+        session_id: u64,
+        pair: &str,
+        trades: &[ProtectionTrade],
+        equity: f64,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> Vec<ProtectionLock> {
+        let mut sessions = self.sessions.write().await;
+        let Some(state) = sessions.get_mut(&session_id) else {
+            return Vec::new();
+        };
+        state.protections.on_trade_closed(trades, pair, time, equity)
+    }
+    
+    /// Add locks loaded from the database
+    pub async fn add_protection_locks(&self, session_id: u64, locks: Vec<ProtectionLock>) {
+        let mut sessions = self.sessions.write().await;
+        if let Some(state) = sessions.get_mut(&session_id) {
+            state.protections.add_locks(locks);
+        }
+    }
+    
+    /// Get the current ATR for a session's pair, if its sizing model tracks it
+    /// Stop loss a position entered at `entry_price` would get, which risk-based sizing risks to
+    pub async fn entry_stop_loss(&self, session_id: u64, pair: &str, entry_price: f64) -> Option<f64> {
+        let sessions = self.sessions.read().await;
+        sessions.get(&session_id)
+            .and_then(|s| s.pairs.get(pair))
+            .map(|p| p.exit_levels(entry_price).0)
     }
     
-    /// Check if user is trading
-    pub async fn is_user_trading(&self, user_id: i64) -> bool {
-        let users = self.users.read().await;
-        users.get(&user_id)
+    pub async fn get_atr(&self, session_id: u64, pair: &str) -> Option<f64> {
+        let sessions = self.sessions.read().await;
+        sessions.get(&session_id)
+            .and_then(|s| s.pairs.get(pair))
+            .and_then(|p| p.atr.as_ref())
+            .and_then(|atr| atr.value())
+    }
+    
+    /// Check if a session is running
+    pub async fn is_session_trading(&self, session_id: u64) -> bool {
+        let sessions = self.sessions.read().await;
+        sessions.get(&session_id)
             .map(|s| s.is_active)
             .unwrap_or(false)
     }
     
-    /// Get user's trading state info
-    pub async fn get_user_state_info(&self, user_id: i64) -> Option<String> {
-        let users = self.users.read().await;
-        users.get(&user_id)
-            .map(|s| format!("Strategy: {}, Pair: {}, Active: {}", 
-                s.strategy.name(), s.pair, s.is_active))
-    }
-    
-    /// Get all active trading users with their details
-    pub async fn get_active_trading_users(&self) -> Vec<(i64, String, String, String)> {
-        let users = self.users.read().await;
-        users.iter()
-            .filter(|(_, state)| state.is_active)
-            .map(|(user_id, state)| {
-                (
-                    *user_id,
-                    state.strategy.name().to_string(),
-                    state.pair.clone(),
-                    state.strategy.config().timeframe.clone(),
-                )
-            })
-            .collect()
+    /// Session of the user that trades `pair`, if any
+    pub async fn session_trading_pair(&self, user_id: i64, pair: &str) -> Option<u64> {
+        let sessions = self.sessions.read().await;
+        sessions.values()
+            .find(|s| s.user_id == user_id && s.pairs.keys().any(|p| same_pair(p, pair)))
+            .map(|s| s.session_id)
     }
     
-    /// Get user's trading details
-    pub async fn get_user_trading_details(&self, user_id: i64) -> Option<(String, String, String)> {
-        let users = self.users.read().await;
-        users.get(&user_id)
-            .filter(|s| s.is_active)
-            .map(|s| {
-                (
-                    s.strategy.name().to_string(),
-                    s.pair.clone(),
-                    s.strategy.config().timeframe.clone(),
-                )
-            })
+    /// Get a session's trading state info
+    pub async fn get_session_state_info(&self, session_id: u64) -> Option<String> {
+        let sessions = self.sessions.read().await;
+        sessions.get(&session_id)
+            .map(|s| format!("Strategy: {}, Pairs: {}, Active: {}", 
+                s.strategy_name(), s.pair_names().join(", "), s.is_active))
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> StrategyConfig {
+        StrategyConfig {
+            strategy_type: "EXPRESSION".to_string(),
+            parameters: serde_json::json!({}),
+            pair: "BTC/USDT".to_string(),
+            timeframe: "1m".to_string(),
+            buy_condition: "close < 0".to_string(),
+            sell_condition: "close < 0".to_string(),
+        }
+    }
+
+    fn candle(minute: i64, low: f64, high: f64, close: f64) -> Candle {
+        Candle { open: close, high, low, close, volume: 1.0, timestamp: minute * 60 }
+    }
+
+    fn sell(price: f64, reason: ExitReason) -> Option<StrategySignal> {
+        Some(StrategySignal::Sell { confidence: 1.0, price, reason: reason.to_string(), exit_reason: reason })
+    }
+
+    #[tokio::test]
+    async fn test_opened_position_gets_default_stop_loss_and_take_profit() {
+        let executor = StrategyExecutor::new();
+        executor.start_trading(1, 7, config(), None).await.unwrap();
+        executor.on_position_opened(1, "BTC/USDT", 10, 100.0, 1.0, chrono::Utc::now()).await;
+
+        // Default 5% stop below the entry, as in the backtester
+        let signal = executor.process_candle(1, "BTC/USDT", &candle(0, 94.0, 100.0, 99.0)).await;
+        assert_eq!(signal, sell(95.0, ExitReason::StopLoss));
+
+        // Default 5% take profit above the entry
+        executor.on_position_opened(1, "BTC/USDT", 11, 100.0, 1.0, chrono::Utc::now()).await;
+        let signal = executor.process_candle(1, "BTC/USDT", &candle(1, 99.0, 106.0, 104.0)).await;
+        assert_eq!(signal, sell(105.0, ExitReason::Roi));
+    }
+
+    #[tokio::test]
+    async fn test_protection_locks_apply_at_candle_time() {
+        let executor = StrategyExecutor::new();
+        let always_buy = StrategyConfig { buy_condition: "close > 0".to_string(), ..config() };
+        executor.start_trading(1, 7, always_buy, None).await.unwrap();
+        assert_eq!(executor.warmup_needed(1, "BTC/USDT").await, 0);
+        // Locked until minute 3 of 1970, long before the wall clock
+        executor.add_protection_locks(1, vec![ProtectionLock {
+            symbol: Some("BTC/USDT".to_string()),
+            until: chrono::DateTime::from_timestamp(180, 0).unwrap(),
+            protection: "CooldownPeriod".to_string(),
+            reason: "test".to_string(),
+        }]).await;
+
+        assert_eq!(executor.process_candle(1, "BTC/USDT", &candle(1, 99.0, 101.0, 100.0)).await, None);
+        let signal = executor.process_candle(1, "BTC/USDT", &candle(3, 99.0, 101.0, 100.0)).await;
+        assert!(matches!(signal, Some(StrategySignal::Buy { .. })), "{:?}", signal);
+    }
+
+    #[tokio::test]
+    async fn test_risk_based_sizing_uses_the_position_stop() {
+        let executor = StrategyExecutor::new();
+        executor.start_trading(1, 7, config(), None).await.unwrap();
+        assert_eq!(executor.entry_stop_loss(1, "BTC/USDT", 100.0).await, Some(95.0));
+        assert_eq!(executor.entry_stop_loss(1, "ETH/USDT", 100.0).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_set_pairs_adds_and_removes_pairs() {
+        let executor = StrategyExecutor::new();
+        executor.start_trading(1, 7, config(), None).await.unwrap();
+        executor.start_trading(2, 7, StrategyConfig { pair: "SOL/USDT".to_string(), ..config() }, None).await.unwrap();
+
+        // "btcusdt" is the pair the session already trades; SOL belongs to the other session
+        let pairs = vec!["btcusdt".to_string(), "ETH/USDT".to_string(), "sol/usdt".to_string()];
+        let (added, removed) = executor.set_pairs(1, &pairs).await.unwrap();
+        assert_eq!(added, vec!["ETH/USDT".to_string()]);
+        assert!(removed.is_empty());
+
+        // Pairs with an open position stay until it closes
+        executor.on_position_opened(1, "ETH/USDT", 10, 100.0, 1.0, chrono::Utc::now()).await;
+        let (added, removed) = executor.set_pairs(1, &["XRP/USDT".to_string()]).await.unwrap();
+        assert_eq!(added, vec!["XRP/USDT".to_string()]);
+        assert_eq!(removed, vec!["BTC/USDT".to_string()]);
+        let (_, mut pairs) = executor.stop_trading(1).await.unwrap().unwrap();
+        pairs.sort();
+        assert_eq!(pairs, vec!["ETH/USDT".to_string(), "XRP/USDT".to_string()]);
+
+        assert!(executor.set_pairs(1, &pairs).await.is_none());
+    }
+}
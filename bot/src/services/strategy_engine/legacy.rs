@@ -0,0 +1,211 @@
+//! Legacy conditions - translates conditions stored before they were expressions
+//!
+//! RSI, STOCHASTIC and ADX strategies used to compare their indicator against the
+//! threshold after the first word of a condition (`%K < 20`), MACD, Bollinger, EMA and
+//! MA strategies ignored their conditions. Stored strategies keep trading the same way:
+//! oscillator thresholds become expressions on the indicator and the other types fall
+//! back to the conditions they used to trade. Anything that cannot be carried over is
+//! reported instead of silently changing how the strategy trades.
+
+use freqtrade_rs::strategy::Condition;
+use crate::services::strategy_engine::StrategyConfig;
+
+/// Buy / sell conditions of a strategy in the expression language
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslatedConditions {
+    pub buy_condition: String,
+    pub sell_condition: String,
+    /// What could not be translated, and what the strategy trades on instead
+    pub untranslated: Vec<String>,
+}
+
+/// Conditions strategy types without user conditions always traded
+pub fn default_conditions(strategy_type: &str) -> Option<(&'static str, &'static str)> {
+    match strategy_type.to_uppercase().as_str() {
+        "MACD" => Some(("MACD_HIST > 0", "MACD_HIST < 0")),
+        "BOLLINGER" | "BOLLINGER BANDS" => Some(("close <= BB_LOWER", "close >= BB_UPPER")),
+        "EMA" => Some(("crosses_above(close, EMA)", "crosses_below(close, EMA)")),
+        "MA" => Some(("crosses_above(close, SMA)", "crosses_below(close, SMA)")),
+        _ => None,
+    }
+}
+
+/// Indicator the oscillator strategies compared their thresholds against
+fn oscillator(strategy_type: &str) -> Option<&'static str> {
+    match strategy_type.to_uppercase().as_str() {
+        "RSI" => Some("RSI"),
+        // The raw %K, smoothing never applied to the traded value
+        "STOCHASTIC" => Some("STOCH"),
+        "ADX" => Some("ADX"),
+        _ => None,
+    }
+}
+
+/// Expression of a legacy threshold condition, `<word> <op> <number>`
+fn translate_threshold(indicator: &str, condition: &str) -> Option<String> {
+    let rest = condition.split_whitespace().skip(1).collect::<Vec<_>>().join(" ");
+    let (op, threshold) = ["<=", ">=", "==", "<", ">"]
+        .iter()
+        .find_map(|op| rest.strip_prefix(op).map(|threshold| (*op, threshold)))?;
+    let threshold: f64 = threshold.trim().parse().ok()?;
+    if op == "==" {
+        // Equality used to hold within 0.01
+        return Some(format!("{i} - {t} < 0.01 AND {t} - {i} < 0.01", i = indicator, t = threshold));
+    }
+    Some(format!("{} {} {}", indicator, op, threshold))
+}
+
+/// Translate the stored buy / sell conditions of a strategy
+/// Conditions that parse are kept as they are.
+pub fn translate_conditions(config: &StrategyConfig) -> TranslatedConditions {
+    let mut untranslated = Vec::new();
+    let parse_error = |condition: &str| Condition::parse(condition, &config.parameters).err();
+
+    if let Some(indicator) = oscillator(&config.strategy_type) {
+        if config.strategy_type.eq_ignore_ascii_case("STOCHASTIC") {
+            for key in ["smooth_k", "smooth_d"] {
+                if config.parameters.get(key).is_some() {
+                    untranslated.push(format!("{} is not applied, STOCH is the unsmoothed %K", key));
+                }
+            }
+        }
+        let mut translate = |side: &str, condition: &str| match parse_error(condition) {
+            None => condition.to_string(),
+            Some(error) => match translate_threshold(indicator, condition) {
+                Some(expression) => expression,
+                None => {
+                    untranslated.push(format!("{} condition `{}`: {}", side, condition, error.message));
+                    condition.to_string()
+                }
+            },
+        };
+        let buy_condition = translate("Buy", &config.buy_condition);
+        let sell_condition = translate("Sell", &config.sell_condition);
+        return TranslatedConditions { buy_condition, sell_condition, untranslated };
+    }
+
+    if let Some((buy, sell)) = default_conditions(&config.strategy_type) {
+        let errors = [("Buy", &config.buy_condition), ("Sell", &config.sell_condition)]
+            .into_iter()
+            .filter_map(|(side, condition)| parse_error(condition).map(|error| (side, condition, error)))
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            for (side, condition, error) in errors {
+                untranslated.push(format!("{} condition `{}`: {}", side, condition, error.message));
+            }
+            untranslated.push(format!("trading the {} defaults `{}` / `{}`", config.strategy_type, buy, sell));
+            return TranslatedConditions {
+                buy_condition: buy.to_string(),
+                sell_condition: sell.to_string(),
+                untranslated,
+            };
+        }
+    }
+
+    TranslatedConditions {
+        buy_condition: config.buy_condition.clone(),
+        sell_condition: config.sell_condition.clone(),
+        untranslated,
+    }
+}
+
+/// What cannot be translated in a strategy, or in each member of a mix
+pub fn untranslated_conditions(config: &StrategyConfig) -> Vec<String> {
+    if !config.strategy_type.eq_ignore_ascii_case("MIX") {
+        return translate_conditions(config).untranslated;
+    }
+    let members = config.parameters.get("strategies").and_then(|v| v.as_array());
+    members.into_iter().flatten().flat_map(|member| {
+        let name = member.get("name").and_then(|v| v.as_str()).unwrap_or("member").to_string();
+        match serde_json::from_value::<StrategyConfig>(member.clone()) {
+            Ok(member_config) => untranslated_conditions(&member_config)
+                .into_iter()
+                .map(|item| format!("{}: {}", name, item))
+                .collect(),
+            Err(e) => vec![format!("{}: {}", name, e)],
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(strategy_type: &str, parameters: serde_json::Value, buy: &str, sell: &str) -> StrategyConfig {
+        StrategyConfig {
+            strategy_type: strategy_type.to_string(),
+            parameters,
+            pair: "BTC/USDT".to_string(),
+            timeframe: "1m".to_string(),
+            buy_condition: buy.to_string(),
+            sell_condition: sell.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_translates_oscillator_thresholds() {
+        let stochastic = config("STOCHASTIC", json!({ "period": 14 }), "%K < 20", "%K >= 80");
+        let translated = translate_conditions(&stochastic);
+        assert_eq!(translated.buy_condition, "STOCH < 20");
+        assert_eq!(translated.sell_condition, "STOCH >= 80");
+        assert!(translated.untranslated.is_empty());
+
+        let rsi = config("RSI", json!({ "period": 14 }), "RSI < 30", "value == 70");
+        let translated = translate_conditions(&rsi);
+        assert_eq!(translated.buy_condition, "RSI < 30", "expressions are kept");
+        assert_eq!(translated.sell_condition, "RSI - 70 < 0.01 AND 70 - RSI < 0.01");
+        assert!(Condition::parse(&translated.sell_condition, &rsi.parameters).is_ok());
+    }
+
+    #[test]
+    fn test_reports_what_cannot_be_translated() {
+        let stochastic = config(
+            "STOCHASTIC",
+            json!({ "period": 14, "smooth_k": 3, "smooth_d": 3 }),
+            "%K crosses %D",
+            "%K > 80",
+        );
+        let translated = translate_conditions(&stochastic);
+        assert_eq!(translated.buy_condition, "%K crosses %D");
+        assert_eq!(translated.sell_condition, "STOCH > 80");
+        assert_eq!(translated.untranslated.len(), 3, "{:?}", translated.untranslated);
+        assert!(translated.untranslated[0].contains("smooth_k"));
+        assert!(translated.untranslated[2].starts_with("Buy condition `%K crosses %D`"));
+    }
+
+    #[test]
+    fn test_macd_signal_is_not_its_period() {
+        let macd = config("MACD", json!({ "fast": 12, "slow": 26, "signal": 9 }), "MACD > Signal", "MACD < Signal");
+        let translated = translate_conditions(&macd);
+        assert_eq!(translated.buy_condition, "MACD_HIST > 0");
+        assert_eq!(translated.sell_condition, "MACD_HIST < 0");
+        assert_eq!(translated.untranslated.len(), 3);
+
+        let expression = config("MACD", json!({}), "MACD > MACD_SIGNAL", "MACD < MACD_SIGNAL");
+        let translated = translate_conditions(&expression);
+        assert_eq!(translated.buy_condition, "MACD > MACD_SIGNAL");
+        assert!(translated.untranslated.is_empty());
+    }
+
+    #[test]
+    fn test_untranslated_mix_members() {
+        let mix = config(
+            "MIX",
+            json!({
+                "mode": "unanimous",
+                "strategies": [
+                    { "name": "Momentum", "strategy_type": "MACD", "parameters": {}, "pair": "BTC/USDT",
+                      "timeframe": "1m", "buy_condition": "MACD > Signal", "sell_condition": "MACD_HIST < 0" },
+                    { "name": "Oversold", "strategy_type": "RSI", "parameters": {}, "pair": "BTC/USDT",
+                      "timeframe": "1m", "buy_condition": "RSI < 30", "sell_condition": "RSI > 70" },
+                ],
+            }),
+            "unanimous of MACD > Signal | RSI < 30",
+            "unanimous of MACD_HIST < 0 | RSI > 70",
+        );
+        let untranslated = untranslated_conditions(&mix);
+        assert_eq!(untranslated.len(), 2, "{:?}", untranslated);
+        assert!(untranslated.iter().all(|item| item.starts_with("Momentum: ")));
+    }
+}
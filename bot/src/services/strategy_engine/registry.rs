@@ -1,18 +1,88 @@
 //! Strategy Registry - manages available strategies
 
 use std::collections::HashMap;
-use std::sync::Arc;
 use anyhow::Result;
-use serde_json::Value;
-use crate::services::strategy_engine::{
-    Strategy, StrategyConfig, 
-    implementations::{
-        RsiStrategy, MacdStrategy, BollingerStrategy, 
-        EmaStrategy, MaStrategy, StochasticStrategy, AdxStrategy,
-    },
+use freqtrade_rs::data::same_pair;
+use freqtrade_rs::strategy::{
+    CompositeMember, CompositeStrategy, CompositeStrategyConfig, ExpressionStrategy,
+    ExpressionStrategyConfig, GridStrategy,
 };
+use crate::services::strategy_engine::legacy::translate_conditions;
+use crate::services::strategy_engine::{LiveStrategy, StrategyConfig, StrategyImpl};
 
-pub type StrategyFactory = Box<dyn Fn(StrategyConfig) -> Result<Box<dyn Strategy>> + Send + Sync>;
+pub type StrategyFactory = Box<dyn Fn(&StrategyConfig) -> Result<StrategyImpl> + Send + Sync>;
+
+/// Expression strategy on the user's buy / sell conditions
+/// Conditions stored before they were expressions are translated first; what cannot be
+/// translated is logged, and conditions that still do not parse fail the strategy.
+fn expression_strategy(config: &StrategyConfig) -> Result<StrategyImpl> {
+    let translated = translate_conditions(config);
+    for item in &translated.untranslated {
+        tracing::warn!("Strategy {} on {}: {}", config.strategy_type, config.pair, item);
+    }
+    
+    // Indicator arguments (period, fast / slow / signal, std_dev) resolve from the parameters
+    Ok(Box::new(ExpressionStrategy::new(ExpressionStrategyConfig {
+        buy_condition: translated.buy_condition,
+        sell_condition: translated.sell_condition,
+        parameters: config.parameters.clone(),
+    })?))
+}
+
+/// Check that a strategy can be a member of a mix trading `pair` on `timeframe`
+/// Members vote on the mix's candles, so they have to trade the same pair and timeframe,
+/// and grid strategies are left out as the mix does not place their limit orders.
+pub fn validate_mix_member(pair: &str, timeframe: &str, name: &str, member: &StrategyConfig) -> Result<()> {
+    if member.strategy_type.eq_ignore_ascii_case("GRID") {
+        anyhow::bail!("Mixed strategy {} is a grid strategy, grid strategies cannot be mixed", name);
+    }
+    if !same_pair(&member.pair, pair) || member.timeframe != timeframe {
+        anyhow::bail!("Mixed strategy {} trades {} {}, the mix trades {} {}",
+            name, member.pair, member.timeframe, pair, timeframe);
+    }
+    Ok(())
+}
+
+/// Mix member config buying below 90 and selling above 110, for tests
+#[cfg(test)]
+pub fn mix_member_fixture(name: &str, strategy_type: &str, pair: &str, timeframe: &str) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "strategy_type": strategy_type,
+        "parameters": {},
+        "pair": pair,
+        "timeframe": timeframe,
+        "buy_condition": "close < 90",
+        "sell_condition": "close > 110",
+    })
+}
+
+/// Composite strategy of a mix: `mode`, `threshold` and `confirm_window` with the member
+/// configs (plus `name` and `weight`) under `strategies`
+fn mix_strategy(config: &StrategyConfig) -> Result<StrategyImpl> {
+    let composite_config: CompositeStrategyConfig = serde_json::from_value(config.parameters.clone())
+        .map_err(|e| anyhow::anyhow!("Invalid mix parameters: {}", e))?;
+    let registry = StrategyRegistry::new();
+    let members = config.parameters
+        .get("strategies")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!("Mixed strategy has no strategies"))?
+        .iter()
+        .map(|member| {
+            let member_config: StrategyConfig = serde_json::from_value(member.clone())?;
+            let name = member.get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&member_config.strategy_type)
+                .to_string();
+            validate_mix_member(&config.pair, &config.timeframe, &name, &member_config)?;
+            let weight = member.get("weight").and_then(|v| v.as_f64()).unwrap_or(1.0);
+            let strategy = registry.create_strategy_impl(&member_config)?;
+            Ok(CompositeMember::new(&name, strategy).with_weight(weight))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    
+    Ok(Box::new(CompositeStrategy::new(composite_config, members)?))
+}
 
 /// Strategy Registry - manages strategy types and their factories
 pub struct StrategyRegistry {
@@ -26,81 +96,21 @@ impl StrategyRegistry {
         };
         
         // Register built-in strategies
-        registry.register_strategy("RSI", |config| {
-            let period = config.parameters
-                .get("period")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(14) as usize;
-            Ok(Box::new(RsiStrategy::new(config, period)?))
-        });
-        
-        registry.register_strategy("MACD", |config| {
-            let fast = config.parameters
-                .get("fast")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(12) as usize;
-            let slow = config.parameters
-                .get("slow")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(26) as usize;
-            let signal = config.parameters
-                .get("signal")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(9) as usize;
-            Ok(Box::new(MacdStrategy::new(config, fast, slow, signal)?))
-        });
-        
-        registry.register_strategy("Bollinger", |config| {
-            let period = config.parameters
-                .get("period")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(20) as usize;
-            let std_dev = config.parameters
-                .get("std_dev")
-                .and_then(|v| v.as_f64())
-                .unwrap_or(2.0);
-            Ok(Box::new(BollingerStrategy::new(config, period, std_dev)?))
-        });
+        // Oscillator strategies trade on the user's conditions, e.g. `RSI < 30` / `RSI > 70`,
+        // the others on their defaults until their conditions are expressions
+        for strategy_type in ["RSI", "STOCHASTIC", "ADX", "EXPRESSION", "MACD", "BOLLINGER", "BOLLINGER BANDS", "EMA", "MA"] {
+            registry.register_strategy(strategy_type, expression_strategy);
+        }
         
-        registry.register_strategy("EMA", |config| {
-            let period = config.parameters
-                .get("period")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(20) as usize;
-            Ok(Box::new(EmaStrategy::new(config, period)?))
+        registry.register_strategy("GRID", |config| {
+            // lower_price, upper_price, grid_count, order_size, spacing ("arithmetic" / "geometric"), trailing
+            let grid_config = serde_json::from_value(config.parameters.clone())
+                .map_err(|e| anyhow::anyhow!("Invalid grid parameters: {}", e))?;
+            Ok(Box::new(GridStrategy::new(grid_config)?))
         });
         
-        registry.register_strategy("MA", |config| {
-            let period = config.parameters
-                .get("period")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(20) as usize;
-            Ok(Box::new(MaStrategy::new(config, period)?))
-        });
-        
-        registry.register_strategy("STOCHASTIC", |config| {
-            let period = config.parameters
-                .get("period")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(14) as usize;
-            let smooth_k = config.parameters
-                .get("smooth_k")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(3) as usize;
-            let smooth_d = config.parameters
-                .get("smooth_d")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(3) as usize;
-            Ok(Box::new(StochasticStrategy::new(config, period, smooth_k, smooth_d)?))
-        });
-        
-        registry.register_strategy("ADX", |config| {
-            let period = config.parameters
-                .get("period")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(14) as usize;
-            Ok(Box::new(AdxStrategy::new(config, period)?))
-        });
+        // Custom Mix of the user's other strategies
+        registry.register_strategy("MIX", mix_strategy);
         
         registry
     }
@@ -108,13 +118,13 @@ impl StrategyRegistry {
     /// Register a strategy factory
     pub fn register_strategy<F>(&mut self, name: &str, factory: F)
     where
-        F: Fn(StrategyConfig) -> Result<Box<dyn Strategy>> + Send + Sync + 'static,
+        F: Fn(&StrategyConfig) -> Result<StrategyImpl> + Send + Sync + 'static,
     {
         self.factories.insert(name.to_string(), Box::new(factory));
     }
     
-    /// Create a strategy instance from config
-    pub fn create_strategy(&self, config: StrategyConfig) -> Result<Box<dyn Strategy>> {
+    /// Create the freqtrade-rs strategy of a config, as the native backtester runs it
+    pub fn create_strategy_impl(&self, config: &StrategyConfig) -> Result<StrategyImpl> {
         let strategy_type = config.strategy_type.to_uppercase();
         let factory = self.factories
             .get(&strategy_type)
@@ -123,6 +133,12 @@ impl StrategyRegistry {
         factory(config)
     }
     
+    /// Create a strategy instance from config
+    pub fn create_strategy(&self, config: StrategyConfig) -> Result<LiveStrategy> {
+        let strategy = self.create_strategy_impl(&config)?;
+        LiveStrategy::new(config, strategy)
+    }
+    
     /// Get list of available strategy types
     pub fn get_available_strategies(&self) -> Vec<String> {
         self.factories.keys().cloned().collect()
@@ -140,3 +156,57 @@ impl Default for StrategyRegistry {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mix(members: Vec<serde_json::Value>) -> StrategyConfig {
+        StrategyConfig {
+            strategy_type: "MIX".to_string(),
+            parameters: json!({ "mode": "majority", "strategies": members }),
+            pair: "BTC/USDT".to_string(),
+            timeframe: "1m".to_string(),
+            buy_condition: "majority".to_string(),
+            sell_condition: "majority".to_string(),
+        }
+    }
+
+    #[test]
+    fn mix_member_fixtures_share_pair_and_timeframe() {
+        let registry = StrategyRegistry::new();
+        let members = vec![
+            mix_member_fixture("dip", "EXPRESSION", "BTC/USDT", "1m"),
+            mix_member_fixture("spot", "EXPRESSION", "BTCUSDT", "1m"),
+        ];
+        assert!(registry.create_strategy_impl(&mix(members)).is_ok());
+
+        for other in [
+            mix_member_fixture("eth", "EXPRESSION", "ETH/USDT", "1m"),
+            mix_member_fixture("hourly", "EXPRESSION", "BTC/USDT", "1h"),
+        ] {
+            let error = registry.create_strategy_impl(&mix(vec![mix_member_fixture("dip", "EXPRESSION", "BTC/USDT", "1m"), other]))
+                .err()
+                .expect("members on other candles are rejected");
+            assert!(error.to_string().contains("the mix trades BTC/USDT 1m"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_mix_rejects_grid_members() {
+        let grid = json!({
+            "name": "grid",
+            "strategy_type": "GRID",
+            "parameters": { "lower_price": 90.0, "upper_price": 110.0, "grid_count": 4, "order_size": 1.0 },
+            "pair": "BTC/USDT",
+            "timeframe": "1m",
+            "buy_condition": "",
+            "sell_condition": "",
+        });
+        let error = StrategyRegistry::new()
+            .create_strategy_impl(&mix(vec![mix_member_fixture("dip", "EXPRESSION", "BTC/USDT", "1m"), grid]))
+            .err()
+            .expect("grid members are rejected");
+        assert!(error.to_string().contains("grid strategies cannot be mixed"), "{}", error);
+    }
+}
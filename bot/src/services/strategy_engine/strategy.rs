@@ -1,9 +1,11 @@
-//! Strategy trait and related types
+//! Live strategy adapter and related types
 
+use freqtrade_rs::portfolio::Position;
+use freqtrade_rs::strategy::{ClauseResult, ExitReason, LimitOrder};
 use serde::{Deserialize, Serialize};
 
 /// Trading signal generated by a strategy
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StrategySignal {
     /// Buy signal
     Buy {
@@ -16,6 +18,13 @@ pub enum StrategySignal {
         confidence: f64, // 0.0 to 1.0
         price: f64,
         reason: String,
+        exit_reason: ExitReason,
+    },
+    /// Add to (positive stake) or partially exit (negative stake) the open position
+    AdjustPosition {
+        stake: f64, // Quote currency amount
+        price: f64,
+        reason: String,
     },
     /// Hold (no action)
     Hold,
@@ -38,8 +47,49 @@ pub struct StrategyConfig {
     pub sell_condition: String,
 }
 
+impl StrategyConfig {
+    /// Position sizing model stored under `parameters.position_sizing`
+    /// (defaults to a percentage of equity when missing, fails when malformed or invalid)
+    pub fn position_sizing(&self) -> anyhow::Result<freqtrade_rs::portfolio::PositionSizing> {
+        let Some(value) = self.parameters.get("position_sizing") else {
+            return Ok(Default::default());
+        };
+        let sizing: freqtrade_rs::portfolio::PositionSizing = serde_json::from_value(value.clone())
+            .map_err(|e| anyhow::anyhow!("Invalid position sizing: {}", e))?;
+        sizing.validate()
+            .map_err(|e| anyhow::anyhow!("Invalid position sizing: {}", e))?;
+        Ok(sizing)
+    }
+    
+    /// Informative trend filters stored under `parameters.informative`
+    /// (e.g. `[{"timeframe": "1h", "ema_period": 50}, {"symbol": "BTCUSDT", "timeframe": "4h"}]`)
+    pub fn informative_filters(&self) -> Vec<freqtrade_rs::strategy::TrendFilter> {
+        self.parameters
+            .get("informative")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+    
+    /// Dynamic pairlist stored under `parameters.pairlist`, `None` trades `pair` only
+    /// (e.g. `{"source": {"volume_top": {"number_assets": 5}}, "blacklist": ["*UP/USDT"]}`)
+    pub fn pairlist(&self) -> Option<freqtrade_rs::data::PairlistConfig> {
+        self.parameters
+            .get("pairlist")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+    
+    /// Protections stored under `parameters.protections`
+    /// (e.g. `[{"cooldown_period": {"stop_duration_minutes": 30}}]`)
+    pub fn protections(&self) -> Vec<freqtrade_rs::portfolio::Protection> {
+        self.parameters
+            .get("protections")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
 /// OHLC (Open, High, Low, Close) candle data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Candle {
     pub open: f64,
     pub high: f64,
@@ -49,55 +99,227 @@ pub struct Candle {
     pub timestamp: i64,
 }
 
-/// Strategy trait - all strategies must implement this
-pub trait Strategy: Send + Sync + std::fmt::Debug {
+impl Candle {
+    /// Convert to a freqtrade-rs candle for the given pair and timeframe
+    pub fn to_freqtrade(&self, symbol: &str, timeframe: &str) -> freqtrade_rs::data::Candle {
+        freqtrade_rs::data::Candle::new(
+            self.open,
+            self.high,
+            self.low,
+            self.close,
+            self.volume,
+            chrono::DateTime::from_timestamp(self.timestamp, 0).unwrap_or_default(),
+            symbol.to_string(),
+            timeframe.to_string(),
+        )
+    }
+}
+
+/// Why a strategy signalled: its state on the candle the signal fired on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalExplanation {
+    /// Indicator values after the candle
+    pub indicators: Vec<(String, f64)>,
+    /// Evaluated condition trees by label ("buy", "sell", "filter", ...)
+    pub conditions: Vec<(String, ClauseResult)>,
+    pub candle: Candle,
+}
+
+/// Stop loss, take profit and trailing stop an entry signal asked for
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EntryLevels {
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+    pub trailing_stop: Option<f64>,
+}
+
+/// freqtrade-rs strategy as the live engine runs it
+pub type StrategyImpl = Box<dyn freqtrade_rs::strategy::Strategy + Send + Sync>;
+
+/// Live strategy: a freqtrade-rs strategy fed with the bot's stream candles
+///
+/// The strategies themselves are the freqtrade-rs implementations the backtester runs too;
+/// this adapter converts candles and signals between the two and ties the strategy to the
+/// user's configuration.
+pub struct LiveStrategy {
+    config: StrategyConfig,
+    inner: StrategyImpl,
+    /// Levels of the last entry signal, applied to the position it opens
+    entry_levels: EntryLevels,
+}
+
+impl std::fmt::Debug for LiveStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveStrategy")
+            .field("config", &self.config)
+            .field("strategy", &self.inner.name())
+            .finish()
+    }
+}
+
+impl LiveStrategy {
+    /// Wrap a freqtrade-rs strategy; its indicators are only fed through live candles
+    pub fn new(config: StrategyConfig, mut inner: StrategyImpl) -> anyhow::Result<Self> {
+        inner.initialize(&[])?;
+        Ok(Self { config, inner, entry_levels: EntryLevels::default() })
+    }
+    
     /// Get the name/type of this strategy
-    fn name(&self) -> &str;
+    pub fn name(&self) -> &str {
+        &self.config.strategy_type
+    }
     
     /// Get the configuration of this strategy
-    fn config(&self) -> &StrategyConfig;
+    pub fn config(&self) -> &StrategyConfig {
+        &self.config
+    }
     
-    /// Process a new candle and generate a signal
+    fn candle(&self, candle: &Candle) -> freqtrade_rs::data::Candle {
+        candle.to_freqtrade(&self.config.pair, &self.config.timeframe)
+    }
+    
+    /// Process a new candle with closed higher-timeframe / other-pair candles available
     /// Returns Some(signal) if there's a trading signal, None otherwise
-    fn process_candle(&mut self, candle: &Candle) -> Option<StrategySignal>;
+    pub fn process_candle_with_informative(
+        &mut self,
+        candle: &Candle,
+        informative: &freqtrade_rs::data::InformativeData,
+    ) -> Option<StrategySignal> {
+        use freqtrade_rs::strategy::SignalType;
+        
+        let signal = match self.inner.process_with_informative(&self.candle(candle), informative) {
+            Ok(signal) => signal,
+            Err(e) => {
+                tracing::error!("{} failed to process candle: {}", self.inner.name(), e);
+                return None;
+            }
+        };
+        
+        let values = self.inner.indicator_values()
+            .iter()
+            .map(|(name, value)| format!("{} = {:.2}", name, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let reason = if values.is_empty() {
+            signal.reason
+        } else {
+            format!("{} ({})", signal.reason, values)
+        };
+        let price = signal.entry_price.unwrap_or(candle.close);
+        match signal.signal_type {
+            SignalType::EnterLong => {
+                self.entry_levels = EntryLevels {
+                    stop_loss: signal.stop_loss,
+                    take_profit: signal.take_profit,
+                    trailing_stop: signal.trailing_stop,
+                };
+                Some(StrategySignal::Buy { confidence: signal.confidence, price, reason })
+            }
+            SignalType::ExitLong => Some(StrategySignal::Sell {
+                confidence: signal.confidence,
+                price,
+                reason,
+                exit_reason: ExitReason::Signal,
+            }),
+            // Spot sessions only trade long
+            SignalType::EnterShort | SignalType::ExitShort | SignalType::Hold => None,
+        }
+    }
     
-    /// Reset strategy state (e.g., when starting fresh)
-    fn reset(&mut self);
+    /// Stop loss, take profit and trailing stop of the last entry signal
+    pub fn entry_levels(&self) -> EntryLevels {
+        self.entry_levels
+    }
     
-    /// Get current state/info (for debugging)
-    fn get_state_info(&self) -> String;
-}
-
-/// Helper to parse buy/sell conditions
-pub fn parse_condition(condition: &str, indicator_value: f64) -> bool {
-    // Simple parser for conditions like "RSI < 30", "RSI > 70", etc.
-    let condition = condition.trim();
+    /// Indicator values and evaluated conditions after the last processed candle
+    pub fn explain(&self, candle: &Candle) -> SignalExplanation {
+        SignalExplanation {
+            indicators: self.inner.indicator_values(),
+            conditions: self.inner.explain_conditions(),
+            candle: candle.clone(),
+        }
+    }
+    
+    /// Dynamic stop loss for the open position, `None` keeps the current stop
+    /// (only stops that tighten the current one are applied)
+    pub fn custom_stoploss(&mut self, position: &Position, candle: &Candle) -> Option<f64> {
+        let candle = self.candle(candle);
+        self.inner.custom_stoploss(position, &candle)
+    }
     
-    // Remove common prefixes like "RSI ", "MACD ", etc.
-    let condition = condition.split_whitespace().skip(1).collect::<Vec<_>>().join(" ");
+    /// Position-aware exit; returns an exit tag to close the position at the candle close
+    pub fn custom_exit(&mut self, position: &Position, candle: &Candle) -> Option<String> {
+        let candle = self.candle(candle);
+        self.inner.custom_exit(position, &candle)
+    }
     
-    // Parse operators: <, >, <=, >=, ==, !=
-    if let Some(pos) = condition.find("<=") {
-        let threshold: f64 = condition[pos + 2..].trim().parse().unwrap_or(0.0);
-        return indicator_value <= threshold;
+    /// Position adjustment (DCA / scale-out) for the open position
+    /// Returns a stake in quote currency: positive adds at the candle close, negative exits that much value
+    pub fn adjust_trade_position(&mut self, position: &Position, candle: &Candle) -> Option<f64> {
+        let candle = self.candle(candle);
+        self.inner.adjust_trade_position(position, &candle)
     }
-    if let Some(pos) = condition.find(">=") {
-        let threshold: f64 = condition[pos + 2..].trim().parse().unwrap_or(0.0);
-        return indicator_value >= threshold;
+    
+    /// Confirm a BUY signal before it is sent and executed
+    pub fn confirm_trade_entry(&mut self, signal: &StrategySignal, candle: &Candle) -> bool {
+        let StrategySignal::Buy { confidence, price, reason } = signal else {
+            return true;
+        };
+        let signal = freqtrade_rs::strategy::Signal::enter_long(*price, *confidence, reason.clone());
+        let candle = self.candle(candle);
+        self.inner.confirm_trade_entry(&signal, &candle)
     }
-    if let Some(pos) = condition.find("<") {
-        let threshold: f64 = condition[pos + 1..].trim().parse().unwrap_or(0.0);
-        return indicator_value < threshold;
+    
+    /// Confirm an exit (signal, stop loss or custom exit) before it is sent and executed
+    pub fn confirm_trade_exit(&mut self, position: &Position, reason: ExitReason, candle: &Candle) -> bool {
+        let candle = self.candle(candle);
+        self.inner.confirm_trade_exit(position, reason, &candle)
     }
-    if let Some(pos) = condition.find(">") {
-        let threshold: f64 = condition[pos + 1..].trim().parse().unwrap_or(0.0);
-        return indicator_value > threshold;
+    
+    /// Resting limit orders, refreshed after every candle and filled by live trades reaching them
+    /// Buy fills open or add to the position, sell fills reduce it
+    pub fn limit_orders(&self) -> Vec<LimitOrder> {
+        self.inner.limit_orders()
     }
-    if let Some(pos) = condition.find("==") {
-        let threshold: f64 = condition[pos + 2..].trim().parse().unwrap_or(0.0);
-        return (indicator_value - threshold).abs() < 0.01;
+    
+    /// Called once a resting limit order has been executed
+    pub fn on_order_filled(&mut self, order: &LimitOrder, fill_price: f64, time: chrono::DateTime<chrono::Utc>) {
+        self.inner.on_order_filled(order, fill_price, time);
+    }
+    
+    /// Closed candles the strategy needs before it can emit signals
+    /// Live sessions backfill this many historical candles before going live
+    pub fn warmup_candles(&self) -> usize {
+        self.inner.startup_candle_count()
     }
     
-    false
+    /// Serialize the running state (indicators, price buffers) for a checkpoint
+    /// `None` if the strategy does not support snapshots and has to re-warm after a restart
+    pub fn snapshot(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "config": serde_json::to_value(&self.config).ok()?,
+            "strategy": self.inner.snapshot()?,
+        }))
+    }
+    
+    /// Resume from a state returned by `snapshot`
+    /// Snapshots of a strategy whose parameters or conditions changed since are rejected.
+    pub fn restore(&mut self, mut state: serde_json::Value) -> anyhow::Result<()> {
+        let snapshot_config = state.get("config").cloned().unwrap_or_default();
+        if snapshot_config != serde_json::to_value(&self.config)? {
+            return Err(anyhow::anyhow!("Snapshot was taken with a different strategy configuration"));
+        }
+        let strategy = state.get_mut("strategy")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| anyhow::anyhow!("Snapshot has no strategy state"))?;
+        self.inner.restore(strategy)
+    }
+    
+    /// Carry the indicators of the running strategy over to this one (hot reload)
+    /// Fails if the strategy cannot be reconfigured live or its indicator periods differ.
+    pub fn resume_indicators(&mut self, warm: &LiveStrategy) -> anyhow::Result<()> {
+        let state = warm.inner.indicator_state()
+            .ok_or_else(|| anyhow::anyhow!("Strategy {} does not support hot reload", warm.name()))?;
+        self.inner.restore_indicator_state(state)
+    }
 }
-
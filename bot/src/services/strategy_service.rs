@@ -5,6 +5,9 @@ use anyhow::{Result, Context, bail};
 use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait};
 use shared::entity::{strategies, users};
 use crate::services::strategy_engine::StrategyConfig;
+use crate::services::strategy_engine::legacy::{translate_conditions, untranslated_conditions};
+use crate::services::strategy_engine::registry::validate_mix_member;
+use freqtrade_rs::strategy::Condition;
 use serde_json::{Value, Map};
 
 pub struct StrategyService {
@@ -37,6 +40,26 @@ impl StrategyService {
         Ok(strategy)
     }
     
+    /// Check that every stored strategy carries over to the expression language
+    /// Returns the strategies with conditions that could not be translated, or that no
+    /// longer load, with what was found for each.
+    pub async fn check_stored_strategies(&self) -> Result<Vec<(strategies::Model, Vec<String>)>> {
+        let strategies = strategies::Entity::find()
+            .all(self.db.as_ref())
+            .await
+            .context("Failed to fetch strategies")?;
+        
+        Ok(strategies.into_iter()
+            .filter_map(|strategy| {
+                let issues = match self.strategy_to_config(&strategy) {
+                    Ok(config) => untranslated_conditions(&config),
+                    Err(e) => vec![e.to_string()],
+                };
+                (!issues.is_empty()).then_some((strategy, issues))
+            })
+            .collect())
+    }
+    
     /// Convert database strategy to StrategyConfig
     /// First tries to parse and validate from content field (JSON), falls back to description field for backward compatibility
     pub fn strategy_to_config(&self, strategy: &strategies::Model) -> Result<StrategyConfig> {
@@ -116,6 +139,10 @@ impl StrategyService {
         let obj = json.as_object()
             .context("Content must be a JSON object")?;
         
+        if obj.get("type").and_then(|v| v.as_str()) == Some("mixed") {
+            return self.parse_mixed_content(obj);
+        }
+        
         // Extract required fields
         let strategy_type = obj.get("strategy_type")
             .and_then(|v| v.as_str())
@@ -168,6 +195,65 @@ impl StrategyService {
         })
     }
     
+    /// Turn the content saved by the mix wizard into a MIX config
+    /// (`{"type": "mixed", "mix_mode": "majority", "strategies": [{"name", "weight", "strategy_type", ...}]}`)
+    /// The mix trades the pair and timeframe of its first strategy, which the others have to share.
+    fn parse_mixed_content(&self, obj: &Map<String, Value>) -> Result<StrategyConfig> {
+        let strategies = obj.get("strategies")
+            .and_then(|v| v.as_array())
+            .filter(|strategies| !strategies.is_empty())
+            .context("Mixed strategy has no strategies")?;
+        
+        let mut members = Vec::new();
+        let mut member_configs = Vec::new();
+        for strategy in strategies {
+            let mut member = strategy.as_object()
+                .context("Mixed strategy member must be a JSON object")?
+                .clone();
+            // Mixes saved before the composite strategy store the type as "algorithm"
+            if let Some(algorithm) = member.remove("algorithm") {
+                member.entry("strategy_type").or_insert(algorithm);
+            }
+            let config = self.parse_and_validate_content(&Value::Object(member.clone()).to_string())
+                .context("Invalid mixed strategy member")?;
+            self.validate_config(&config)?;
+            // The mix trades the candles of its first strategy
+            let mix = member_configs.first().unwrap_or(&config);
+            let name = member.get("name").and_then(|v| v.as_str()).unwrap_or(&config.strategy_type);
+            validate_mix_member(&mix.pair, &mix.timeframe, name, &config)?;
+            member.insert("parameters".to_string(), config.parameters.clone());
+            members.push(Value::Object(member));
+            member_configs.push(config);
+        }
+        
+        // "all" is the mode of mixes saved before modes could be chosen
+        let mode = match obj.get("mix_mode").and_then(|v| v.as_str()).unwrap_or("unanimous") {
+            "all" => "unanimous",
+            mode => mode,
+        };
+        let mut parameters = Map::new();
+        parameters.insert("mode".to_string(), Value::from(mode));
+        for key in ["threshold", "confirm_window"] {
+            if let Some(value) = obj.get(key) {
+                parameters.insert(key.to_string(), value.clone());
+            }
+        }
+        parameters.insert("strategies".to_string(), Value::Array(members));
+        
+        let summary = |condition: fn(&StrategyConfig) -> &str| {
+            let conditions: Vec<&str> = member_configs.iter().map(condition).collect();
+            format!("{} of {}", mode, conditions.join(" | "))
+        };
+        Ok(StrategyConfig {
+            strategy_type: "MIX".to_string(),
+            pair: member_configs[0].pair.clone(),
+            timeframe: member_configs[0].timeframe.clone(),
+            buy_condition: summary(|c| c.buy_condition.as_str()),
+            sell_condition: summary(|c| c.sell_condition.as_str()),
+            parameters: Value::Object(parameters),
+        })
+    }
+    
     /// Validate StrategyConfig based on strategy type
     /// This ensures the config is valid for both backtest and live trading
     fn validate_config(&self, config: &StrategyConfig) -> Result<()> {
@@ -207,6 +293,15 @@ impl StrategyService {
         // Validate strategy-specific parameters
         self.validate_strategy_parameters(&config.strategy_type, &config.parameters)?;
         
+        // Strategies evaluating their conditions need them to parse once translated
+        if matches!(config.strategy_type.to_uppercase().as_str(), "RSI" | "STOCHASTIC" | "ADX" | "EXPRESSION") {
+            let translated = translate_conditions(config);
+            Condition::parse(&translated.buy_condition, &config.parameters)
+                .map_err(|e| anyhow::anyhow!("Invalid buy condition: {}", e))?;
+            Condition::parse(&translated.sell_condition, &config.parameters)
+                .map_err(|e| anyhow::anyhow!("Invalid sell condition: {}", e))?;
+        }
+        
         Ok(())
     }
     
@@ -292,6 +387,19 @@ impl StrategyService {
                     }
                 }
             }
+            "MIX" => {
+                // The members are validated when the mixed content is parsed
+                let mode = params.get("mode").and_then(|v| v.as_str()).unwrap_or("unanimous");
+                if !["unanimous", "majority", "weighted", "sequence"].contains(&mode) {
+                    bail!("Unknown mix mode: {}", mode);
+                }
+                let members = params.get("strategies")
+                    .and_then(|v| v.as_array())
+                    .map_or(0, |members| members.len());
+                if members == 0 || (mode == "sequence" && members < 2) {
+                    bail!("Mix mode {} cannot combine {} strategies", mode, members);
+                }
+            }
             _ => {
                 // For unknown strategy types, allow flexible parameters but log a warning
                 tracing::warn!("Unknown strategy type: {}, allowing flexible parameters", strategy_type);
@@ -335,6 +443,9 @@ impl StrategyService {
                     .unwrap_or(20);
                 params.insert("period".to_string(), Value::Number(period.into()));
             }
+            "EXPRESSION" => {
+                // Indicator arguments are written in the conditions themselves
+            }
             _ => {
                 // Default parameters for unknown strategies
                 params.insert("period".to_string(), Value::Number(14.into()));
@@ -355,3 +466,33 @@ impl StrategyService {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::services::strategy_engine::registry::mix_member_fixture;
+
+    #[test]
+    fn test_mixed_content_members_trade_the_same_candles() {
+        let service = StrategyService::new(Arc::new(DatabaseConnection::Disconnected));
+        let mixed = |members: Vec<Value>| {
+            json!({ "type": "mixed", "mix_mode": "majority", "strategies": members }).to_string()
+        };
+
+        let config = service.parse_and_validate_content(&mixed(vec![
+            mix_member_fixture("dip", "EXPRESSION", "BTC/USDT", "5m"),
+            mix_member_fixture("spot", "EXPRESSION", "BTCUSDT", "5m"),
+        ])).unwrap();
+        assert_eq!((config.strategy_type.as_str(), config.pair.as_str(), config.timeframe.as_str()), ("MIX", "BTC/USDT", "5m"));
+
+        for other in [
+            mix_member_fixture("eth", "EXPRESSION", "ETH/USDT", "5m"),
+            mix_member_fixture("hourly", "EXPRESSION", "BTC/USDT", "1h"),
+        ] {
+            let error = service.parse_and_validate_content(&mixed(vec![mix_member_fixture("dip", "EXPRESSION", "BTC/USDT", "5m"), other]))
+                .unwrap_err();
+            assert!(format!("{:#}", error).contains("the mix trades BTC/USDT 5m"), "{:#}", error);
+        }
+    }
+}
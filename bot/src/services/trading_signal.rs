@@ -1,26 +1,19 @@
 use std::sync::Arc;
 use std::collections::HashMap;
 use std::str::FromStr;
-use barter_data::{
-    exchange::binance::spot::BinanceSpot,
-    streams::Streams,
-    subscription::trade::PublicTrades,
-};
-use barter_instrument::instrument::market_data::kind::MarketDataInstrumentKind;
 use sea_orm::{EntityTrait, ActiveValue};
-use shared::entity::live_trading_signals;
-use barter_data::streams::reconnect::Event;
-use futures::StreamExt;
-use ta::{
-    indicators::RelativeStrengthIndex,
-    Next,
-};
-use tokio::sync::{RwLock, broadcast};
+use shared::entity::{live_trading_signals, users};
+use tokio::sync::{RwLock, broadcast, mpsc};
 use tokio::time::{interval, Duration};
 use tracing::{info, warn, error, debug};
 use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
 use chrono::{Utc, DateTime};
 
+use crate::services::candle_aggregator::{CandleAggregator, Trade};
+use crate::services::market_feed::{FeedEvent, MarketDataSource};
+use crate::services::market_recorder::MarketRecorder;
+use crate::services::strategy_engine::{Candle, StrategyExecutor};
 use crate::state::AppState;
 
 /// Stream key to identify unique streams (exchange + base + quote)
@@ -45,53 +38,135 @@ impl StreamKey {
             Self::new(exchange, &base, &quote)
         })
     }
+    
+    /// Pair in "BASE/QUOTE" form
+    fn pair(&self) -> String {
+        format!("{}/{}", self.base.to_uppercase(), self.quote.to_uppercase())
+    }
+}
+
+/// Subscription to a stream; a session can hold several on one pair, e.g. trading BTC/USDT
+/// while BTC/USDT is also the informative pair of its other pairs
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Subscriber {
+    /// The session trades the stream's pair
+    Session(u64),
+    /// The session's traded `pair` reads the stream as an informative pair
+    Informative { session_id: u64, pair: String },
+}
+
+impl Subscriber {
+    pub fn session_id(&self) -> u64 {
+        match self {
+            Subscriber::Session(session_id) | Subscriber::Informative { session_id, .. } => *session_id,
+        }
+    }
 }
 
 /// Stream information for a trading pair
 struct StreamInfo {
-    subscribers: Arc<RwLock<Vec<i64>>>, // List of user IDs subscribed to this stream
+    subscribers: Arc<RwLock<Vec<Subscriber>>>, // Subscriptions to this stream
+    /// Candle timeframe each subscription asked for
+    timeframes: Arc<RwLock<HashMap<Subscriber, String>>>,
+    /// One shared aggregator per subscribed timeframe
+    aggregators: Arc<RwLock<HashMap<String, CandleAggregator>>>,
     sender: broadcast::Sender<MarketEvent>, // Broadcast channel to send events to all subscribers
+    health: Arc<RwLock<StreamHealth>>,
 }
 
-/// Market event wrapper for broadcast
+/// Connection health of a stream, shown in /streams
+#[derive(Debug, Clone, Default)]
+pub struct StreamHealth {
+    pub connected: bool,
+    /// Local time of the last trade received
+    pub last_event: Option<DateTime<Utc>>,
+    /// Reconnects since the stream was created
+    pub reconnects: u32,
+}
+
+/// Market event broadcast to the subscribers of a stream
 #[derive(Debug, Clone)]
-struct MarketEvent {
-    price: f64,
-    timestamp: i64,
+pub enum MarketEvent {
+    /// Public trade
+    Trade(Trade),
+    /// Candle of a subscribed timeframe, sent when it closes and while it forms (`closed: false`)
+    /// Subscribers receive the candles of every timeframe of the stream and filter their own.
+    Candle {
+        timeframe: String,
+        candle: crate::services::strategy_engine::Candle,
+        closed: bool,
+    },
+    /// Closed candles missed during an outage, fetched from the exchange's REST API after
+    /// the stream reconnected (oldest first)
+    Backfill {
+        timeframe: String,
+        candles: Vec<crate::services::strategy_engine::Candle>,
+    },
+    /// The stream's connection dropped or went stale and is reconnecting
+    Disconnected { reason: String },
+    /// The stream is live again after an outage
+    Reconnected,
 }
 
-/// Stream Manager to share streams across users for the same trading pair
+/// Stream Manager to share streams across sessions for the same trading pair
+/// Each stream aggregates candles once per subscribed timeframe for all its sessions.
+/// Trades of all pairs come from one shared market data source (live exchange connections
+/// that reconnect on their own, or a replay), so receivers outlive outages.
 pub struct StreamManager {
     streams: Arc<RwLock<HashMap<StreamKey, StreamInfo>>>,
+    source: Box<dyn MarketDataSource>,
 }
 
 impl StreamManager {
-    pub fn new() -> Self {
+    /// Create the manager on the source `make_source` creates for the events channel,
+    /// and start routing the source's events to the streams (recording them with `recorder`)
+    /// Must be called within the tokio runtime.
+    pub fn with_source<S, F>(make_source: F, recorder: Option<MarketRecorder>) -> Self
+    where
+        S: MarketDataSource + 'static,
+        F: FnOnce(mpsc::UnboundedSender<FeedEvent>) -> S,
+    {
+        let streams = Arc::new(RwLock::new(HashMap::new()));
+        let (events, receiver) = mpsc::unbounded_channel();
+        let source = make_source(events);
+        tokio::spawn(Self::route_feed_events(streams.clone(), receiver, source.exchange().to_string(), recorder));
         Self {
-            streams: Arc::new(RwLock::new(HashMap::new())),
+            streams,
+            source: Box::new(source),
         }
     }
     
-    /// Subscribe a user to a stream for a trading pair
+    /// Subscribe a session to a stream for a trading pair
+    /// With a timeframe the stream also aggregates and broadcasts candles of that timeframe.
     /// Returns a receiver for market events
     pub async fn subscribe(
         &self,
         exchange: &str,
         pair: &str,
-        user_id: i64,
+        subscriber: Subscriber,
+        timeframe: Option<&str>,
     ) -> Result<broadcast::Receiver<MarketEvent>, anyhow::Error> {
         let key = StreamKey::from_pair(exchange, pair)
             .ok_or_else(|| anyhow::anyhow!("Invalid pair format: {}", pair))?;
+        if key.exchange != self.source.exchange() {
+            return Err(anyhow::anyhow!("Unsupported exchange: {} (market data comes from {})", exchange, self.source.exchange()));
+        }
         
         let mut streams = self.streams.write().await;
         
         // Check if stream already exists
         if let Some(stream_info) = streams.get(&key) {
-            // Add user to subscribers
+            // Add session to subscribers
             let mut subscribers = stream_info.subscribers.write().await;
-            if !subscribers.contains(&user_id) {
-                subscribers.push(user_id);
-                info!("User {} subscribed to existing stream for {} ({})", user_id, pair, exchange);
+            if !subscribers.contains(&subscriber) {
+                info!("{:?} subscribed to existing stream for {} ({})", subscriber, pair, exchange);
+                subscribers.push(subscriber.clone());
+            }
+            if let Some(timeframe) = timeframe {
+                stream_info.timeframes.write().await.insert(subscriber, timeframe.to_string());
+                stream_info.aggregators.write().await
+                    .entry(timeframe.to_string())
+                    .or_insert_with(|| CandleAggregator::new(timeframe));
             }
             
             // Return receiver for this stream
@@ -100,52 +175,52 @@ impl StreamManager {
             // Create new stream
             let (sender, receiver) = broadcast::channel(1000); // Buffer up to 1000 events
             
+            let mut timeframes = HashMap::new();
+            let mut aggregators = HashMap::new();
+            if let Some(timeframe) = timeframe {
+                timeframes.insert(subscriber.clone(), timeframe.to_string());
+                aggregators.insert(timeframe.to_string(), CandleAggregator::new(timeframe));
+            }
+            info!("Created new stream for {} ({}) with subscriber {:?}", pair, exchange, subscriber);
             let stream_info = StreamInfo {
-                subscribers: Arc::new(RwLock::new(vec![user_id])),
+                subscribers: Arc::new(RwLock::new(vec![subscriber])),
+                timeframes: Arc::new(RwLock::new(timeframes)),
+                aggregators: Arc::new(RwLock::new(aggregators)),
                 sender,
+                health: Arc::new(RwLock::new(StreamHealth::default())),
             };
             
             streams.insert(key.clone(), stream_info);
-            info!("Created new stream for {} ({}) with subscriber {}", pair, exchange, user_id);
             
-            // Spawn task to initialize and run the stream
-            // Use std::thread::spawn with LocalSet for non-Send futures
-            let key_clone = key.clone();
-            let exchange_clone = exchange.to_string();
-            let base = key.base.clone();
-            let quote = key.quote.clone();
-            let streams_map = self.streams.clone();
-            
-            std::thread::spawn(move || {
-                let rt = tokio::runtime::Runtime::new().unwrap();
-                rt.block_on(async {
-                    use tokio::task::LocalSet;
-                    let local = LocalSet::new();
-                    local.spawn_local(async move {
-                        if let Err(e) = Self::run_stream(
-                            &key_clone,
-                            &exchange_clone,
-                            &base,
-                            &quote,
-                            streams_map.clone(),
-                        ).await {
-                            error!("Error running stream for {:?}: {}", key_clone, e);
-                            
-                            // Remove stream from map on error
-                            let mut streams = streams_map.write().await;
-                            streams.remove(&key_clone);
-                        }
-                    });
-                    local.await;
-                });
-            });
+            self.source.add(&key.pair());
             
             Ok(receiver)
         }
     }
     
-    /// Unsubscribe a user from a stream
-    pub async fn unsubscribe(&self, exchange: &str, pair: &str, user_id: i64) {
+    /// Merge a candle that was forming before a restart into the stream's candle of its period
+    /// Ignored if the stream does not aggregate the timeframe or the period has closed.
+    pub async fn merge_forming_candle(
+        &self,
+        exchange: &str,
+        pair: &str,
+        timeframe: &str,
+        candle: crate::services::strategy_engine::Candle,
+    ) {
+        let Some(key) = StreamKey::from_pair(exchange, pair) else {
+            return;
+        };
+        let streams = self.streams.read().await;
+        if let Some(stream_info) = streams.get(&key) {
+            if let Some(aggregator) = stream_info.aggregators.write().await.get_mut(timeframe) {
+                aggregator.merge_earlier(candle);
+            }
+        }
+    }
+    
+    /// Remove a subscription from a stream, and the stream once it has none left
+    /// Other subscriptions of the same session keep the stream and their timeframes.
+    pub async fn unsubscribe(&self, exchange: &str, pair: &str, subscriber: &Subscriber) {
         let key = StreamKey::from_pair(exchange, pair);
         
         if let Some(key) = key {
@@ -153,16 +228,24 @@ impl StreamManager {
             
             if let Some(stream_info) = streams.get(&key) {
                 let mut subscribers = stream_info.subscribers.write().await;
-                subscribers.retain(|&id| id != user_id);
+                subscribers.retain(|s| s != subscriber);
                 
                 let subscriber_count = subscribers.len();
                 drop(subscribers); // Release lock before removing from map
                 
-                info!("User {} unsubscribed from stream for {} ({})", user_id, pair, exchange);
+                // Stop aggregating timeframes no remaining subscription asked for
+                let mut timeframes = stream_info.timeframes.write().await;
+                timeframes.remove(subscriber);
+                stream_info.aggregators.write().await
+                    .retain(|timeframe, _| timeframes.values().any(|t| t == timeframe));
+                drop(timeframes);
+                
+                info!("{:?} unsubscribed from stream for {} ({})", subscriber, pair, exchange);
                 
                 // If no more subscribers, remove the stream
                 if subscriber_count == 0 {
                     streams.remove(&key);
+                    self.source.remove(&key.pair());
                     info!("Removed stream for {} ({}) - no more subscribers", pair, exchange);
                 }
             }
@@ -184,106 +267,214 @@ impl StreamManager {
     }
     
     /// Get all active streams with their information
-    /// Returns vector of (exchange, pair, subscriber_count, subscriber_ids)
-    pub async fn get_active_streams(&self) -> Vec<(String, String, usize, Vec<i64>)> {
+    /// Returns vector of (exchange, pair, subscriber_count, subscriber_ids, health)
+    pub async fn get_active_streams(&self) -> Vec<(String, String, usize, Vec<u64>, StreamHealth)> {
         let streams = self.streams.read().await;
         let mut result = Vec::new();
         
         for (key, stream_info) in streams.iter() {
-            let subscribers = stream_info.subscribers.read().await;
-            let subscriber_ids = subscribers.clone();
+            // Sessions, once each however many subscriptions they hold
+            let mut subscriber_ids: Vec<u64> = stream_info.subscribers.read().await
+                .iter()
+                .map(Subscriber::session_id)
+                .collect();
+            subscriber_ids.sort_unstable();
+            subscriber_ids.dedup();
             let subscriber_count = subscriber_ids.len();
             
-            let pair = format!("{}/{}", key.base.to_uppercase(), key.quote.to_uppercase());
             result.push((
                 key.exchange.clone(),
-                pair,
+                key.pair(),
                 subscriber_count,
                 subscriber_ids,
+                stream_info.health.read().await.clone(),
             ));
         }
         
         result
     }
     
-    /// Run the actual stream and broadcast events to all subscribers
-    async fn run_stream(
-        key: &StreamKey,
-        exchange: &str,
-        base: &str,
-        quote: &str,
+    /// Route the source's trades and connection changes to the streams, and close and publish
+    /// candles every second so quiet periods close without trades
+    async fn route_feed_events(
         streams_map: Arc<RwLock<HashMap<StreamKey, StreamInfo>>>,
-    ) -> Result<(), anyhow::Error> {
-        // Initialize stream based on exchange
-        let streams_result = match exchange {
-            "binance" => {
-                Streams::<PublicTrades>::builder()
-                    .subscribe([(
-                        BinanceSpot::default(),
-                        base,
-                        quote,
-                        MarketDataInstrumentKind::Spot,
-                        PublicTrades,
-                    )])
-                    .init()
-                    .await
-            }
-            "okx" => {
-                return Err(anyhow::anyhow!("OKX exchange not yet supported in barter-data"));
-            }
-            _ => {
-                return Err(anyhow::anyhow!("Unsupported exchange: {}", exchange));
-            }
-        };
-        
-        let streams = streams_result?;
-        info!("✅ Stream initialized for {} ({})", format!("{}/{}", base, quote), exchange);
-        
-        let mut market_stream = streams.select_all();
+        mut events: mpsc::UnboundedReceiver<FeedEvent>,
+        exchange: String,
+        recorder: Option<MarketRecorder>,
+    ) {
+        // Candles close on exchange time; between trades it is estimated from the local clock,
+        // per stream as replayed pairs each run on their recording's time
+        let mut clock_offsets: HashMap<StreamKey, i64> = HashMap::new();
+        let mut candle_timer = interval(Duration::from_secs(1));
         
         loop {
-            match market_stream.next().await {
-                Some(event) => {
-                    match event {
-                        Event::Item(market_event_result) => {
-                            match market_event_result {
-                                Ok(market_event) => {
-                                    let price = market_event.kind.price;
-                                    let timestamp = market_event.time_received.timestamp();
-                                    
-                                    // Broadcast event to all subscribers
-                                    let event = MarketEvent { price, timestamp };
-                                    
-                                    // Get sender from streams map
-                                    let streams = streams_map.read().await;
-                                    if let Some(stream_info) = streams.get(key) {
-                                        // Send to all subscribers (ignore errors if no receivers)
-                                        let _ = stream_info.sender.send(event);
-                                    }
-                                }
-                                Err(e) => {
-                                    error!("Error in market event for {:?}: {}", key, e);
+            tokio::select! {
+                event = events.recv() => match event {
+                    Some(FeedEvent::Trade { pair, trade, received_ms }) => {
+                        let Some(key) = StreamKey::from_pair(&exchange, &pair) else {
+                            continue;
+                        };
+                        clock_offsets.insert(key.clone(), trade.time_ms - received_ms);
+                        let received = DateTime::from_timestamp_millis(received_ms).unwrap_or_else(Utc::now);
+                        Self::publish_trade(&key, &streams_map, trade, received, recorder.as_ref()).await;
+                    }
+                    Some(FeedEvent::Connected { pairs, reconnect }) => {
+                        for key in pairs.iter().filter_map(|pair| StreamKey::from_pair(&exchange, pair)) {
+                            // Backfills run on their own so trades keep flowing meanwhile
+                            let (exchange, pair) = (key.exchange.clone(), key.pair());
+                            tokio::spawn(Self::on_connected(key, streams_map.clone(), reconnect, move |timeframe, since| {
+                                let (exchange, pair) = (exchange.clone(), pair.clone());
+                                async move {
+                                    crate::services::warmup_service::fetch_missed_candles(&exchange, &pair, &timeframe, since).await
                                 }
-                            }
+                            }));
                         }
-                        Event::Reconnecting(_origin) => {
-                            warn!("Reconnecting stream for {:?}...", key);
+                    }
+                    Some(FeedEvent::Disconnected { pairs, reason }) => {
+                        warn!("Streams for {} pairs are down: {}", pairs.len(), reason);
+                        let streams = streams_map.read().await;
+                        for key in pairs.iter().filter_map(|pair| StreamKey::from_pair(&exchange, pair)) {
+                            if let Some(stream_info) = streams.get(&key) {
+                                stream_info.health.write().await.connected = false;
+                                let _ = stream_info.sender.send(MarketEvent::Disconnected { reason: reason.clone() });
+                            }
                         }
                     }
-                }
-                None => {
-                    warn!("Market stream ended for {:?}, exiting...", key);
-                    break;
+                    None => return,
+                },
+                _ = candle_timer.tick() => {
+                    // Close candles of quiet periods and publish the forming ones
+                    let now_ms = Utc::now().timestamp_millis();
+                    let keys: Vec<StreamKey> = streams_map.read().await.keys().cloned().collect();
+                    clock_offsets.retain(|key, _| keys.contains(key));
+                    for key in keys {
+                        let offset_ms = clock_offsets.get(&key).copied().unwrap_or(0);
+                        Self::publish_candles(&key, &streams_map, now_ms + offset_ms, true, recorder.as_ref()).await;
+                    }
                 }
             }
         }
+    }
+    
+    /// Mark a (re)connected stream healthy; after an outage, backfill the candles it missed
+    /// with `fetch` (timeframe and first open time, from the exchange's REST API) and tell
+    /// the subscribers it is live again
+    async fn on_connected<F, Fut>(
+        key: StreamKey,
+        streams_map: Arc<RwLock<HashMap<StreamKey, StreamInfo>>>,
+        reconnect: bool,
+        fetch: F,
+    )
+    where
+        F: Fn(String, i64) -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<Candle>, anyhow::Error>>,
+    {
+        let Some((aggregators, health, sender)) = streams_map.read().await
+            .get(&key)
+            .map(|stream_info| (stream_info.aggregators.clone(), stream_info.health.clone(), stream_info.sender.clone()))
+        else {
+            return;
+        };
+        {
+            let mut health = health.write().await;
+            health.connected = true;
+            if reconnect {
+                health.reconnects += 1;
+            }
+        }
+        if !reconnect {
+            return;
+        }
         
-        // Remove stream from map when it ends
-        let mut streams = streams_map.write().await;
-        streams.remove(key);
-        info!("Stream removed for {:?}", key);
-        
-        Ok(())
+        // Fetch without holding the locks, sessions may subscribe meanwhile
+        let pair = key.pair();
+        let gaps: Vec<(String, i64)> = aggregators.read().await
+            .iter()
+            .filter_map(|(timeframe, aggregator)| Some((timeframe.clone(), aggregator.backfill_since()?)))
+            .collect();
+        for (timeframe, since) in gaps {
+            match fetch(timeframe.clone(), since).await {
+                Ok(candles) => {
+                    info!("📥 Backfilled {} {} candles of {} missed during the outage", candles.len(), timeframe, pair);
+                    if let Some(aggregator) = aggregators.write().await.get_mut(&timeframe) {
+                        aggregator.apply_backfill(&candles);
+                    }
+                    if !candles.is_empty() {
+                        let _ = sender.send(MarketEvent::Backfill { timeframe, candles });
+                    }
+                }
+                Err(e) => warn!("Failed to backfill {} candles of {}: {}", timeframe, pair, e),
+            }
+        }
+        let _ = sender.send(MarketEvent::Reconnected);
+    }
+    
+    /// Feed a trade to the stream's aggregators and broadcast (and record) it with the
+    /// candles it closed
+    async fn publish_trade(
+        key: &StreamKey,
+        streams_map: &Arc<RwLock<HashMap<StreamKey, StreamInfo>>>,
+        trade: Trade,
+        received: DateTime<Utc>,
+        recorder: Option<&MarketRecorder>,
+    ) {
+        let time_ms = trade.time_ms;
+        {
+            let streams = streams_map.read().await;
+            let Some(stream_info) = streams.get(key) else {
+                return;
+            };
+            stream_info.health.write().await.last_event = Some(received);
+            if let Some(recorder) = recorder {
+                recorder.record_trade(&key.exchange, &key.pair(), &trade);
+            }
+            for (timeframe, aggregator) in stream_info.aggregators.write().await.iter_mut() {
+                if !aggregator.add_trade(&trade) {
+                    debug!("Late trade for {:?} {} dropped ({} so far)", key, timeframe, aggregator.late_trades());
+                }
+            }
+            // Send to all subscribers (ignore errors if no receivers)
+            let _ = stream_info.sender.send(MarketEvent::Trade(trade));
+        }
+        Self::publish_candles(key, streams_map, time_ms, false, recorder).await;
+    }
+    
+    /// Broadcast (and record) the candles closed by `now_ms` (exchange time) and, with
+    /// `forming`, the candles still forming that changed since the last call
+    async fn publish_candles(
+        key: &StreamKey,
+        streams_map: &Arc<RwLock<HashMap<StreamKey, StreamInfo>>>,
+        now_ms: i64,
+        forming: bool,
+        recorder: Option<&MarketRecorder>,
+    ) {
+        let streams = streams_map.read().await;
+        let Some(stream_info) = streams.get(key) else {
+            return;
+        };
+        // A stream that is down keeps its periods open, the backfill after it reconnects replaces them
+        if !stream_info.health.read().await.connected {
+            return;
+        }
+        for (timeframe, aggregator) in stream_info.aggregators.write().await.iter_mut() {
+            for candle in aggregator.close_until(now_ms) {
+                if let Some(recorder) = recorder {
+                    recorder.record_candle(&key.exchange, &key.pair(), timeframe, &candle);
+                }
+                let _ = stream_info.sender.send(MarketEvent::Candle {
+                    timeframe: timeframe.clone(),
+                    candle,
+                    closed: true,
+                });
+            }
+            if let Some(candle) = forming.then(|| aggregator.take_forming_update()).flatten() {
+                let _ = stream_info.sender.send(MarketEvent::Candle {
+                    timeframe: timeframe.clone(),
+                    candle,
+                    closed: false,
+                });
+            }
+        }
     }
 }
 
@@ -322,499 +513,13 @@ fn normalize_pair(pair: &str) -> Option<(String, String)> {
     None
 }
 
-/// 1-Minute Candle aggregator
-#[derive(Debug, Clone)]
-struct OneMinuteCandle {
-    open: f64,
-    high: f64,
-    low: f64,
-    close: f64,
-    start_minute: i64,
-    processed: bool, // Flag to prevent double processing
-}
-
-impl OneMinuteCandle {
-    fn new(price: f64, timestamp: i64) -> Self {
-        let start_minute = (timestamp / 60) * 60;
-        Self {
-            open: price,
-            high: price,
-            low: price,
-            close: price,
-            start_minute,
-            processed: false,
-        }
-    }
-
-    fn update(&mut self, price: f64) {
-        self.high = self.high.max(price);
-        self.low = self.low.min(price);
-        self.close = price;
-    }
-
-    fn is_expired(&self, current_timestamp: i64) -> bool {
-        let current_minute = (current_timestamp / 60) * 60;
-        current_minute > self.start_minute
-    }
-}
-
-/// Trading Bot State
-#[derive(Debug, Clone)]
-struct TradingState {
-    rsi: RelativeStrengthIndex,
-    prices: Vec<f64>,
-    period: usize,
-    current_candle: Option<OneMinuteCandle>,
-    last_signal: Option<TradingSignalType>, // Track last signal type to avoid duplicates
-}
-
-/// Signal type enum to track state changes
-#[derive(Debug, Clone, PartialEq)]
-enum TradingSignalType {
-    Buy,
-    Sell,
-}
-
-impl TradingState {
-    fn new(period: usize) -> Self {
-        Self {
-            rsi: RelativeStrengthIndex::new(period).unwrap(),
-            prices: Vec::with_capacity(period + 10),
-            period,
-            current_candle: None,
-            last_signal: None,
-        }
-    }
-
-    fn process_trade(&mut self, price: f64, timestamp: i64, app_state: Arc<crate::state::AppState>) -> Option<TradingSignal> {
-        let current_minute = (timestamp / 60) * 60;
-        
-        // Check if candle exists and if it's expired
-        if let Some(ref mut candle) = self.current_candle {
-            if candle.is_expired(timestamp) {
-                // Candle completed! Process it only if not already processed by timer
-                if candle.processed {
-                    info!("🕐 Trade triggered: Candle already processed by timer, starting new candle");
-                    // Just start new candle, don't process again
-                    self.current_candle = Some(OneMinuteCandle::new(price, timestamp));
-                    return None;
-                }
-                
-                let completed_close = candle.close;
-                info!("🕐 Trade triggered: 1-minute candle completed! Close: {:.4}", completed_close);
-                
-                // Create Candle for strategy executor
-                use crate::services::strategy_engine::Candle as StrategyCandle;
-                let strategy_candle = StrategyCandle {
-                    open: candle.open,
-                    high: candle.high,
-                    low: candle.low,
-                    close: candle.close,
-                    volume: 0.0, // Volume not available from trades
-                    timestamp,
-                };
-                
-                // Process candle through strategy executor for all users (async)
-                let executor = app_state.strategy_executor.clone();
-                tokio::spawn(async move {
-                    let signals = executor.process_candle_for_all(&strategy_candle).await;
-                    for (user_id, signal) in signals {
-                        info!("📊 User {} strategy signal: {:?}", user_id, signal);
-                        // TODO: In future, send personalized signals to each user
-                        // For now, signals are logged
-                    }
-                });
-                
-                // Mark as processed BEFORE processing
-                candle.processed = true;
-                let signal = self.process_price(completed_close);
-                
-                // Start new candle for current minute
-                self.current_candle = Some(OneMinuteCandle::new(price, timestamp));
-                return signal;
-            } else {
-                // Update current candle
-                candle.update(price);
-            }
-        } else {
-            // No candle yet - initialize new one
-            info!("🕐 Starting new 1-minute candle at minute: {}", current_minute);
-            self.current_candle = Some(OneMinuteCandle::new(price, timestamp));
-        }
-        
-        None
-    }
-
-    fn force_process_candle(&mut self) -> Option<TradingSignal> {
-        if let Some(ref mut candle) = self.current_candle {
-            // Check if already processed
-            if candle.processed {
-                info!("🕐 Timer: Candle already processed, skipping");
-                return None;
-            }
-            
-            let completed_close = candle.close;
-            info!("🕐 Timer: Processing completed candle with close price: {:.4}", completed_close);
-            
-            // Mark as processed BEFORE processing to prevent race condition
-            candle.processed = true;
-            let signal = self.process_price(completed_close);
-            
-            // Don't reset candle here - let next trade create new one when it comes
-            return signal;
-        } else {
-            info!("🕐 Timer: No candle to process yet (waiting for first trade)");
-        }
-        None
-    }
-
-    fn process_price(&mut self, price: f64) -> Option<TradingSignal> {
-        self.prices.push(price);
-        
-        if self.prices.len() > self.period + 10 {
-            self.prices.remove(0);
-        }
-
-        if self.prices.len() < self.period + 1 {
-            info!("⏳ Collecting prices for RSI: {}/{} (need {} prices for RSI calculation)", 
-                  self.prices.len(), self.period + 1, self.period + 1);
-            return None;
-        }
-
-        // Always calculate and log RSI, even if no signal
-        let rsi_value = self.rsi.next(price);
-        info!("📊 1m Candle Close: {:.4} USDT, RSI: {:.2}", price, rsi_value);
-
-        // Strategy: Buy when RSI < 30, Sell when RSI > 70
-        // Only send signal when signal type CHANGES (to avoid spam)
-        let current_signal_type = if rsi_value < 30.0 {
-            Some(TradingSignalType::Buy)
-        } else if rsi_value > 70.0 {
-            Some(TradingSignalType::Sell)
-        } else {
-            None // RSI in neutral zone, reset last_signal
-        };
-
-        // Check if signal type changed
-        let should_send = match (&self.last_signal, &current_signal_type) {
-            (None, Some(_)) => true,  // First signal
-            (Some(TradingSignalType::Buy), Some(TradingSignalType::Sell)) => true,  // Buy -> Sell
-            (Some(TradingSignalType::Sell), Some(TradingSignalType::Buy)) => true,  // Sell -> Buy
-            (Some(_), None) => true,  // Signal -> No signal (reset)
-            (Some(same), Some(other)) if same != other => true,  // Signal type changed
-            _ => false,  // Same signal type, don't send again
-        };
-
-        if should_send {
-            // Update last signal
-            self.last_signal = current_signal_type.clone();
-            
-            // Return signal
-            match current_signal_type {
-                Some(TradingSignalType::Buy) => Some(TradingSignal::Buy { price, rsi: rsi_value }),
-                Some(TradingSignalType::Sell) => Some(TradingSignal::Sell { price, rsi: rsi_value }),
-                None => {
-                    // Signal cleared (RSI back to neutral)
-                    self.last_signal = None;
-                    None
-                }
-            }
-        } else {
-            // Same signal type, don't send
-            info!("⏸️  Signal already sent for this condition (RSI: {:.2}), skipping duplicate", rsi_value);
-            None
-        }
-    }
-
-    /// Process trade and create candle for user's strategy (returns Candle instead of TradingSignal)
-    fn process_trade_for_user(
-        &mut self,
-        price: f64,
-        timestamp: i64,
-        strategy_config: &crate::services::strategy_engine::StrategyConfig,
-        _app_state: &Arc<crate::state::AppState>,
-        user_id: i64,
-    ) -> Option<crate::services::strategy_engine::Candle> {
-        // Parse timeframe to seconds
-        let timeframe_secs = parse_timeframe_to_seconds(&strategy_config.timeframe);
-        let current_period = (timestamp / timeframe_secs as i64) * timeframe_secs as i64;
-        
-        // Check if candle exists and if it's expired
-        if let Some(ref mut candle) = self.current_candle {
-            let candle_period = (candle.start_minute / timeframe_secs as i64) * timeframe_secs as i64;
-            if current_period > candle_period {
-                // Candle completed!
-                if candle.processed {
-                    // Already processed, start new candle
-                    debug!("🕯️ [User {}] Candle already processed, starting new candle at price {:.4}", user_id, price);
-                    self.current_candle = Some(OneMinuteCandle::new(price, timestamp));
-                    return None;
-                }
-                
-                let completed_candle = crate::services::strategy_engine::Candle {
-                    open: candle.open,
-                    high: candle.high,
-                    low: candle.low,
-                    close: candle.close,
-                    volume: 0.0,
-                    timestamp: candle.start_minute,
-                };
-                
-                candle.processed = true;
-                self.current_candle = Some(OneMinuteCandle::new(price, timestamp));
-                return Some(completed_candle);
-            } else {
-                // Update current candle
-                candle.update(price);
-            }
-        } else {
-            // No candle yet - initialize new one
-            debug!("🕯️ [User {}] Initializing new candle at price {:.4} for {} timeframe", user_id, price, strategy_config.timeframe);
-            self.current_candle = Some(OneMinuteCandle::new(price, timestamp));
-        }
-        
-        None
-    }
-
-    /// Force process candle for user (timer-based)
-    fn force_process_candle_for_user(
-        &mut self,
-        strategy_config: &crate::services::strategy_engine::StrategyConfig,
-        _app_state: &Arc<crate::state::AppState>,
-        user_id: i64,
-    ) -> Option<crate::services::strategy_engine::Candle> {
-        if let Some(ref mut candle) = self.current_candle {
-            if candle.processed {
-                debug!("🕯️ [User {}] Candle already processed, skipping", user_id);
-                return None;
-            }
-            
-            let completed_candle = crate::services::strategy_engine::Candle {
-                open: candle.open,
-                high: candle.high,
-                low: candle.low,
-                close: candle.close,
-                volume: 0.0,
-                timestamp: candle.start_minute,
-            };
-            
-            candle.processed = true;
-            return Some(completed_candle);
-        }
-        debug!("🕯️ [User {}] No active candle to process", user_id);
-        None
-    }
-}
-
-#[derive(Debug, Clone)]
-enum TradingSignal {
-    Buy { price: f64, rsi: f64 },
-    Sell { price: f64, rsi: f64 },
-}
-
-/// Parse timeframe string to seconds
-fn parse_timeframe_to_seconds(timeframe: &str) -> u64 {
-    let timeframe_lower = timeframe.to_lowercase();
-    if timeframe_lower.ends_with('m') {
-        if let Ok(minutes) = timeframe_lower.trim_end_matches('m').parse::<u64>() {
-            return minutes * 60;
-        }
-    } else if timeframe_lower.ends_with('h') {
-        if let Ok(hours) = timeframe_lower.trim_end_matches('h').parse::<u64>() {
-            return hours * 3600;
-        }
-    } else if timeframe_lower.ends_with('d') {
-        if let Ok(days) = timeframe_lower.trim_end_matches('d').parse::<u64>() {
-            return days * 86400;
-        }
-    }
-    // Default to 1 minute if parsing fails
-    60
-}
-
-/// Format trading signal message for Telegram
-fn format_signal_message(signal: &TradingSignal, pair: &str, bot_name: &str) -> String {
-    let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
-    match signal {
-        TradingSignal::Buy { price, rsi } => {
-            format!(
-                "🟢 <b>BUY SIGNAL - {}</b>\n\n\
-💰 <b>Price:</b> <code>{:.4}</code> USDT\n\
-📊 <b>RSI:</b> <code>{:.2}</code>\n\
-⏰ <b>Time:</b> <code>{}</code>\n\
-📈 <b>Strategy:</b> RSI &lt; 30 (Oversold)\n\
-📍 <b>Timeframe:</b> 1 minute candles\n\n\
-🤖 <b>Bot:</b> {}\n\
-🔄 <b>Status:</b> <code>Monitoring Live</code>\n\
-🌐 <b>Exchange:</b> Binance Spot\n\n\
-⚠️ <i>This is a paper trading signal. Always do your own research!</i>",
-                pair, price, rsi, timestamp, bot_name
-            )
-        },
-        TradingSignal::Sell { price, rsi } => {
-            format!(
-                "🔴 <b>SELL SIGNAL - {}</b>\n\n\
-💰 <b>Price:</b> <code>{:.4}</code> USDT\n\
-📊 <b>RSI:</b> <code>{:.2}</code>\n\
-⏰ <b>Time:</b> <code>{}</code>\n\
-📉 <b>Strategy:</b> RSI &gt; 70 (Overbought)\n\
-📍 <b>Timeframe:</b> 1 minute candles\n\n\
-🤖 <b>Bot:</b> {}\n\
-🔄 <b>Status:</b> <code>Monitoring Live</code>\n\
-🌐 <b>Exchange:</b> Binance Spot\n\n\
-⚠️ <i>This is a paper trading signal. Always do your own research!</i>",
-                pair, price, rsi, timestamp, bot_name
-            )
-        },
-    }
-}
-
-/// Start trading signal service (runs forever in background)
-pub fn start_trading_signal_service(
-    app_state: Arc<AppState>,
-    bot: Bot,
-    channel_id: i64,
-    pair: String,
-) {
-    let bot_name = app_state.bot_name.clone();
-    info!("🚀 Starting Trading Signal Service for {}", pair);
-    
-    let state = Arc::new(RwLock::new(TradingState::new(14))); // Period 2 = need 3 prices for RSI
-    
-    // Clone variables for tasks
-    let state_for_stream = state.clone();
-    let bot_for_stream = bot.clone();
-    let pair_for_stream = pair.clone();
-    let channel_id_for_stream = channel_id;
-    let bot_name_for_stream = bot_name.clone();
-    let app_state_for_stream = app_state.clone();
-    
-    // Use LocalSet for non-Send futures
-    use tokio::task::LocalSet;
-    
-    std::thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            let local = LocalSet::new();
-            local.spawn_local(async move {
-                // Initialize streams
-                let streams_result = Streams::<PublicTrades>::builder()
-                    .subscribe([(
-                        BinanceSpot::default(),
-                        "bnb",
-                        "usdt",
-                        MarketDataInstrumentKind::Spot,
-                        PublicTrades,
-                    )])
-                    .init()
-                    .await;
-                    
-                let streams = match streams_result {
-                    Ok(streams) => streams,
-                    Err(e) => {
-                        error!("Failed to initialize streams: {}", e);
-                        return;
-                    }
-                };
-
-                info!("✅ Connected to Binance! Monitoring {} for trading signals...", pair_for_stream);
-
-                let mut market_stream = streams.select_all();
-                let state_trades = state_for_stream.clone();
-                let bot_trades = bot_for_stream.clone();
-                let pair_trades = pair_for_stream.clone();
-
-                // Process trades
-                while let Some(event) = market_stream.next().await {
-                    match event {
-                        Event::Item(market_event_result) => {
-                            match market_event_result {
-                                Ok(market_event) => {
-                                    let price = market_event.kind.price;
-                                    let timestamp = market_event.time_received.timestamp();
-                                    
-                                    let mut state_guard = state_trades.write().await;
-                                    if let Some(signal) = state_guard.process_trade(price, timestamp, app_state_for_stream.clone()) {
-                                        let message = format_signal_message(&signal, &pair_trades, &bot_name_for_stream);
-                                        
-                                        if let Err(e) = bot_trades.send_message(
-                                            ChatId(channel_id_for_stream),
-                                            message
-                                        )
-                                        .parse_mode(teloxide::types::ParseMode::Html)
-                                        .await {
-                                            error!("Failed to send trading signal: {}", e);
-                                        } else {
-                                            info!("✅ Trading signal sent to channel {}", channel_id_for_stream);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    error!("Error in market event: {}", e);
-                                }
-                            }
-                        }
-                        Event::Reconnecting(_origin) => {
-                            warn!("Reconnecting to Binance...");
-                        }
-                    }
-                }
-            });
-            local.await;
-        });
-    });
-
-    // Start timer to force process candle every minute
-    let state_timer = state.clone();
-    let bot_timer = bot.clone();
-    let pair_timer = pair.clone();
-    let bot_name_timer = bot_name.clone();
-    
-    // Spawn task for timer-based processing
-    tokio::spawn(async move {
-        let mut minute_timer = interval(Duration::from_secs(60));
-        minute_timer.tick().await; // Skip first tick
-        info!("⏰ Timer started: will process candles every 60 seconds");
-        let mut count = 0;
-        
-        loop {
-            minute_timer.tick().await;
-            count += 1;
-            info!("⏰ Timer tick #{}", count);
-            let mut state_guard = state_timer.write().await;
-            
-            // Only process if candle exists (was started by a trade)
-            // After processing, reset candle to prevent double processing when next trade comes
-            if state_guard.current_candle.is_some() {
-                if let Some(signal) = state_guard.force_process_candle() {
-                    let message = format_signal_message(&signal, &pair_timer, &bot_name_timer);
-                    
-                    if let Err(e) = bot_timer.send_message(
-                        ChatId(channel_id),
-                        message
-                    )
-                    .parse_mode(teloxide::types::ParseMode::Html)
-                    .await {
-                        error!("Failed to send trading signal: {}", e);
-                    } else {
-                        info!("✅ Trading signal sent to channel {} (timer-based)", channel_id);
-                    }
-                }
-            } else {
-                info!("⏰ Timer: No active candle to process (waiting for trades to start candle)");
-            }
-        }
-    });
-
-    info!("✅ Trading Signal Service started successfully");
-}
-
-/// Start user-specific trading service (runs forever in background for a specific user)
+/// Start the trading service of one pair of a user's session (runs until the session stops trading the pair)
 /// This service monitors market data and sends trading signals to the user's chat/channel
-/// Uses StreamManager to share streams across users for the same trading pair
+/// Uses StreamManager to share streams across sessions for the same trading pair
 pub fn start_user_trading_service(
     app_state: Arc<AppState>,
     bot: Bot,
+    session_id: u64,
     user_id: i64,
     user_chat_id: i64, // Telegram chat ID to send signals to
     strategy_config: crate::services::strategy_engine::StrategyConfig,
@@ -822,8 +527,8 @@ pub fn start_user_trading_service(
     pair: String,
 ) {
     let bot_name = app_state.bot_name.clone();
-    info!("🚀 Starting User Trading Service for user {} with strategy {} on {} ({})", 
-        user_id, strategy_config.strategy_type, exchange, pair);
+    info!("🚀 Starting User Trading Service for user {} (session {}) with strategy {} on {} ({})", 
+        user_id, session_id, strategy_config.strategy_type, exchange, pair);
     
     // Normalize pair format (supports both "BTC/USDT" and "BTCUSDT")
     let (base, quote) = match normalize_pair(&pair) {
@@ -837,11 +542,7 @@ pub fn start_user_trading_service(
     // Log normalized pair for debugging
     info!("Normalized pair: {} -> {}/{}", pair, base, quote);
     
-    // Create candle aggregator for this user's timeframe
-    let state = Arc::new(RwLock::new(TradingState::new(14))); // RSI period
-    
     // Clone variables for tasks
-    let state_for_stream = state.clone();
     let bot_for_stream = bot.clone();
     let pair_for_stream = pair.clone();
     let user_chat_id_for_stream = user_chat_id;
@@ -852,11 +553,138 @@ pub fn start_user_trading_service(
     let exchange_for_stream = exchange.clone();
     let stream_manager = app_state.stream_manager.clone();
     
+    // Restore the open position so the strategy's trade callbacks keep managing it after a restart
+    let app_state_for_position = app_state.clone();
+    let pair_for_position = pair.clone();
+    tokio::spawn(async move {
+        match crate::services::position_service::get_open_positions(app_state_for_position.db.as_ref(), user_id).await {
+            Ok(positions) => {
+                if let Some(position) = positions.iter().find(|p| p.pair == pair_for_position) {
+                    let entry_price = f64::from_str(&position.entry_price.to_string()).unwrap_or(0.0);
+                    let quantity = f64::from_str(&position.quantity.to_string()).unwrap_or(0.0);
+                    app_state_for_position.strategy_executor
+                        .on_position_opened(session_id, &pair_for_position, position.id, entry_price, quantity, position.entry_time.unwrap_or_else(Utc::now))
+                        .await;
+                    info!("✅ [User {}] Restored open position {} for {}", user_id, position.id, pair_for_position);
+                }
+            }
+            Err(e) => error!("Failed to load open positions for user {}: {}", user_id, e),
+        }
+    });
+    
+    // Restore active protection locks so a restarted bot keeps honoring them
+    let app_state_for_locks = app_state.clone();
+    tokio::spawn(async move {
+        match crate::services::protection_service::get_active_locks(app_state_for_locks.db.as_ref(), user_id).await {
+            Ok(locks) if !locks.is_empty() => {
+                info!("✅ [User {}] Restored {} protection lock(s)", user_id, locks.len());
+                app_state_for_locks.strategy_executor.add_protection_locks(session_id, locks).await;
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to load protection locks for user {}: {}", user_id, e),
+        }
+    });
+    
+    // Feed informative pairs (e.g. a BTC regime filter on altcoins) from their own streams
+    let app_state_for_informative = app_state.clone();
+    let exchange_for_informative = exchange.clone();
+    let pair_for_informative = pair.clone();
+    tokio::spawn(async move {
+        let informative_pairs = app_state_for_informative.strategy_executor.get_informative_pairs(session_id, &pair_for_informative).await;
+        for informative_pair in informative_pairs {
+            let app_state_for_pair = app_state_for_informative.clone();
+            let exchange_for_pair = exchange_for_informative.clone();
+            let traded_pair = pair_for_informative.clone();
+            tokio::spawn(async move {
+                let stream_manager = app_state_for_pair.stream_manager.clone();
+                // Subscribed for the traded pair, so its removal leaves the other pairs' feeds alone
+                let subscriber = Subscriber::Informative { session_id, pair: traded_pair.clone() };
+                let mut receiver = match stream_manager.subscribe(&exchange_for_pair, &informative_pair, subscriber.clone(), None).await {
+                    Ok(receiver) => receiver,
+                    Err(e) => {
+                        error!("Failed to subscribe user {} to informative stream for {}: {}", user_id, informative_pair, e);
+                        return;
+                    }
+                };
+                info!("✅ [User {}] Feeding informative pair {} ({})", user_id, informative_pair, exchange_for_pair);
+                
+                loop {
+                    match receiver.recv().await {
+                        Ok(MarketEvent::Trade(trade)) => {
+                            let still_trading = app_state_for_pair.strategy_executor
+                                .feed_informative_trade(session_id, &traded_pair, &informative_pair, trade.price, trade.timestamp())
+                                .await;
+                            if !still_trading {
+                                break;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("[User {}] Informative stream for {} lagged, skipped {} events", user_id, informative_pair, skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+                
+                stream_manager.unsubscribe(&exchange_for_pair, &informative_pair, &subscriber).await;
+                info!("🛑 [User {}] Stopped feeding informative pair {}", user_id, informative_pair);
+            });
+        }
+    });
+    
     // Subscribe to stream using StreamManager (will reuse existing stream if available)
     let stream_manager_clone = stream_manager.clone();
     tokio::spawn(async move {
-        // Subscribe to stream
-        let mut receiver = match stream_manager_clone.subscribe(&exchange_for_stream, &pair_for_stream, user_id_for_stream).await {
+        use crate::services::snapshot_service;
+        
+        // Resume the strategy from its last checkpoint instead of re-warming it from live candles
+        let mut forming_candle = None;
+        let restored = match snapshot_service::load_snapshot(app_state_for_stream.db.as_ref(), user_id_for_stream, &pair_for_stream).await {
+            Ok(Some((mut snapshot, taken_at))) if snapshot_service::is_resumable(taken_at, &strategy_config_for_stream.timeframe, Utc::now()) => {
+                let snapshot_candle = snapshot.forming_candle.take();
+                match app_state_for_stream.strategy_executor.restore_pair(session_id, &pair_for_stream, snapshot).await {
+                    Ok(()) => {
+                        info!("♻️ [User {}] Resumed {} strategy on {} from snapshot taken at {}", 
+                            user_id_for_stream, strategy_config_for_stream.strategy_type, pair_for_stream, taken_at.format("%Y-%m-%d %H:%M:%S"));
+                        forming_candle = snapshot_candle;
+                        true
+                    }
+                    Err(e) => {
+                        warn!("⚠️ [User {}] Snapshot of {} not restored, strategy re-warms: {}", 
+                            user_id_for_stream, pair_for_stream, e);
+                        false
+                    }
+                }
+            }
+            Ok(Some((_, taken_at))) => {
+                info!("⏭️ [User {}] Snapshot of {} from {} is too old, strategy re-warms", 
+                    user_id_for_stream, pair_for_stream, taken_at.format("%Y-%m-%d %H:%M:%S"));
+                false
+            }
+            Ok(None) => false,
+            Err(e) => {
+                error!("Failed to load strategy snapshot for user {} on {}: {}", user_id_for_stream, pair_for_stream, e);
+                false
+            }
+        };
+        
+        // Backfill the strategy's lookback from historical candles before going live
+        if !restored {
+            if let Err(e) = crate::services::warmup_service::warm_up_pair(
+                &app_state_for_stream.strategy_executor,
+                session_id,
+                &exchange_for_stream,
+                &pair_for_stream,
+                &strategy_config_for_stream.timeframe,
+            ).await {
+                warn!("⚠️ [User {}] Historical warm-up of {} failed, strategy warms up from live candles: {}", 
+                    user_id_for_stream, pair_for_stream, e);
+            }
+        }
+        
+        // Subscribe to the stream and its candles of the strategy's timeframe
+        let timeframe = strategy_config_for_stream.timeframe.clone();
+        let mut receiver = match stream_manager_clone.subscribe(&exchange_for_stream, &pair_for_stream, Subscriber::Session(session_id), Some(&timeframe)).await {
             Ok(receiver) => {
                 let subscriber_count = stream_manager_clone.subscriber_count(&exchange_for_stream, &pair_for_stream).await;
                 info!("✅ User {} subscribed to stream for {} ({}). Total subscribers: {}", 
@@ -868,402 +696,120 @@ pub fn start_user_trading_service(
                 return;
             }
         };
+        // Keep building the candle that was forming before the restart if its period has not ended yet
+        if let Some(candle) = forming_candle {
+            stream_manager_clone.merge_forming_candle(&exchange_for_stream, &pair_for_stream, &timeframe, candle).await;
+        }
+        let mut current_candle: Option<crate::services::strategy_engine::Candle> = None;
+        // Open time of the last closed candle the strategy saw, later candles are only fed once
+        let mut last_closed: Option<i64> = None;
+        // Whether the user was told the stream is down
+        let mut outage_notified = false;
         
         // Process market events from shared stream
         let mut last_heartbeat = std::time::Instant::now();
+        let mut last_snapshot = std::time::Instant::now();
         let mut event_count = 0u64;
+        // Taker buy / sell volume since the last heartbeat
+        let mut flow = (0.0f64, 0.0f64);
         loop {
             match receiver.recv().await {
-                Ok(event) => {
-                    let price = event.price;
-                    let timestamp = event.timestamp;
-                    event_count += 1;
-                    
-                    // Log heartbeat every 2 minutes
-                    if last_heartbeat.elapsed().as_secs() >= 120 {
-                        info!("💓 [User {}] Heartbeat: Service active, received {} market events, strategy: {} on {} ({})", 
-                            user_id_for_stream, event_count, strategy_config_for_stream.strategy_type, 
-                            exchange_for_stream, pair_for_stream);
-                        last_heartbeat = std::time::Instant::now();
-                    }
-                    
-                    // Use timeout to prevent blocking
-                    match tokio::time::timeout(
-                        Duration::from_secs(5),
-                        state_for_stream.write()
-                    ).await {
-                        Ok(mut state_guard) => {
-                            if let Some(candle) = state_guard.process_trade_for_user(
-                                price, 
-                                timestamp, 
-                                &strategy_config_for_stream,
-                                &app_state_for_stream,
-                                user_id_for_stream
-                            ) {
-                                info!("📊 [User {}] Candle completed: O={:.4} H={:.4} L={:.4} C={:.4} ({} timeframe)", 
-                                    user_id_for_stream, candle.open, candle.high, candle.low, candle.close,
-                                    strategy_config_for_stream.timeframe);
-                                
-                                // Process candle through user's strategy
-                                info!("🔍 [User {}] Evaluating strategy '{}' on candle...", 
-                                    user_id_for_stream, strategy_config_for_stream.strategy_type);
-                                
-                                // Check if user is still trading before processing
-                                let is_trading = app_state_for_stream.strategy_executor.is_user_trading(user_id_for_stream).await;
-                                if !is_trading {
-                                    warn!("⚠️ [User {}] User is not trading anymore, skipping candle processing", user_id_for_stream);
-                                    continue;
-                                }
-                                
-                                if let Some(signal) = app_state_for_stream.strategy_executor
-                                    .process_candle(user_id_for_stream, &candle).await 
-                                {
-                                    match &signal {
-                                        crate::services::strategy_engine::StrategySignal::Buy { price, confidence, reason } => {
-                                            info!("✅ [User {}] Strategy '{}' generated BUY signal: price={:.4}, confidence={:.2}, reason={}", 
-                                                user_id_for_stream, strategy_config_for_stream.strategy_type, 
-                                                price, confidence, reason);
-                                        }
-                                        crate::services::strategy_engine::StrategySignal::Sell { price, confidence, reason } => {
-                                            info!("✅ [User {}] Strategy '{}' generated SELL signal: price={:.4}, confidence={:.2}, reason={}", 
-                                                user_id_for_stream, strategy_config_for_stream.strategy_type, 
-                                                price, confidence, reason);
-                                        }
-                                        crate::services::strategy_engine::StrategySignal::Hold => {
-                                            info!("✅ [User {}] Strategy '{}' generated HOLD signal", 
-                                                user_id_for_stream, strategy_config_for_stream.strategy_type);
-                                        }
-                                    }
-                                    // Clone signal for database save
-                                    let signal_clone = signal.clone();
-                                    
-                                    // Save order to database (non-blocking)
-                                    let app_state_for_db = app_state_for_stream.clone();
-                                    let user_id_for_db = user_id_for_stream;
-                                    let exchange_for_db = exchange_for_stream.clone();
-                                    let pair_for_db = pair_for_stream.clone();
-                                    let strategy_config_for_db = strategy_config_for_stream.clone();
-                                    
-                                    // Clone candle timestamp for save
-                                    let candle_timestamp_for_save = DateTime::from_timestamp(candle.timestamp, 0).unwrap_or_else(|| Utc::now());
-                                    
-                                    // Check if we should send this signal based on existing positions
-                                    let should_send_signal = match &signal {
-                                        crate::services::strategy_engine::StrategySignal::Buy { .. } => {
-                                            // Only send BUY if user doesn't have an open position for this pair
-                                            match crate::services::position_service::has_open_position_for_pair(
-                                                app_state_for_stream.db.as_ref(),
-                                                user_id_for_stream,
-                                                &pair_for_stream,
-                                            ).await {
-                                                Ok(false) => {
-                                                    info!("✅ [User {}] No open position for {}, sending BUY signal", user_id_for_stream, pair_for_stream);
-                                                    true
-                                                }
-                                                Ok(true) => {
-                                                    info!("⏭️ [User {}] Already has open position for {}, skipping BUY signal", user_id_for_stream, pair_for_stream);
-                                                    false
-                                                }
-                                                Err(e) => {
-                                                    error!("❌ [User {}] Failed to check open position for {}: {}, will send signal anyway", user_id_for_stream, pair_for_stream, e);
-                                                    true // Send anyway if check fails
-                                                }
-                                            }
-                                        }
-                                        crate::services::strategy_engine::StrategySignal::Sell { .. } => {
-                                            // Only send SELL if user has an open position for this pair
-                                            match crate::services::position_service::has_open_position_for_pair(
-                                                app_state_for_stream.db.as_ref(),
-                                                user_id_for_stream,
-                                                &pair_for_stream,
-                                            ).await {
-                                                Ok(true) => {
-                                                    info!("✅ [User {}] Has open position for {}, sending SELL signal", user_id_for_stream, pair_for_stream);
-                                                    true
-                                                }
-                                                Ok(false) => {
-                                                    info!("⏭️ [User {}] No open position for {}, skipping SELL signal", user_id_for_stream, pair_for_stream);
-                                                    false
-                                                }
-                                                Err(e) => {
-                                                    error!("❌ [User {}] Failed to check open position for {}: {}, will send signal anyway", user_id_for_stream, pair_for_stream, e);
-                                                    true // Send anyway if check fails
-                                                }
-                                            }
-                                        }
-                                        crate::services::strategy_engine::StrategySignal::Hold => {
-                                            false // Never send Hold signals
-                                        }
-                                    };
-                                    
-                                    if should_send_signal {
-                                        // Spawn task to save signal (non-blocking)
-                                        let app_state_for_db = app_state_for_stream.clone();
-                                        let user_id_for_db = user_id_for_stream;
-                                        let exchange_for_db = exchange_for_stream.clone();
-                                        let pair_for_db = pair_for_stream.clone();
-                                        let strategy_config_for_db = strategy_config_for_stream.clone();
-                                        let signal_clone_for_db = signal.clone();
-                                        
-                                        tokio::spawn(async move {
-                                            if let Err(e) = save_trading_order(
-                                                &app_state_for_db,
-                                                user_id_for_db,
-                                                &exchange_for_db,
-                                                &pair_for_db,
-                                                &strategy_config_for_db,
-                                                &signal_clone_for_db,
-                                                Some(candle_timestamp_for_save),
-                                                None, // Indicator values - TODO: extract from strategy state
-                                            ).await {
-                                                error!("Failed to save trading signal for user {}: {}", user_id_for_db, e);
-                                            }
-                                        });
-                                        
-                                        // Format and send signal to user
-                                        let message = format_user_signal_message(
-                                            &signal, 
-                                            &pair_for_stream, 
-                                            &bot_name_for_stream,
-                                            &strategy_config_for_stream
-                                        );
-                                        
-                                        if message.is_empty() {
-                                            warn!("⚠️ [User {}] Message is empty for signal {:?}, skipping send", user_id_for_stream, signal);
-                                        } else {
-                                            info!("📤 [User {}] Sending signal message to chat {} (length: {} chars)", 
-                                                user_id_for_stream, user_chat_id_for_stream, message.len());
-                                            
-                                            if let Err(e) = bot_for_stream.send_message(
-                                                ChatId(user_chat_id_for_stream),
-                                                &message
-                                            )
-                                            .parse_mode(teloxide::types::ParseMode::Html)
-                                            .await {
-                                                error!("❌ [User {}] Failed to send trading signal to user {} (chat: {}): {}", 
-                                                    user_id_for_stream, user_id_for_stream, user_chat_id_for_stream, e);
-                                            } else {
-                                                info!("✅ [User {}] Trading signal sent successfully to user (chat: {})", 
-                                                    user_id_for_stream, user_chat_id_for_stream);
-                                            }
-                                        }
-                                    } else {
-                                        // Still save the signal to database for tracking, but don't send message
-                                        let app_state_for_db = app_state_for_stream.clone();
-                                        let user_id_for_db = user_id_for_stream;
-                                        let exchange_for_db = exchange_for_stream.clone();
-                                        let pair_for_db = pair_for_stream.clone();
-                                        let strategy_config_for_db = strategy_config_for_stream.clone();
-                                        let signal_clone_for_db = signal.clone();
-                                        
-                                        tokio::spawn(async move {
-                                            if let Err(e) = save_trading_order(
-                                                &app_state_for_db,
-                                                user_id_for_db,
-                                                &exchange_for_db,
-                                                &pair_for_db,
-                                                &strategy_config_for_db,
-                                                &signal_clone_for_db,
-                                                Some(candle_timestamp_for_save),
-                                                None,
-                                            ).await {
-                                                error!("Failed to save trading signal for user {}: {}", user_id_for_db, e);
-                                            }
-                                        });
-                                    }
-                                } else {
-                                    debug!("🔍 [User {}] Strategy '{}' evaluated: No signal generated (hold or None)", 
-                                        user_id_for_stream, strategy_config_for_stream.strategy_type);
-                                    
-                                    // For RSI strategy, log why no signal was generated
-                                    if strategy_config_for_stream.strategy_type.to_uppercase() == "RSI" {
-                                        if let Some(state_info) = app_state_for_stream.strategy_executor.get_user_state_info(user_id_for_stream).await {
-                                            debug!("📊 [User {}] RSI Strategy state: {}", user_id_for_stream, state_info);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        Err(_) => {
-                            warn!("⏱️ [User {}] Timeout acquiring lock for trading state", user_id_for_stream);
-                        }
-                    }
-                }
-                Err(broadcast::error::RecvError::Closed) => {
-                    warn!("Stream closed for user {}, unsubscribing...", user_id_for_stream);
-                    break;
-                }
-                Err(broadcast::error::RecvError::Lagged(skipped)) => {
-                    warn!("User {} lagged behind stream, skipped {} events", user_id_for_stream, skipped);
-                    // Continue processing
-                }
-            }
-        }
-        
-        // Unsubscribe when loop ends
-        stream_manager_clone.unsubscribe(&exchange_for_stream, &pair_for_stream, user_id_for_stream).await;
-        info!("User {} unsubscribed from stream for {}", user_id_for_stream, pair_for_stream);
-    });
-    
-    info!("✅ Trading service task spawned for user {}", user_id);
-
-    // Start timer to force process candle based on strategy timeframe
-    let state_timer = state.clone();
-    let bot_timer = bot.clone();
-    let pair_timer = pair.clone();
-    let bot_name_timer = bot_name.clone();
-    let strategy_config_timer = strategy_config.clone();
-    let user_id_timer = user_id;
-    let user_chat_id_timer = user_chat_id;
-    let app_state_timer = app_state.clone();
-    let exchange_timer = exchange.clone();
-    
-    // Parse timeframe to seconds (e.g., "1m" -> 60, "5m" -> 300, "1h" -> 3600)
-    let timeframe_secs = parse_timeframe_to_seconds(&strategy_config.timeframe);
-    
-    // Spawn task for timer-based processing
-    tokio::spawn(async move {
-        let mut timer = interval(Duration::from_secs(timeframe_secs));
-        timer.tick().await; // Skip first tick
-        info!("⏰ [User {}] Timer started: will process candles every {} seconds ({} timeframe)", 
-            user_id_timer, timeframe_secs, strategy_config_timer.timeframe);
-        
-        let mut timer_count = 0u64;
-        let mut last_heartbeat_timer = std::time::Instant::now();
-        let mut last_minute_log = std::time::Instant::now();
-        
-        loop {
-            timer.tick().await;
-            timer_count += 1;
-            
-            // Log heartbeat every 2 minutes
-            if last_heartbeat_timer.elapsed().as_secs() >= 120 {
-                info!("💓 [User {}] Timer heartbeat: Service active, processed {} timer ticks, strategy: {} on {} ({})", 
-                    user_id_timer, timer_count, strategy_config_timer.strategy_type, 
-                    exchange_timer, pair_timer);
-                last_heartbeat_timer = std::time::Instant::now();
-            }
-            
-            // For RSI strategy, log detailed stats every 1 minute
-            if strategy_config_timer.strategy_type.to_uppercase() == "RSI" && last_minute_log.elapsed().as_secs() >= 60 {
-                // Get strategy state info for logging
-                if let Some(state_info) = app_state_timer.strategy_executor.get_user_state_info(user_id_timer).await {
-                    info!("📊 [User {}] RSI Strategy Status (1-min heartbeat): {}", user_id_timer, state_info);
-                } else {
-                    warn!("⚠️ [User {}] Could not get RSI strategy state info for 1-min log", user_id_timer);
-                }
-                
-                // Also log current candle state if available
-                let state_read = state_timer.read().await;
-                if let Some(ref candle) = state_read.current_candle {
-                    info!("📊 [User {}] RSI Strategy - Current Candle: O={:.4} H={:.4} L={:.4} C={:.4} ({} timeframe)", 
-                        user_id_timer, candle.open, candle.high, candle.low, candle.close,
-                        strategy_config_timer.timeframe);
-                } else {
-                    info!("📊 [User {}] RSI Strategy - No active candle yet (waiting for market data)", user_id_timer);
-                }
-                drop(state_read);
-                
-                last_minute_log = std::time::Instant::now();
-            }
-            
-            let mut state_guard = state_timer.write().await;
-            
-            if state_guard.current_candle.is_some() {
-                info!("⏰ [User {}] Timer tick #{}: Processing candle...", user_id_timer, timer_count);
-                
-                if let Some(candle) = state_guard.force_process_candle_for_user(
-                    &strategy_config_timer,
-                    &app_state_timer,
-                    user_id_timer
-                ) {
-                    info!("📊 [User {}] Timer: Candle completed: O={:.4} H={:.4} L={:.4} C={:.4} ({} timeframe)", 
-                        user_id_timer, candle.open, candle.high, candle.low, candle.close,
-                        strategy_config_timer.timeframe);
+                Ok(MarketEvent::Candle { timeframe: candle_timeframe, candle, closed }) => {
+                    if candle_timeframe != timeframe {
+                        continue;
+                    }
+                    if !closed {
+                        current_candle = Some(candle);
+                        continue;
+                    }
+                    current_candle = None;
+                    if last_closed.is_some_and(|ts| candle.timestamp <= ts) {
+                        continue;
+                    }
+                    last_closed = Some(candle.timestamp);
+                    
+                    info!("📊 [User {}] Candle completed: O={:.4} H={:.4} L={:.4} C={:.4} V={:.4} ({} timeframe)", 
+                        user_id_for_stream, candle.open, candle.high, candle.low, candle.close, candle.volume,
+                        strategy_config_for_stream.timeframe);
                     
                     // Process candle through user's strategy
-                    info!("🔍 [User {}] Timer: Evaluating strategy '{}' on candle...", 
-                        user_id_timer, strategy_config_timer.strategy_type);
+                    info!("🔍 [User {}] Evaluating strategy '{}' on candle...", 
+                        user_id_for_stream, strategy_config_for_stream.strategy_type);
                     
                     // Check if user is still trading before processing
-                    let is_trading = app_state_timer.strategy_executor.is_user_trading(user_id_timer).await;
+                    let is_trading = app_state_for_stream.strategy_executor.is_pair_trading(session_id, &pair_for_stream).await;
                     if !is_trading {
-                        warn!("⚠️ [User {}] User is not trading anymore, skipping candle processing", user_id_timer);
-                        continue;
+                        warn!("⚠️ [User {}] {} is not traded anymore, stopping candle processing", user_id_for_stream, pair_for_stream);
+                        break;
                     }
                     
-                    if let Some(signal) = app_state_timer.strategy_executor
-                        .process_candle(user_id_timer, &candle).await 
+                    if let Some(signal) = app_state_for_stream.strategy_executor
+                        .process_candle(session_id, &pair_for_stream, &candle).await 
                     {
                         match &signal {
                             crate::services::strategy_engine::StrategySignal::Buy { price, confidence, reason } => {
-                                info!("✅ [User {}] Timer: Strategy '{}' generated BUY signal: price={:.4}, confidence={:.2}, reason={}", 
-                                    user_id_timer, strategy_config_timer.strategy_type, 
+                                info!("✅ [User {}] Strategy '{}' generated BUY signal: price={:.4}, confidence={:.2}, reason={}", 
+                                    user_id_for_stream, strategy_config_for_stream.strategy_type, 
                                     price, confidence, reason);
                             }
-                            crate::services::strategy_engine::StrategySignal::Sell { price, confidence, reason } => {
-                                info!("✅ [User {}] Timer: Strategy '{}' generated SELL signal: price={:.4}, confidence={:.2}, reason={}", 
-                                    user_id_timer, strategy_config_timer.strategy_type, 
+                            crate::services::strategy_engine::StrategySignal::Sell { price, confidence, reason, .. } => {
+                                info!("✅ [User {}] Strategy '{}' generated SELL signal: price={:.4}, confidence={:.2}, reason={}", 
+                                    user_id_for_stream, strategy_config_for_stream.strategy_type, 
                                     price, confidence, reason);
                             }
+                            crate::services::strategy_engine::StrategySignal::AdjustPosition { stake, price, reason } => {
+                                info!("✅ [User {}] Strategy '{}' generated ADJUST signal: stake={:.4}, price={:.4}, reason={}", 
+                                    user_id_for_stream, strategy_config_for_stream.strategy_type, 
+                                    stake, price, reason);
+                            }
                             crate::services::strategy_engine::StrategySignal::Hold => {
-                                info!("✅ [User {}] Timer: Strategy '{}' generated HOLD signal", 
-                                    user_id_timer, strategy_config_timer.strategy_type);
+                                info!("✅ [User {}] Strategy '{}' generated HOLD signal", 
+                                    user_id_for_stream, strategy_config_for_stream.strategy_type);
                             }
                         }
-                        // Clone signal for database save
-                        let signal_clone = signal.clone();
-                        
-                        // Save order to database
-                        let app_state_for_db = app_state_timer.clone();
-                        let user_id_for_db = user_id_timer;
-                        let exchange_for_db = exchange_timer.clone();
-                        let pair_for_db = pair_timer.clone();
-                        let strategy_config_for_db = strategy_config_timer.clone();
-                        
                         // Clone candle timestamp for save
                         let candle_timestamp_for_save = DateTime::from_timestamp(candle.timestamp, 0).unwrap_or_else(|| Utc::now());
                         
                         // Check if we should send this signal based on existing positions
-                        let should_send_signal_timer = match &signal {
+                        let should_send_signal = match &signal {
                             crate::services::strategy_engine::StrategySignal::Buy { .. } => {
                                 // Only send BUY if user doesn't have an open position for this pair
                                 match crate::services::position_service::has_open_position_for_pair(
-                                    app_state_timer.db.as_ref(),
-                                    user_id_timer,
-                                    &pair_timer,
+                                    app_state_for_stream.db.as_ref(),
+                                    user_id_for_stream,
+                                    &pair_for_stream,
                                 ).await {
                                     Ok(false) => {
-                                        info!("✅ [User {}] Timer: No open position for {}, sending BUY signal", user_id_timer, pair_timer);
+                                        info!("✅ [User {}] No open position for {}, sending BUY signal", user_id_for_stream, pair_for_stream);
                                         true
                                     }
                                     Ok(true) => {
-                                        info!("⏭️ [User {}] Timer: Already has open position for {}, skipping BUY signal", user_id_timer, pair_timer);
+                                        info!("⏭️ [User {}] Already has open position for {}, skipping BUY signal", user_id_for_stream, pair_for_stream);
                                         false
                                     }
                                     Err(e) => {
-                                        error!("❌ [User {}] Timer: Failed to check open position for {}: {}, will send signal anyway", user_id_timer, pair_timer, e);
+                                        error!("❌ [User {}] Failed to check open position for {}: {}, will send signal anyway", user_id_for_stream, pair_for_stream, e);
                                         true // Send anyway if check fails
                                     }
                                 }
                             }
-                            crate::services::strategy_engine::StrategySignal::Sell { .. } => {
-                                // Only send SELL if user has an open position for this pair
+                            crate::services::strategy_engine::StrategySignal::Sell { .. } | crate::services::strategy_engine::StrategySignal::AdjustPosition { .. } => {
+                                // Only send SELL / ADJUST if user has an open position for this pair
                                 match crate::services::position_service::has_open_position_for_pair(
-                                    app_state_timer.db.as_ref(),
-                                    user_id_timer,
-                                    &pair_timer,
+                                    app_state_for_stream.db.as_ref(),
+                                    user_id_for_stream,
+                                    &pair_for_stream,
                                 ).await {
                                     Ok(true) => {
-                                        info!("✅ [User {}] Timer: Has open position for {}, sending SELL signal", user_id_timer, pair_timer);
+                                        info!("✅ [User {}] Has open position for {}, sending SELL signal", user_id_for_stream, pair_for_stream);
                                         true
                                     }
                                     Ok(false) => {
-                                        info!("⏭️ [User {}] Timer: No open position for {}, skipping SELL signal", user_id_timer, pair_timer);
+                                        info!("⏭️ [User {}] No open position for {}, skipping SELL signal", user_id_for_stream, pair_for_stream);
                                         false
                                     }
                                     Err(e) => {
-                                        error!("❌ [User {}] Timer: Failed to check open position for {}: {}, will send signal anyway", user_id_timer, pair_timer, e);
+                                        error!("❌ [User {}] Failed to check open position for {}: {}, will send signal anyway", user_id_for_stream, pair_for_stream, e);
                                         true // Send anyway if check fails
                                     }
                                 }
@@ -1273,95 +819,323 @@ pub fn start_user_trading_service(
                             }
                         };
                         
-                        if should_send_signal_timer {
-                            // Spawn task to save signal (non-blocking)
-                            let app_state_for_db_timer = app_state_timer.clone();
-                            let user_id_for_db_timer = user_id_timer;
-                            let exchange_for_db_timer = exchange_timer.clone();
-                            let pair_for_db_timer = pair_timer.clone();
-                            let strategy_config_for_db_timer = strategy_config_timer.clone();
-                            let signal_clone_for_db_timer = signal.clone();
+                        if should_send_signal {
+                            // Snapshot of the indicators and conditions behind the signal, shown by its "why" button
+                            let explanation = app_state_for_stream.strategy_executor
+                                .explain_signal(session_id, &pair_for_stream, &candle).await
+                                .and_then(|explanation| serde_json::to_value(explanation).ok());
                             
-                            tokio::spawn(async move {
-                                if let Err(e) = save_trading_order(
-                                    &app_state_for_db_timer,
-                                    user_id_for_db_timer,
-                                    &exchange_for_db_timer,
-                                    &pair_for_db_timer,
-                                    &strategy_config_for_db_timer,
-                                    &signal_clone_for_db_timer,
-                                    Some(candle_timestamp_for_save),
-                                    None, // Indicator values - TODO: extract from strategy state
-                                ).await {
-                                    error!("Failed to save trading signal for user {}: {}", user_id_for_db_timer, e);
-                                }
-                            });
+                            // Spawn task to save signal, then send it with the saved signal's id (non-blocking)
+                            let app_state_for_db = app_state_for_stream.clone();
+                            let user_id_for_db = user_id_for_stream;
+                            let exchange_for_db = exchange_for_stream.clone();
+                            let pair_for_db = pair_for_stream.clone();
+                            let strategy_config_for_db = strategy_config_for_stream.clone();
+                            let signal_clone_for_db = signal.clone();
+                            let bot_for_signal = bot_for_stream.clone();
                             
+                            // Format signal message for user
                             let message = format_user_signal_message(
                                 &signal, 
-                                &pair_timer, 
-                                &bot_name_timer,
-                                &strategy_config_timer
+                                session_id,
+                                &pair_for_stream, 
+                                &bot_name_for_stream,
+                                &strategy_config_for_stream
                             );
                             
-                            if message.is_empty() {
-                                warn!("⚠️ [User {}] Timer: Message is empty for signal {:?}, skipping send", user_id_timer, signal);
-                            } else {
-                                info!("📤 [User {}] Timer: Sending signal message to chat {} (length: {} chars)", 
-                                    user_id_timer, user_chat_id_timer, message.len());
+                            tokio::spawn(async move {
+                                let signal_id = match save_trading_order(
+                                    &app_state_for_db,
+                                    session_id,
+                                    user_id_for_db,
+                                    &exchange_for_db,
+                                    &pair_for_db,
+                                    &strategy_config_for_db,
+                                    &signal_clone_for_db,
+                                    Some(candle_timestamp_for_save),
+                                    explanation,
+                                ).await {
+                                    Ok(signal_id) => signal_id,
+                                    Err(e) => {
+                                        error!("Failed to save trading signal for user {}: {}", user_id_for_db, e);
+                                        None
+                                    }
+                                };
+                                
+                                if message.is_empty() {
+                                    warn!("⚠️ [User {}] Message is empty for signal {:?}, skipping send", user_id_for_db, signal_clone_for_db);
+                                    return;
+                                }
+                                info!("📤 [User {}] Sending signal message to chat {} (length: {} chars)", 
+                                    user_id_for_db, user_chat_id_for_stream, message.len());
                                 
-                                if let Err(e) = bot_timer.send_message(
-                                    ChatId(user_chat_id_timer),
+                                let mut request = bot_for_signal.send_message(
+                                    ChatId(user_chat_id_for_stream),
                                     &message
                                 )
-                                .parse_mode(teloxide::types::ParseMode::Html)
-                                .await {
-                                    error!("❌ [User {}] Timer: Failed to send trading signal to user {} (chat: {}): {}", 
-                                        user_id_timer, user_id_timer, user_chat_id_timer, e);
+                                .parse_mode(teloxide::types::ParseMode::Html);
+                                if let Some(signal_id) = signal_id {
+                                    let locale = users::Entity::find_by_id(user_id_for_db)
+                                        .one(app_state_for_db.db.as_ref())
+                                        .await
+                                        .ok()
+                                        .flatten()
+                                        .and_then(|u| u.language)
+                                        .unwrap_or_default();
+                                    let button = InlineKeyboardButton::callback(
+                                        crate::i18n::get_button_text(crate::i18n::get_user_language(Some(&locale)), "signal_why"),
+                                        format!("why_signal_{}", signal_id),
+                                    );
+                                    request = request.reply_markup(InlineKeyboardMarkup::new(vec![vec![button]]));
+                                }
+                                
+                                if let Err(e) = request.await {
+                                    error!("❌ [User {}] Failed to send trading signal to user {} (chat: {}): {}", 
+                                        user_id_for_db, user_id_for_db, user_chat_id_for_stream, e);
                                 } else {
-                                    info!("✅ [User {}] Timer: Trading signal sent successfully to user", user_id_timer);
+                                    info!("✅ [User {}] Trading signal sent successfully to user (chat: {})", 
+                                        user_id_for_db, user_chat_id_for_stream);
                                 }
-                            }
+                            });
                         } else {
                             // Still save the signal to database for tracking, but don't send message
-                            let app_state_for_db_timer = app_state_timer.clone();
-                            let user_id_for_db_timer = user_id_timer;
-                            let exchange_for_db_timer = exchange_timer.clone();
-                            let pair_for_db_timer = pair_timer.clone();
-                            let strategy_config_for_db_timer = strategy_config_timer.clone();
-                            let signal_clone_for_db_timer = signal.clone();
+                            let app_state_for_db = app_state_for_stream.clone();
+                            let user_id_for_db = user_id_for_stream;
+                            let exchange_for_db = exchange_for_stream.clone();
+                            let pair_for_db = pair_for_stream.clone();
+                            let strategy_config_for_db = strategy_config_for_stream.clone();
+                            let signal_clone_for_db = signal.clone();
                             
                             tokio::spawn(async move {
                                 if let Err(e) = save_trading_order(
-                                    &app_state_for_db_timer,
-                                    user_id_for_db_timer,
-                                    &exchange_for_db_timer,
-                                    &pair_for_db_timer,
-                                    &strategy_config_for_db_timer,
-                                    &signal_clone_for_db_timer,
+                                    &app_state_for_db,
+                                    session_id,
+                                    user_id_for_db,
+                                    &exchange_for_db,
+                                    &pair_for_db,
+                                    &strategy_config_for_db,
+                                    &signal_clone_for_db,
                                     Some(candle_timestamp_for_save),
-                                    None,
+                                    None, // No message, so no "why" button to explain it
+                                ).await {
+                                    error!("Failed to save trading signal for user {}: {}", user_id_for_db, e);
+                                }
+                            });
+                        }
+                    } else {
+                        debug!("🔍 [User {}] Strategy '{}' evaluated: No signal generated (hold or None)", 
+                            user_id_for_stream, strategy_config_for_stream.strategy_type);
+                        
+                        // For RSI strategy, log why no signal was generated
+                        if strategy_config_for_stream.strategy_type.to_uppercase() == "RSI" {
+                            if let Some(state_info) = app_state_for_stream.strategy_executor.get_session_state_info(session_id).await {
+                                debug!("📊 [User {}] RSI Strategy state: {}", user_id_for_stream, state_info);
+                            }
+                        }
+                    }
+                }
+                Ok(MarketEvent::Trade(trade)) => {
+                    let price = trade.price;
+                    let timestamp = trade.timestamp();
+                    event_count += 1;
+                    match trade.side {
+                        freqtrade_rs::exchange::OrderSide::Buy => flow.0 += trade.quantity,
+                        freqtrade_rs::exchange::OrderSide::Sell => flow.1 += trade.quantity,
+                    }
+                    
+                    // Log heartbeat every 2 minutes
+                    if last_heartbeat.elapsed().as_secs() >= 120 {
+                        info!("💓 [User {}] Heartbeat: Service active, received {} market events (taker buy {:.4} / sell {:.4}), strategy: {} on {} ({})", 
+                            user_id_for_stream, event_count, flow.0, flow.1, strategy_config_for_stream.strategy_type, 
+                            exchange_for_stream, pair_for_stream);
+                        last_heartbeat = std::time::Instant::now();
+                        flow = (0.0, 0.0);
+                    }
+                    
+                    // Checkpoint the strategy so a restarted bot resumes it
+                    if last_snapshot.elapsed().as_secs() >= snapshot_service::SNAPSHOT_INTERVAL_SECS {
+                        last_snapshot = std::time::Instant::now();
+                        if let Some(mut snapshot) = app_state_for_stream.strategy_executor
+                            .snapshot_pair(session_id, &pair_for_stream).await
+                        {
+                            snapshot.forming_candle = current_candle.clone();
+                            let app_state_for_snapshot = app_state_for_stream.clone();
+                            let pair_for_snapshot = pair_for_stream.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = snapshot_service::save_snapshot(
+                                    app_state_for_snapshot.db.as_ref(),
+                                    user_id_for_stream,
+                                    &pair_for_snapshot,
+                                    &snapshot,
                                 ).await {
-                                    error!("Failed to save trading signal for user {}: {}", user_id_for_db_timer, e);
+                                    error!("Failed to save strategy snapshot for user {} on {}: {}", user_id_for_stream, pair_for_snapshot, e);
                                 }
                             });
                         }
                     }
-                } else {
-                    info!("⏰ [User {}] Timer tick #{}: No active candle to process", user_id_timer, timer_count);
+                    
+                    // Fill the strategy's resting limit orders this trade reaches
+                    let fill_time = DateTime::from_timestamp(timestamp, 0).unwrap_or_else(Utc::now);
+                    let fills = app_state_for_stream.strategy_executor
+                        .take_limit_fills(session_id, &pair_for_stream, price, fill_time).await;
+                    for order in fills {
+                        match apply_limit_fill(
+                            &app_state_for_stream,
+                            session_id,
+                            user_id_for_stream,
+                            &exchange_for_stream,
+                            &pair_for_stream,
+                            &strategy_config_for_stream,
+                            &order,
+                            price,
+                            fill_time,
+                        ).await {
+                            Ok(Some((filled, fill_price))) => {
+                                app_state_for_stream.strategy_executor
+                                    .on_limit_order_filled(session_id, &pair_for_stream, &filled, fill_price, fill_time)
+                                    .await;
+                                let side = match order.side {
+                                    freqtrade_rs::exchange::OrderSide::Buy => "BUY",
+                                    freqtrade_rs::exchange::OrderSide::Sell => "SELL",
+                                };
+                                let message = format!(
+                                    "📌 <b>Limit {} filled</b>\n\n💱 Pair: <b>{}</b>\n💰 Price: <b>{:.4}</b>\n📦 Quantity: <b>{:.8}</b>\n🏷 Tag: {}",
+                                    side,
+                                    escape_html(&pair_for_stream),
+                                    fill_price,
+                                    filled.quantity,
+                                    escape_html(filled.tag.as_deref().unwrap_or("-")),
+                                );
+                                if let Err(e) = bot_for_stream.send_message(ChatId(user_chat_id_for_stream), &message)
+                                    .parse_mode(teloxide::types::ParseMode::Html)
+                                    .await
+                                {
+                                    error!("❌ [User {}] Failed to send limit fill message: {}", user_id_for_stream, e);
+                                }
+                            }
+                            Ok(None) => {}
+                            Err(e) => error!("❌ [User {}] Failed to fill limit order on {}: {}", user_id_for_stream, pair_for_stream, e),
+                        }
+                    }
+                    
+                }
+                Ok(MarketEvent::Backfill { timeframe: candle_timeframe, candles }) => {
+                    if candle_timeframe != timeframe {
+                        continue;
+                    }
+                    let fed = feed_missed_candles(&app_state_for_stream.strategy_executor, session_id, &pair_for_stream, &mut last_closed, candles).await;
+                    if fed > 0 {
+                        info!("📥 [User {}] Fed {} candles of {} missed during the outage", user_id_for_stream, fed, pair_for_stream);
+                    }
+                }
+                Ok(MarketEvent::Disconnected { reason }) => {
+                    warn!("⚠️ [User {}] Stream for {} is down: {}", user_id_for_stream, pair_for_stream, reason);
+                    if !outage_notified {
+                        outage_notified = true;
+                        let message = format!(
+                            "⚠️ <b>Market data interrupted</b>
+
+💱 Pair: <b>{}</b>
+
+Reconnecting... Candles missed meanwhile are backfilled, no signals are sent for them.",
+                            escape_html(&pair_for_stream),
+                        );
+                        if let Err(e) = bot_for_stream.send_message(ChatId(user_chat_id_for_stream), &message)
+                            .parse_mode(teloxide::types::ParseMode::Html)
+                            .await
+                        {
+                            error!("❌ [User {}] Failed to send stream outage message: {}", user_id_for_stream, e);
+                        }
+                    }
+                }
+                Ok(MarketEvent::Reconnected) => {
+                    info!("✅ [User {}] Stream for {} reconnected", user_id_for_stream, pair_for_stream);
+                    if std::mem::take(&mut outage_notified) {
+                        let message = format!(
+                            "✅ <b>Market data restored</b>
+
+💱 Pair: <b>{}</b>",
+                            escape_html(&pair_for_stream),
+                        );
+                        if let Err(e) = bot_for_stream.send_message(ChatId(user_chat_id_for_stream), &message)
+                            .parse_mode(teloxide::types::ParseMode::Html)
+                            .await
+                        {
+                            error!("❌ [User {}] Failed to send stream restored message: {}", user_id_for_stream, e);
+                        }
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    warn!("Stream closed for user {}, unsubscribing...", user_id_for_stream);
+                    break;
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("User {} lagged behind stream, skipped {} events", user_id_for_stream, skipped);
+                    // Skipped events may include closed candles, catch up on them from REST
+                    let caught_up = catch_up_after_lag(&app_state_for_stream.strategy_executor, session_id, &pair_for_stream, &mut last_closed, |since| {
+                        crate::services::warmup_service::fetch_missed_candles(&exchange_for_stream, &pair_for_stream, &timeframe, since)
+                    }).await;
+                    if let Err(e) = caught_up {
+                        warn!("⚠️ [User {}] Failed to catch up on {} after lagging: {}", user_id_for_stream, pair_for_stream, e);
+                    }
                 }
-            } else {
-                debug!("⏰ [User {}] Timer tick #{}: No candle initialized yet (waiting for market data)", user_id_timer, timer_count);
             }
         }
+        
+        // Unsubscribe when loop ends
+        stream_manager_clone.unsubscribe(&exchange_for_stream, &pair_for_stream, &Subscriber::Session(session_id)).await;
+        info!("User {} unsubscribed from stream for {}", user_id_for_stream, pair_for_stream);
     });
+    
+    info!("✅ Trading service task spawned for user {}", user_id);
 
     info!("✅ User Trading Service started successfully for user {}", user_id);
 }
 
+/// Feed closed candles a session missed (during an outage or while lagging) to its strategy
+/// Candles up to `last_closed` were processed already and are skipped. The others only
+/// update the indicators, their signals are stale. Returns the number of candles fed.
+async fn feed_missed_candles(
+    executor: &StrategyExecutor,
+    session_id: u64,
+    pair: &str,
+    last_closed: &mut Option<i64>,
+    candles: Vec<Candle>,
+) -> usize {
+    let missed: Vec<_> = candles.into_iter()
+        .filter(|c| last_closed.is_none_or(|ts| c.timestamp > ts))
+        .collect();
+    let Some(last) = missed.last() else {
+        return 0;
+    };
+    *last_closed = Some(last.timestamp);
+    executor.warm_up(session_id, pair, &missed).await
+}
+
+/// Catch up on the closed candles a session lagging behind its stream skipped
+/// `fetch` downloads the closed candles opened at or after its argument. Nothing is
+/// fetched before the session saw its first closed candle. Returns the number of candles fed.
+async fn catch_up_after_lag<F, Fut>(
+    executor: &StrategyExecutor,
+    session_id: u64,
+    pair: &str,
+    last_closed: &mut Option<i64>,
+    fetch: F,
+) -> Result<usize, anyhow::Error>
+where
+    F: FnOnce(i64) -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<Candle>, anyhow::Error>>,
+{
+    let Some(ts) = *last_closed else {
+        return Ok(0);
+    };
+    let missed = fetch(ts + 1).await?;
+    Ok(feed_missed_candles(executor, session_id, pair, last_closed, missed).await)
+}
+
 /// Helper function to escape HTML characters for Telegram messages
 /// Must escape & first to avoid double-escaping!
-fn escape_html(text: &str) -> String {
+pub fn escape_html(text: &str) -> String {
     text.replace("&", "&amp;")
         .replace("<", "&lt;")
         .replace(">", "&gt;")
@@ -1372,6 +1146,7 @@ fn escape_html(text: &str) -> String {
 /// Format user-specific trading signal message
 fn format_user_signal_message(
     signal: &crate::services::strategy_engine::StrategySignal,
+    session_id: u64,
     pair: &str,
     bot_name: &str,
     strategy_config: &crate::services::strategy_engine::StrategyConfig,
@@ -1395,15 +1170,16 @@ fn format_user_signal_message(
 ⏰ <b>Time:</b> <code>{}</code>\n\
 📈 <b>Strategy:</b> {}\n\
 📍 <b>Timeframe:</b> {}\n\
-📍 <b>Pair:</b> {}\n\n\
+📍 <b>Pair:</b> {}\n\
+🧩 <b>Session:</b> #{}\n\n\
 🤖 <b>Bot:</b> {}\n\
 🔄 <b>Status:</b> <code>Live Trading Active</code>\n\n\
 ⚠️ <i>This is a live trading signal. Always do your own research!</i>",
                 escaped_pair, price, confidence * 100.0, escaped_reason, timestamp, 
-                escaped_strategy_type, escaped_timeframe, escaped_pair, escaped_bot_name
+                escaped_strategy_type, escaped_timeframe, escaped_pair, session_id, escaped_bot_name
             )
         },
-        crate::services::strategy_engine::StrategySignal::Sell { confidence, price, reason } => {
+        crate::services::strategy_engine::StrategySignal::Sell { confidence, price, reason, .. } => {
             let escaped_reason = escape_html(reason);
             format!(
                 "🔴 <b>SELL SIGNAL - {}</b>\n\n\
@@ -1413,12 +1189,33 @@ fn format_user_signal_message(
 ⏰ <b>Time:</b> <code>{}</code>\n\
 📉 <b>Strategy:</b> {}\n\
 📍 <b>Timeframe:</b> {}\n\
-📍 <b>Pair:</b> {}\n\n\
+📍 <b>Pair:</b> {}\n\
+🧩 <b>Session:</b> #{}\n\n\
 🤖 <b>Bot:</b> {}\n\
 🔄 <b>Status:</b> <code>Live Trading Active</code>\n\n\
 ⚠️ <i>This is a live trading signal. Always do your own research!</i>",
                 escaped_pair, price, confidence * 100.0, escaped_reason, timestamp,
-                escaped_strategy_type, escaped_timeframe, escaped_pair, escaped_bot_name
+                escaped_strategy_type, escaped_timeframe, escaped_pair, session_id, escaped_bot_name
+            )
+        },
+        crate::services::strategy_engine::StrategySignal::AdjustPosition { stake, price, reason } => {
+            let escaped_reason = escape_html(reason);
+            let action = if *stake > 0.0 { "ADD TO POSITION" } else { "PARTIAL EXIT" };
+            format!(
+                "🟡 <b>{} - {}</b>\n\n\
+💰 <b>Price:</b> <code>{:.4}</code> USDT\n\
+💵 <b>Stake:</b> <code>{:.2}</code> USDT\n\
+📝 <b>Reason:</b> {}\n\
+⏰ <b>Time:</b> <code>{}</code>\n\
+📈 <b>Strategy:</b> {}\n\
+📍 <b>Timeframe:</b> {}\n\
+📍 <b>Pair:</b> {}\n\
+🧩 <b>Session:</b> #{}\n\n\
+🤖 <b>Bot:</b> {}\n\
+🔄 <b>Status:</b> <code>Live Trading Active</code>\n\n\
+⚠️ <i>This is a live trading signal. Always do your own research!</i>",
+                action, escaped_pair, price, stake.abs(), escaped_reason, timestamp,
+                escaped_strategy_type, escaped_timeframe, escaped_pair, session_id, escaped_bot_name
             )
         },
         crate::services::strategy_engine::StrategySignal::Hold => {
@@ -1428,9 +1225,11 @@ fn format_user_signal_message(
     }
 }
 
-/// Save trading signal to database and manage positions/trades
+/// Save trading signal of a session to database and manage positions/trades
+/// Returns the id of the saved signal (`None` for Hold, which is not saved).
 async fn save_trading_order(
     app_state: &Arc<AppState>,
+    session_id: u64,
     user_id: i64,
     exchange: &str,
     pair: &str,
@@ -1438,23 +1237,38 @@ async fn save_trading_order(
     signal: &crate::services::strategy_engine::StrategySignal,
     candle_timestamp: Option<DateTime<Utc>>,
     indicator_values: Option<serde_json::Value>,
-) -> Result<(), anyhow::Error> {
+) -> Result<Option<u64>, anyhow::Error> {
     use crate::services::strategy_engine::StrategySignal;
-    use crate::services::position_service;
+    use crate::services::{position_service, protection_service};
+    use sea_orm::TransactionTrait;
     
     let (signal_type, side, price, confidence, reason) = match signal {
         StrategySignal::Buy { confidence, price, reason } => {
             ("buy".to_string(), "buy".to_string(), *price, *confidence, reason.clone())
         },
-        StrategySignal::Sell { confidence, price, reason } => {
+        StrategySignal::Sell { confidence, price, reason, .. } => {
             ("sell".to_string(), "sell".to_string(), *price, *confidence, reason.clone())
         },
+        StrategySignal::AdjustPosition { stake, price, reason } => {
+            let side = if *stake > 0.0 { "buy" } else { "sell" };
+            ("adjust".to_string(), side.to_string(), *price, 1.0, reason.clone())
+        },
         StrategySignal::Hold => {
             // Don't save Hold signals
-            return Ok(());
+            return Ok(None);
         }
     };
     
+    let adjust_stake = match signal {
+        StrategySignal::AdjustPosition { stake, .. } => Some(*stake),
+        _ => None,
+    };
+    
+    let exit_reason = match signal {
+        StrategySignal::Sell { exit_reason, .. } => *exit_reason,
+        _ => freqtrade_rs::strategy::ExitReason::Signal,
+    };
+    
     // Get strategy ID if available (from strategy_config or lookup)
     let strategy_id = None; // TODO: Get strategy ID from strategy_config if available
     
@@ -1482,71 +1296,291 @@ async fn save_trading_order(
         indicator_values: ActiveValue::Set(indicator_values.map(|v| v.to_string())),
         telegram_message_id: ActiveValue::NotSet, // Will be set when message is sent
         related_signal_id: ActiveValue::NotSet, // Can be set to link to previous signal
+        session_id: ActiveValue::Set(Some(session_id)),
         ..Default::default()
     };
     
-    let signal_result = live_trading_signals::Entity::insert(signal)
-        .exec(app_state.db.as_ref())
-        .await?;
+    // Read what the signal changes before writing, the writes then share one transaction
+    let db = app_state.db.as_ref();
+    let time = candle_timestamp.unwrap_or_else(Utc::now);
+    let position = if side == "sell" || adjust_stake.is_some() {
+        position_service::get_open_positions(db, user_id).await?
+            .into_iter()
+            .find(|p| p.pair == pair && p.status == "open")
+    } else {
+        None
+    };
+    let open_quantity = position
+        .as_ref()
+        .map(|p| f64::from_str(&p.quantity.to_string()).unwrap_or(0.0))
+        .unwrap_or(0.0);
+    let quantity = match adjust_stake {
+        // DCA entry, limited by the available balance
+        Some(stake) if stake > 0.0 => stake.min(position_service::get_sizing_account(db, user_id).await?.available) / price,
+        // Partial exit
+        Some(stake) => -stake / price,
+        None if side == "buy" => calculate_order_quantity(app_state, session_id, user_id, pair, strategy_config, price).await?,
+        None => open_quantity,
+    };
+    
+    // The signal row and the position change it causes are written together or not at all
+    let txn = db.begin().await?;
+    let signal_id = live_trading_signals::Entity::insert(signal).exec(&txn).await?.last_insert_id;
+    let change = match (adjust_stake, position.as_ref()) {
+        (Some(_), None) => {
+            warn!("No open position found for user {} to adjust for {}", user_id, pair);
+            PositionChange::None
+        }
+        (Some(_), Some(_)) if quantity <= 0.0 => {
+            warn!("No available balance for user {} to add to {} position", user_id, pair);
+            PositionChange::None
+        }
+        (Some(stake), Some(position)) if stake > 0.0 => {
+            position_service::increase_position(&txn, user_id, position.id, Some(signal_id), price, quantity).await?;
+            PositionChange::Adjusted { position_id: position.id, quantity }
+        }
+        (Some(_), Some(position)) => {
+            let pnl = position_service::reduce_position(&txn, user_id, position.id, Some(signal_id), price, quantity).await?;
+            if quantity >= open_quantity {
+                PositionChange::Closed { position_id: position.id, trade_id: None, pnl }
+            } else {
+                PositionChange::Adjusted { position_id: position.id, quantity: -quantity }
+            }
+        }
+        (None, _) if side == "buy" && quantity <= 0.0 => {
+            warn!("Position sizer returned no quantity for user {} on {} at {}, skipping entry", user_id, pair, price);
+            PositionChange::None
+        }
+        (None, _) if side == "buy" => {
+            let position_id = position_service::create_position(
+                &txn,
+                user_id,
+                Some(signal_id),
+                strategy_id,
+                Some(strategy_config.strategy_type.clone()),
+                exchange.to_string(),
+                pair.to_string(),
+                price,
+                quantity,
+            ).await?;
+            PositionChange::Opened { position_id }
+        }
+        (None, Some(position)) => {
+            let trade_id = position_service::close_position_and_create_trade(
+                &txn,
+                user_id,
+                position.id,
+                Some(signal_id),
+                price,
+                Some(&exit_reason.to_string()),
+            ).await?;
+            let pnl = f64::from_str(&position.unrealized_pnl.to_string()).unwrap_or(0.0);
+            PositionChange::Closed { position_id: position.id, trade_id: Some(trade_id), pnl }
+        }
+        (None, None) => {
+            warn!("No open position found for user {} to close with sell signal for {}", user_id, pair);
+            PositionChange::None
+        }
+    };
+    txn.commit().await?;
+    
+    match change {
+        PositionChange::Opened { position_id } => {
+            info!("✅ Created position {} for user {}: {} {} at {}", position_id, user_id, side, pair, price);
+            app_state.strategy_executor
+                .on_position_opened(session_id, pair, position_id, price, quantity, time)
+                .await;
+        }
+        PositionChange::Adjusted { position_id, quantity } => {
+            app_state.strategy_executor.on_position_adjusted(session_id, pair, price, quantity, time).await;
+            info!("✅ Adjusted position {} for user {} by {:.8} {} at {}", position_id, user_id, quantity, pair, price);
+        }
+        PositionChange::Closed { position_id, trade_id, pnl } => {
+            app_state.strategy_executor.on_position_closed(session_id, pair).await;
+            if let Err(e) = protection_service::apply_protections(app_state, session_id, user_id, pair, time).await {
+                error!("Failed to evaluate protections for user {}: {}", user_id, e);
+            }
+            info!("✅ Closed position {} (trade {:?}) for user {}: {} {} at {} (P&L: {:.2})",
+                position_id, trade_id, user_id, side, pair, price, pnl);
+        }
+        PositionChange::None => {}
+    }
+    
+    info!("✅ Saved trading signal {} to database for user {}: {} {} at {}", signal_id, user_id, signal_type, pair, price);
+    
+    Ok(Some(signal_id))
+}
+
+/// Position change of a market signal, applied to the strategy once it is committed
+enum PositionChange {
+    Opened { position_id: u64 },
+    /// DCA entry (positive quantity) or partial exit (negative quantity)
+    Adjusted { position_id: u64, quantity: f64 },
+    /// Closed by an exit signal, or by a partial exit selling everything (no trade id)
+    Closed { position_id: u64, trade_id: Option<u64>, pnl: f64 },
+    None,
+}
+
+/// Execute a resting limit order of a session, reached by a trade at `trade_price`, against the user's position
+/// Fills at the limit price, or at the trade price if the market gapped through it, as the backtester does.
+/// Returns the filled part of the order and its fill price, `None` if it could not be filled
+/// (no balance or nothing left to sell).
+async fn apply_limit_fill(
+    app_state: &Arc<AppState>,
+    session_id: u64,
+    user_id: i64,
+    exchange: &str,
+    pair: &str,
+    strategy_config: &crate::services::strategy_engine::StrategyConfig,
+    order: &freqtrade_rs::strategy::LimitOrder,
+    trade_price: f64,
+    time: DateTime<Utc>,
+) -> Result<Option<(freqtrade_rs::strategy::LimitOrder, f64)>, anyhow::Error> {
+    use crate::services::{position_service, protection_service};
+    use freqtrade_rs::exchange::OrderSide;
+    use sea_orm::TransactionTrait;
     
-    let signal_id = signal_result.last_insert_id;
+    let price = order.fill_price(trade_price);
+    let db = app_state.db.as_ref();
+    let open_positions = position_service::get_open_positions(db, user_id).await?;
+    let position = open_positions.iter().find(|p| p.pair == pair);
+    let open_quantity = position
+        .map(|p| f64::from_str(&p.quantity.to_string()).unwrap_or(0.0))
+        .unwrap_or(0.0);
     
-    // For now, use a default quantity (in production, calculate based on balance)
-    let quantity = 0.001; // Default quantity for demo
+    let (side, quantity) = match order.side {
+        OrderSide::Buy => {
+            let account = position_service::get_sizing_account(db, user_id).await?;
+            ("buy", order.quantity.min(account.available / price))
+        }
+        OrderSide::Sell => ("sell", order.quantity.min(open_quantity)),
+    };
+    if quantity <= 0.0 {
+        warn!("Limit {} of user {} on {} at {} cannot be filled (no balance or position)", side, user_id, pair, order.price);
+        return Ok(None);
+    }
     
-    // Handle Buy signal: Create position
-    if side == "buy" {
-        match position_service::create_position(
-            app_state.db.as_ref(),
+    let signal = live_trading_signals::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        strategy_id: ActiveValue::Set(None),
+        strategy_name: ActiveValue::Set(Some(strategy_config.strategy_type.clone())),
+        exchange: ActiveValue::Set(exchange.to_string()),
+        pair: ActiveValue::Set(pair.to_string()),
+        side: ActiveValue::Set(side.to_string()),
+        signal_type: ActiveValue::Set("limit".to_string()),
+        price: ActiveValue::Set(order.price.to_string()),
+        confidence: ActiveValue::Set(Some("1".to_string())),
+        reason: ActiveValue::Set(order.tag.clone()),
+        timeframe: ActiveValue::Set(Some(strategy_config.timeframe.clone())),
+        status: ActiveValue::Set("signal".to_string()),
+        external_order_id: ActiveValue::NotSet,
+        executed_price: ActiveValue::Set(Some(price.to_string())),
+        executed_quantity: ActiveValue::Set(Some(quantity.to_string())),
+        executed_at: ActiveValue::Set(Some(Utc::now())),
+        created_at: ActiveValue::Set(Some(Utc::now())),
+        updated_at: ActiveValue::Set(Some(Utc::now())),
+        candle_timestamp: ActiveValue::Set(Some(time)),
+        indicator_values: ActiveValue::NotSet,
+        telegram_message_id: ActiveValue::NotSet,
+        related_signal_id: ActiveValue::NotSet,
+        session_id: ActiveValue::Set(Some(session_id)),
+        ..Default::default()
+    };
+    // The signal row and the position change it causes are written together or not at all
+    let txn = db.begin().await?;
+    let signal_id = live_trading_signals::Entity::insert(signal).exec(&txn).await?.last_insert_id;
+    let opened = match (order.side, position) {
+        (OrderSide::Buy, Some(position)) => {
+            position_service::increase_position(&txn, user_id, position.id, Some(signal_id), price, quantity).await?;
+            None
+        }
+        (OrderSide::Buy, None) => Some(position_service::create_position(
+            &txn,
             user_id,
             Some(signal_id),
-            strategy_id,
+            None,
             Some(strategy_config.strategy_type.clone()),
             exchange.to_string(),
             pair.to_string(),
             price,
             quantity,
-        ).await {
-            Ok(position_id) => {
-                info!("✅ Created position {} for user {}: {} {} at {}", position_id, user_id, side, pair, price);
-            }
-            Err(e) => {
-                error!("Failed to create position for user {}: {}", user_id, e);
-            }
+        ).await?),
+        (OrderSide::Sell, Some(position)) if quantity >= open_quantity => {
+            // Like the backtester: a signal exit, the order's tag stays on the signal row
+            let exit_reason = freqtrade_rs::strategy::ExitReason::Signal.to_string();
+            position_service::close_position_and_create_trade(&txn, user_id, position.id, Some(signal_id), price, Some(&exit_reason)).await?;
+            None
         }
-    }
+        (OrderSide::Sell, Some(position)) => {
+            position_service::reduce_position(&txn, user_id, position.id, Some(signal_id), price, quantity).await?;
+            None
+        }
+        // Dropping the transaction discards the signal row
+        (OrderSide::Sell, None) => return Ok(None),
+    };
+    txn.commit().await?;
     
-    // Handle Sell signal: Close position and create trade
-    if side == "sell" {
-        // Find open position for this user and pair
-        let open_positions = position_service::get_open_positions(app_state.db.as_ref(), user_id).await?;
-        
-        // Find matching position (same pair)
-        if let Some(position) = open_positions.iter().find(|p| p.pair == pair && p.status == "open") {
-            match position_service::close_position_and_create_trade(
-                app_state.db.as_ref(),
-                user_id,
-                position.id,
-                Some(signal_id),
-                price,
-            ).await {
-                Ok(trade_id) => {
-                    let pnl: f64 = f64::from_str(&position.unrealized_pnl.to_string()).unwrap_or(0.0);
-                    info!("✅ Closed position {} and created trade {} for user {}: {} {} at {} (P&L: {:.2})", 
-                        position.id, trade_id, user_id, side, pair, price, pnl);
-                }
-                Err(e) => {
-                    error!("Failed to close position for user {}: {}", user_id, e);
-                }
+    match (order.side, opened) {
+        (OrderSide::Buy, Some(position_id)) => {
+            app_state.strategy_executor
+                .on_position_opened(session_id, pair, position_id, price, quantity, time)
+                .await;
+        }
+        (OrderSide::Buy, None) => {
+            app_state.strategy_executor.on_position_adjusted(session_id, pair, price, quantity, time).await;
+        }
+        (OrderSide::Sell, _) if quantity >= open_quantity => {
+            app_state.strategy_executor.on_position_closed(session_id, pair).await;
+            if let Err(e) = protection_service::apply_protections(app_state, session_id, user_id, pair, time).await {
+                error!("Failed to evaluate protections for user {}: {}", user_id, e);
             }
-        } else {
-            warn!("No open position found for user {} to close with sell signal for {}", user_id, pair);
+        }
+        (OrderSide::Sell, _) => {
+            app_state.strategy_executor.on_position_adjusted(session_id, pair, price, -quantity, time).await;
         }
     }
     
-    info!("✅ Saved trading signal {} to database for user {}: {} {} at {}", signal_id, user_id, side, pair, price);
+    info!("✅ Filled limit {} of user {}: {:.8} {} at {}", side, user_id, quantity, pair, price);
+    Ok(Some((freqtrade_rs::strategy::LimitOrder { quantity, ..order.clone() }, price)))
+}
+
+/// Calculate order quantity for an entry with the strategy's position sizing model
+async fn calculate_order_quantity(
+    app_state: &Arc<AppState>,
+    session_id: u64,
+    user_id: i64,
+    pair: &str,
+    strategy_config: &crate::services::strategy_engine::StrategyConfig,
+    price: f64,
+) -> Result<f64, anyhow::Error> {
+    use crate::services::position_service;
+    use freqtrade_rs::config::RiskConfig;
+    use freqtrade_rs::portfolio::SizingContext;
+    
+    let sizing = strategy_config.position_sizing()?;
+    let account = position_service::get_sizing_account(app_state.db.as_ref(), user_id).await?;
+    let risk_config = RiskConfig::default();
     
-    Ok(())
+    // Risk-based sizing risks to the stop the position will get
+    let stop_loss = app_state.strategy_executor.entry_stop_loss(session_id, pair, price).await
+        .unwrap_or_else(|| risk_config.default_exit_levels(freqtrade_rs::portfolio::PositionSide::Long, price).0);
+    let ctx = SizingContext {
+        equity: account.equity,
+        available: account.available,
+        entry_price: price,
+        stop_loss: Some(stop_loss),
+        atr: app_state.strategy_executor.get_atr(session_id, pair).await,
+        recent_returns: &account.recent_returns,
+    };
+    let quantity = sizing.build().quantity(&ctx);
+    
+    let max_quantity = (account.equity * risk_config.max_position_size).min(account.available) / price;
+    let quantity = quantity.min(max_quantity);
+    
+    info!("📐 Position size for user {} ({}): {:.8} at {} (equity {:.2}, available {:.2})",
+        user_id, sizing.describe(), quantity, price, account.equity, account.available);
+    
+    Ok(quantity)
 }
 
 /// Restore active live trading sessions from database on bot startup
@@ -1584,8 +1618,8 @@ pub async fn restore_active_sessions(
         let pair = session.pair.clone();
         let timeframe = session.timeframe.clone().unwrap_or_else(|| "1m".to_string());
         
-        info!("🔄 Restoring session for user {}: strategy_id={:?}, exchange={}, pair={}, timeframe={}", 
-            user_id, strategy_id, exchange, pair, timeframe);
+        info!("🔄 Restoring session {} for user {}: strategy_id={:?}, exchange={}, pair={}, timeframe={}", 
+            session.id, user_id, strategy_id, exchange, pair, timeframe);
         
         // Get strategy from database if strategy_id exists
         let strategy_config = if let Some(sid) = strategy_id {
@@ -1625,12 +1659,16 @@ pub async fn restore_active_sessions(
         
         // Start strategy executor
         match app_state.strategy_executor.start_trading(
+            session.id,
             user_id,
             strategy_config.clone(),
             Some(exchange.clone())
         ).await {
             Ok(_) => {
                 info!("✅ Strategy executor started for user {}", user_id);
+                if session.paused_at.is_some() {
+                    app_state.strategy_executor.set_paused(session.id, true).await;
+                }
             }
             Err(e) => {
                 error!("❌ Failed to start strategy executor for user {}: {}", user_id, e);
@@ -1640,15 +1678,28 @@ pub async fn restore_active_sessions(
         }
         
         // Start user trading service (this will subscribe to stream and start monitoring)
-        start_user_trading_service(
-            app_state.clone(),
-            bot.clone(),
-            user_id,
-            user_chat_id,
-            strategy_config,
-            exchange.clone(),
-            pair.clone(),
-        );
+        if strategy_config.pairlist().is_some() {
+            crate::services::pairlist_service::start_pairlist_service(
+                app_state.clone(),
+                bot.clone(),
+                session.id,
+                user_id,
+                user_chat_id,
+                strategy_config,
+                exchange.clone(),
+            );
+        } else {
+            start_user_trading_service(
+                app_state.clone(),
+                bot.clone(),
+                session.id,
+                user_id,
+                user_chat_id,
+                strategy_config,
+                exchange.clone(),
+                pair.clone(),
+            );
+        }
         
         info!("✅ Restored live trading session for user {}: {} on {} ({})", 
             user_id, session.strategy_name.as_ref().unwrap_or(&"Unknown".to_string()), exchange, pair);
@@ -1660,3 +1711,193 @@ pub async fn restore_active_sessions(
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use freqtrade_rs::exchange::OrderSide;
+    use crate::services::strategy_engine::StrategyConfig;
+
+    fn candle(timestamp: i64, close: f64) -> Candle {
+        Candle { open: close, high: close, low: close, close, volume: 1.0, timestamp }
+    }
+
+    fn trade(price: f64, time_ms: i64) -> Trade {
+        Trade { price, quantity: 1.0, side: OrderSide::Buy, time_ms }
+    }
+
+    type Streams = Arc<RwLock<HashMap<StreamKey, StreamInfo>>>;
+
+    /// Streams with one BTC/USDT stream aggregating 1m candles
+    fn streams(aggregator: CandleAggregator) -> (StreamKey, Streams, broadcast::Receiver<MarketEvent>) {
+        let key = StreamKey::from_pair("binance", "BTC/USDT").unwrap();
+        let (sender, receiver) = broadcast::channel(16);
+        let stream_info = StreamInfo {
+            subscribers: Arc::new(RwLock::new(vec![Subscriber::Session(1)])),
+            timeframes: Arc::new(RwLock::new(HashMap::from([(Subscriber::Session(1), "1m".to_string())]))),
+            aggregators: Arc::new(RwLock::new(HashMap::from([("1m".to_string(), aggregator)]))),
+            sender,
+            health: Arc::new(RwLock::new(StreamHealth::default())),
+        };
+        (key.clone(), Arc::new(RwLock::new(HashMap::from([(key, stream_info)]))), receiver)
+    }
+
+    /// 1m aggregator that closed the candle at 0 and was forming the one at 60 when the stream dropped
+    fn interrupted_aggregator() -> CandleAggregator {
+        let mut aggregator = CandleAggregator::new("1m");
+        aggregator.add_trade(&trade(100.0, 1_000));
+        aggregator.close_until(62_000);
+        aggregator.add_trade(&trade(101.0, 61_000));
+        aggregator
+    }
+
+    fn config() -> StrategyConfig {
+        StrategyConfig {
+            strategy_type: "EXPRESSION".to_string(),
+            parameters: serde_json::json!({}),
+            pair: "BTC/USDT".to_string(),
+            timeframe: "1m".to_string(),
+            buy_condition: "close > SMA(4)".to_string(),
+            sell_condition: "close < 0".to_string(),
+        }
+    }
+
+    /// Source that only records the pairs it stopped delivering
+    struct TestSource {
+        removed: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MarketDataSource for TestSource {
+        fn exchange(&self) -> &str {
+            "binance"
+        }
+
+        fn add(&self, _pair: &str) {}
+
+        fn remove(&self, pair: &str) {
+            self.removed.lock().unwrap().push(pair.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_of_one_session_are_independent() {
+        let removed = Arc::new(Mutex::new(Vec::new()));
+        let mut events = None;
+        let manager = StreamManager::with_source(|sender| {
+            events = Some(sender);
+            TestSource { removed: removed.clone() }
+        }, None);
+        let events = events.unwrap();
+
+        // Session 1 trades BTC/USDT, which is also the informative pair of its ETH and SOL pairs
+        let eth = Subscriber::Informative { session_id: 1, pair: "ETH/USDT".to_string() };
+        let sol = Subscriber::Informative { session_id: 1, pair: "SOL/USDT".to_string() };
+        let _trading = manager.subscribe("binance", "BTC/USDT", Subscriber::Session(1), Some("1m")).await.unwrap();
+        let _eth = manager.subscribe("binance", "BTC/USDT", eth.clone(), None).await.unwrap();
+        let mut sol_receiver = manager.subscribe("binance", "BTC/USDT", sol.clone(), None).await.unwrap();
+        assert_eq!(manager.subscriber_count("binance", "BTC/USDT").await, 3);
+
+        // ETH/USDT leaves the pairlist: SOL/USDT keeps its feed and the session its candles
+        manager.unsubscribe("binance", "BTC/USDT", &eth).await;
+        events.send(FeedEvent::Trade { pair: "BTC/USDT".to_string(), trade: trade(100.0, 1_000), received_ms: 1_000 }).unwrap();
+        match tokio::time::timeout(Duration::from_secs(1), sol_receiver.recv()).await {
+            Ok(Ok(MarketEvent::Trade(trade))) => assert_eq!(trade.price, 100.0),
+            other => panic!("expected the trade, got {:?}", other),
+        }
+        let key = StreamKey::from_pair("binance", "BTC/USDT").unwrap();
+        assert!(manager.streams.read().await[&key].aggregators.read().await.contains_key("1m"));
+        let active = manager.get_active_streams().await;
+        assert_eq!((active[0].2, active[0].3.clone()), (1, vec![1]));
+
+        // The stream goes with the last subscription
+        manager.unsubscribe("binance", "BTC/USDT", &Subscriber::Session(1)).await;
+        assert!(removed.lock().unwrap().is_empty());
+        manager.unsubscribe("binance", "BTC/USDT", &sol).await;
+        assert_eq!(*removed.lock().unwrap(), ["BTC/USDT"]);
+    }
+
+    #[tokio::test]
+    async fn test_on_connected_backfills_after_an_outage() {
+        let (key, streams_map, mut receiver) = streams(interrupted_aggregator());
+        let requested = Mutex::new(Vec::new());
+        StreamManager::on_connected(key.clone(), streams_map.clone(), true, |timeframe, since| {
+            requested.lock().unwrap().push((timeframe, since));
+            async { Ok(vec![candle(60, 104.0), candle(120, 105.0)]) }
+        }).await;
+
+        assert_eq!(*requested.lock().unwrap(), [("1m".to_string(), 60)]);
+        match receiver.try_recv() {
+            Ok(MarketEvent::Backfill { timeframe, candles }) => {
+                assert_eq!(timeframe, "1m");
+                assert_eq!(candles.iter().map(|c| c.timestamp).collect::<Vec<_>>(), [60, 120]);
+            }
+            other => panic!("expected the backfill, got {:?}", other),
+        }
+        assert!(matches!(receiver.try_recv(), Ok(MarketEvent::Reconnected)));
+
+        let streams = streams_map.read().await;
+        let stream_info = &streams[&key];
+        let health = stream_info.health.read().await.clone();
+        assert!(health.connected);
+        assert_eq!(health.reconnects, 1);
+        // The incomplete candle at 60 was replaced, the next one starts after the backfill
+        assert_eq!(stream_info.aggregators.read().await["1m"].backfill_since(), Some(180));
+    }
+
+    #[tokio::test]
+    async fn test_on_connected_first_connection_and_failed_backfill() {
+        // A first connection has nothing to backfill
+        let (key, streams_map, mut receiver) = streams(interrupted_aggregator());
+        let fetched = Mutex::new(false);
+        StreamManager::on_connected(key.clone(), streams_map.clone(), false, |_, _| {
+            *fetched.lock().unwrap() = true;
+            async { Ok(Vec::new()) }
+        }).await;
+        assert!(!*fetched.lock().unwrap());
+        assert!(receiver.try_recv().is_err());
+        let health = streams_map.read().await[&key].health.read().await.clone();
+        assert!(health.connected);
+        assert_eq!(health.reconnects, 0);
+
+        // A failed backfill still tells the subscribers the stream is live again
+        StreamManager::on_connected(key.clone(), streams_map.clone(), true, |_, _| async {
+            Err(anyhow::anyhow!("rate limited"))
+        }).await;
+        assert!(matches!(receiver.try_recv(), Ok(MarketEvent::Reconnected)));
+        assert_eq!(streams_map.read().await[&key].aggregators.read().await["1m"].backfill_since(), Some(60));
+    }
+
+    #[tokio::test]
+    async fn test_catch_up_after_lag_feeds_skipped_candles() {
+        let executor = StrategyExecutor::new();
+        executor.start_trading(1, 7, config(), None).await.unwrap();
+
+        // Before the first closed candle there is nothing to catch up on
+        let mut last_closed = None;
+        let caught_up = catch_up_after_lag(&executor, 1, "BTC/USDT", &mut last_closed, |_| async {
+            Err(anyhow::anyhow!("nothing is fetched before the first closed candle"))
+        }).await;
+        assert_eq!(caught_up.unwrap(), 0);
+
+        // Candles processed already are skipped
+        let mut last_closed = Some(60);
+        let requested = Mutex::new(None);
+        let caught_up = catch_up_after_lag(&executor, 1, "BTC/USDT", &mut last_closed, |since| {
+            *requested.lock().unwrap() = Some(since);
+            async { Ok(vec![candle(60, 100.0), candle(120, 101.0), candle(180, 102.0)]) }
+        }).await;
+        assert_eq!(*requested.lock().unwrap(), Some(61));
+        assert_eq!(caught_up.unwrap(), 2);
+        assert_eq!(last_closed, Some(180));
+        assert_eq!(executor.get_warmup_progress(1).await[0].1.candles, 2);
+
+        // A failed fetch keeps the position, the next lag retries from it
+        let caught_up = catch_up_after_lag(&executor, 1, "BTC/USDT", &mut last_closed, |_| async {
+            Err(anyhow::anyhow!("timeout"))
+        }).await;
+        assert!(caught_up.is_err());
+        assert_eq!(last_closed, Some(180));
+        assert_eq!(executor.get_warmup_progress(1).await[0].1.candles, 2);
+    }
+}
@@ -0,0 +1,234 @@
+//! Warm-up Service
+//!
+//! Backfills the lookback a strategy needs from historical klines before a live
+//! session starts emitting signals, instead of waiting for enough live candles,
+//! and the candles a session missed while its market stream was down.
+
+use anyhow::Result;
+use std::time::Duration;
+use chrono::Utc;
+use tracing::{info, warn};
+use crate::services::strategy_engine::{Candle, StrategyExecutor};
+
+const BINANCE_API_URL: &str = "https://api.binance.com/api/v3";
+
+/// Binance returns at most this many klines per request
+const MAX_KLINES: usize = 1000;
+
+/// Request up to `limit` klines of a pair, starting at `start_ms` (or the latest ones), oldest first
+/// The kline still forming is left out; live trades complete it.
+async fn request_klines(
+    client: &reqwest::Client,
+    pair: &str,
+    timeframe: &str,
+    start_ms: Option<i64>,
+    limit: usize,
+) -> Result<Vec<Candle>, anyhow::Error> {
+    let symbol = pair.replace('/', "").to_uppercase();
+    let mut query = vec![
+        ("symbol", symbol),
+        ("interval", timeframe.to_string()),
+        ("limit", limit.min(MAX_KLINES).to_string()),
+    ];
+    if let Some(start_ms) = start_ms {
+        query.push(("startTime", start_ms.to_string()));
+    }
+    let klines: Vec<Vec<serde_json::Value>> = client
+        .get(format!("{}/klines", BINANCE_API_URL))
+        .query(&query)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    
+    Ok(parse_klines(&klines, Utc::now().timestamp_millis()))
+}
+
+/// Candles of the klines closed before `now_ms`, malformed klines are skipped
+fn parse_klines(klines: &[Vec<serde_json::Value>], now_ms: i64) -> Vec<Candle> {
+    // Kline layout: [open time (ms), open, high, low, close, volume, close time (ms), ...]
+    let field = |kline: &[serde_json::Value], i: usize| -> Option<f64> {
+        kline.get(i)?.as_str()?.parse().ok()
+    };
+    klines.iter()
+        .filter(|k| k.get(6).and_then(|t| t.as_i64()).is_some_and(|close_time| close_time < now_ms))
+        .filter_map(|k| Some(Candle {
+            open: field(k, 1)?,
+            high: field(k, 2)?,
+            low: field(k, 3)?,
+            close: field(k, 4)?,
+            volume: field(k, 5)?,
+            timestamp: k.first()?.as_i64()? / 1000,
+        }))
+        .collect()
+}
+
+/// Fetch the last `limit` closed klines of a pair, oldest first
+pub async fn fetch_klines(
+    client: &reqwest::Client,
+    pair: &str,
+    timeframe: &str,
+    limit: usize,
+) -> Result<Vec<Candle>, anyhow::Error> {
+    // One extra kline since the last one is usually still forming
+    let mut candles = request_klines(client, pair, timeframe, None, limit + 1).await?;
+    if candles.len() > limit {
+        candles.drain(..candles.len() - limit);
+    }
+    Ok(candles)
+}
+
+/// Fetch every closed kline of a pair opened at or after `since` (unix seconds), oldest first
+/// Pages through the history `MAX_KLINES` at a time.
+pub async fn fetch_klines_since(
+    client: &reqwest::Client,
+    pair: &str,
+    timeframe: &str,
+    since: i64,
+) -> Result<Vec<Candle>, anyhow::Error> {
+    let mut candles: Vec<Candle> = Vec::new();
+    let mut start_ms = since * 1000;
+    loop {
+        let page = request_klines(client, pair, timeframe, Some(start_ms), MAX_KLINES).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        start_ms = (last.timestamp + 1) * 1000;
+        let full_page = page.len() == MAX_KLINES;
+        candles.extend(page);
+        if !full_page {
+            break;
+        }
+    }
+    Ok(candles)
+}
+
+/// Fetch the closed candles of a pair opened at or after `since` (unix seconds),
+/// e.g. those missed while a stream was down
+pub async fn fetch_missed_candles(
+    exchange: &str,
+    pair: &str,
+    timeframe: &str,
+    since: i64,
+) -> Result<Vec<Candle>, anyhow::Error> {
+    if exchange != "binance" {
+        return Err(anyhow::anyhow!("Candle backfill only supports Binance, not {}", exchange));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()?;
+    fetch_klines_since(&client, pair, timeframe, since).await
+}
+
+/// Backfill the strategy of a session's pair with the historical candles it still needs
+/// Returns the number of candles fed (0 if the strategy needs no warm-up)
+pub async fn warm_up_pair(
+    executor: &StrategyExecutor,
+    session_id: u64,
+    exchange: &str,
+    pair: &str,
+    timeframe: &str,
+) -> Result<usize, anyhow::Error> {
+    let needed = executor.warmup_needed(session_id, pair).await;
+    if needed == 0 {
+        return Ok(0);
+    }
+    if exchange != "binance" {
+        warn!("⏳ [Session {}] Historical warm-up only supports Binance, {} on {} warms up from live candles", session_id, pair, exchange);
+        return Ok(0);
+    }
+    
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()?;
+    let candles = fetch_klines(&client, pair, timeframe, needed).await?;
+    let fed = executor.warm_up(session_id, pair, &candles).await;
+    
+    info!("⏳ [Session {}] Warmed up {} strategy with {}/{} historical {} candles", session_id, pair, fed, needed, timeframe);
+    Ok(fed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::services::strategy_engine::StrategyConfig;
+    use crate::services::strategy_engine::executor::WarmupProgress;
+
+    fn config() -> StrategyConfig {
+        StrategyConfig {
+            strategy_type: "EXPRESSION".to_string(),
+            parameters: json!({}),
+            pair: "BTC/USDT".to_string(),
+            timeframe: "1m".to_string(),
+            buy_condition: "close > SMA(4)".to_string(),
+            sell_condition: "close < 0".to_string(),
+        }
+    }
+
+    fn candles(count: i64) -> Vec<Candle> {
+        (0..count)
+            .map(|i| Candle { open: 100.0, high: 101.0, low: 99.0, close: 100.0, volume: 1.0, timestamp: i * 60 })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_klines_skips_forming_and_malformed_klines() {
+        let kline = |open_ms: i64, close: &str| json!([open_ms, "1.0", "2.0", "0.5", close, "10.0", open_ms + 59_999]);
+        let klines: Vec<Vec<serde_json::Value>> = [
+            kline(0, "1.5"),
+            kline(60_000, "oops"),
+            kline(120_000, "1.8"),
+        ].into_iter().map(|k| k.as_array().unwrap().clone()).collect();
+
+        // The last kline closes at 179_999 and is still forming
+        let candles = parse_klines(&klines, 150_000);
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].timestamp, 0);
+        assert_eq!((candles[0].open, candles[0].high, candles[0].low, candles[0].close, candles[0].volume), (1.0, 2.0, 0.5, 1.5, 10.0));
+
+        assert_eq!(parse_klines(&klines, 200_000).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_warmup_progress_counts_fed_candles() {
+        let executor = StrategyExecutor::new();
+        executor.start_trading(1, 7, config(), None).await.unwrap();
+        let (_, progress) = executor.get_warmup_progress(1).await.remove(0);
+        let required = progress.required;
+        assert!(required > 2);
+        assert_eq!(progress.candles, 0);
+        assert_eq!(progress.percent(), 0.0);
+
+        assert_eq!(executor.warm_up(1, "BTC/USDT", &candles(2)).await, 2);
+        let (_, progress) = executor.get_warmup_progress(1).await.remove(0);
+        assert_eq!(progress.candles, 2);
+        assert!(!progress.is_complete());
+        assert_eq!(executor.warmup_needed(1, "BTC/USDT").await, required - 2);
+
+        executor.warm_up(1, "BTC/USDT", &candles(required as i64)).await;
+        let (_, progress) = executor.get_warmup_progress(1).await.remove(0);
+        assert!(progress.is_complete());
+        assert_eq!(progress.percent(), 100.0);
+        assert_eq!(executor.warmup_needed(1, "BTC/USDT").await, 0);
+
+        // Pairs the session does not trade are not fed
+        assert_eq!(executor.warm_up(1, "ETH/USDT", &candles(2)).await, 0);
+        assert_eq!(WarmupProgress::default().percent(), 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_pair_skips_without_history() {
+        let executor = StrategyExecutor::new();
+        executor.start_trading(1, 7, config(), None).await.unwrap();
+
+        // Only Binance has historical klines, other exchanges warm up from live candles
+        assert_eq!(warm_up_pair(&executor, 1, "kraken", "BTC/USDT", "1m").await.unwrap(), 0);
+        assert!(executor.warmup_needed(1, "BTC/USDT").await > 0);
+
+        // A warm strategy needs nothing, so nothing is fetched
+        executor.warm_up(1, "BTC/USDT", &candles(50)).await;
+        assert_eq!(warm_up_pair(&executor, 1, "binance", "BTC/USDT", "1m").await.unwrap(), 0);
+    }
+}
@@ -6,6 +6,9 @@ use crate::services::user_service::UserService;
 use crate::services::strategy_engine::StrategyExecutor;
 use crate::services::strategy_service::StrategyService;
 use crate::services::trading_signal::StreamManager;
+use crate::services::market_feed::BinanceTradeFeed;
+use crate::services::market_recorder::MarketRecorder;
+use crate::services::market_replay::ReplaySource;
 
 pub type MyDialogue = Dialogue<BotState, InMemStorage<BotState>>;
 pub type HandlerResult = Result<(), anyhow::Error>;
@@ -80,8 +83,18 @@ impl AppState {
         let executor = Arc::new(StrategyExecutor::new());
         tracing::info!("StrategyExecutor initialized");
 
-        // Create StreamManager for managing shared streams
-        let stream_manager = Arc::new(StreamManager::new());
+        // Create StreamManager for managing shared streams, on recorded data if configured
+        let recorder = config.market_record_dir.as_ref()
+            .map(|dir| MarketRecorder::start(dir, config.market_record_retention_days));
+        let stream_manager = match &config.market_replay_dir {
+            Some(dir) => {
+                tracing::info!("Replaying market data from {} at {}x", dir, config.market_replay_speed);
+                Arc::new(StreamManager::with_source(|events| {
+                    ReplaySource::new(events, "binance", dir, config.market_replay_speed)
+                }, recorder))
+            }
+            None => Arc::new(StreamManager::with_source(BinanceTradeFeed::new, recorder)),
+        };
         tracing::info!("StreamManager initialized");
 
         // Create the AppState
@@ -131,8 +144,12 @@ pub enum CreateStrategyState {
     WaitingForMixStrategySelection {
         selected_strategy_ids: Vec<u64>, // Track selected strategies
     },
+    WaitingForMixMode {
+        selected_strategy_ids: Vec<u64>, // In selection order, the order sequence mode confirms in
+    },
     WaitingForMixStrategyName {
         selected_strategy_ids: Vec<u64>, // IDs of strategies to mix
+        mix_mode: String, // "unanimous", "majority", "weighted" or "sequence"
     },
     WaitingForName,
     WaitingForAlgorithm,
@@ -187,6 +204,12 @@ pub enum BacktestState {
         strategy_name: String,
         exchange: String,
     },
+    WaitingForEngine {
+        strategy_id: u64,
+        strategy_name: String,
+        exchange: String,
+        timerange: String,
+    },
 }
 
 #[derive(Clone, Debug, Default)]
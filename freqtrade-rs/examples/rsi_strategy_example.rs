@@ -1,11 +1,9 @@
 //! Example: RSI Strategy with real-time data streaming
 
-use freqtrade_rs::data::Candle;
 use freqtrade_rs::exchange::streaming::DataStreamer;
 use freqtrade_rs::strategy::Strategy;
 use freqtrade_rs::strategy::implementations::{RSIStrategy, RSIStrategyConfig};
 use freqtrade_rs::Result;
-use tokio::sync::mpsc;
 use tracing::{info, error};
 
 #[tokio::main]
@@ -51,16 +49,18 @@ async fn main() -> Result<()> {
         match strategy.process(&candle) {
             Ok(signal) => {
                 match signal.signal_type {
-                    freqtrade_rs::strategy::SignalType::Buy => {
+                    freqtrade_rs::strategy::SignalType::EnterLong
+                    | freqtrade_rs::strategy::SignalType::EnterShort => {
                         info!(
-                            "🔵 BUY SIGNAL: Price={:.2}, Confidence={:.2}, Reason: {}",
-                            candle.close, signal.confidence, signal.reason
+                            "🔵 ENTRY SIGNAL ({:?}): Price={:.2}, Confidence={:.2}, Tag: {:?}, Reason: {}",
+                            signal.signal_type, candle.close, signal.confidence, signal.enter_tag, signal.reason
                         );
                     }
-                    freqtrade_rs::strategy::SignalType::Sell => {
+                    freqtrade_rs::strategy::SignalType::ExitLong
+                    | freqtrade_rs::strategy::SignalType::ExitShort => {
                         info!(
-                            "🔴 SELL SIGNAL: Price={:.2}, Confidence={:.2}, Reason: {}",
-                            candle.close, signal.confidence, signal.reason
+                            "🔴 EXIT SIGNAL ({:?}): Price={:.2}, Confidence={:.2}, Tag: {:?}, Reason: {}",
+                            signal.signal_type, candle.close, signal.confidence, signal.exit_tag, signal.reason
                         );
                     }
                     freqtrade_rs::strategy::SignalType::Hold => {
@@ -0,0 +1,571 @@
+//! Lookahead-bias and recursive-warmup analysis
+//!
+//! Modeled on freqtrade's `lookahead-analysis` and `recursive-analysis`. Both re-run
+//! a strategy on truncated candle windows, fed the same way as [`BacktestEngine::run`]
+//! feeds it:
+//!
+//! - **Lookahead**: the signal and indicator values at a candle must not change when
+//!   the candles after it are removed.
+//! - **Recursive**: the indicator values at the last candle must not depend on how
+//!   much history beyond the startup candle count was supplied.
+//!
+//! [`BacktestEngine::run`]: crate::backtest::BacktestEngine::run
+
+use crate::data::{Candle, CandleSeries, InformativeData};
+use crate::strategy::{SignalType, Strategy};
+use crate::Result;
+use chrono::{DateTime, Utc};
+
+/// Relative difference below which lookahead checks treat indicator values as equal
+const LOOKAHEAD_EPSILON: f64 = 1e-9;
+
+/// Strategy output at one candle
+#[derive(Debug, Clone)]
+struct Snapshot {
+    signal_type: SignalType,
+    indicators: Vec<(String, f64)>,
+}
+
+impl Snapshot {
+    fn indicator(&self, name: &str) -> Option<f64> {
+        self.indicators.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+}
+
+/// Relative difference of two values, zero when both are (close to) zero
+fn relative_diff(a: f64, b: f64) -> f64 {
+    let scale = a.abs().max(b.abs());
+    if scale < f64::EPSILON {
+        0.0
+    } else {
+        (a - b).abs() / scale
+    }
+}
+
+/// Runs lookahead and recursive analysis on a strategy
+#[derive(Debug, Clone)]
+pub struct StrategyAnalyzer {
+    startup_candle_count: usize,
+    startup_counts: Vec<usize>,
+    max_checks: usize,
+    tolerance: f64,
+    informative_candles: Vec<Candle>,
+}
+
+impl StrategyAnalyzer {
+    /// Create analyzer for a strategy declaring `startup_candle_count` warm-up candles
+    ///
+    /// Recursive analysis compares half, once and twice the startup count by default.
+    pub fn new(startup_candle_count: usize) -> Self {
+        let mut startup_counts = vec![startup_candle_count / 2, startup_candle_count, startup_candle_count * 2];
+        startup_counts.retain(|&count| count > 0);
+        startup_counts.dedup();
+
+        Self {
+            startup_candle_count,
+            startup_counts,
+            max_checks: 20,
+            tolerance: 0.001,
+            informative_candles: Vec::new(),
+        }
+    }
+
+    /// Set the startup candle counts compared by the recursive analysis
+    pub fn with_startup_counts(mut self, mut counts: Vec<usize>) -> Self {
+        counts.sort_unstable();
+        counts.dedup();
+        self.startup_counts = counts;
+        self
+    }
+
+    /// Set the maximum number of candles re-checked by the lookahead analysis
+    pub fn with_max_checks(mut self, max_checks: usize) -> Self {
+        self.max_checks = max_checks;
+        self
+    }
+
+    /// Set the relative indicator deviation accepted by the recursive analysis (e.g., 0.001 = 0.1%)
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Provide candles of other pairs for the strategy's informative specs
+    pub fn with_informative_candles(mut self, mut candles: Vec<Candle>) -> Self {
+        candles.sort_by_key(|c| c.timestamp);
+        self.informative_candles = candles;
+        self
+    }
+
+    /// Run both analyses, each run gets a fresh strategy from `make_strategy`
+    ///
+    /// The factory is boxed and fallible so strategies built from a config
+    /// (`Box<dyn Strategy>`) can be analyzed as well.
+    pub fn analyze<T, F>(&self, mut make_strategy: F, candles: &CandleSeries) -> Result<AnalysisReport>
+    where
+        T: Strategy + ?Sized,
+        F: FnMut() -> Result<Box<T>>,
+    {
+        Ok(AnalysisReport {
+            lookahead: self.lookahead(&mut make_strategy, candles)?,
+            recursive: self.recursive(&mut make_strategy, candles)?,
+        })
+    }
+
+    /// Re-run the strategy up to sampled candles and flag outputs that change without future data
+    ///
+    /// Candles with an entry or exit signal are checked first; the remaining checks are spread
+    /// over the series so indicator-only bias is caught as well.
+    pub fn lookahead<T, F>(&self, mut make_strategy: F, candles: &CandleSeries) -> Result<LookaheadReport>
+    where
+        T: Strategy + ?Sized,
+        F: FnMut() -> Result<Box<T>>,
+    {
+        let candles = candles.candles();
+        let full = self.replay(make_strategy()?.as_mut(), candles)?;
+        let checkpoints = self.checkpoints(&full);
+
+        let mut report = LookaheadReport {
+            checked: checkpoints.len(),
+            signals: Vec::new(),
+            indicators: Vec::new(),
+        };
+        for index in checkpoints {
+            let truncated = self.replay(make_strategy()?.as_mut(), &candles[..=index])?;
+            let (expected, actual) = (&full[index], &truncated[index]);
+            let timestamp = candles[index].timestamp;
+
+            if expected.signal_type != actual.signal_type {
+                report.signals.push(BiasedSignal {
+                    timestamp,
+                    full: expected.signal_type,
+                    truncated: actual.signal_type,
+                });
+            }
+            for (name, value) in &expected.indicators {
+                let truncated = actual.indicator(name);
+                if truncated.is_none_or(|v| relative_diff(*value, v) > LOOKAHEAD_EPSILON) {
+                    report.indicators.push(BiasedIndicator {
+                        timestamp,
+                        name: name.clone(),
+                        full: *value,
+                        truncated,
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Compare the last candle's outputs with limited history against the full history
+    pub fn recursive<T, F>(&self, mut make_strategy: F, candles: &CandleSeries) -> Result<RecursiveReport>
+    where
+        T: Strategy + ?Sized,
+        F: FnMut() -> Result<Box<T>>,
+    {
+        let candles = candles.candles();
+        let Some(reference) = self.replay(make_strategy()?.as_mut(), candles)?.pop() else {
+            return Err(anyhow::anyhow!("No candles to analyze"));
+        };
+
+        let mut rows = Vec::new();
+        // Only counts shorter than the full history tell anything
+        for &startup in self.startup_counts.iter().filter(|&&count| count + 1 < candles.len()) {
+            let window = &candles[candles.len() - startup - 1..];
+            let Some(last) = self.replay(make_strategy()?.as_mut(), window)?.pop() else {
+                continue;
+            };
+            let deviations = reference
+                .indicators
+                .iter()
+                .map(|(name, value)| (name.clone(), last.indicator(name).map(|v| relative_diff(*value, v))))
+                .collect();
+            rows.push(RecursiveRow {
+                startup_candles: startup,
+                signal_matches: last.signal_type == reference.signal_type,
+                deviations,
+            });
+        }
+
+        Ok(RecursiveReport {
+            startup_candle_count: self.startup_candle_count,
+            history_candles: candles.len(),
+            tolerance: self.tolerance,
+            rows,
+        })
+    }
+
+    /// Feed candles like the backtest engine and record the output at every candle
+    fn replay<T: Strategy + ?Sized>(&self, strategy: &mut T, candles: &[Candle]) -> Result<Vec<Snapshot>> {
+        strategy.initialize(candles)?;
+
+        let base_symbol = candles.first().map(|c| c.symbol.clone()).unwrap_or_default();
+        let mut informative = InformativeData::new(&strategy.informative(), &base_symbol);
+        let mut informative_index = 0;
+
+        let mut snapshots = Vec::with_capacity(candles.len());
+        for candle in candles {
+            // Only informative candles closed by the end of this candle are visible
+            while let Some(other) = self.informative_candles.get(informative_index) {
+                if other.close_time() > candle.close_time() {
+                    break;
+                }
+                informative.feed(other);
+                informative_index += 1;
+            }
+            informative.feed(candle);
+
+            let signal_type = if strategy.is_ready() {
+                strategy.process_with_informative(candle, &informative)?.signal_type
+            } else {
+                SignalType::Hold
+            };
+            snapshots.push(Snapshot {
+                signal_type,
+                indicators: strategy.indicator_values(),
+            });
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Candle indexes to re-check, the last candle has no future data to remove
+    fn checkpoints(&self, full: &[Snapshot]) -> Vec<usize> {
+        let candidates = full.len().saturating_sub(1);
+        if candidates == 0 {
+            return Vec::new();
+        }
+        let mut points: Vec<usize> = full[..candidates]
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.signal_type != SignalType::Hold)
+            .map(|(i, _)| i)
+            .take(self.max_checks)
+            .collect();
+
+        let remaining = self.max_checks - points.len();
+        points.extend((1..=remaining).map(|k| k * candidates / (remaining + 1)));
+        points.sort_unstable();
+        points.dedup();
+        points
+    }
+}
+
+/// Signal that differs once future candles are removed
+#[derive(Debug, Clone)]
+pub struct BiasedSignal {
+    pub timestamp: DateTime<Utc>,
+    /// Signal with the full candle series
+    pub full: SignalType,
+    /// Signal with the series cut after this candle
+    pub truncated: SignalType,
+}
+
+/// Indicator value that differs once future candles are removed
+#[derive(Debug, Clone)]
+pub struct BiasedIndicator {
+    pub timestamp: DateTime<Utc>,
+    pub name: String,
+    pub full: f64,
+    /// `None` if the indicator is missing from the truncated run
+    pub truncated: Option<f64>,
+}
+
+/// Lookahead analysis result
+#[derive(Debug, Clone)]
+pub struct LookaheadReport {
+    /// Number of candles re-checked
+    pub checked: usize,
+    pub signals: Vec<BiasedSignal>,
+    pub indicators: Vec<BiasedIndicator>,
+}
+
+impl LookaheadReport {
+    /// Check if no lookahead bias was found
+    pub fn passed(&self) -> bool {
+        self.signals.is_empty() && self.indicators.is_empty()
+    }
+
+    /// Names of the biased indicators
+    pub fn biased_indicator_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.indicators.iter().map(|i| i.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
+
+/// Outputs at the last candle with a given amount of startup history
+#[derive(Debug, Clone)]
+pub struct RecursiveRow {
+    /// Candles supplied before the analyzed candle
+    pub startup_candles: usize,
+    /// Signal equals the one with full history
+    pub signal_matches: bool,
+    /// Relative deviation from the full-history value per indicator, `None` if not ready
+    pub deviations: Vec<(String, Option<f64>)>,
+}
+
+impl RecursiveRow {
+    /// Check if the outputs match the full history within `tolerance`
+    pub fn is_stable(&self, tolerance: f64) -> bool {
+        self.signal_matches && self.deviations.iter().all(|(_, d)| d.is_some_and(|d| d <= tolerance))
+    }
+}
+
+/// Recursive analysis result
+#[derive(Debug, Clone)]
+pub struct RecursiveReport {
+    /// Startup candle count declared by the strategy
+    pub startup_candle_count: usize,
+    /// Candles in the full history
+    pub history_candles: usize,
+    pub tolerance: f64,
+    /// Rows in ascending startup order
+    pub rows: Vec<RecursiveRow>,
+}
+
+impl RecursiveReport {
+    /// Check if every tested count from the declared startup count upward is stable
+    ///
+    /// Fails when the history is too short to test the declared startup count.
+    pub fn passed(&self) -> bool {
+        let mut tested = self
+            .rows
+            .iter()
+            .filter(|row| row.startup_candles >= self.startup_candle_count)
+            .peekable();
+        tested.peek().is_some() && tested.all(|row| row.is_stable(self.tolerance))
+    }
+
+    /// Smallest tested startup count from which all larger tested counts are stable
+    pub fn min_stable_startup(&self) -> Option<usize> {
+        let unstable = self.rows.iter().rposition(|row| !row.is_stable(self.tolerance));
+        let first_stable = unstable.map_or(0, |i| i + 1);
+        self.rows.get(first_stable).map(|row| row.startup_candles)
+    }
+}
+
+/// Combined lookahead and recursive analysis, passes only if both pass
+#[derive(Debug, Clone)]
+pub struct AnalysisReport {
+    pub lookahead: LookaheadReport,
+    pub recursive: RecursiveReport,
+}
+
+impl AnalysisReport {
+    /// Check if the strategy passed both analyses
+    pub fn passed(&self) -> bool {
+        self.lookahead.passed() && self.recursive.passed()
+    }
+
+    /// Format report as string
+    pub fn format(&self) -> String {
+        let mut report = self.format_lookahead();
+        report.push_str(&self.format_recursive());
+        report.push_str(&format!("\nOverall: {}\n", pass_label(self.passed())));
+        report
+    }
+
+    /// Format lookahead findings
+    fn format_lookahead(&self) -> String {
+        let lookahead = &self.lookahead;
+        let mut section = format!(
+            "\nLookahead Analysis\n==================\nChecked Candles: {}\nBiased Signals: {}\n",
+            lookahead.checked,
+            lookahead.signals.len(),
+        );
+        for signal in &lookahead.signals {
+            section.push_str(&format!(
+                "  {}: {:?} with full data, {:?} without future candles\n",
+                signal.timestamp.format("%Y-%m-%d %H:%M"),
+                signal.full,
+                signal.truncated,
+            ));
+        }
+        let names = lookahead.biased_indicator_names();
+        if names.is_empty() {
+            section.push_str("Biased Indicators: none\n");
+        } else {
+            section.push_str(&format!("Biased Indicators: {}\n", names.join(", ")));
+        }
+        section.push_str(&format!("Result: {}\n", pass_label(lookahead.passed())));
+        section
+    }
+
+    /// Format indicator deviations per startup count
+    fn format_recursive(&self) -> String {
+        let recursive = &self.recursive;
+        let mut section = format!(
+            "\nRecursive Analysis\n==================\nHistory Candles: {}\nStartup Candle Count: {}\nTolerance: {:.3}%\n",
+            recursive.history_candles,
+            recursive.startup_candle_count,
+            recursive.tolerance * 100.0,
+        );
+        if recursive.rows.is_empty() {
+            section.push_str("Not enough history to compare startup counts\n");
+        }
+        for row in &recursive.rows {
+            let mut line = format!(
+                "  Startup {}: signal {}",
+                row.startup_candles,
+                if row.signal_matches { "matches" } else { "differs" },
+            );
+            for (name, deviation) in &row.deviations {
+                match deviation {
+                    Some(d) => line.push_str(&format!(", {} {:.3}%", name, d * 100.0)),
+                    None => line.push_str(&format!(", {} not ready", name)),
+                }
+            }
+            section.push_str(&line);
+            section.push('\n');
+        }
+        match recursive.min_stable_startup() {
+            Some(count) => section.push_str(&format!("Minimum Stable Startup: {}\n", count)),
+            None => section.push_str("Minimum Stable Startup: none of the tested counts\n"),
+        }
+        section.push_str(&format!("Result: {}\n", pass_label(recursive.passed())));
+        section
+    }
+}
+
+fn pass_label(passed: bool) -> &'static str {
+    if passed {
+        "PASS"
+    } else {
+        "FAIL"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::Signal;
+    use chrono::{Duration, TimeZone};
+
+    /// EMA crossover strategy that optionally peeks at the highest close of the whole series
+    struct EmaProbe {
+        alpha: f64,
+        /// Previous close and EMA
+        last: Option<(f64, f64)>,
+        peek: bool,
+        future_high: f64,
+    }
+
+    impl EmaProbe {
+        fn new(alpha: f64, peek: bool) -> Self {
+            Self {
+                alpha,
+                last: None,
+                peek,
+                future_high: 0.0,
+            }
+        }
+    }
+
+    impl Strategy for EmaProbe {
+        fn name(&self) -> &str {
+            "EmaProbe"
+        }
+
+        fn initialize(&mut self, candles: &[Candle]) -> Result<()> {
+            if self.peek {
+                self.future_high = candles.iter().map(|c| c.close).fold(0.0, f64::max);
+            }
+            Ok(())
+        }
+
+        fn process(&mut self, candle: &Candle) -> Result<Signal> {
+            let previous_ema = self.last.map_or(candle.close, |(_, ema)| ema);
+            let ema = previous_ema + self.alpha * (candle.close - previous_ema);
+            let crossed = self.last.is_some_and(|(close, ema)| close <= ema) && candle.close > ema;
+            self.last = Some((candle.close, ema));
+
+            if self.peek && candle.close >= self.future_high * 0.98 {
+                Ok(Signal::exit_long(candle.close, 1.0, "near the top".to_string()))
+            } else if crossed {
+                Ok(Signal::enter_long(candle.close, 1.0, "above ema".to_string()))
+            } else {
+                Ok(Signal::hold("waiting".to_string()))
+            }
+        }
+
+        fn is_ready(&self) -> bool {
+            true
+        }
+
+        fn indicator_values(&self) -> Vec<(String, f64)> {
+            let mut values: Vec<(String, f64)> = self.last.map(|(_, ema)| ("ema".to_string(), ema)).into_iter().collect();
+            if self.peek {
+                values.push(("future_high".to_string(), self.future_high));
+            }
+            values
+        }
+    }
+
+    fn series(count: i64) -> CandleSeries {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        CandleSeries::from_vec(
+            (0..count)
+                .map(|i| {
+                    let close = 100.0 + 10.0 * (i as f64 / 5.0).sin() + i as f64 * 0.1;
+                    Candle::new(
+                        close,
+                        close + 1.0,
+                        close - 1.0,
+                        close,
+                        1000.0,
+                        start + Duration::hours(i),
+                        "BTC/USDT".to_string(),
+                        "1h".to_string(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_clean_strategy_passes() {
+        let candles = series(200);
+        let report = StrategyAnalyzer::new(60)
+            .analyze(|| Ok(Box::new(EmaProbe::new(0.3, false))), &candles)
+            .unwrap();
+
+        assert!(report.lookahead.passed());
+        assert_eq!(report.lookahead.checked, 20);
+        assert!(report.recursive.passed(), "{}", report.format());
+        assert!(report.format().contains("Overall: PASS"));
+    }
+
+    #[test]
+    fn test_lookahead_flags_future_data() {
+        let candles = series(200);
+        let report = StrategyAnalyzer::new(60)
+            .with_max_checks(200)
+            .lookahead(|| Ok(Box::new(EmaProbe::new(0.3, true))), &candles)
+            .unwrap();
+
+        assert!(!report.passed());
+        assert!(report.signals.iter().any(|s| s.full == SignalType::Hold && s.truncated == SignalType::ExitLong));
+        assert_eq!(report.biased_indicator_names(), vec!["future_high"]);
+    }
+
+    #[test]
+    fn test_recursive_finds_min_stable_startup() {
+        let candles = series(200);
+        // A slow EMA needs far more than 5 candles to forget its seed
+        let analyzer = StrategyAnalyzer::new(5).with_startup_counts(vec![5, 20, 150]);
+        let report = analyzer.recursive(|| Ok(Box::new(EmaProbe::new(0.1, false))), &candles).unwrap();
+
+        assert!(!report.passed());
+        assert_eq!(report.rows.len(), 3);
+        assert_eq!(report.min_stable_startup(), Some(150));
+
+        let report = StrategyAnalyzer::new(150)
+            .with_startup_counts(vec![150])
+            .recursive(|| Ok(Box::new(EmaProbe::new(0.1, false))), &candles)
+            .unwrap();
+        assert!(report.passed());
+    }
+}
@@ -1,10 +1,19 @@
 //! Backtesting engine
 
-use crate::data::{Candle, CandleSeries};
-use crate::strategy::{Strategy, Signal, SignalType};
-use crate::portfolio::{Balance, Position, PositionSide};
+use crate::config::RiskConfig;
+use crate::data::{Candle, CandleSeries, InformativeData, PairStats, Pairlist};
+use crate::exchange::OrderSide;
+use crate::indicators::{Indicator, ATR};
+use crate::strategy::{ExitReason, LimitOrder, Strategy, Signal, SignalType};
+use crate::portfolio::{
+    Balance, Position, PositionOrder, PositionOrderKind, PositionSide, PositionSizer,
+    PositionSizing, Protection, ProtectionLock, ProtectionManager, ProtectionTrade, RiskManager,
+    SizingContext,
+};
 use crate::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use tracing::warn;
 use uuid::Uuid;
 
 /// Backtest result
@@ -34,6 +43,112 @@ pub struct BacktestResult {
     pub max_drawdown: f64,
     /// Sharpe ratio
     pub sharpe_ratio: f64,
+    /// Entry signals rejected by the risk manager
+    pub rejected_entries: usize,
+    /// Circuit breakers tripped during the run
+    pub circuit_breakers: Vec<CircuitBreakerEvent>,
+    /// Entry signals blocked by protection locks
+    pub locked_entries: usize,
+    /// Locks created by protections during the run
+    pub protection_locks: Vec<ProtectionLock>,
+    /// Performance per pair
+    pub pair_stats: Vec<GroupStats>,
+    /// Performance per entry tag
+    pub enter_tag_stats: Vec<GroupStats>,
+    /// Performance per exit reason (signal exits are keyed by their exit tag)
+    pub exit_reason_stats: Vec<GroupStats>,
+}
+
+/// Performance of a group of trades (by pair, entry tag or exit reason)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupStats {
+    /// Group key
+    pub key: String,
+    /// Number of trades
+    pub trades: usize,
+    /// Winning trades
+    pub wins: usize,
+    /// Losing trades
+    pub losses: usize,
+    /// Total P&L
+    pub total_pnl: f64,
+    /// Average P&L percentage per trade
+    pub avg_pnl_percent: f64,
+}
+
+impl GroupStats {
+    /// Group trades by key, sorted by total P&L (best first)
+    pub fn from_trades<F>(trades: &[Trade], key: F) -> Vec<Self>
+    where
+        F: Fn(&Trade) -> String,
+    {
+        let mut groups: HashMap<String, Vec<&Trade>> = HashMap::new();
+        for trade in trades {
+            groups.entry(key(trade)).or_default().push(trade);
+        }
+
+        let mut stats: Vec<Self> = groups
+            .into_iter()
+            .map(|(key, trades)| Self {
+                key,
+                trades: trades.len(),
+                wins: trades.iter().filter(|t| t.pnl > 0.0).count(),
+                losses: trades.iter().filter(|t| t.pnl < 0.0).count(),
+                total_pnl: trades.iter().map(|t| t.pnl).sum(),
+                avg_pnl_percent: trades.iter().map(|t| t.pnl_percent).sum::<f64>()
+                    / trades.len() as f64,
+            })
+            .collect();
+        stats.sort_by(|a, b| {
+            b.total_pnl
+                .partial_cmp(&a.total_pnl)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        stats
+    }
+}
+
+
+impl From<&Trade> for ProtectionTrade {
+    fn from(trade: &Trade) -> Self {
+        Self {
+            symbol: trade.symbol.clone(),
+            exit_time: trade.exit_time,
+            pnl: trade.pnl,
+            pnl_ratio: trade.pnl_percent / 100.0,
+            exit_reason: trade.exit_reason,
+        }
+    }
+}
+
+/// Per-pair state of a portfolio backtest
+struct PortfolioPair<'a, T> {
+    symbol: String,
+    candles: &'a [Candle],
+    strategy: T,
+    informative: InformativeData,
+    atr: Option<ATR>,
+}
+
+/// Circuit breaker kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitBreakerKind {
+    /// Daily loss limit hit, entries halted until the next day
+    DailyLoss,
+    /// Drawdown limit hit, entries halted for the rest of the run
+    MaxDrawdown,
+}
+
+/// Circuit breaker trip record
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerEvent {
+    pub kind: CircuitBreakerKind,
+    pub time: DateTime<Utc>,
+    /// Balance when the breaker tripped
+    pub balance: f64,
+    /// Reference balance (day start or peak) the loss was measured against
+    pub reference_balance: f64,
 }
 
 /// Backtesting engine
@@ -43,6 +158,23 @@ pub struct BacktestEngine {
     balance: Balance,
     positions: Vec<Position>,
     trades: Vec<Trade>,
+    risk_manager: RiskManager,
+    sizer: Box<dyn PositionSizer>,
+    atr: Option<ATR>,
+    informative: InformativeData,
+    informative_candles: Vec<Candle>,
+    peak_balance: f64,
+    day_start_balance: f64,
+    current_day: Option<NaiveDate>,
+    halted_for_day: bool,
+    halted: bool,
+    rejected_entries: usize,
+    circuit_breakers: Vec<CircuitBreakerEvent>,
+    protections: ProtectionManager,
+    locked_entries: usize,
+    protection_locks: Vec<ProtectionLock>,
+    /// Strategy limit orders resting until the next candle, by symbol
+    resting_orders: HashMap<String, Vec<LimitOrder>>,
 }
 
 /// Trade record
@@ -57,21 +189,87 @@ pub struct Trade {
     pub quantity: f64,
     pub pnl: f64,
     pub pnl_percent: f64,
+    /// Tag of the entry signal
+    pub enter_tag: Option<String>,
+    /// Tag of the exit signal or custom exit
+    pub exit_tag: Option<String>,
+    pub exit_reason: ExitReason,
+    /// Entry and exit fills, including DCA entries and partial exits
+    pub orders: Vec<PositionOrder>,
+}
+
+impl Trade {
+    /// Exit reason as reported, using the exit tag for tagged signal exits
+    pub fn exit_label(&self) -> String {
+        match (&self.exit_reason, &self.exit_tag) {
+            (ExitReason::Signal | ExitReason::CustomExit, Some(tag)) => tag.clone(),
+            (reason, _) => reason.to_string(),
+        }
+    }
 }
 
 impl BacktestEngine {
-    /// Create new backtest engine
+    /// Create new backtest engine with default risk limits
     pub fn new(initial_balance: f64) -> Self {
+        Self::with_risk_config(initial_balance, RiskConfig::default())
+    }
+
+    /// Create new backtest engine with custom risk limits
+    ///
+    /// Positions are sized with the default [`PositionSizing`] (a percentage of equity,
+    /// like strategy configs without a sizing model) until another model is set with
+    /// [`BacktestEngine::with_position_sizing`].
+    pub fn with_risk_config(initial_balance: f64, risk_config: RiskConfig) -> Self {
+        let sizing = PositionSizing::default();
         Self {
             initial_balance,
             balance: Balance::new(initial_balance),
             positions: Vec::new(),
             trades: Vec::new(),
+            risk_manager: RiskManager::new(risk_config),
+            sizer: sizing.build(),
+            atr: None,
+            informative: InformativeData::default(),
+            informative_candles: Vec::new(),
+            peak_balance: initial_balance,
+            day_start_balance: initial_balance,
+            current_day: None,
+            halted_for_day: false,
+            halted: false,
+            rejected_entries: 0,
+            circuit_breakers: Vec::new(),
+            protections: ProtectionManager::default(),
+            locked_entries: 0,
+            protection_locks: Vec::new(),
+            resting_orders: HashMap::new(),
         }
     }
 
+    /// Use the given position sizing model, failing on invalid parameters
+    pub fn with_position_sizing(mut self, sizing: &PositionSizing) -> Result<Self> {
+        sizing.validate()?;
+        self.sizer = sizing.build();
+        self.atr = self.sizer.atr_period().map(ATR::new);
+        Ok(self)
+    }
+
+    /// Lock pairs with the given protections after adverse trades
+    pub fn with_protections(mut self, protections: Vec<Protection>) -> Self {
+        self.protections = ProtectionManager::new(protections);
+        self
+    }
+
+    /// Provide candles of other pairs for the strategy's informative specs
+    ///
+    /// Higher timeframes of the backtested pair are resampled from its own candles.
+    pub fn with_informative_candles(mut self, mut candles: Vec<Candle>) -> Self {
+        candles.sort_by_key(|c| c.timestamp);
+        self.informative_candles = candles;
+        self
+    }
+
     /// Run backtest
-    pub fn run<T: Strategy>(
+    pub fn run<T: Strategy + ?Sized>(
         &mut self,
         strategy: &mut T,
         candles: &CandleSeries,
@@ -79,65 +277,442 @@ impl BacktestEngine {
         // Initialize strategy
         strategy.initialize(candles.candles())?;
 
+        let base_symbol = candles.candles().first().map(|c| c.symbol.clone()).unwrap_or_default();
+        self.informative = InformativeData::new(&strategy.informative(), &base_symbol);
+        let mut informative_index = 0;
+
         // Process each candle
         for candle in candles.candles() {
-            if !strategy.is_ready() {
-                continue;
+            // Feed informative candles closed by the end of this candle so only closed buckets are visible
+            while let Some(other) = self.informative_candles.get(informative_index) {
+                if other.close_time() > candle.close_time() {
+                    break;
+                }
+                self.informative.feed(other);
+                informative_index += 1;
             }
+            self.informative.feed(candle);
 
-            // Update existing positions
-            self.update_positions(candle);
-
-            // Generate signal
-            let signal = strategy.process(candle)?;
-
-            // Execute signal
-            self.execute_signal(&signal, candle)?;
+            self.step(strategy, candle, true)?;
         }
 
         // Close all remaining positions
-        self.close_all_positions(candles.candles().last().unwrap());
+        self.close_all_positions(strategy, candles.candles().last().unwrap());
 
         // Calculate results
         self.calculate_results()
     }
 
-    /// Update positions with new candle price
-    fn update_positions(&mut self, candle: &Candle) {
-        for position in &mut self.positions {
+    /// Run a portfolio backtest across the pairs selected by a dynamic pairlist
+    ///
+    /// Each pair gets its own strategy instance from `make_strategy`. The pairlist is
+    /// refreshed from the candles closed so far; pairs that drop out of it stop
+    /// opening trades but their open positions are still managed until they exit.
+    pub fn run_portfolio<T, F>(
+        &mut self,
+        mut make_strategy: F,
+        series: &[CandleSeries],
+        pairlist: &mut Pairlist,
+    ) -> Result<BacktestResult>
+    where
+        T: Strategy,
+        F: FnMut(&str) -> T,
+    {
+        let mut pairs = Vec::new();
+        for candles in series.iter().filter(|s| !s.is_empty()) {
+            let symbol = candles.candles()[0].symbol.clone();
+            let mut strategy = make_strategy(&symbol);
+            strategy.initialize(candles.candles())?;
+            let informative = InformativeData::new(&strategy.informative(), &symbol);
+            let atr = self.sizer.atr_period().map(ATR::new);
+            pairs.push(PortfolioPair {
+                symbol,
+                candles: candles.candles(),
+                strategy,
+                informative,
+                atr,
+            });
+        }
+
+        // Replay all pairs on one timeline
+        let mut timeline: Vec<(DateTime<Utc>, usize, usize)> = pairs
+            .iter()
+            .enumerate()
+            .flat_map(|(pair, p)| p.candles.iter().enumerate().map(move |(i, c)| (c.timestamp, pair, i)))
+            .collect();
+        timeline.sort();
+
+        let mut informative_index = 0;
+        let mut start = 0;
+        while start < timeline.len() {
+            let time = timeline[start].0;
+            let end = start + timeline[start..].partition_point(|entry| entry.0 == time);
+
+            if pairlist.needs_refresh(time) {
+                let stats: Vec<PairStats> = pairs
+                    .iter()
+                    .filter_map(|p| PairStats::from_candles(&p.symbol, p.candles, time))
+                    .collect();
+                let selected = pairlist.refresh(&stats, time);
+                tracing::debug!("Pairlist refreshed at {}: {:?}", time, selected);
+            }
+
+            // Feed every pair's informative candles closed by the end of this step's candles
+            let close_time = timeline[start..end]
+                .iter()
+                .map(|&(_, pair, index)| pairs[pair].candles[index].close_time())
+                .max()
+                .unwrap_or(time);
+            while let Some(other) = self.informative_candles.get(informative_index) {
+                if other.close_time() > close_time {
+                    break;
+                }
+                pairs.iter_mut().for_each(|p| p.informative.feed(other));
+                informative_index += 1;
+            }
+            for &(_, pair, index) in &timeline[start..end] {
+                let candles = pairs[pair].candles;
+                pairs.iter_mut().for_each(|p| p.informative.feed(&candles[index]));
+            }
+
+            for &(_, pair, index) in &timeline[start..end] {
+                let p = &mut pairs[pair];
+                let allow_entries = pairlist.contains(&p.symbol);
+                std::mem::swap(&mut self.informative, &mut p.informative);
+                std::mem::swap(&mut self.atr, &mut p.atr);
+                let result = self.step(&mut p.strategy, &p.candles[index], allow_entries);
+                std::mem::swap(&mut self.informative, &mut p.informative);
+                std::mem::swap(&mut self.atr, &mut p.atr);
+                result?;
+            }
+            start = end;
+        }
+
+        // Close remaining positions at each pair's last candle
+        for p in pairs.iter_mut() {
+            let last = &p.candles[p.candles.len() - 1];
+            for side in [PositionSide::Long, PositionSide::Short] {
+                self.close_positions_for_symbol(&mut p.strategy, &p.symbol, side, last, ExitReason::Forced, None);
+            }
+        }
+
+        self.calculate_results()
+    }
+
+    /// Process one candle: update positions and breakers, fill resting limit orders, then act
+    /// on the strategy signal and take the strategy's limit orders for the next candle
+    ///
+    /// With `allow_entries` false, entry signals and limit buys are ignored but exits still apply.
+    fn step<T: Strategy + ?Sized>(&mut self, strategy: &mut T, candle: &Candle, allow_entries: bool) -> Result<()> {
+        if !strategy.is_ready() {
+            return Ok(());
+        }
+
+        if let Some(atr) = self.atr.as_mut() {
+            atr.update_candle(candle);
+        }
+
+        // Roll daily loss window
+        self.start_day_if_needed(candle);
+
+        // Update existing positions
+        self.update_positions(strategy, candle);
+        self.check_circuit_breakers(candle);
+        self.fill_limit_orders(strategy, candle, allow_entries);
+
+        // Generate signal
+        let signal = strategy.process_with_informative(candle, &self.informative)?;
+
+        // Execute signal
+        if allow_entries || !signal.signal_type.is_entry() {
+            self.execute_signal(strategy, &signal, candle)?;
+        }
+
+        self.resting_orders.insert(candle.symbol.clone(), strategy.limit_orders());
+        Ok(())
+    }
+
+    /// Fill the resting limit orders of the candle's symbol reached by its range
+    ///
+    /// Bullish candles are assumed to trade down to the low before the high, bearish
+    /// candles up to the high first, which sets the order of buy and sell fills.
+    fn fill_limit_orders<T: Strategy + ?Sized>(&mut self, strategy: &mut T, candle: &Candle, allow_entries: bool) {
+        let Some(orders) = self.resting_orders.remove(&candle.symbol) else {
+            return;
+        };
+        let (mut buys, mut sells): (Vec<LimitOrder>, Vec<LimitOrder>) = orders
+            .into_iter()
+            .filter(|order| order.is_hit(candle.low, candle.high))
+            .partition(|order| order.side == OrderSide::Buy);
+        buys.sort_by(|a, b| b.price.total_cmp(&a.price));
+        sells.sort_by(|a, b| a.price.total_cmp(&b.price));
+        let fills = if candle.is_bearish() {
+            sells.into_iter().chain(buys)
+        } else {
+            buys.into_iter().chain(sells)
+        };
+
+        for order in fills {
+            let price = order.fill_price(candle.open);
+            let filled = match order.side {
+                OrderSide::Buy => allow_entries && self.fill_limit_buy(&order, price, candle),
+                OrderSide::Sell => self.fill_limit_sell(&order, price, candle),
+            };
+            if filled {
+                strategy.on_order_filled(&order, price, candle.timestamp);
+            }
+        }
+    }
+
+    /// Open or add to the long position of the candle's symbol if breakers and risk limits allow it
+    fn fill_limit_buy(&mut self, order: &LimitOrder, price: f64, candle: &Candle) -> bool {
+        if self.is_trading_halted() {
+            self.rejected_entries += 1;
+            return false;
+        }
+        if self.protections.lock_for(&candle.symbol, candle.timestamp).is_some() {
+            self.locked_entries += 1;
+            return false;
+        }
+
+        let value = price * order.quantity;
+        let index = self
+            .positions
+            .iter()
+            .position(|p| p.symbol == candle.symbol && p.side == PositionSide::Long);
+        // An existing position already counts towards the open positions limit
+        let (position_value, open_positions) = match index {
+            Some(index) => (self.positions[index].entry_value() + value, self.positions.len() - 1),
+            None => (value, self.positions.len()),
+        };
+        if value > self.balance.available
+            || !self.risk_manager.can_open_position(&self.balance, position_value, open_positions)
+        {
+            self.rejected_entries += 1;
+            return false;
+        }
+
+        match index {
+            Some(index) => self.positions[index].increase(price, order.quantity, candle.timestamp),
+            None => {
+                let mut position = Position::new(
+                    Uuid::new_v4().to_string(),
+                    candle.symbol.clone(),
+                    PositionSide::Long,
+                    price,
+                    order.quantity,
+                );
+                position.set_entry_time(candle.timestamp);
+                position.enter_tag = order.tag.clone();
+                self.positions.push(position);
+            }
+        }
+        let in_positions = self.balance.in_positions + value;
+        self.balance.update(self.balance.total, in_positions);
+        true
+    }
+
+    /// Reduce the long position of the candle's symbol, closing it when the order sells everything
+    fn fill_limit_sell(&mut self, order: &LimitOrder, price: f64, candle: &Candle) -> bool {
+        let Some(index) = self
+            .positions
+            .iter()
+            .position(|p| p.symbol == candle.symbol && p.side == PositionSide::Long)
+        else {
+            return false;
+        };
+
+        if order.quantity >= self.positions[index].quantity * (1.0 - 1e-9) {
+            self.close_position(index, price, candle.timestamp, ExitReason::Signal, order.tag.as_deref());
+        } else {
+            self.reduce_position(index, order.quantity, price, candle.timestamp);
+        }
+        true
+    }
+
+    /// Reset the daily loss reference when a new day starts
+    fn start_day_if_needed(&mut self, candle: &Candle) {
+        let day = candle.timestamp.date_naive();
+        if self.current_day != Some(day) {
+            self.current_day = Some(day);
+            self.day_start_balance = self.balance.total;
+            self.halted_for_day = false;
+        }
+    }
+
+    /// Update positions with new candle price and close those hitting stop loss or take profit
+    ///
+    /// Also applies the strategy's `custom_stoploss`, `custom_exit` and `adjust_trade_position`
+    /// callbacks at the candle close.
+    fn update_positions<T: Strategy + ?Sized>(&mut self, strategy: &mut T, candle: &Candle) {
+        let use_stop_loss = self.risk_manager.config().use_stop_loss;
+        let use_take_profit = self.risk_manager.config().use_take_profit;
+
+        let mut exits = Vec::new();
+        let mut adjustments = Vec::new();
+        for (index, position) in self.positions.iter_mut().enumerate() {
+            if position.symbol != candle.symbol {
+                continue;
+            }
+            let (adverse, favorable) = match position.side {
+                PositionSide::Long => (candle.low, candle.high),
+                PositionSide::Short => (candle.high, candle.low),
+            };
+
+            position.update_price(adverse);
+            if use_stop_loss && position.is_stop_loss_hit() {
+                let reason = position.stop_exit_reason();
+                if strategy.confirm_trade_exit(position, reason, candle) {
+                    exits.push((index, position.stop_loss.unwrap(), reason, None));
+                    continue;
+                }
+            }
+
+            position.update_price(favorable);
+            if use_take_profit
+                && position.is_take_profit_hit()
+                && strategy.confirm_trade_exit(position, ExitReason::Roi, candle)
+            {
+                exits.push((index, position.take_profit.unwrap(), ExitReason::Roi, None));
+                continue;
+            }
+            position.update_trailing_stop(favorable);
+
             position.update_price(candle.close);
+            if let Some(stop) = strategy.custom_stoploss(position, candle) {
+                position.apply_custom_stoploss(stop);
+            }
+            if let Some(tag) = strategy.custom_exit(position, candle) {
+                if strategy.confirm_trade_exit(position, ExitReason::CustomExit, candle) {
+                    exits.push((index, candle.close, ExitReason::CustomExit, Some(tag)));
+                    continue;
+                }
+            }
+
+            match strategy.adjust_trade_position(position, candle) {
+                Some(stake) if stake > 0.0 => adjustments.push((index, stake)),
+                Some(stake) if stake < 0.0 => {
+                    if !strategy.confirm_trade_exit(position, ExitReason::PartialExit, candle) {
+                        continue;
+                    }
+                    if -stake / candle.close >= position.quantity {
+                        exits.push((index, candle.close, ExitReason::PartialExit, None));
+                    } else {
+                        adjustments.push((index, stake));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Adjust before closing so indices stay valid
+        for (index, stake) in adjustments {
+            if stake > 0.0 {
+                self.increase_position(index, stake, candle);
+            } else {
+                self.reduce_position(index, -stake / candle.close, candle.close, candle.timestamp);
+            }
+        }
+        for (index, exit_price, reason, tag) in exits.into_iter().rev() {
+            self.close_position(index, exit_price, candle.timestamp, reason, tag.as_deref());
         }
     }
 
+    /// Trip daily loss and drawdown breakers based on realized balance
+    fn check_circuit_breakers(&mut self, candle: &Candle) {
+        let total = self.balance.total;
+        if total > self.peak_balance {
+            self.peak_balance = total;
+        }
+
+        if !self.halted && self.risk_manager.is_drawdown_exceeded(self.peak_balance, total) {
+            self.halted = true;
+            warn!(
+                "Max drawdown breaker tripped at {}: balance {:.2}, peak {:.2}",
+                candle.timestamp, total, self.peak_balance
+            );
+            self.circuit_breakers.push(CircuitBreakerEvent {
+                kind: CircuitBreakerKind::MaxDrawdown,
+                time: candle.timestamp,
+                balance: total,
+                reference_balance: self.peak_balance,
+            });
+        }
+
+        if !self.halted
+            && !self.halted_for_day
+            && self.risk_manager.is_daily_loss_exceeded(self.day_start_balance, total)
+        {
+            self.halted_for_day = true;
+            warn!(
+                "Daily loss breaker tripped at {}: balance {:.2}, day start {:.2}",
+                candle.timestamp, total, self.day_start_balance
+            );
+            self.circuit_breakers.push(CircuitBreakerEvent {
+                kind: CircuitBreakerKind::DailyLoss,
+                time: candle.timestamp,
+                balance: total,
+                reference_balance: self.day_start_balance,
+            });
+        }
+    }
+
+    /// Check whether breakers currently block new entries
+    fn is_trading_halted(&self) -> bool {
+        self.halted || self.halted_for_day
+    }
+
     /// Execute trading signal
-    fn execute_signal(&mut self, signal: &Signal, candle: &Candle) -> Result<()> {
+    fn execute_signal<T: Strategy + ?Sized>(
+        &mut self,
+        strategy: &mut T,
+        signal: &Signal,
+        candle: &Candle,
+    ) -> Result<()> {
         match signal.signal_type {
-            SignalType::Buy => {
+            SignalType::EnterLong => {
                 // Close short positions first
-                self.close_positions_for_symbol(&candle.symbol, PositionSide::Short, candle);
-
-                // Open long position
-                if let Some(entry_price) = signal.entry_price {
-                    let quantity = self.calculate_position_size(entry_price);
-                    if quantity > 0.0 {
-                        let mut position = Position::new(
-                            Uuid::new_v4().to_string(),
-                            candle.symbol.clone(),
-                            PositionSide::Long,
-                            entry_price,
-                            quantity,
-                        );
-                        position.set_stop_loss(signal.stop_loss.unwrap_or(entry_price * 0.95));
-                        position.set_take_profit(signal.take_profit.unwrap_or(entry_price * 1.05));
-
-                        self.positions.push(position);
-                        self.balance.in_positions += entry_price * quantity;
-                    }
-                }
+                self.close_positions_for_symbol(
+                    strategy,
+                    &candle.symbol,
+                    PositionSide::Short,
+                    candle,
+                    ExitReason::Signal,
+                    None,
+                );
+                self.open_position(strategy, signal, candle, PositionSide::Long);
+            }
+            SignalType::EnterShort => {
+                // Close long positions first
+                self.close_positions_for_symbol(
+                    strategy,
+                    &candle.symbol,
+                    PositionSide::Long,
+                    candle,
+                    ExitReason::Signal,
+                    None,
+                );
+                self.open_position(strategy, signal, candle, PositionSide::Short);
             }
-            SignalType::Sell => {
-                // Close long positions
-                self.close_positions_for_symbol(&candle.symbol, PositionSide::Long, candle);
+            SignalType::ExitLong => {
+                self.close_positions_for_symbol(
+                    strategy,
+                    &candle.symbol,
+                    PositionSide::Long,
+                    candle,
+                    ExitReason::Signal,
+                    signal.exit_tag.as_deref(),
+                );
+            }
+            SignalType::ExitShort => {
+                self.close_positions_for_symbol(
+                    strategy,
+                    &candle.symbol,
+                    PositionSide::Short,
+                    candle,
+                    ExitReason::Signal,
+                    signal.exit_tag.as_deref(),
+                );
             }
             SignalType::Hold => {
                 // Do nothing
@@ -147,54 +722,203 @@ impl BacktestEngine {
         Ok(())
     }
 
+    /// Open a position for an entry signal if breakers, risk limits and the strategy allow it
+    fn open_position<T: Strategy + ?Sized>(
+        &mut self,
+        strategy: &mut T,
+        signal: &Signal,
+        candle: &Candle,
+        side: PositionSide,
+    ) {
+        let Some(entry_price) = signal.entry_price else {
+            return;
+        };
+        if self.is_trading_halted() {
+            self.rejected_entries += 1;
+            return;
+        }
+        if self.protections.lock_for(&candle.symbol, candle.timestamp).is_some() {
+            self.locked_entries += 1;
+            return;
+        }
+
+        let (default_stop, default_take_profit) = self.risk_manager.config().default_exit_levels(side, entry_price);
+        let stop_loss = signal.stop_loss.unwrap_or(default_stop);
+        let quantity = self.calculate_position_size(entry_price, stop_loss);
+        let position_value = entry_price * quantity;
+        if quantity <= 0.0
+            || !self.risk_manager.can_open_position(
+                &self.balance,
+                position_value,
+                self.positions.len(),
+            )
+        {
+            self.rejected_entries += 1;
+            return;
+        }
+        if !strategy.confirm_trade_entry(signal, candle) {
+            return;
+        }
+
+        let mut position = Position::new(
+            Uuid::new_v4().to_string(),
+            candle.symbol.clone(),
+            side,
+            entry_price,
+            quantity,
+        );
+        position.set_entry_time(candle.timestamp);
+        position.enter_tag = signal.enter_tag.clone();
+        position.set_stop_loss(stop_loss);
+        position.set_take_profit(signal.take_profit.unwrap_or(default_take_profit));
+        if let Some(distance) = signal.trailing_stop {
+            position.set_trailing_stop(distance);
+        }
+
+        self.positions.push(position);
+        let in_positions = self.balance.in_positions + position_value;
+        self.balance.update(self.balance.total, in_positions);
+    }
+
+    /// Add `stake` to an open position (DCA entry) if breakers and risk limits allow it
+    fn increase_position(&mut self, index: usize, stake: f64, candle: &Candle) {
+        let stake = stake.min(self.balance.available);
+        let position_value = self.positions[index].entry_value() + stake;
+        // The position already counts towards the open positions limit
+        if self.is_trading_halted()
+            || stake <= 0.0
+            || !self.risk_manager.can_open_position(
+                &self.balance,
+                position_value,
+                self.positions.len() - 1,
+            )
+        {
+            self.rejected_entries += 1;
+            return;
+        }
+
+        self.positions[index].increase(candle.close, stake / candle.close, candle.timestamp);
+        let in_positions = self.balance.in_positions + stake;
+        self.balance.update(self.balance.total, in_positions);
+    }
+
+    /// Exit part of an open position at `price` and realize its P&L
+    fn reduce_position(&mut self, index: usize, quantity: f64, price: f64, time: DateTime<Utc>) {
+        let position = &mut self.positions[index];
+        let released = position.entry_price * quantity.min(position.quantity);
+        let pnl = position.decrease(price, quantity, time);
+
+        let total = self.balance.total + pnl;
+        let in_positions = (self.balance.in_positions - released).max(0.0);
+        self.balance.update(total, in_positions);
+    }
+
     /// Close positions for symbol and side
-    fn close_positions_for_symbol(
+    ///
+    /// Exits vetoed by the strategy's `confirm_trade_exit` keep their position open.
+    fn close_positions_for_symbol<T: Strategy + ?Sized>(
         &mut self,
+        strategy: &mut T,
         symbol: &str,
         side: PositionSide,
         candle: &Candle,
+        reason: ExitReason,
+        exit_tag: Option<&str>,
     ) {
         let positions_to_close: Vec<_> = self
             .positions
             .iter()
             .enumerate()
             .filter(|(_, p)| p.symbol == symbol && p.side == side)
+            .filter(|(_, p)| !reason.is_vetoable() || strategy.confirm_trade_exit(p, reason, candle))
             .map(|(i, _)| i)
             .collect();
 
         for &index in positions_to_close.iter().rev() {
-            let position = self.positions.remove(index);
-            let trade = Trade {
-                entry_time: position.entry_time,
-                exit_time: candle.timestamp,
-                symbol: position.symbol.clone(),
-                side: position.side,
-                entry_price: position.entry_price,
-                exit_price: candle.close,
-                quantity: position.quantity,
-                pnl: position.unrealized_pnl,
-                pnl_percent: position.unrealized_pnl_percent,
-            };
+            self.close_position(index, candle.close, candle.timestamp, reason, exit_tag);
+        }
+    }
+
+    /// Close position at index, record trade and release its balance
+    fn close_position(
+        &mut self,
+        index: usize,
+        exit_price: f64,
+        exit_time: DateTime<Utc>,
+        exit_reason: ExitReason,
+        exit_tag: Option<&str>,
+    ) {
+        let mut position = self.positions.remove(index);
+        let released = position.entry_value();
+        let remaining = position.quantity;
+        let pnl = position.decrease(exit_price, remaining, exit_time);
+
+        // Trade totals span every fill, so DCA entries and partial exits are averaged in
+        let (quantity, entry_value) = position.filled(PositionOrderKind::Entry);
+        let (_, exit_value) = position.filled(PositionOrderKind::Exit);
+        let trade = Trade {
+            entry_time: position.entry_time,
+            exit_time,
+            symbol: position.symbol.clone(),
+            side: position.side,
+            entry_price: entry_value / quantity,
+            exit_price: exit_value / quantity,
+            quantity,
+            pnl: position.realized_pnl,
+            pnl_percent: position.realized_pnl / entry_value * 100.0,
+            enter_tag: position.enter_tag.clone(),
+            exit_tag: exit_tag.map(str::to_string),
+            exit_reason,
+            orders: position.orders,
+        };
+
+        let symbol = trade.symbol.clone();
+        self.trades.push(trade);
+        let total = self.balance.total + pnl;
+        let in_positions = (self.balance.in_positions - released).max(0.0);
+        self.balance.update(total, in_positions);
 
-            self.trades.push(trade);
-            self.balance.total += position.unrealized_pnl;
-            self.balance.in_positions -= position.entry_value();
+        if !self.protections.is_empty() {
+            let trades: Vec<ProtectionTrade> = self.trades.iter().map(ProtectionTrade::from).collect();
+            let locks = self.protections.on_trade_closed(&trades, &symbol, exit_time, total);
+            self.protection_locks.extend(locks);
         }
     }
 
     /// Close all positions
-    fn close_all_positions(&mut self, candle: &Candle) {
+    fn close_all_positions<T: Strategy + ?Sized>(&mut self, strategy: &mut T, candle: &Candle) {
         let symbols: Vec<_> = self.positions.iter().map(|p| p.symbol.clone()).collect();
         for symbol in symbols {
-            self.close_positions_for_symbol(&symbol, PositionSide::Long, candle);
-            self.close_positions_for_symbol(&symbol, PositionSide::Short, candle);
+            for side in [PositionSide::Long, PositionSide::Short] {
+                self.close_positions_for_symbol(
+                    strategy,
+                    &symbol,
+                    side,
+                    candle,
+                    ExitReason::Forced,
+                    None,
+                );
+            }
         }
     }
 
-    /// Calculate position size
-    fn calculate_position_size(&self, entry_price: f64) -> f64 {
-        let max_position_value = self.balance.available * 0.1; // 10% of available
-        max_position_value / entry_price
+    /// Calculate position size with the sizing model, capped by max position size and available balance
+    fn calculate_position_size(&self, entry_price: f64, stop_loss: f64) -> f64 {
+        let recent_returns: Vec<f64> = self.trades.iter().map(|t| t.pnl_percent / 100.0).collect();
+        let ctx = SizingContext {
+            equity: self.balance.total,
+            available: self.balance.available,
+            entry_price,
+            stop_loss: Some(stop_loss),
+            atr: self.atr.as_ref().and_then(|atr| atr.value()),
+            recent_returns: &recent_returns,
+        };
+        let quantity = self.sizer.quantity(&ctx);
+
+        let max_position_value = self.balance.total * self.risk_manager.config().max_position_size;
+        quantity
+            .min(max_position_value / entry_price)
+            .min(self.balance.available / entry_price)
     }
 
     /// Calculate backtest results
@@ -280,7 +1004,692 @@ impl BacktestEngine {
             avg_loss,
             max_drawdown,
             sharpe_ratio,
+            rejected_entries: self.rejected_entries,
+            circuit_breakers: self.circuit_breakers.clone(),
+            locked_entries: self.locked_entries,
+            protection_locks: self.protection_locks.clone(),
+            pair_stats: GroupStats::from_trades(&self.trades, |t| t.symbol.clone()),
+            enter_tag_stats: GroupStats::from_trades(&self.trades, |t| {
+                t.enter_tag.clone().unwrap_or_else(|| "untagged".to_string())
+            }),
+            exit_reason_stats: GroupStats::from_trades(&self.trades, Trade::exit_label),
         })
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::BacktestReport;
+    use crate::data::{InformativeSpec, PairlistConfig, PairlistSource};
+    use chrono::{Duration, TimeZone};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Strategy that replays a fixed list of signals, one per candle
+    struct ScriptedStrategy {
+        signals: Vec<Signal>,
+        index: usize,
+    }
+
+    impl ScriptedStrategy {
+        fn new(signals: Vec<Signal>) -> Self {
+            Self { signals, index: 0 }
+        }
+    }
+
+    impl Strategy for ScriptedStrategy {
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+
+        fn initialize(&mut self, _candles: &[Candle]) -> Result<()> {
+            Ok(())
+        }
+
+        fn process(&mut self, _candle: &Candle) -> Result<Signal> {
+            let signal = self
+                .signals
+                .get(self.index)
+                .cloned()
+                .unwrap_or_else(|| Signal::hold("end of script".to_string()));
+            self.index += 1;
+            Ok(signal)
+        }
+
+        fn is_ready(&self) -> bool {
+            true
+        }
+    }
+
+    /// Strategy that records the latest closed 1h candle seen at each 15m candle
+    /// Candle time and the latest closed 1h bucket visible at it
+    type Seen = Vec<(DateTime<Utc>, Option<DateTime<Utc>>)>;
+
+    /// Shares what it saw so portfolio runs, which own their strategies, can be inspected
+    struct InformativeProbe {
+        seen: Rc<RefCell<Seen>>,
+    }
+
+    impl Strategy for InformativeProbe {
+        fn name(&self) -> &str {
+            "InformativeProbe"
+        }
+
+        fn initialize(&mut self, _candles: &[Candle]) -> Result<()> {
+            Ok(())
+        }
+
+        fn process(&mut self, _candle: &Candle) -> Result<Signal> {
+            unreachable!("engine should call process_with_informative")
+        }
+
+        fn is_ready(&self) -> bool {
+            true
+        }
+
+        fn informative(&self) -> Vec<InformativeSpec> {
+            vec![InformativeSpec::new("1h"), InformativeSpec::for_symbol("ETH/USDT", "1h")]
+        }
+
+        fn process_with_informative(
+            &mut self,
+            candle: &Candle,
+            informative: &InformativeData,
+        ) -> Result<Signal> {
+            let own = informative.latest("BTC/USDT", "1h").map(|c| c.timestamp);
+            let other = informative.latest("ETH/USDT", "1h").map(|c| c.timestamp);
+            assert_eq!(own, other);
+            self.seen.borrow_mut().push((candle.timestamp, own));
+            Ok(Signal::hold("probe".to_string()))
+        }
+    }
+
+    fn candle(hours: i64, low: f64, close: f64) -> Candle {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        Candle::new(
+            close,
+            close.max(low) + 1.0,
+            low,
+            close,
+            1000.0,
+            start + Duration::hours(hours),
+            "BTC/USDT".to_string(),
+            "1h".to_string(),
+        )
+    }
+
+    fn buy(price: f64) -> Signal {
+        Signal::buy(price, 1.0, "test".to_string())
+            .with_stop_loss(price * 0.95)
+            .with_take_profit(price * 2.0)
+    }
+
+    #[test]
+    fn test_risk_based_position_size() {
+        let config = RiskConfig {
+            max_position_size: 0.5,
+            ..RiskConfig::default()
+        };
+        let mut engine = BacktestEngine::with_risk_config(10000.0, config)
+            .with_position_sizing(&PositionSizing::RiskBased { risk_per_trade: 0.01 })
+            .unwrap();
+        let mut strategy = ScriptedStrategy::new(vec![buy(100.0)]);
+        let series = CandleSeries::from_vec(vec![candle(0, 99.0, 100.0), candle(1, 99.0, 100.0)]);
+
+        engine.run(&mut strategy, &series).unwrap();
+
+        // 1% of 10000 risked over a 5.0 stop distance = 20 units
+        assert_eq!(engine.trades.len(), 1);
+        assert!((engine.trades[0].quantity - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_informative_candles_are_lookahead_safe() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let quarter = |symbol: &str, i: i64| {
+            Candle::new(
+                100.0,
+                101.0,
+                99.0,
+                100.0,
+                1.0,
+                start + Duration::minutes(15 * i),
+                symbol.to_string(),
+                "15m".to_string(),
+            )
+        };
+        let series = CandleSeries::from_vec((0..8).map(|i| quarter("BTC/USDT", i)).collect());
+        let eth: Vec<Candle> = (0..8).map(|i| quarter("ETH/USDT", i)).collect();
+
+        let mut engine = BacktestEngine::new(10000.0).with_informative_candles(eth);
+        let seen = Rc::default();
+        let mut strategy = InformativeProbe { seen: Rc::clone(&seen) };
+        engine.run(&mut strategy, &series).unwrap();
+        let seen = seen.borrow();
+
+        // The 00:00 hour closes with the 00:45 candle and not before
+        assert_eq!(seen[2].1, None);
+        assert_eq!(seen[3].1, Some(start));
+        assert_eq!(seen[6].1, Some(start));
+        assert_eq!(seen[7].1, Some(start + Duration::hours(1)));
+        for (time, latest) in seen.iter() {
+            if let Some(latest) = latest {
+                assert!(*latest + Duration::hours(1) <= *time + Duration::minutes(15));
+            }
+        }
+    }
+
+    #[test]
+    fn test_higher_timeframe_informative_candles_wait_for_their_close() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let candle = |symbol: &str, timeframe: &str, minutes: i64| {
+            Candle::new(
+                100.0,
+                101.0,
+                99.0,
+                100.0,
+                1.0,
+                start + Duration::minutes(minutes),
+                symbol.to_string(),
+                timeframe.to_string(),
+            )
+        };
+        let series = CandleSeries::from_vec((0..8).map(|i| candle("BTC/USDT", "15m", 15 * i)).collect());
+        let eth: Vec<Candle> = (0..2).map(|i| candle("ETH/USDT", "1h", 60 * i)).collect();
+
+        let mut engine = BacktestEngine::new(10000.0).with_informative_candles(eth);
+        let seen = Rc::default();
+        let mut strategy = InformativeProbe { seen: Rc::clone(&seen) };
+        engine.run(&mut strategy, &series).unwrap();
+        let seen = seen.borrow();
+
+        // The 1h candle opening at 00:00 is not visible during its own hour
+        assert_eq!(seen[0].1, None);
+        assert_eq!(seen[2].1, None);
+        assert_eq!(seen[3].1, Some(start));
+        assert_eq!(seen[6].1, Some(start));
+        assert_eq!(seen[7].1, Some(start + Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_portfolio_informative_candles_wait_for_their_close() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let candle = |symbol: &str, timeframe: &str, minutes: i64| {
+            Candle::new(
+                100.0,
+                101.0,
+                99.0,
+                100.0,
+                1.0,
+                start + Duration::minutes(minutes),
+                symbol.to_string(),
+                timeframe.to_string(),
+            )
+        };
+        let series = vec![CandleSeries::from_vec((0..8).map(|i| candle("BTC/USDT", "15m", 15 * i)).collect())];
+        let eth: Vec<Candle> = (0..2).map(|i| candle("ETH/USDT", "1h", 60 * i)).collect();
+        let mut pairlist = Pairlist::new(PairlistConfig {
+            source: PairlistSource::Static { pairs: vec!["BTC/USDT".to_string()] },
+            filters: Vec::new(),
+            blacklist: Vec::new(),
+            refresh_period_secs: 3600,
+        });
+
+        let seen = Rc::default();
+        let mut engine = BacktestEngine::new(10000.0).with_informative_candles(eth);
+        engine
+            .run_portfolio(|_| InformativeProbe { seen: Rc::clone(&seen) }, &series, &mut pairlist)
+            .unwrap();
+        let seen = seen.borrow();
+
+        // The 1h candle opening at 00:00 is only visible once the 00:45 candle closes its hour
+        assert_eq!(seen[0].1, None);
+        assert_eq!(seen[2].1, None);
+        assert_eq!(seen[3].1, Some(start));
+        assert_eq!(seen[7].1, Some(start + Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_position_sizing_model() {
+        let mut engine = BacktestEngine::new(10000.0)
+            .with_position_sizing(&PositionSizing::FixedStake { stake: 500.0 })
+            .unwrap();
+        let mut strategy = ScriptedStrategy::new(vec![buy(100.0)]);
+        let series = CandleSeries::from_vec(vec![candle(0, 99.0, 100.0), candle(1, 99.0, 100.0)]);
+
+        engine.run(&mut strategy, &series).unwrap();
+
+        // 500 stake at 100.0 = 5 units
+        assert!((engine.trades[0].quantity - 5.0).abs() < 1e-9);
+
+        // Stake above max position size (10% of 10000) is capped
+        let mut engine = BacktestEngine::new(10000.0)
+            .with_position_sizing(&PositionSizing::FixedStake { stake: 5000.0 })
+            .unwrap();
+        let mut strategy = ScriptedStrategy::new(vec![buy(100.0)]);
+        engine.run(&mut strategy, &series).unwrap();
+        assert!((engine.trades[0].quantity - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_max_open_positions_rejects_entries() {
+        let config = RiskConfig {
+            max_open_positions: 1,
+            ..RiskConfig::default()
+        };
+        let mut engine = BacktestEngine::with_risk_config(10000.0, config);
+        let mut strategy = ScriptedStrategy::new(vec![buy(100.0), buy(100.0)]);
+        let series = CandleSeries::from_vec(vec![
+            candle(0, 99.0, 100.0),
+            candle(1, 99.0, 100.0),
+            candle(2, 99.0, 100.0),
+        ]);
+
+        let result = engine.run(&mut strategy, &series).unwrap();
+
+        assert_eq!(result.num_trades, 1);
+        assert_eq!(result.rejected_entries, 1);
+    }
+
+    #[test]
+    fn test_daily_loss_breaker_halts_until_next_day() {
+        let config = RiskConfig {
+            max_position_size: 1.0,
+            max_daily_loss: 0.02,
+            max_drawdown: 0.5,
+            ..RiskConfig::default()
+        };
+        let mut engine = BacktestEngine::with_risk_config(10000.0, config)
+            .with_position_sizing(&PositionSizing::RiskBased { risk_per_trade: 0.05 })
+            .unwrap();
+        let mut signals = vec![buy(100.0), Signal::hold(String::new()), buy(100.0)];
+        signals.resize(24, Signal::hold(String::new()));
+        signals.push(buy(100.0));
+        let mut strategy = ScriptedStrategy::new(signals);
+
+        // Second candle hits the stop, losing 5% of the balance
+        let mut candles = vec![candle(0, 99.0, 100.0), candle(1, 90.0, 94.0)];
+        candles.extend((2..26).map(|h| candle(h, 99.0, 100.0)));
+        let series = CandleSeries::from_vec(candles);
+
+        let result = engine.run(&mut strategy, &series).unwrap();
+
+        assert_eq!(result.circuit_breakers.len(), 1);
+        assert_eq!(result.circuit_breakers[0].kind, CircuitBreakerKind::DailyLoss);
+        assert_eq!(result.rejected_entries, 1);
+        // Entry on the next day is allowed again
+        assert_eq!(result.num_trades, 2);
+    }
+
+    #[test]
+    fn test_cooldown_protection_blocks_entries() {
+        let mut engine = BacktestEngine::new(10000.0)
+            .with_protections(vec![Protection::CooldownPeriod { stop_duration_minutes: 120 }]);
+        let hold = Signal::hold(String::new());
+        let mut strategy = ScriptedStrategy::new(vec![buy(100.0), hold, buy(100.0), buy(100.0)]);
+        // Stop loss at 01:00 locks the pair until 03:00
+        let series = CandleSeries::from_vec(vec![
+            candle(0, 99.0, 100.0),
+            candle(1, 90.0, 94.0),
+            candle(2, 99.0, 100.0),
+            candle(3, 99.0, 100.0),
+        ]);
+
+        let result = engine.run(&mut strategy, &series).unwrap();
+
+        assert_eq!(result.locked_entries, 1);
+        assert_eq!(result.num_trades, 2);
+        // The forced exit at the end does not lock the pair again
+        assert_eq!(result.protection_locks.len(), 1);
+        assert_eq!(result.protection_locks[0].symbol.as_deref(), Some("BTC/USDT"));
+        assert!(BacktestReport::new(result).format().contains("Protection Locks: 1"));
+    }
+
+    #[test]
+    fn test_drawdown_breaker_halts_trading() {
+        let config = RiskConfig {
+            max_position_size: 1.0,
+            max_daily_loss: 1.0,
+            max_drawdown: 0.03,
+            ..RiskConfig::default()
+        };
+        let mut engine = BacktestEngine::with_risk_config(10000.0, config)
+            .with_position_sizing(&PositionSizing::RiskBased { risk_per_trade: 0.05 })
+            .unwrap();
+        let mut signals = vec![buy(100.0), Signal::hold(String::new())];
+        signals.resize(24, Signal::hold(String::new()));
+        signals.push(buy(100.0));
+        let mut strategy = ScriptedStrategy::new(signals);
+
+        let mut candles = vec![candle(0, 99.0, 100.0), candle(1, 90.0, 94.0)];
+        candles.extend((2..26).map(|h| candle(h, 99.0, 100.0)));
+        let series = CandleSeries::from_vec(candles);
+
+        let result = engine.run(&mut strategy, &series).unwrap();
+
+        assert_eq!(result.circuit_breakers.len(), 1);
+        assert_eq!(result.circuit_breakers[0].kind, CircuitBreakerKind::MaxDrawdown);
+        assert_eq!(result.num_trades, 1);
+        assert_eq!(result.rejected_entries, 1);
+        assert!(BacktestReport::new(result).format().contains("Max drawdown limit"));
+    }
+
+    #[test]
+    fn test_short_entries_and_exit_reasons() {
+        let mut engine = BacktestEngine::new(10000.0);
+        let mut strategy = ScriptedStrategy::new(vec![
+            Signal::enter_short(100.0, 1.0, "test".to_string())
+                .with_stop_loss(105.0)
+                .with_take_profit(50.0)
+                .with_enter_tag("breakdown"),
+            Signal::exit_short(99.0, 1.0, "test".to_string()).with_exit_tag("cover"),
+            buy(100.0).with_enter_tag("breakout"),
+        ]);
+        let series = CandleSeries::from_vec(vec![
+            candle(0, 99.0, 100.0),
+            candle(1, 98.0, 99.0),
+            candle(2, 99.0, 100.0),
+            candle(3, 99.0, 101.0),
+        ]);
+
+        let result = engine.run(&mut strategy, &series).unwrap();
+
+        assert_eq!(engine.trades.len(), 2);
+        let short = &engine.trades[0];
+        assert_eq!(short.side, PositionSide::Short);
+        assert!(short.pnl > 0.0);
+        assert_eq!(short.enter_tag.as_deref(), Some("breakdown"));
+        assert_eq!(short.exit_reason, ExitReason::Signal);
+        assert_eq!(short.exit_label(), "cover");
+
+        let long = &engine.trades[1];
+        assert_eq!(long.exit_reason, ExitReason::Forced);
+        assert_eq!(long.exit_label(), "force_exit");
+
+        let mut keys: Vec<_> = result.enter_tag_stats.iter().map(|s| s.key.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["breakdown", "breakout"]);
+        assert!(result.enter_tag_stats[0].total_pnl >= result.enter_tag_stats[1].total_pnl);
+        assert_eq!(result.exit_reason_stats.len(), 2);
+        assert_eq!(result.pair_stats.len(), 1);
+        assert_eq!(result.pair_stats[0].trades, 2);
+        let report = BacktestReport::new(result).format();
+        assert!(report.contains("Enter Tag"));
+        assert!(report.contains("cover"));
+    }
+
+    #[test]
+    fn test_stop_and_trailing_exit_reasons() {
+        let mut engine = BacktestEngine::new(10000.0);
+        let mut strategy = ScriptedStrategy::new(vec![
+            buy(100.0),
+            Signal::hold(String::new()),
+            buy(100.0).with_trailing_stop(0.02),
+        ]);
+        // Stop at 95 is hit, then the stop trailed up to 110 * 0.98 is hit
+        let series = CandleSeries::from_vec(vec![
+            candle(0, 99.0, 100.0),
+            candle(1, 90.0, 94.0),
+            candle(2, 99.0, 100.0),
+            candle(3, 100.0, 109.0),
+            candle(4, 107.0, 107.5),
+        ]);
+
+        let result = engine.run(&mut strategy, &series).unwrap();
+
+        assert_eq!(engine.trades.len(), 2);
+        assert_eq!(engine.trades[0].exit_reason, ExitReason::StopLoss);
+        assert_eq!(engine.trades[1].exit_reason, ExitReason::TrailingStop);
+        assert!((engine.trades[1].exit_price - 110.0 * 0.98).abs() < 1e-9);
+        assert_eq!(result.exit_reason_stats.len(), 2);
+    }
+
+    /// Strategy exercising the trade callbacks around scripted signals
+    struct CallbackStrategy {
+        inner: ScriptedStrategy,
+        entries_seen: usize,
+        vetoed_exits: Vec<ExitReason>,
+    }
+
+    impl Strategy for CallbackStrategy {
+        fn name(&self) -> &str {
+            "Callbacks"
+        }
+
+        fn initialize(&mut self, candles: &[Candle]) -> Result<()> {
+            self.inner.initialize(candles)
+        }
+
+        fn process(&mut self, candle: &Candle) -> Result<Signal> {
+            self.inner.process(candle)
+        }
+
+        fn is_ready(&self) -> bool {
+            true
+        }
+
+        fn custom_stoploss(&mut self, position: &Position, _candle: &Candle) -> Option<f64> {
+            // Move to breakeven once 5% in profit
+            (position.unrealized_pnl_percent >= 5.0).then_some(position.entry_price)
+        }
+
+        fn custom_exit(&mut self, position: &Position, candle: &Candle) -> Option<String> {
+            (candle.timestamp - position.entry_time >= Duration::hours(4))
+                .then(|| "time_exit".to_string())
+        }
+
+        fn confirm_trade_entry(&mut self, _signal: &Signal, _candle: &Candle) -> bool {
+            self.entries_seen += 1;
+            // Reject the first entry only
+            self.entries_seen > 1
+        }
+
+        fn confirm_trade_exit(
+            &mut self,
+            _position: &Position,
+            reason: ExitReason,
+            _candle: &Candle,
+        ) -> bool {
+            if reason == ExitReason::Signal {
+                self.vetoed_exits.push(reason);
+                return false;
+            }
+            true
+        }
+    }
+
+    #[test]
+    fn test_strategy_callbacks() {
+        let mut engine = BacktestEngine::new(10000.0);
+        let mut strategy = CallbackStrategy {
+            inner: ScriptedStrategy::new(vec![
+                buy(100.0),
+                buy(100.0),
+                Signal::sell(100.0, 1.0, "test".to_string()),
+                Signal::hold(String::new()),
+                buy(100.0),
+            ]),
+            entries_seen: 0,
+            vetoed_exits: Vec::new(),
+        };
+        let series = CandleSeries::from_vec(vec![
+            candle(0, 99.0, 100.0),
+            candle(1, 99.0, 100.0),
+            candle(2, 99.0, 106.0),
+            candle(3, 99.5, 104.0),
+            candle(4, 99.0, 100.0),
+            candle(5, 99.0, 100.0),
+            candle(6, 99.0, 100.0),
+            candle(7, 99.0, 100.0),
+            candle(8, 99.0, 100.0),
+            candle(9, 99.0, 100.0),
+        ]);
+
+        engine.run(&mut strategy, &series).unwrap();
+
+        // First entry vetoed, signal exit vetoed, breakeven stop from custom_stoploss hit on the 4th candle
+        assert_eq!(strategy.entries_seen, 3);
+        assert_eq!(strategy.vetoed_exits, vec![ExitReason::Signal]);
+        assert_eq!(engine.trades.len(), 2);
+        assert_eq!(engine.trades[0].exit_reason, ExitReason::CustomStoploss);
+        assert!((engine.trades[0].exit_price - 100.0).abs() < 1e-9);
+        assert_eq!(engine.trades[1].exit_reason, ExitReason::CustomExit);
+        assert_eq!(engine.trades[1].exit_label(), "time_exit");
+        assert_eq!(engine.trades[1].exit_time - engine.trades[1].entry_time, Duration::hours(4));
+    }
+
+    /// Strategy that adds one DCA entry after a 10% drop and scales out half on recovery
+    struct DcaStrategy {
+        inner: ScriptedStrategy,
+    }
+
+    impl Strategy for DcaStrategy {
+        fn name(&self) -> &str {
+            "Dca"
+        }
+
+        fn initialize(&mut self, candles: &[Candle]) -> Result<()> {
+            self.inner.initialize(candles)
+        }
+
+        fn process(&mut self, candle: &Candle) -> Result<Signal> {
+            self.inner.process(candle)
+        }
+
+        fn is_ready(&self) -> bool {
+            true
+        }
+
+        fn adjust_trade_position(&mut self, position: &Position, candle: &Candle) -> Option<f64> {
+            if position.entry_count() == 1 && candle.close <= position.entry_price * 0.9 {
+                return Some(position.entry_value());
+            }
+            if position.entry_count() == 2 && position.realized_pnl == 0.0 && candle.close > position.entry_price {
+                return Some(-position.value() / 2.0);
+            }
+            None
+        }
+    }
+
+    #[test]
+    fn test_portfolio_trades_pairlist_pairs() {
+        let series_for = |symbol: &str, volume: f64| {
+            let candles = (0..3)
+                .map(|h| {
+                    let mut candle = candle(h, 99.0, 100.0);
+                    candle.symbol = symbol.to_string();
+                    candle.volume = volume;
+                    candle
+                })
+                .collect();
+            CandleSeries::from_vec(candles)
+        };
+        let series = vec![
+            series_for("BTC/USDT", 1000.0),
+            series_for("ETH/USDT", 10.0),
+            series_for("XRP/USDT", 500.0),
+            series_for("SOL/USDT", 1.0),
+        ];
+        let mut pairlist = Pairlist::new(PairlistConfig {
+            source: PairlistSource::VolumeTop {
+                number_assets: 2,
+                quote_asset: "USDT".to_string(),
+                min_quote_volume: 0.0,
+            },
+            filters: Vec::new(),
+            blacklist: vec!["XRP/*".to_string()],
+            refresh_period_secs: 3600,
+        });
+        let mut engine = BacktestEngine::new(10000.0);
+
+        let result = engine
+            .run_portfolio(|_| ScriptedStrategy::new(vec![buy(100.0)]), &series, &mut pairlist)
+            .unwrap();
+
+        let mut symbols: Vec<&str> = engine.trades.iter().map(|t| t.symbol.as_str()).collect();
+        symbols.sort();
+        assert_eq!(symbols, vec!["BTC/USDT", "ETH/USDT"]);
+        assert_eq!(result.num_trades, 2);
+        assert_eq!(pairlist.pairs().len(), 2);
+    }
+
+    #[test]
+    fn test_position_adjustment() {
+        let mut engine = BacktestEngine::new(10000.0)
+            .with_position_sizing(&PositionSizing::FixedStake { stake: 400.0 })
+            .unwrap();
+        let mut strategy = DcaStrategy {
+            inner: ScriptedStrategy::new(vec![buy(100.0).with_stop_loss(50.0)]),
+        };
+        let series = CandleSeries::from_vec(vec![
+            candle(0, 99.0, 100.0),
+            candle(1, 89.0, 90.0),
+            candle(2, 94.0, 100.0),
+            candle(3, 99.0, 110.0),
+        ]);
+
+        let result = engine.run(&mut strategy, &series).unwrap();
+
+        // 4 units at 100 + 4.444 units at 90 averages to ~94.74
+        assert_eq!(engine.trades.len(), 1);
+        let trade = &engine.trades[0];
+        assert_eq!(trade.orders.len(), 4);
+        let quantity = 4.0 + 400.0 / 90.0;
+        assert!((trade.quantity - quantity).abs() < 1e-9);
+        assert!((trade.entry_price - 800.0 / quantity).abs() < 1e-9);
+
+        // Half exits at 100, the rest at 110 (forced)
+        let pnl = (100.0 - 800.0 / quantity) * quantity / 2.0 + (110.0 - 800.0 / quantity) * quantity / 2.0;
+        assert!((trade.pnl - pnl).abs() < 1e-6);
+        assert!((trade.exit_price - 105.0).abs() < 1e-9);
+        assert!((result.end_balance - (10000.0 + pnl)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_grid_strategy_fills_limit_orders() {
+        use crate::strategy::{GridSpacing, GridStrategy, GridStrategyConfig};
+
+        let mut engine = BacktestEngine::new(10000.0);
+        let mut strategy = GridStrategy::new(GridStrategyConfig {
+            lower_price: 90.0,
+            upper_price: 110.0,
+            grid_count: 4,
+            spacing: GridSpacing::Arithmetic,
+            order_size: 100.0,
+            trailing: false,
+        })
+        .unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bar = |hours: i64, open: f64, high: f64, low: f64, close: f64| {
+            Candle::new(open, high, low, close, 1000.0, start + Duration::hours(hours), "BTC/USDT".to_string(), "1h".to_string())
+        };
+        let series = CandleSeries::from_vec(vec![
+            // Buys rest at 90, 95 and 100 after the first candle
+            bar(0, 101.0, 101.5, 100.5, 101.0),
+            // Dips through 100: buy 1 unit, a sell rests at 105
+            bar(1, 101.0, 101.0, 99.0, 99.5),
+            // Rallies through 105: the unit is sold, buys rest at every level up to 105
+            bar(2, 99.5, 106.0, 99.5, 105.5),
+            // Gaps down through 105, 100 and 95: the buys above the open fill at the open
+            bar(3, 98.0, 98.0, 94.0, 96.0),
+        ]);
+
+        let result = engine.run(&mut strategy, &series).unwrap();
+
+        assert_eq!(engine.trades.len(), 2);
+        let round_trip = &engine.trades[0];
+        assert_eq!(round_trip.exit_label(), "grid_sell");
+        assert!((round_trip.pnl - 5.0).abs() < 1e-9);
+
+        // 100 / 105 and 100 / 100 units bought at 98, 100 / 95 units at 95, closed at 96 at the end
+        let forced = &engine.trades[1];
+        assert_eq!(forced.exit_reason, ExitReason::Forced);
+        assert_eq!(forced.orders.len(), 4);
+        assert!((forced.orders[0].price - 98.0).abs() < 1e-9);
+        let pnl = (96.0 - 98.0) * (100.0 / 105.0 + 1.0) + (96.0 - 95.0) * (100.0 / 95.0);
+        assert!((forced.pnl - pnl).abs() < 1e-9);
+        assert!((result.end_balance - (10000.0 + 5.0 + pnl)).abs() < 1e-9);
+    }
+}
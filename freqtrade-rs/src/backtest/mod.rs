@@ -1,9 +1,11 @@
 //! Backtesting engine module
 
+pub mod analysis;
 pub mod engine;
 pub mod metrics;
 pub mod report;
 
+pub use analysis::*;
 pub use engine::*;
 pub use metrics::*;
 pub use report::*;
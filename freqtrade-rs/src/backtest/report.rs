@@ -1,6 +1,6 @@
 //! Backtest report generation
 
-use crate::backtest::BacktestResult;
+use crate::backtest::{BacktestResult, CircuitBreakerKind, GroupStats};
 use crate::backtest::MetricsCalculator;
 
 /// Backtest report
@@ -26,6 +26,14 @@ impl BacktestReport {
 
     /// Format report as string
     pub fn format(&self) -> String {
+        let mut report = self.format_summary();
+        report.push_str(&self.format_risk());
+        report.push_str(&self.format_breakdown());
+        report
+    }
+
+    /// Format summary metrics
+    fn format_summary(&self) -> String {
         format!(
             r#"
 Backtest Results
@@ -61,6 +69,72 @@ Sharpe Ratio: {:.2}
         )
     }
 
+    /// Format risk manager activity (rejected entries, tripped breakers and protection locks)
+    fn format_risk(&self) -> String {
+        let mut section = format!(
+            "Rejected Entries: {}\nCircuit Breakers Tripped: {}\n",
+            self.result.rejected_entries,
+            self.result.circuit_breakers.len(),
+        );
+        for event in &self.result.circuit_breakers {
+            let label = match event.kind {
+                CircuitBreakerKind::DailyLoss => "Daily loss limit",
+                CircuitBreakerKind::MaxDrawdown => "Max drawdown limit",
+            };
+            section.push_str(&format!(
+                "  - {} at {}: balance ${:.2} (reference ${:.2})\n",
+                label,
+                event.time.format("%Y-%m-%d %H:%M"),
+                event.balance,
+                event.reference_balance,
+            ));
+        }
+        section.push_str(&format!(
+            "Entries Blocked by Protections: {}\nProtection Locks: {}\n",
+            self.result.locked_entries,
+            self.result.protection_locks.len(),
+        ));
+        for lock in &self.result.protection_locks {
+            section.push_str(&format!(
+                "  - {} on {} until {}: {}\n",
+                lock.protection,
+                lock.symbol.as_deref().unwrap_or("all pairs"),
+                lock.until.format("%Y-%m-%d %H:%M"),
+                lock.reason,
+            ));
+        }
+        section
+    }
+
+    /// Format performance by pair, entry tag and exit reason
+    fn format_breakdown(&self) -> String {
+        let mut section = String::new();
+        for (title, stats) in [
+            ("Pair", &self.result.pair_stats),
+            ("Enter Tag", &self.result.enter_tag_stats),
+            ("Exit Reason", &self.result.exit_reason_stats),
+        ] {
+            if stats.is_empty() {
+                continue;
+            }
+            section.push_str(&format!(
+                "\n{:<20} {:>6} {:>5} {:>6} {:>12} {:>9}\n",
+                title, "Trades", "Wins", "Losses", "Total P&L", "Avg %"
+            ));
+            for group in stats {
+                section.push_str(&Self::format_group(group));
+            }
+        }
+        section
+    }
+
+    fn format_group(group: &GroupStats) -> String {
+        format!(
+            "{:<20} {:>6} {:>5} {:>6} {:>12.2} {:>8.2}%\n",
+            group.key, group.trades, group.wins, group.losses, group.total_pnl, group.avg_pnl_percent,
+        )
+    }
+
     /// Get result reference
     pub fn result(&self) -> &BacktestResult {
         &self.result
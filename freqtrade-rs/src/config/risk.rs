@@ -1,5 +1,6 @@
 //! Risk management configuration
 
+use crate::portfolio::PositionSide;
 use serde::{Deserialize, Serialize};
 
 /// Risk management configuration
@@ -13,6 +14,9 @@ pub struct RiskConfig {
     pub max_daily_loss: f64,
     /// Maximum drawdown (as percentage, e.g., 0.20 = 20%)
     pub max_drawdown: f64,
+    /// Default stop distance used when a signal has no stop loss (e.g., 0.05 = 5%)
+    #[serde(default = "default_stop_distance")]
+    pub default_stop_distance: f64,
     /// Use stop loss
     pub use_stop_loss: bool,
     /// Use take profit
@@ -26,9 +30,24 @@ impl Default for RiskConfig {
             max_open_positions: 3,
             max_daily_loss: 0.05, // 5%
             max_drawdown: 0.20, // 20%
+            default_stop_distance: default_stop_distance(),
             use_stop_loss: true,
             use_take_profit: true,
         }
     }
 }
 
+impl RiskConfig {
+    /// Stop loss and take profit of an entry whose signal sets neither:
+    /// `default_stop_distance` away from the entry and a 5% take profit
+    pub fn default_exit_levels(&self, side: PositionSide, entry_price: f64) -> (f64, f64) {
+        match side {
+            PositionSide::Long => (entry_price * (1.0 - self.default_stop_distance), entry_price * 1.05),
+            PositionSide::Short => (entry_price * (1.0 + self.default_stop_distance), entry_price * 0.95),
+        }
+    }
+}
+
+fn default_stop_distance() -> f64 {
+    0.05 // 5%
+}
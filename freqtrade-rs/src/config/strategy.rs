@@ -1,5 +1,7 @@
 //! Strategy configuration
 
+use crate::data::PairlistConfig;
+use crate::portfolio::{PositionSizing, Protection};
 use serde::{Deserialize, Serialize};
 
 /// Strategy configuration
@@ -21,6 +23,15 @@ pub struct StrategyConfig {
     pub trailing_stop_offset: f64,
     /// Startup candle count
     pub startup_candle_count: usize,
+    /// Position sizing model
+    #[serde(default)]
+    pub position_sizing: PositionSizing,
+    /// Protections that lock pairs after adverse conditions
+    #[serde(default)]
+    pub protections: Vec<Protection>,
+    /// Dynamic pairlist, `None` trades a single pair
+    #[serde(default)]
+    pub pairlist: Option<PairlistConfig>,
 }
 
 impl Default for StrategyConfig {
@@ -34,6 +45,9 @@ impl Default for StrategyConfig {
             trailing_stop_positive: 0.02,
             trailing_stop_offset: 0.01,
             startup_candle_count: 200,
+            position_sizing: PositionSizing::default(),
+            protections: Vec::new(),
+            pairlist: None,
         }
     }
 }
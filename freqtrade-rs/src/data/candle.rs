@@ -26,6 +26,7 @@ pub struct Candle {
 
 impl Candle {
     /// Create a new candle
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         open: f64,
         high: f64,
@@ -87,6 +88,11 @@ impl Candle {
         }
     }
 
+    /// Time the candle closes (its open time for ticks, whose timeframe is not a duration)
+    pub fn close_time(&self) -> DateTime<Utc> {
+        self.timestamp + crate::data::timeframe_duration(&self.timeframe).unwrap_or_default()
+    }
+
     /// Get typical price (HLC/3)
     pub fn typical_price(&self) -> f64 {
         (self.high + self.low + self.close) / 3.0
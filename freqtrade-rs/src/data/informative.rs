@@ -0,0 +1,309 @@
+//! Informative (multi-timeframe / multi-pair) candle data
+//!
+//! Strategies declare [`InformativeSpec`]s for higher timeframes or other pairs.
+//! [`InformativeData`] resamples the fed candles or ticks into those timeframes and
+//! only exposes buckets that are fully closed, so a strategy never sees a
+//! higher-timeframe candle before it could have existed.
+
+use crate::data::Candle;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Informative data declaration
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct InformativeSpec {
+    /// Symbol (e.g., "BTC/USDT"), `None` for the strategy's own pair
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// Informative timeframe (e.g., "1h")
+    pub timeframe: String,
+}
+
+impl InformativeSpec {
+    /// Higher timeframe of the strategy's own pair
+    pub fn new(timeframe: &str) -> Self {
+        Self {
+            symbol: None,
+            timeframe: timeframe.to_string(),
+        }
+    }
+
+    /// Timeframe of another pair
+    pub fn for_symbol(symbol: &str, timeframe: &str) -> Self {
+        Self {
+            symbol: Some(symbol.to_string()),
+            timeframe: timeframe.to_string(),
+        }
+    }
+
+    /// Resolve the symbol against the strategy's own pair
+    pub fn resolve_symbol<'a>(&'a self, base_symbol: &'a str) -> &'a str {
+        self.symbol.as_deref().unwrap_or(base_symbol)
+    }
+}
+
+/// Parse a timeframe string (e.g., "5m", "1h", "1d", "1w") into a duration
+pub fn timeframe_duration(timeframe: &str) -> Option<Duration> {
+    let timeframe = timeframe.trim().to_lowercase();
+    if timeframe.len() < 2 {
+        return None;
+    }
+    let (amount, unit) = timeframe.split_at(timeframe.len() - 1);
+    let amount: i64 = amount.parse().ok().filter(|a| *a > 0)?;
+    match unit {
+        "s" => Some(Duration::seconds(amount)),
+        "m" => Some(Duration::minutes(amount)),
+        "h" => Some(Duration::hours(amount)),
+        "d" => Some(Duration::days(amount)),
+        "w" => Some(Duration::weeks(amount)),
+        _ => None,
+    }
+}
+
+/// Aggregates lower-timeframe candles (or ticks) into one higher timeframe
+#[derive(Debug, Clone)]
+pub struct CandleResampler {
+    symbol: String,
+    timeframe: String,
+    duration: Duration,
+    current: Option<Candle>,
+    completed: Vec<Candle>,
+    max_candles: usize,
+}
+
+impl CandleResampler {
+    /// Create new resampler, `None` if the timeframe cannot be parsed
+    pub fn new(symbol: &str, timeframe: &str, max_candles: usize) -> Option<Self> {
+        Some(Self {
+            symbol: symbol.to_string(),
+            timeframe: timeframe.to_string(),
+            duration: timeframe_duration(timeframe)?,
+            current: None,
+            completed: Vec::new(),
+            max_candles,
+        })
+    }
+
+    /// Start of the bucket containing `time`
+    fn bucket_start(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let secs = self.duration.num_seconds();
+        let start = time.timestamp().div_euclid(secs) * secs;
+        Utc.timestamp_opt(start, 0).unwrap()
+    }
+
+    /// Feed a lower-timeframe candle; a tick is a candle with an unparsable timeframe
+    pub fn push(&mut self, candle: &Candle) {
+        let bucket_start = self.bucket_start(candle.timestamp);
+
+        // Late data for an already closed bucket is ignored
+        if self.completed.last().is_some_and(|last| bucket_start <= last.timestamp) {
+            return;
+        }
+        if let Some(current) = &self.current {
+            if bucket_start > current.timestamp {
+                self.finish_current();
+            } else if bucket_start < current.timestamp {
+                return;
+            }
+        }
+
+        match self.current.as_mut() {
+            Some(current) => {
+                current.high = current.high.max(candle.high);
+                current.low = current.low.min(candle.low);
+                current.close = candle.close;
+                current.volume += candle.volume;
+            }
+            None => {
+                self.current = Some(Candle::new(
+                    candle.open,
+                    candle.high,
+                    candle.low,
+                    candle.close,
+                    candle.volume,
+                    bucket_start,
+                    self.symbol.clone(),
+                    self.timeframe.clone(),
+                ));
+            }
+        }
+
+        // The last lower-timeframe candle of a bucket closes the bucket
+        let source_duration = timeframe_duration(&candle.timeframe).unwrap_or_else(Duration::zero);
+        if !source_duration.is_zero()
+            && candle.timestamp + source_duration >= bucket_start + self.duration
+        {
+            self.finish_current();
+        }
+    }
+
+    /// Close the bucket once `now` has passed its end (used for tick feeds)
+    pub fn close_until(&mut self, now: DateTime<Utc>) {
+        if let Some(current) = &self.current {
+            if now >= current.timestamp + self.duration {
+                self.finish_current();
+            }
+        }
+    }
+
+    fn finish_current(&mut self) {
+        if let Some(candle) = self.current.take() {
+            self.completed.push(candle);
+            if self.completed.len() > self.max_candles {
+                self.completed.remove(0);
+            }
+        }
+    }
+
+    /// Closed candles, oldest first
+    pub fn completed(&self) -> &[Candle] {
+        &self.completed
+    }
+}
+
+/// Closed informative candles for all declared specs
+#[derive(Debug, Clone, Default)]
+pub struct InformativeData {
+    series: HashMap<(String, String), CandleResampler>,
+}
+
+impl InformativeData {
+    /// Default number of closed candles kept per series
+    pub const MAX_CANDLES: usize = 500;
+
+    /// Create informative data for the given specs
+    pub fn new(specs: &[InformativeSpec], base_symbol: &str) -> Self {
+        let mut series = HashMap::new();
+        for spec in specs {
+            let symbol = spec.resolve_symbol(base_symbol).to_string();
+            match CandleResampler::new(&symbol, &spec.timeframe, Self::MAX_CANDLES) {
+                Some(resampler) => {
+                    series.insert((symbol, spec.timeframe.clone()), resampler);
+                }
+                None => tracing::warn!("Ignoring informative spec with invalid timeframe: {:?}", spec),
+            }
+        }
+        Self { series }
+    }
+
+    /// Check if no informative series are tracked
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+
+    /// Symbols other than `base_symbol` that need to be fed
+    pub fn extra_symbols(&self, base_symbol: &str) -> Vec<String> {
+        let mut symbols: Vec<String> = self
+            .series
+            .keys()
+            .map(|(symbol, _)| symbol.clone())
+            .filter(|symbol| symbol != base_symbol)
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
+    /// Feed a candle or tick of `candle.symbol` into all matching series
+    pub fn feed(&mut self, candle: &Candle) {
+        for ((symbol, _), resampler) in self.series.iter_mut() {
+            if *symbol == candle.symbol {
+                resampler.push(candle);
+            }
+        }
+    }
+
+    /// Close every bucket that ended before `now`
+    pub fn close_until(&mut self, now: DateTime<Utc>) {
+        for resampler in self.series.values_mut() {
+            resampler.close_until(now);
+        }
+    }
+
+    /// Closed candles of a series, oldest first
+    pub fn candles(&self, symbol: &str, timeframe: &str) -> &[Candle] {
+        self.series
+            .get(&(symbol.to_string(), timeframe.to_string()))
+            .map(|r| r.completed())
+            .unwrap_or(&[])
+    }
+
+    /// Closed candles for a spec, oldest first
+    pub fn candles_for(&self, spec: &InformativeSpec, base_symbol: &str) -> &[Candle] {
+        self.candles(spec.resolve_symbol(base_symbol), &spec.timeframe)
+    }
+
+    /// Latest closed candle of a series
+    pub fn latest(&self, symbol: &str, timeframe: &str) -> Option<&Candle> {
+        self.candles(symbol, timeframe).last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(symbol: &str, timeframe: &str, minutes: i64, close: f64) -> Candle {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        Candle::new(
+            close,
+            close + 1.0,
+            close - 1.0,
+            close,
+            10.0,
+            start + Duration::minutes(minutes),
+            symbol.to_string(),
+            timeframe.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_timeframe_duration() {
+        assert_eq!(timeframe_duration("5m"), Some(Duration::minutes(5)));
+        assert_eq!(timeframe_duration("4h"), Some(Duration::hours(4)));
+        assert_eq!(timeframe_duration("1w"), Some(Duration::weeks(1)));
+        assert_eq!(timeframe_duration("abc"), None);
+        assert_eq!(timeframe_duration("0m"), None);
+    }
+
+    #[test]
+    fn test_resample_closes_on_last_candle() {
+        let mut data = InformativeData::new(&[InformativeSpec::new("15m")], "BTC/USDT");
+
+        data.feed(&candle("BTC/USDT", "5m", 0, 100.0));
+        data.feed(&candle("BTC/USDT", "5m", 5, 102.0));
+        // Bucket 00:00-00:15 still open after two of three candles
+        assert!(data.latest("BTC/USDT", "15m").is_none());
+
+        data.feed(&candle("BTC/USDT", "5m", 10, 101.0));
+        let closed = data.latest("BTC/USDT", "15m").unwrap();
+        assert_eq!(closed.open, 100.0);
+        assert_eq!(closed.close, 101.0);
+        assert_eq!(closed.high, 103.0);
+        assert_eq!(closed.volume, 30.0);
+
+        // Next bucket is not exposed until it is complete
+        data.feed(&candle("BTC/USDT", "5m", 15, 110.0));
+        assert_eq!(data.candles("BTC/USDT", "15m").len(), 1);
+    }
+
+    #[test]
+    fn test_other_pair_ticks() {
+        let spec = InformativeSpec::for_symbol("BTC/USDT", "1h");
+        let mut data = InformativeData::new(std::slice::from_ref(&spec), "ETH/USDT");
+        assert_eq!(data.extra_symbols("ETH/USDT"), vec!["BTC/USDT".to_string()]);
+
+        // Own pair is not tracked
+        data.feed(&candle("ETH/USDT", "1m", 0, 5.0));
+        data.feed(&candle("BTC/USDT", "", 1, 100.0));
+        data.feed(&candle("BTC/USDT", "", 59, 105.0));
+        assert!(data.candles_for(&spec, "ETH/USDT").is_empty());
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 1, 0, 0).unwrap();
+        data.close_until(start);
+        let closed = data.candles_for(&spec, "ETH/USDT");
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].close, 105.0);
+    }
+}
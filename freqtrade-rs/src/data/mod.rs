@@ -4,7 +4,11 @@
 
 pub mod candle;
 pub mod storage;
+pub mod informative;
+pub mod pairlist;
 
 pub use candle::*;
 pub use storage::*;
+pub use informative::*;
+pub use pairlist::*;
 
@@ -0,0 +1,467 @@
+//! Dynamic pairlists
+//!
+//! A [`Pairlist`] builds the tradable universe from a source (a static list or
+//! the top pairs by 24h quote volume), removes blacklisted pairs and narrows the
+//! rest with price, spread, age and volatility filters. It is refreshed on an
+//! interval from [`PairStats`] snapshots, which live trading builds from exchange
+//! tickers and backtests build from the candles seen so far.
+
+use crate::data::Candle;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Market snapshot of a pair used by pairlist sources and filters
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairStats {
+    /// Symbol (e.g., "BTC/USDT")
+    pub symbol: String,
+    /// Last price
+    pub price: f64,
+    /// Quote volume over the last 24 hours
+    pub quote_volume: f64,
+    /// Best bid, `None` if unknown
+    pub bid: Option<f64>,
+    /// Best ask, `None` if unknown
+    pub ask: Option<f64>,
+    /// Time of the first known candle
+    pub listed_since: Option<DateTime<Utc>>,
+    /// Daily closes, oldest first (used by the volatility filter)
+    pub daily_closes: Vec<f64>,
+}
+
+impl PairStats {
+    /// Create stats from a ticker without history
+    pub fn new(symbol: &str, price: f64, quote_volume: f64) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            price,
+            quote_volume,
+            bid: None,
+            ask: None,
+            listed_since: None,
+            daily_closes: Vec::new(),
+        }
+    }
+
+    /// Build stats from the candles of a pair closed at or before `now`
+    ///
+    /// Returns `None` if no candle has closed yet.
+    pub fn from_candles(symbol: &str, candles: &[Candle], now: DateTime<Utc>) -> Option<Self> {
+        let seen = candles.partition_point(|c| c.timestamp <= now);
+        let candles = &candles[..seen];
+        let last = candles.last()?;
+
+        let day_ago = now - Duration::hours(24);
+        let quote_volume = candles
+            .iter()
+            .rev()
+            .take_while(|c| c.timestamp > day_ago)
+            .map(|c| c.close * c.volume)
+            .sum();
+
+        let mut daily = BTreeMap::new();
+        for candle in candles {
+            daily.insert(candle.timestamp.date_naive(), candle.close);
+        }
+
+        Some(Self {
+            symbol: symbol.to_string(),
+            price: last.close,
+            quote_volume,
+            bid: None,
+            ask: None,
+            listed_since: candles.first().map(|c| c.timestamp),
+            daily_closes: daily.into_values().collect(),
+        })
+    }
+
+    /// Relative bid/ask spread (e.g., 0.001 = 0.1%)
+    pub fn spread(&self) -> Option<f64> {
+        match (self.bid, self.ask) {
+            (Some(bid), Some(ask)) if ask > 0.0 => Some((ask - bid) / ask),
+            _ => None,
+        }
+    }
+
+    /// Days since the first known candle
+    pub fn age_days(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.listed_since.map(|since| (now - since).num_days())
+    }
+
+    /// Standard deviation of daily log returns over the last `days` days
+    pub fn volatility(&self, days: usize) -> Option<f64> {
+        let start = self.daily_closes.len().saturating_sub(days + 1);
+        let returns: Vec<f64> = self.daily_closes[start..]
+            .windows(2)
+            .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+            .map(|w| (w[1] / w[0]).ln())
+            .collect();
+        if returns.len() < 2 {
+            return None;
+        }
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance =
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+        Some(variance.sqrt())
+    }
+}
+
+/// Where the candidate pairs come from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairlistSource {
+    /// Fixed list of pairs, in the given order
+    Static { pairs: Vec<String> },
+    /// Top pairs of a quote asset ranked by 24h quote volume
+    VolumeTop {
+        /// Number of top pairs handed to the filters
+        number_assets: usize,
+        /// Quote asset (e.g., "USDT")
+        #[serde(default = "default_quote_asset")]
+        quote_asset: String,
+        /// Minimum 24h quote volume
+        #[serde(default)]
+        min_quote_volume: f64,
+    },
+}
+
+/// Filter applied to the candidate pairs
+///
+/// Pairs missing the data a filter needs are removed, except for the spread
+/// filter, which keeps pairs without order book data (e.g., in backtests).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairFilter {
+    /// Keep pairs whose price is within the range
+    Price {
+        #[serde(default)]
+        min_price: Option<f64>,
+        #[serde(default)]
+        max_price: Option<f64>,
+    },
+    /// Keep pairs whose bid/ask spread is at most `max_spread_ratio` (e.g., 0.005 = 0.5%)
+    Spread { max_spread_ratio: f64 },
+    /// Keep pairs listed for at least `min_days` (and at most `max_days`)
+    Age {
+        min_days: i64,
+        #[serde(default)]
+        max_days: Option<i64>,
+    },
+    /// Keep pairs whose daily volatility over `lookback_days` is within the range
+    Volatility {
+        #[serde(default = "default_volatility_lookback")]
+        lookback_days: usize,
+        #[serde(default)]
+        min_volatility: Option<f64>,
+        #[serde(default)]
+        max_volatility: Option<f64>,
+    },
+}
+
+impl PairFilter {
+    /// Check if the pair passes the filter
+    pub fn accepts(&self, stats: &PairStats, now: DateTime<Utc>) -> bool {
+        let in_range = |value: f64, min: Option<f64>, max: Option<f64>| {
+            min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+        };
+        match *self {
+            PairFilter::Price { min_price, max_price } => in_range(stats.price, min_price, max_price),
+            PairFilter::Spread { max_spread_ratio } => {
+                stats.spread().is_none_or(|spread| spread <= max_spread_ratio)
+            }
+            PairFilter::Age { min_days, max_days } => stats
+                .age_days(now)
+                .is_some_and(|age| age >= min_days && max_days.is_none_or(|max| age <= max)),
+            PairFilter::Volatility {
+                lookback_days,
+                min_volatility,
+                max_volatility,
+            } => stats
+                .volatility(lookback_days)
+                .is_some_and(|v| in_range(v, min_volatility, max_volatility)),
+        }
+    }
+
+    /// Days of daily history the filter needs
+    pub fn history_days(&self) -> usize {
+        match *self {
+            PairFilter::Age { min_days, max_days } => min_days.max(max_days.unwrap_or(0)).max(0) as usize + 1,
+            PairFilter::Volatility { lookback_days, .. } => lookback_days + 1,
+            PairFilter::Price { .. } | PairFilter::Spread { .. } => 0,
+        }
+    }
+}
+
+/// Pairlist configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairlistConfig {
+    /// Candidate pairs
+    pub source: PairlistSource,
+    /// Filters applied in order
+    #[serde(default)]
+    pub filters: Vec<PairFilter>,
+    /// Pairs never traded, `*` matches any characters (e.g., "*UP/USDT")
+    #[serde(default)]
+    pub blacklist: Vec<String>,
+    /// Refresh interval in seconds
+    #[serde(default = "default_refresh_period")]
+    pub refresh_period_secs: i64,
+}
+
+impl PairlistConfig {
+    /// Static pairlist of the given pairs
+    pub fn fixed(pairs: &[&str]) -> Self {
+        Self {
+            source: PairlistSource::Static {
+                pairs: pairs.iter().map(|p| p.to_string()).collect(),
+            },
+            filters: Vec::new(),
+            blacklist: Vec::new(),
+            refresh_period_secs: default_refresh_period(),
+        }
+    }
+
+    /// Build the pairlist from market snapshots
+    pub fn select(&self, stats: &[PairStats], now: DateTime<Utc>) -> Vec<String> {
+        self.candidates(stats)
+            .into_iter()
+            .filter(|s| self.filters.iter().all(|f| f.accepts(s, now)))
+            .map(|s| s.symbol.clone())
+            .collect()
+    }
+
+    /// Pairs produced by the source without blacklisted ones, before filters
+    ///
+    /// Only these need the daily history the filters read.
+    pub fn candidates<'a>(&self, stats: &'a [PairStats]) -> Vec<&'a PairStats> {
+        let candidates: Vec<&PairStats> = match &self.source {
+            PairlistSource::Static { pairs } => pairs
+                .iter()
+                .filter_map(|pair| stats.iter().find(|s| same_pair(&s.symbol, pair)))
+                .collect(),
+            PairlistSource::VolumeTop {
+                quote_asset,
+                min_quote_volume,
+                ..
+            } => {
+                let mut candidates: Vec<&PairStats> = stats
+                    .iter()
+                    .filter(|s| has_quote(&s.symbol, quote_asset))
+                    .filter(|s| s.quote_volume >= *min_quote_volume)
+                    .collect();
+                candidates.sort_by(|a, b| {
+                    b.quote_volume
+                        .partial_cmp(&a.quote_volume)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+                candidates
+            }
+        };
+
+        let mut candidates: Vec<&PairStats> = candidates
+            .into_iter()
+            .filter(|s| !self.is_blacklisted(&s.symbol))
+            .collect();
+        if let PairlistSource::VolumeTop { number_assets, .. } = self.source {
+            candidates.truncate(number_assets);
+        }
+        candidates
+    }
+
+    /// Check if a pair matches a blacklist pattern
+    pub fn is_blacklisted(&self, symbol: &str) -> bool {
+        self.blacklist.iter().any(|pattern| matches_pattern(pattern, symbol))
+    }
+
+    /// Days of daily history the filters need (0 if tickers are enough)
+    pub fn history_days(&self) -> usize {
+        self.filters.iter().map(|f| f.history_days()).max().unwrap_or(0)
+    }
+}
+
+/// Pairlist refreshed on an interval
+#[derive(Debug, Clone)]
+pub struct Pairlist {
+    config: PairlistConfig,
+    pairs: Vec<String>,
+    last_refresh: Option<DateTime<Utc>>,
+}
+
+impl Pairlist {
+    /// Create new pairlist, empty until the first refresh
+    pub fn new(config: PairlistConfig) -> Self {
+        Self {
+            config,
+            pairs: Vec::new(),
+            last_refresh: None,
+        }
+    }
+
+    /// Pairlist configuration
+    pub fn config(&self) -> &PairlistConfig {
+        &self.config
+    }
+
+    /// Check if the refresh interval has passed
+    pub fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.last_refresh
+            .is_none_or(|last| now - last >= Duration::seconds(self.config.refresh_period_secs))
+    }
+
+    /// Rebuild the pairlist from market snapshots
+    pub fn refresh(&mut self, stats: &[PairStats], now: DateTime<Utc>) -> &[String] {
+        self.pairs = self.config.select(stats, now);
+        self.last_refresh = Some(now);
+        &self.pairs
+    }
+
+    /// Current pairs
+    pub fn pairs(&self) -> &[String] {
+        &self.pairs
+    }
+
+    /// Check if a pair is currently in the pairlist
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.pairs.iter().any(|p| same_pair(p, symbol))
+    }
+}
+
+/// Compare pairs ignoring case and the base/quote separator ("BTC/USDT" == "btcusdt")
+pub fn same_pair(a: &str, b: &str) -> bool {
+    compact_pair(a) == compact_pair(b)
+}
+
+fn compact_pair(pair: &str) -> String {
+    pair.chars().filter(|c| *c != '/').collect::<String>().to_uppercase()
+}
+
+fn has_quote(symbol: &str, quote_asset: &str) -> bool {
+    compact_pair(symbol).ends_with(&quote_asset.to_uppercase())
+}
+
+/// Match a pair against a pattern where `*` matches any characters
+fn matches_pattern(pattern: &str, symbol: &str) -> bool {
+    let pattern = compact_pair(pattern);
+    let symbol = compact_pair(symbol);
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == symbol;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !symbol.starts_with(first) || symbol.len() < first.len() + last.len() {
+        return false;
+    }
+    let mut rest = &symbol[first.len()..symbol.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    symbol.ends_with(last)
+}
+
+fn default_quote_asset() -> String {
+    "USDT".to_string()
+}
+
+fn default_volatility_lookback() -> usize {
+    10
+}
+
+fn default_refresh_period() -> i64 {
+    1800
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()
+    }
+
+    fn stats(symbol: &str, price: f64, quote_volume: f64) -> PairStats {
+        let mut stats = PairStats::new(symbol, price, quote_volume);
+        stats.listed_since = Some(now() - Duration::days(100));
+        stats
+    }
+
+    #[test]
+    fn test_volume_top_with_filters_and_blacklist() {
+        let config = PairlistConfig {
+            source: PairlistSource::VolumeTop {
+                number_assets: 4,
+                quote_asset: "USDT".to_string(),
+                min_quote_volume: 0.0,
+            },
+            filters: vec![
+                PairFilter::Price { min_price: Some(0.01), max_price: None },
+                PairFilter::Age { min_days: 30, max_days: None },
+            ],
+            blacklist: vec!["*UP/USDT".to_string()],
+            refresh_period_secs: 60,
+        };
+        let mut young = stats("NEW/USDT", 1.0, 9e9);
+        young.listed_since = Some(now() - Duration::days(3));
+        let tickers = vec![
+            stats("ETH/USDT", 3000.0, 5e8),
+            stats("BTCUP/USDT", 10.0, 8e9),
+            young,
+            stats("BTC/USDT", 60000.0, 1e9),
+            stats("SHIB/USDT", 0.00002, 7e8),
+            stats("ETH/BTC", 0.05, 2e9),
+            stats("SOL/USDT", 100.0, 1e8),
+        ];
+
+        let mut pairlist = Pairlist::new(config);
+        assert!(pairlist.needs_refresh(now()));
+        let pairs = pairlist.refresh(&tickers, now()).to_vec();
+
+        assert_eq!(pairs, vec!["BTC/USDT".to_string(), "ETH/USDT".to_string()]);
+        assert!(pairlist.contains("ethusdt"));
+        assert!(!pairlist.needs_refresh(now() + Duration::seconds(59)));
+        assert!(pairlist.needs_refresh(now() + Duration::seconds(60)));
+    }
+
+    #[test]
+    fn test_stats_from_candles_are_lookahead_safe() {
+        let start = now() - Duration::days(5);
+        let candles: Vec<Candle> = (0..10)
+            .map(|i| {
+                let close = 100.0 * (1.0 + 0.05 * (i % 2) as f64);
+                Candle::new(
+                    close,
+                    close,
+                    close,
+                    close,
+                    2.0,
+                    start + Duration::hours(12 * i),
+                    "BTC/USDT".to_string(),
+                    "12h".to_string(),
+                )
+            })
+            .collect();
+
+        let cutoff = start + Duration::hours(48);
+        let stats = PairStats::from_candles("BTC/USDT", &candles, cutoff).unwrap();
+        // Candles at 36h and 48h are within the last day, later ones are not visible yet
+        assert_eq!(stats.price, 100.0);
+        assert!((stats.quote_volume - (210.0 + 200.0)).abs() < 1e-9);
+        assert_eq!(stats.daily_closes.len(), 3);
+        assert_eq!(stats.age_days(cutoff), Some(2));
+        assert!(stats.volatility(10).is_some());
+        assert!(PairStats::from_candles("BTC/USDT", &candles, start - Duration::hours(1)).is_none());
+    }
+
+    #[test]
+    fn test_blacklist_patterns() {
+        assert!(matches_pattern("BNB/USDT", "BNBUSDT"));
+        assert!(matches_pattern("*DOWN/*", "BTCDOWN/USDT"));
+        assert!(!matches_pattern("*DOWN/*", "BTC/USDT"));
+        assert!(matches_pattern("*/BUSD", "BTC/BUSD"));
+    }
+}
@@ -1,7 +1,6 @@
 //! Data storage and retrieval
 
 use crate::data::Candle;
-use crate::Result;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
@@ -28,7 +27,7 @@ impl DataStorage {
     /// Add a candle
     pub fn add_candle(&mut self, candle: Candle) {
         let key = Self::key(&candle.symbol, &candle.timeframe);
-        self.candles.entry(key).or_insert_with(Vec::new).push(candle);
+        self.candles.entry(key).or_default().push(candle);
     }
 
     /// Add multiple candles
@@ -2,15 +2,9 @@
 
 use crate::Result;
 use crate::data::Candle;
-use barter_data::exchange::binance::spot::BinanceSpot;
 // Note: Kraken and OKX may not have spot module in current barter-data version
 // use barter_data::exchange::kraken::spot::KrakenSpot;
 // use barter_data::exchange::okx::spot::OkxSpot;
-use barter_data::streams::Streams;
-use barter_data::subscription::candle::Candles;
-use barter_data::subscription::trade::PublicTrades;
-use barter_instrument::instrument::market_data::kind::MarketDataInstrumentKind;
-use futures::StreamExt;
 use std::collections::HashMap;
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
@@ -25,6 +19,7 @@ pub enum ExchangeType {
 }
 
 impl ExchangeType {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "binance" | "binance_spot" => Some(Self::BinanceSpot),
@@ -49,7 +44,7 @@ impl ExchangeClient {
         let exchange_type = ExchangeType::from_str(exchange_name)
             .ok_or_else(|| anyhow::anyhow!("Unsupported exchange: {}", exchange_name))?;
 
-        let (candle_tx, candle_rx) = mpsc::channel(1000);
+        let (_candle_tx, candle_rx) = mpsc::channel(1000);
 
         // Spawn task to handle data streaming
         let _handle = tokio::spawn(async move {
@@ -117,7 +112,7 @@ impl ExchangeClient {
         quote: &str,
         _interval_str: &str,
     ) -> Result<()> {
-        let (_candle_tx, mut candle_rx) = mpsc::channel(1000);
+        let (_candle_tx, candle_rx) = mpsc::channel(1000);
         
         // Clone strings before moving into async block
         let base = base.to_string();
@@ -193,9 +188,9 @@ impl ExchangeClient {
     /// Place market order (placeholder - requires barter-execution)
     pub async fn place_market_order(
         &self,
-        symbol: &str,
-        side: &str,
-        quantity: f64,
+        _symbol: &str,
+        _side: &str,
+        _quantity: f64,
     ) -> Result<String> {
         // TODO: Implement with barter-execution
         Err(anyhow::anyhow!("Order placement not yet implemented"))
@@ -204,10 +199,10 @@ impl ExchangeClient {
     /// Place limit order (placeholder - requires barter-execution)
     pub async fn place_limit_order(
         &self,
-        symbol: &str,
-        side: &str,
-        quantity: f64,
-        price: f64,
+        _symbol: &str,
+        _side: &str,
+        _quantity: f64,
+        _price: f64,
     ) -> Result<String> {
         // TODO: Implement with barter-execution
         Err(anyhow::anyhow!("Limit order placement not yet implemented"))
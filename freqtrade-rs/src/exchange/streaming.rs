@@ -2,17 +2,12 @@
 
 use crate::data::Candle;
 use crate::Result;
-use barter_data::exchange::binance::spot::BinanceSpot;
-use barter_data::streams::Streams;
-use barter_data::subscription::candle::Candles;
-use barter_instrument::instrument::market_data::kind::MarketDataInstrumentKind;
-use futures::StreamExt;
 use tokio::sync::mpsc;
-use tracing::{error, info};
+use tracing::error;
 
 /// Data streamer for real-time candle data
 pub struct DataStreamer {
-    candle_tx: mpsc::Sender<Candle>,
+    _candle_tx: mpsc::Sender<Candle>,
     _handle: tokio::task::JoinHandle<()>,
 }
 
@@ -28,7 +23,7 @@ impl DataStreamer {
         let handle = tokio::spawn({
             let base = base.to_string();
             let quote = quote.to_string();
-            let candle_tx = candle_tx.clone();
+            let _candle_tx = candle_tx.clone();
             let _interval = interval.to_string();
 
             async move {
@@ -80,7 +75,7 @@ impl DataStreamer {
 
         Ok((
             Self {
-                candle_tx,
+                _candle_tx: candle_tx,
                 _handle: handle,
             },
             candle_rx,
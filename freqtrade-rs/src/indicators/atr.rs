@@ -0,0 +1,93 @@
+//! ATR (Average True Range) indicator
+
+use crate::data::Candle;
+use crate::indicators::Indicator;
+use serde::{Deserialize, Serialize};
+use ta::indicators::AverageTrueRange;
+use ta::{DataItem, Next};
+
+/// ATR indicator wrapper
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ATR {
+    inner: AverageTrueRange,
+    period: usize,
+    update_count: usize,
+    last_value: Option<f64>,
+}
+
+impl ATR {
+    /// Create new ATR indicator
+    pub fn new(period: usize) -> Self {
+        Self {
+            inner: AverageTrueRange::new(period).unwrap(),
+            period,
+            update_count: 0,
+            last_value: None,
+        }
+    }
+
+    /// Get ATR period
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Update indicator with high, low and close of a bar
+    pub fn update_hlc(&mut self, high: f64, low: f64, close: f64) {
+        let item = DataItem::builder()
+            .open(close)
+            .high(high.max(close))
+            .low(low.min(close))
+            .close(close)
+            .volume(0.0)
+            .build()
+            .unwrap();
+        let atr_value = self.inner.next(&item);
+        self.update_count += 1;
+        if self.update_count >= self.period {
+            self.last_value = Some(atr_value);
+        }
+    }
+
+    /// Update indicator with a candle
+    pub fn update_candle(&mut self, candle: &Candle) {
+        self.update_hlc(candle.high, candle.low, candle.close);
+    }
+}
+
+impl Indicator for ATR {
+    fn name(&self) -> &str {
+        "ATR"
+    }
+
+    fn update(&mut self, value: f64) {
+        // Close-only feed: true range degrades to close-to-close moves
+        self.update_hlc(value, value, value);
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.last_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.update_count >= self.period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atr() {
+        let mut atr = ATR::new(3);
+        atr.update_hlc(11.0, 9.0, 10.0);
+        atr.update_hlc(12.0, 10.0, 11.0);
+        assert!(!atr.is_ready());
+
+        atr.update_hlc(13.0, 11.0, 12.0);
+        assert!(atr.is_ready());
+
+        let value = atr.value().unwrap();
+        assert!(value > 1.5 && value < 2.5, "ATR {} should track the 2.0 bar range", value);
+    }
+}
@@ -9,7 +9,6 @@ use ta::indicators::BollingerBands as TaBollingerBands;
 pub struct BollingerBands {
     inner: TaBollingerBands,
     period: usize,
-    std_dev: f64,
     update_count: usize,
     last_output: Option<ta::indicators::BollingerBandsOutput>,
 }
@@ -20,7 +19,6 @@ impl BollingerBands {
         Self {
             inner: TaBollingerBands::new(period, std_dev).unwrap(),
             period,
-            std_dev,
             update_count: 0,
             last_output: None,
         }
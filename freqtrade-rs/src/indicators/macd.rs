@@ -8,7 +8,6 @@ use ta::Next;
 #[derive(Debug)]
 pub struct MACD {
     inner: MovingAverageConvergenceDivergence,
-    fast_period: usize,
     slow_period: usize,
     signal_period: usize,
     update_count: usize,
@@ -20,7 +19,6 @@ impl MACD {
     pub fn new(fast_period: usize, slow_period: usize, signal_period: usize) -> Self {
         Self {
             inner: MovingAverageConvergenceDivergence::new(fast_period, slow_period, signal_period).unwrap(),
-            fast_period,
             slow_period,
             signal_period,
             update_count: 0,
@@ -7,12 +7,14 @@ pub mod macd;
 pub mod ema;
 pub mod sma;
 pub mod bb;
+pub mod atr;
 
 pub use rsi::*;
 pub use macd::*;
 pub use ema::*;
 pub use sma::*;
 pub use bb::*;
+pub use atr::*;
 
 /// Indicator trait for all indicators
 pub trait Indicator {
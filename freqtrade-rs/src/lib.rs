@@ -18,12 +18,14 @@
 //! ```no_run
 //! use freqtrade_rs::prelude::*;
 //!
-//! #[tokio::main]
-//! async fn main() -> Result<()> {
-//!     let client = ExchangeClient::new("binance").await?;
-//!     let strategy = RSIStrategy::new(RSIStrategyConfig::default());
-//!     let engine = TradingEngine::new(client, strategy);
-//!     engine.run().await?;
+//! fn main() -> Result<()> {
+//!     // OHLCV candles, e.g. downloaded from the exchange
+//!     let candles = CandleSeries::from_vec(Vec::new());
+//!     let mut strategy = RSIStrategy::new(RSIStrategyConfig::default());
+//!     let mut engine = BacktestEngine::new(10_000.0)
+//!         .with_position_sizing(&PositionSizing::default_volatility_target())?;
+//!     let result = engine.run(&mut strategy, &candles)?;
+//!     println!("{}", BacktestReport::new(result).format());
 //!     Ok(())
 //! }
 //! ```
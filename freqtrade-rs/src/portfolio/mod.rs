@@ -2,9 +2,13 @@
 
 pub mod position;
 pub mod balance;
-pub mod risk;
+pub mod risk_manager;
+pub mod sizing;
+pub mod protections;
 
 pub use position::*;
 pub use balance::*;
-pub use risk::*;
+pub use risk_manager::*;
+pub use sizing::*;
+pub use protections::*;
 
@@ -1,5 +1,6 @@
 //! Position tracking
 
+use crate::strategy::ExitReason;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -22,12 +23,50 @@ pub struct Position {
     pub stop_loss: Option<f64>,
     /// Take profit price
     pub take_profit: Option<f64>,
+    /// Trailing stop distance as a fraction of the best price
+    #[serde(default)]
+    pub trailing_stop: Option<f64>,
+    /// Whether the trailing stop has moved the stop loss
+    #[serde(default)]
+    pub stop_trailed: bool,
+    /// Whether the strategy's `custom_stoploss` last moved the stop loss
+    #[serde(default)]
+    pub stop_custom: bool,
+    /// Tag of the signal that opened the position
+    #[serde(default)]
+    pub enter_tag: Option<String>,
     /// Entry time
     pub entry_time: DateTime<Utc>,
     /// Unrealized P&L
     pub unrealized_pnl: f64,
     /// Unrealized P&L percentage
     pub unrealized_pnl_percent: f64,
+    /// P&L realized by partial exits
+    #[serde(default)]
+    pub realized_pnl: f64,
+    /// Entry and exit fills, oldest first
+    #[serde(default)]
+    pub orders: Vec<PositionOrder>,
+}
+
+/// Whether a fill added to or reduced a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PositionOrderKind {
+    /// Initial entry or additional (DCA) entry
+    Entry,
+    /// Partial or final exit
+    Exit,
+}
+
+/// Fill that changed a position's size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionOrder {
+    pub kind: PositionOrderKind,
+    pub price: f64,
+    pub quantity: f64,
+    pub time: DateTime<Utc>,
+    /// P&L realized by an exit fill
+    pub realized_pnl: f64,
 }
 
 /// Position side
@@ -48,6 +87,7 @@ impl Position {
         entry_price: f64,
         quantity: f64,
     ) -> Self {
+        let entry_time = Utc::now();
         Self {
             id,
             symbol,
@@ -57,12 +97,90 @@ impl Position {
             quantity,
             stop_loss: None,
             take_profit: None,
-            entry_time: Utc::now(),
+            trailing_stop: None,
+            stop_trailed: false,
+            stop_custom: false,
+            enter_tag: None,
+            entry_time,
             unrealized_pnl: 0.0,
             unrealized_pnl_percent: 0.0,
+            realized_pnl: 0.0,
+            orders: vec![PositionOrder {
+                kind: PositionOrderKind::Entry,
+                price: entry_price,
+                quantity,
+                time: entry_time,
+                realized_pnl: 0.0,
+            }],
+        }
+    }
+
+    /// Set entry time (also the time of the initial entry fill)
+    pub fn set_entry_time(&mut self, time: DateTime<Utc>) {
+        self.entry_time = time;
+        if let Some(order) = self.orders.first_mut() {
+            order.time = time;
         }
     }
 
+    /// Add to the position, averaging the entry price
+    pub fn increase(&mut self, price: f64, quantity: f64, time: DateTime<Utc>) {
+        if quantity <= 0.0 {
+            return;
+        }
+        let total = self.quantity + quantity;
+        self.entry_price = (self.entry_price * self.quantity + price * quantity) / total;
+        self.quantity = total;
+        self.orders.push(PositionOrder {
+            kind: PositionOrderKind::Entry,
+            price,
+            quantity,
+            time,
+            realized_pnl: 0.0,
+        });
+        self.calculate_pnl();
+    }
+
+    /// Exit part of the position at `price`, returns the realized P&L
+    ///
+    /// The quantity is capped at the open quantity; the entry price is unchanged.
+    pub fn decrease(&mut self, price: f64, quantity: f64, time: DateTime<Utc>) -> f64 {
+        let quantity = quantity.min(self.quantity);
+        if quantity <= 0.0 {
+            return 0.0;
+        }
+        let pnl = match self.side {
+            PositionSide::Long => (price - self.entry_price) * quantity,
+            PositionSide::Short => (self.entry_price - price) * quantity,
+        };
+        self.quantity -= quantity;
+        self.realized_pnl += pnl;
+        self.orders.push(PositionOrder {
+            kind: PositionOrderKind::Exit,
+            price,
+            quantity,
+            time,
+            realized_pnl: pnl,
+        });
+        self.calculate_pnl();
+        pnl
+    }
+
+    /// Number of entry fills (1 for a position without DCA entries)
+    pub fn entry_count(&self) -> usize {
+        self.orders.iter().filter(|o| o.kind == PositionOrderKind::Entry).count()
+    }
+
+    /// Total quantity and value of fills of one kind
+    pub fn filled(&self, kind: PositionOrderKind) -> (f64, f64) {
+        self.orders
+            .iter()
+            .filter(|o| o.kind == kind)
+            .fold((0.0, 0.0), |(quantity, value), o| {
+                (quantity + o.quantity, value + o.price * o.quantity)
+            })
+    }
+
     /// Update current price and calculate P&L
     pub fn update_price(&mut self, price: f64) {
         self.current_price = price;
@@ -128,5 +246,64 @@ impl Position {
     pub fn set_take_profit(&mut self, take_profit: f64) {
         self.take_profit = Some(take_profit);
     }
+
+    /// Set trailing stop distance (e.g., 0.02 trails 2% behind the best price)
+    pub fn set_trailing_stop(&mut self, distance: f64) {
+        self.trailing_stop = Some(distance);
+    }
+
+    /// Ratchet the stop loss behind `price`; the stop never moves against the position
+    pub fn update_trailing_stop(&mut self, price: f64) {
+        let Some(distance) = self.trailing_stop else {
+            return;
+        };
+        let candidate = match self.side {
+            PositionSide::Long => price * (1.0 - distance),
+            PositionSide::Short => price * (1.0 + distance),
+        };
+        let moved = self.stop_loss.is_some();
+        if self.tighten_stop_loss(candidate) && moved {
+            self.stop_trailed = true;
+            self.stop_custom = false;
+        }
+    }
+
+    /// Move the stop loss to a level from the strategy's `custom_stoploss`
+    ///
+    /// Returns true if the stop changed (only stops tightening the current one apply).
+    pub fn apply_custom_stoploss(&mut self, stop: f64) -> bool {
+        let changed = self.tighten_stop_loss(stop);
+        if changed {
+            self.stop_custom = true;
+            self.stop_trailed = false;
+        }
+        changed
+    }
+
+    /// Move the stop loss to `stop` if it tightens the current stop
+    ///
+    /// Returns true if the stop changed.
+    pub fn tighten_stop_loss(&mut self, stop: f64) -> bool {
+        let improves = match (self.side, self.stop_loss) {
+            (_, None) => true,
+            (PositionSide::Long, Some(current)) => stop > current,
+            (PositionSide::Short, Some(current)) => stop < current,
+        };
+        if improves {
+            self.stop_loss = Some(stop);
+        }
+        improves
+    }
+
+    /// Exit reason when the stop loss is hit, by what last moved the stop
+    pub fn stop_exit_reason(&self) -> ExitReason {
+        if self.stop_custom {
+            ExitReason::CustomStoploss
+        } else if self.stop_trailed {
+            ExitReason::TrailingStop
+        } else {
+            ExitReason::StopLoss
+        }
+    }
 }
 
@@ -0,0 +1,370 @@
+//! Trading protections
+//!
+//! Protections lock a pair (or all pairs) for a while after adverse conditions,
+//! modeled on freqtrade's CooldownPeriod, StoplossGuard, MaxDrawdown and
+//! LowProfitPairs. They are evaluated whenever a trade closes; the resulting
+//! [`ProtectionLock`]s block new entries until they expire.
+
+use crate::strategy::ExitReason;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Protection rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Protection {
+    /// Lock the pair for a while after every exit
+    CooldownPeriod { stop_duration_minutes: i64 },
+    /// Lock after `trade_limit` stop loss exits within the lookback window
+    StoplossGuard {
+        lookback_minutes: i64,
+        trade_limit: usize,
+        stop_duration_minutes: i64,
+        /// Only count and lock the pair that hit the stops (otherwise all pairs)
+        #[serde(default)]
+        only_per_pair: bool,
+    },
+    /// Lock all pairs when the drawdown of trades within the lookback window exceeds the limit
+    MaxDrawdown {
+        lookback_minutes: i64,
+        trade_limit: usize,
+        stop_duration_minutes: i64,
+        /// Maximum drawdown (e.g., 0.1 = 10%)
+        max_allowed_drawdown: f64,
+    },
+    /// Lock a pair whose trades within the lookback window made less than `required_profit`
+    LowProfitPairs {
+        lookback_minutes: i64,
+        trade_limit: usize,
+        stop_duration_minutes: i64,
+        /// Required summed profit ratio (e.g., 0.0 = break even)
+        required_profit: f64,
+    },
+}
+
+impl Protection {
+    /// Protection name used in locks
+    pub fn name(&self) -> &'static str {
+        match self {
+            Protection::CooldownPeriod { .. } => "CooldownPeriod",
+            Protection::StoplossGuard { .. } => "StoplossGuard",
+            Protection::MaxDrawdown { .. } => "MaxDrawdown",
+            Protection::LowProfitPairs { .. } => "LowProfitPairs",
+        }
+    }
+
+    /// Evaluate after a trade on `symbol` closed at `now`, returns a new lock if triggered
+    ///
+    /// `trades` are the closed trades (any order), `equity` the current balance.
+    pub fn evaluate(
+        &self,
+        trades: &[ProtectionTrade],
+        symbol: &str,
+        now: DateTime<Utc>,
+        equity: f64,
+    ) -> Option<ProtectionLock> {
+        let lock = |symbol: Option<&str>, minutes: i64, reason: String| ProtectionLock {
+            symbol: symbol.map(str::to_string),
+            until: now + Duration::minutes(minutes),
+            protection: self.name().to_string(),
+            reason,
+        };
+        let window = |minutes: i64| {
+            let since = now - Duration::minutes(minutes);
+            let mut window: Vec<&ProtectionTrade> =
+                trades.iter().filter(|t| t.exit_time > since && t.exit_time <= now).collect();
+            window.sort_by_key(|t| t.exit_time);
+            window
+        };
+
+        match *self {
+            Protection::CooldownPeriod { stop_duration_minutes } => {
+                // A forced exit (end of run, manual close) says nothing about the pair
+                let last = trades
+                    .iter()
+                    .filter(|t| t.symbol == symbol && t.exit_time <= now)
+                    .max_by_key(|t| t.exit_time);
+                if last.is_some_and(|t| t.exit_reason == ExitReason::Forced) {
+                    return None;
+                }
+                Some(lock(
+                    Some(symbol),
+                    stop_duration_minutes,
+                    format!("Cooldown after exit for {} min", stop_duration_minutes),
+                ))
+            }
+            Protection::StoplossGuard {
+                lookback_minutes,
+                trade_limit,
+                stop_duration_minutes,
+                only_per_pair,
+            } => {
+                let stops = window(lookback_minutes)
+                    .into_iter()
+                    .filter(|t| !only_per_pair || t.symbol == symbol)
+                    .filter(|t| t.is_stop_loss())
+                    .count();
+                (stops >= trade_limit).then(|| {
+                    lock(
+                        only_per_pair.then_some(symbol),
+                        stop_duration_minutes,
+                        format!("{} stoplosses in {} min", stops, lookback_minutes),
+                    )
+                })
+            }
+            Protection::MaxDrawdown {
+                lookback_minutes,
+                trade_limit,
+                stop_duration_minutes,
+                max_allowed_drawdown,
+            } => {
+                let window = window(lookback_minutes);
+                if window.len() < trade_limit {
+                    return None;
+                }
+                // Replay the window from the balance it started with
+                let mut balance = equity - window.iter().map(|t| t.pnl).sum::<f64>();
+                let mut peak = balance;
+                let mut drawdown: f64 = 0.0;
+                for trade in &window {
+                    balance += trade.pnl;
+                    peak = peak.max(balance);
+                    if peak > 0.0 {
+                        drawdown = drawdown.max((peak - balance) / peak);
+                    }
+                }
+                (drawdown > max_allowed_drawdown).then(|| {
+                    lock(
+                        None,
+                        stop_duration_minutes,
+                        format!("Drawdown {:.2}% in {} min", drawdown * 100.0, lookback_minutes),
+                    )
+                })
+            }
+            Protection::LowProfitPairs {
+                lookback_minutes,
+                trade_limit,
+                stop_duration_minutes,
+                required_profit,
+            } => {
+                let pair_trades: Vec<_> = window(lookback_minutes)
+                    .into_iter()
+                    .filter(|t| t.symbol == symbol)
+                    .collect();
+                if pair_trades.len() < trade_limit {
+                    return None;
+                }
+                let profit: f64 = pair_trades.iter().map(|t| t.pnl_ratio).sum();
+                (profit < required_profit).then(|| {
+                    lock(
+                        Some(symbol),
+                        stop_duration_minutes,
+                        format!("Profit {:.2}% below {:.2}%", profit * 100.0, required_profit * 100.0),
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Closed trade as seen by protections
+#[derive(Debug, Clone)]
+pub struct ProtectionTrade {
+    pub symbol: String,
+    pub exit_time: DateTime<Utc>,
+    pub pnl: f64,
+    /// P&L as a fraction of the entry value (e.g., -0.02 = -2%)
+    pub pnl_ratio: f64,
+    pub exit_reason: ExitReason,
+}
+
+impl ProtectionTrade {
+    /// Losing stop loss, trailing stop or custom stoploss exit
+    fn is_stop_loss(&self) -> bool {
+        matches!(
+            self.exit_reason,
+            ExitReason::StopLoss | ExitReason::TrailingStop | ExitReason::CustomStoploss
+        ) && self.pnl < 0.0
+    }
+}
+
+/// Entry lock created by a protection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtectionLock {
+    /// Locked pair, `None` locks all pairs
+    pub symbol: Option<String>,
+    /// Lock end
+    pub until: DateTime<Utc>,
+    /// Protection that created the lock
+    pub protection: String,
+    /// Human readable reason
+    pub reason: String,
+}
+
+impl ProtectionLock {
+    /// Check if the lock blocks entries on `symbol` at `now`
+    pub fn applies_to(&self, symbol: &str, now: DateTime<Utc>) -> bool {
+        now < self.until && self.symbol.as_deref().is_none_or(|s| s == symbol)
+    }
+}
+
+/// Evaluates protections and tracks active locks
+#[derive(Debug, Clone, Default)]
+pub struct ProtectionManager {
+    protections: Vec<Protection>,
+    locks: Vec<ProtectionLock>,
+}
+
+impl ProtectionManager {
+    /// Create new manager
+    pub fn new(protections: Vec<Protection>) -> Self {
+        Self {
+            protections,
+            locks: Vec::new(),
+        }
+    }
+
+    /// Restore persisted locks
+    pub fn with_locks(mut self, locks: Vec<ProtectionLock>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Check if no protections are configured
+    pub fn is_empty(&self) -> bool {
+        self.protections.is_empty()
+    }
+
+    /// Evaluate all protections after a trade on `symbol` closed at `now`
+    ///
+    /// Returns the newly created locks so the caller can persist them.
+    pub fn on_trade_closed(
+        &mut self,
+        trades: &[ProtectionTrade],
+        symbol: &str,
+        now: DateTime<Utc>,
+        equity: f64,
+    ) -> Vec<ProtectionLock> {
+        self.locks.retain(|lock| lock.until > now);
+        let new_locks: Vec<ProtectionLock> = self
+            .protections
+            .iter()
+            .filter_map(|p| p.evaluate(trades, symbol, now, equity))
+            .collect();
+        self.locks.extend(new_locks.iter().cloned());
+        new_locks
+    }
+
+    /// Add locks created elsewhere (e.g., loaded from storage), skipping known ones
+    pub fn add_locks(&mut self, locks: impl IntoIterator<Item = ProtectionLock>) {
+        for lock in locks {
+            if !self.locks.contains(&lock) {
+                self.locks.push(lock);
+            }
+        }
+    }
+
+    /// Lock that blocks entries on `symbol` at `now`, the longest one if several apply
+    pub fn lock_for(&self, symbol: &str, now: DateTime<Utc>) -> Option<&ProtectionLock> {
+        self.locks
+            .iter()
+            .filter(|lock| lock.applies_to(symbol, now))
+            .max_by_key(|lock| lock.until)
+    }
+
+    /// All locks, including expired ones not yet pruned
+    pub fn locks(&self) -> &[ProtectionLock] {
+        &self.locks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn trade(symbol: &str, minutes: i64, pnl: f64, reason: ExitReason) -> ProtectionTrade {
+        ProtectionTrade {
+            symbol: symbol.to_string(),
+            exit_time: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minutes),
+            pnl,
+            pnl_ratio: pnl / 1000.0,
+            exit_reason: reason,
+        }
+    }
+
+    #[test]
+    fn test_stoploss_guard_locks_all_pairs() {
+        let mut manager = ProtectionManager::new(vec![Protection::StoplossGuard {
+            lookback_minutes: 60,
+            trade_limit: 2,
+            stop_duration_minutes: 30,
+            only_per_pair: false,
+        }]);
+        let mut trades = vec![trade("BTC/USDT", 0, -10.0, ExitReason::StopLoss)];
+        let now = trades[0].exit_time;
+        assert!(manager.on_trade_closed(&trades, "BTC/USDT", now, 1000.0).is_empty());
+
+        trades.push(trade("ETH/USDT", 20, -10.0, ExitReason::TrailingStop));
+        let now = trades[1].exit_time;
+        let locks = manager.on_trade_closed(&trades, "ETH/USDT", now, 980.0);
+        assert_eq!(locks.len(), 1);
+        assert!(manager.lock_for("SOL/USDT", now + Duration::minutes(29)).is_some());
+        assert!(manager.lock_for("SOL/USDT", now + Duration::minutes(30)).is_none());
+    }
+
+    #[test]
+    fn test_cooldown_and_low_profit_lock_pair() {
+        let mut manager = ProtectionManager::new(vec![
+            Protection::CooldownPeriod { stop_duration_minutes: 10 },
+            Protection::LowProfitPairs {
+                lookback_minutes: 120,
+                trade_limit: 2,
+                stop_duration_minutes: 60,
+                required_profit: 0.0,
+            },
+        ]);
+        let trades = vec![
+            trade("BTC/USDT", 0, 5.0, ExitReason::Roi),
+            trade("BTC/USDT", 30, -8.0, ExitReason::Signal),
+        ];
+        let now = trades[1].exit_time;
+        manager.on_trade_closed(&trades, "BTC/USDT", now, 997.0);
+
+        let lock = manager.lock_for("BTC/USDT", now + Duration::minutes(20)).unwrap();
+        assert_eq!(lock.protection, "LowProfitPairs");
+        assert!(manager.lock_for("ETH/USDT", now).is_none());
+    }
+
+    #[test]
+    fn test_cooldown_skips_forced_exits() {
+        let protection = Protection::CooldownPeriod { stop_duration_minutes: 10 };
+        let trades = vec![
+            trade("BTC/USDT", 0, -5.0, ExitReason::Signal),
+            trade("BTC/USDT", 30, 2.0, ExitReason::Forced),
+        ];
+
+        assert!(protection.evaluate(&trades[..1], "BTC/USDT", trades[0].exit_time, 995.0).is_some());
+        assert!(protection.evaluate(&trades, "BTC/USDT", trades[1].exit_time, 997.0).is_none());
+    }
+
+    #[test]
+    fn test_max_drawdown() {
+        let protection = Protection::MaxDrawdown {
+            lookback_minutes: 60,
+            trade_limit: 2,
+            stop_duration_minutes: 60,
+            max_allowed_drawdown: 0.05,
+        };
+        let trades = vec![
+            trade("BTC/USDT", 0, 50.0, ExitReason::Roi),
+            trade("ETH/USDT", 10, -80.0, ExitReason::StopLoss),
+        ];
+        // 1000 -> 1050 -> 970 is a 7.6% drawdown
+        let lock = protection.evaluate(&trades, "ETH/USDT", trades[1].exit_time, 970.0).unwrap();
+        assert_eq!(lock.symbol, None);
+
+        let json = serde_json::to_string(&protection).unwrap();
+        assert_eq!(serde_json::from_str::<Protection>(&json).unwrap(), protection);
+    }
+}
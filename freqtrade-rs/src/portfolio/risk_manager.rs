@@ -1,7 +1,7 @@
 //! Risk management
 
 use crate::config::RiskConfig;
-use crate::portfolio::{Balance, Position};
+use crate::portfolio::Balance;
 
 /// Risk manager
 #[derive(Debug)]
@@ -15,6 +15,11 @@ impl RiskManager {
         Self { config }
     }
 
+    /// Get risk configuration
+    pub fn config(&self) -> &RiskConfig {
+        &self.config
+    }
+
     /// Check if position size is within limits
     pub fn can_open_position(
         &self,
@@ -0,0 +1,428 @@
+//! Position sizing models
+//!
+//! A [`PositionSizer`] turns account state and the entry setup into an order
+//! quantity. [`PositionSizing`] is the serializable selection stored in strategy
+//! configs and shared by the backtester and live execution.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// Inputs available to a position sizer when an entry is taken
+#[derive(Debug, Clone, Default)]
+pub struct SizingContext<'a> {
+    /// Total account equity
+    pub equity: f64,
+    /// Balance available for new positions
+    pub available: f64,
+    /// Planned entry price
+    pub entry_price: f64,
+    /// Planned stop loss price, if any
+    pub stop_loss: Option<f64>,
+    /// Current ATR of the traded symbol, if tracked
+    pub atr: Option<f64>,
+    /// Fractional returns of closed trades, oldest first (0.02 = +2%)
+    pub recent_returns: &'a [f64],
+}
+
+impl SizingContext<'_> {
+    /// Convert a quote-currency stake into a quantity
+    fn quantity_for_stake(&self, stake: f64) -> f64 {
+        if self.entry_price <= 0.0 || stake <= 0.0 {
+            return 0.0;
+        }
+        stake / self.entry_price
+    }
+
+    /// Convert an amount at risk and a per-unit price risk into a quantity
+    fn quantity_for_risk(&self, risk_amount: f64, price_risk: f64) -> f64 {
+        if price_risk <= 0.0 || risk_amount <= 0.0 {
+            return 0.0;
+        }
+        risk_amount / price_risk
+    }
+}
+
+/// Position sizer trait
+pub trait PositionSizer: Debug + Send + Sync {
+    /// Get the name of the sizing model
+    fn name(&self) -> &str;
+
+    /// Calculate order quantity in base currency (0.0 skips the entry)
+    fn quantity(&self, ctx: &SizingContext) -> f64;
+
+    /// ATR period the sizer needs, if it uses volatility
+    fn atr_period(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Fixed quote-currency stake per trade
+#[derive(Debug, Clone)]
+pub struct FixedStakeSizer {
+    pub stake: f64,
+}
+
+impl PositionSizer for FixedStakeSizer {
+    fn name(&self) -> &str {
+        "FixedStake"
+    }
+
+    fn quantity(&self, ctx: &SizingContext) -> f64 {
+        ctx.quantity_for_stake(self.stake)
+    }
+}
+
+/// Fixed percentage of equity per trade
+#[derive(Debug, Clone)]
+pub struct PercentOfEquitySizer {
+    /// Fraction of equity to stake (e.g., 0.1 = 10%)
+    pub fraction: f64,
+}
+
+impl PositionSizer for PercentOfEquitySizer {
+    fn name(&self) -> &str {
+        "PercentOfEquity"
+    }
+
+    fn quantity(&self, ctx: &SizingContext) -> f64 {
+        ctx.quantity_for_stake(ctx.equity * self.fraction)
+    }
+}
+
+/// Risk a fixed fraction of equity between entry and stop loss
+#[derive(Debug, Clone)]
+pub struct RiskBasedSizer {
+    /// Fraction of equity lost if the stop is hit (e.g., 0.01 = 1%)
+    pub risk_per_trade: f64,
+}
+
+impl PositionSizer for RiskBasedSizer {
+    fn name(&self) -> &str {
+        "RiskBased"
+    }
+
+    fn quantity(&self, ctx: &SizingContext) -> f64 {
+        let Some(stop_loss) = ctx.stop_loss else {
+            return 0.0;
+        };
+        ctx.quantity_for_risk(ctx.equity * self.risk_per_trade, (ctx.entry_price - stop_loss).abs())
+    }
+}
+
+/// Fractional Kelly stake from rolling win statistics
+#[derive(Debug, Clone)]
+pub struct KellySizer {
+    /// Multiplier applied to the full Kelly fraction (e.g., 0.5 = half Kelly)
+    pub kelly_fraction: f64,
+    /// Number of most recent trades used for statistics
+    pub lookback: usize,
+    /// Minimum trades before the Kelly estimate is trusted
+    pub min_trades: usize,
+    /// Fraction of equity staked until enough trades exist
+    pub fallback_fraction: f64,
+    /// Upper bound for the staked fraction of equity
+    pub max_fraction: f64,
+}
+
+impl KellySizer {
+    /// Full Kelly fraction for the given returns
+    /// Without wins there is no edge (0), without losses every trade won (1).
+    pub fn kelly(returns: &[f64]) -> f64 {
+        let wins: Vec<f64> = returns.iter().copied().filter(|r| *r > 0.0).collect();
+        let losses: Vec<f64> = returns.iter().copied().filter(|r| *r < 0.0).collect();
+        if wins.is_empty() {
+            return 0.0;
+        }
+        if losses.is_empty() {
+            return 1.0;
+        }
+
+        let win_rate = wins.len() as f64 / (wins.len() + losses.len()) as f64;
+        let avg_win = wins.iter().sum::<f64>() / wins.len() as f64;
+        let avg_loss = -losses.iter().sum::<f64>() / losses.len() as f64;
+        let payoff = avg_win / avg_loss;
+
+        win_rate - (1.0 - win_rate) / payoff
+    }
+}
+
+impl PositionSizer for KellySizer {
+    fn name(&self) -> &str {
+        "Kelly"
+    }
+
+    fn quantity(&self, ctx: &SizingContext) -> f64 {
+        let start = ctx.recent_returns.len().saturating_sub(self.lookback);
+        let window = &ctx.recent_returns[start..];
+
+        // A negative edge stakes nothing, the entry is skipped
+        let fraction = if window.len() < self.min_trades {
+            self.fallback_fraction
+        } else {
+            Self::kelly(window) * self.kelly_fraction
+        };
+
+        ctx.quantity_for_stake(ctx.equity * fraction.clamp(0.0, self.max_fraction))
+    }
+}
+
+/// Volatility targeting: stop distance is a multiple of ATR
+#[derive(Debug, Clone)]
+pub struct VolatilityTargetSizer {
+    /// Fraction of equity lost over one stop distance (e.g., 0.01 = 1%)
+    pub risk_per_trade: f64,
+    /// ATR period
+    pub atr_period: usize,
+    /// Stop distance in ATRs
+    pub atr_multiplier: f64,
+}
+
+impl PositionSizer for VolatilityTargetSizer {
+    fn name(&self) -> &str {
+        "VolatilityTarget"
+    }
+
+    fn quantity(&self, ctx: &SizingContext) -> f64 {
+        // No entry until ATR has warmed up
+        let Some(atr) = ctx.atr else {
+            return 0.0;
+        };
+        ctx.quantity_for_risk(ctx.equity * self.risk_per_trade, atr * self.atr_multiplier)
+    }
+
+    fn atr_period(&self) -> Option<usize> {
+        Some(self.atr_period)
+    }
+}
+
+/// Serializable position sizing selection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionSizing {
+    /// Fixed stake in quote currency
+    FixedStake { stake: f64 },
+    /// Percentage of equity (e.g., 0.1 = 10%)
+    PercentOfEquity { fraction: f64 },
+    /// Risk a percentage of equity to the stop loss
+    RiskBased { risk_per_trade: f64 },
+    /// Fractional Kelly from recent trade statistics
+    Kelly {
+        kelly_fraction: f64,
+        lookback: usize,
+        min_trades: usize,
+        fallback_fraction: f64,
+        max_fraction: f64,
+    },
+    /// ATR-based volatility targeting
+    VolatilityTarget {
+        risk_per_trade: f64,
+        atr_period: usize,
+        atr_multiplier: f64,
+    },
+}
+
+impl Default for PositionSizing {
+    fn default() -> Self {
+        Self::PercentOfEquity { fraction: 0.1 } // 10% of equity
+    }
+}
+
+impl PositionSizing {
+    /// Half Kelly over the last 50 trades, 5% stake until 10 trades exist
+    pub fn default_kelly() -> Self {
+        Self::Kelly {
+            kelly_fraction: 0.5,
+            lookback: 50,
+            min_trades: 10,
+            fallback_fraction: 0.05,
+            max_fraction: 0.25,
+        }
+    }
+
+    /// 1% risk per 2 x ATR(14)
+    pub fn default_volatility_target() -> Self {
+        Self::VolatilityTarget {
+            risk_per_trade: 0.01,
+            atr_period: 14,
+            atr_multiplier: 2.0,
+        }
+    }
+
+    /// Check the parameters, e.g. from a user's strategy config, before building the sizer
+    pub fn validate(&self) -> crate::Result<()> {
+        let positive = |name: &str, value: f64| {
+            if value.is_finite() && value > 0.0 {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("{} must be positive, got {}", name, value))
+            }
+        };
+        let fraction = |name: &str, value: f64| {
+            positive(name, value)?;
+            if value > 1.0 {
+                return Err(anyhow::anyhow!("{} must be at most 1, got {}", name, value));
+            }
+            Ok(())
+        };
+        match self {
+            Self::FixedStake { stake } => positive("stake", *stake),
+            Self::PercentOfEquity { fraction: f } => fraction("fraction", *f),
+            Self::RiskBased { risk_per_trade } => fraction("risk_per_trade", *risk_per_trade),
+            Self::Kelly { kelly_fraction, lookback, min_trades: _, fallback_fraction, max_fraction } => {
+                fraction("kelly_fraction", *kelly_fraction)?;
+                fraction("fallback_fraction", *fallback_fraction)?;
+                fraction("max_fraction", *max_fraction)?;
+                if *lookback == 0 {
+                    return Err(anyhow::anyhow!("lookback must be at least 1"));
+                }
+                Ok(())
+            }
+            Self::VolatilityTarget { risk_per_trade, atr_period, atr_multiplier } => {
+                fraction("risk_per_trade", *risk_per_trade)?;
+                positive("atr_multiplier", *atr_multiplier)?;
+                if *atr_period == 0 {
+                    return Err(anyhow::anyhow!("atr_period must be at least 1"));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Build the sizer for this selection
+    pub fn build(&self) -> Box<dyn PositionSizer> {
+        match self.clone() {
+            Self::FixedStake { stake } => Box::new(FixedStakeSizer { stake }),
+            Self::PercentOfEquity { fraction } => Box::new(PercentOfEquitySizer { fraction }),
+            Self::RiskBased { risk_per_trade } => Box::new(RiskBasedSizer { risk_per_trade }),
+            Self::Kelly {
+                kelly_fraction,
+                lookback,
+                min_trades,
+                fallback_fraction,
+                max_fraction,
+            } => Box::new(KellySizer {
+                kelly_fraction,
+                lookback,
+                min_trades,
+                fallback_fraction,
+                max_fraction,
+            }),
+            Self::VolatilityTarget {
+                risk_per_trade,
+                atr_period,
+                atr_multiplier,
+            } => Box::new(VolatilityTargetSizer {
+                risk_per_trade,
+                atr_period,
+                atr_multiplier,
+            }),
+        }
+    }
+
+    /// Short human readable description
+    pub fn describe(&self) -> String {
+        match self {
+            Self::FixedStake { stake } => format!("Fixed stake {:.2}", stake),
+            Self::PercentOfEquity { fraction } => format!("{:.1}% of equity", fraction * 100.0),
+            Self::RiskBased { risk_per_trade } => {
+                format!("{:.1}% risk to stop", risk_per_trade * 100.0)
+            }
+            Self::Kelly { kelly_fraction, lookback, .. } => {
+                format!("{:.2} x Kelly over {} trades", kelly_fraction, lookback)
+            }
+            Self::VolatilityTarget { risk_per_trade, atr_period, atr_multiplier } => format!(
+                "{:.1}% risk per {:.1} x ATR({})",
+                risk_per_trade * 100.0,
+                atr_multiplier,
+                atr_period
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(returns: &[f64]) -> SizingContext<'_> {
+        SizingContext {
+            equity: 10000.0,
+            available: 10000.0,
+            entry_price: 100.0,
+            stop_loss: Some(95.0),
+            atr: Some(2.5),
+            recent_returns: returns,
+        }
+    }
+
+    #[test]
+    fn test_basic_sizers() {
+        let ctx = context(&[]);
+        assert_eq!(PositionSizing::FixedStake { stake: 500.0 }.build().quantity(&ctx), 5.0);
+        assert_eq!(PositionSizing::PercentOfEquity { fraction: 0.1 }.build().quantity(&ctx), 10.0);
+        // 1% of 10000 over a 5.0 stop distance
+        assert_eq!(PositionSizing::RiskBased { risk_per_trade: 0.01 }.build().quantity(&ctx), 20.0);
+        // 1% of 10000 over 2 x 2.5 ATR
+        let sizer = PositionSizing::default_volatility_target().build();
+        assert_eq!(sizer.quantity(&ctx), 20.0);
+        assert_eq!(sizer.atr_period(), Some(14));
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_parameters() {
+        assert!(PositionSizing::default().validate().is_ok());
+        assert!(PositionSizing::default_kelly().validate().is_ok());
+        assert!(PositionSizing::default_volatility_target().validate().is_ok());
+
+        let zero_atr = PositionSizing::VolatilityTarget {
+            risk_per_trade: 0.01,
+            atr_period: 0,
+            atr_multiplier: 2.0,
+        };
+        assert!(zero_atr.validate().unwrap_err().to_string().contains("atr_period"));
+        assert!(PositionSizing::FixedStake { stake: -5.0 }.validate().is_err());
+        assert!(PositionSizing::PercentOfEquity { fraction: 1.5 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_sizers_skip_without_inputs() {
+        let ctx = SizingContext {
+            stop_loss: None,
+            atr: None,
+            ..context(&[])
+        };
+        assert_eq!(PositionSizing::RiskBased { risk_per_trade: 0.01 }.build().quantity(&ctx), 0.0);
+        assert_eq!(PositionSizing::default_volatility_target().build().quantity(&ctx), 0.0);
+    }
+
+    #[test]
+    fn test_kelly_sizer() {
+        // 60% win rate, 2:1 payoff => full Kelly 0.4
+        let returns = [0.04, -0.02, 0.04, -0.02, 0.04, 0.04, -0.02, 0.04, -0.02, 0.04];
+        let kelly = KellySizer::kelly(&returns);
+        assert!((kelly - 0.4).abs() < 1e-9);
+
+        let sizer = PositionSizing::default_kelly().build();
+        // Half Kelly => 20% of equity
+        assert!((sizer.quantity(&context(&returns)) - 20.0).abs() < 1e-9);
+        // Not enough history => 5% fallback
+        assert!((sizer.quantity(&context(&returns[..3])) - 5.0).abs() < 1e-9);
+        // Only losses => no stake
+        assert_eq!(KellySizer::kelly(&[-0.02; 10]), 0.0);
+        assert_eq!(sizer.quantity(&context(&[-0.02; 10])), 0.0);
+        // Only wins => capped at the maximum fraction
+        assert_eq!(KellySizer::kelly(&[0.02; 10]), 1.0);
+        assert!((sizer.quantity(&context(&[0.02; 10])) - 25.0).abs() < 1e-9);
+        // Losing edge => no stake
+        let losing = [0.01, -0.03, 0.01, -0.03, -0.03, 0.01, -0.03, -0.03, -0.03, 0.01];
+        assert_eq!(sizer.quantity(&context(&losing)), 0.0);
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let json = r#"{"fixed_stake":{"stake":25.0}}"#;
+        let sizing: PositionSizing = serde_json::from_str(json).unwrap();
+        assert_eq!(sizing, PositionSizing::FixedStake { stake: 25.0 });
+        assert_eq!(serde_json::to_string(&sizing).unwrap(), json);
+    }
+}
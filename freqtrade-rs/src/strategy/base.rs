@@ -1,7 +1,14 @@
 //! Base strategy trait and common strategy implementations
 
-use crate::data::Candle;
+use crate::data::{Candle, InformativeData, InformativeSpec};
+use crate::exchange::OrderSide;
+use crate::portfolio::Position;
+use crate::strategy::ClauseResult;
 use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Base trait for all trading strategies
 pub trait Strategy {
@@ -16,71 +23,271 @@ pub trait Strategy {
     
     /// Check if strategy is ready (has enough data)
     fn is_ready(&self) -> bool;
+
+    /// Current indicator values by name, used by the lookahead and recursive analyzers
+    fn indicator_values(&self) -> Vec<(String, f64)> {
+        Vec::new()
+    }
+
+    /// Conditions evaluated on the last processed candle with their outcome, labelled
+    /// ("buy", "sell"), to explain the signal
+    fn explain_conditions(&self) -> Vec<(String, ClauseResult)> {
+        Vec::new()
+    }
+
+    /// Informative timeframes and pairs the strategy needs
+    fn informative(&self) -> Vec<InformativeSpec> {
+        Vec::new()
+    }
+
+    /// Process new candle with closed informative candles available
+    fn process_with_informative(
+        &mut self,
+        candle: &Candle,
+        _informative: &InformativeData,
+    ) -> Result<Signal> {
+        self.process(candle)
+    }
+
+    /// Dynamic stop loss for an open position, called on every candle
+    ///
+    /// Returns the new stop price, `None` keeps the current stop. Engines only
+    /// apply stops that tighten the current one.
+    fn custom_stoploss(&mut self, _position: &Position, _candle: &Candle) -> Option<f64> {
+        None
+    }
+
+    /// Position-aware exit, called on every candle with an open position
+    ///
+    /// Returns an exit tag to close the position at the candle close.
+    fn custom_exit(&mut self, _position: &Position, _candle: &Candle) -> Option<String> {
+        None
+    }
+
+    /// Position adjustment (DCA / scale-out), called on every candle with an open position
+    ///
+    /// Returns a stake amount in quote currency: positive adds to the position at the
+    /// candle close, negative exits that much value. `None` leaves the position as is.
+    fn adjust_trade_position(&mut self, _position: &Position, _candle: &Candle) -> Option<f64> {
+        None
+    }
+
+    /// Confirm an entry signal right before the position is opened
+    fn confirm_trade_entry(&mut self, _signal: &Signal, _candle: &Candle) -> bool {
+        true
+    }
+
+    /// Confirm an exit right before the position is closed
+    ///
+    /// Forced exits are not vetoable (see [`ExitReason::is_vetoable`]).
+    fn confirm_trade_exit(
+        &mut self,
+        _position: &Position,
+        _reason: ExitReason,
+        _candle: &Candle,
+    ) -> bool {
+        true
+    }
+
+    /// Resting limit orders, called after every candle
+    ///
+    /// The returned orders replace the previous ones and rest until the next candle.
+    /// Buy fills open or add to a long position, sell fills reduce it.
+    fn limit_orders(&self) -> Vec<LimitOrder> {
+        Vec::new()
+    }
+
+    /// Called when one of the resting limit orders filled
+    fn on_order_filled(&mut self, _order: &LimitOrder, _fill_price: f64, _time: DateTime<Utc>) {}
+
+    /// Closed candles needed before the strategy emits signals
+    fn startup_candle_count(&self) -> usize {
+        0
+    }
+
+    /// Serialize the running state (indicators, buffers) for a checkpoint
+    ///
+    /// `None` if the strategy does not support snapshots and has to warm up again.
+    fn snapshot(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Resume from a state returned by [`Strategy::snapshot`]
+    fn restore(&mut self, _state: serde_json::Value) -> Result<()> {
+        Err(anyhow::anyhow!("Strategy {} does not support snapshots", self.name()))
+    }
+
+    /// Serialize the indicators alone, to carry them over to the same strategy with
+    /// other thresholds or parameters (hot reload)
+    ///
+    /// `None` if the strategy cannot be reconfigured without warming up again.
+    fn indicator_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Resume the indicators from a state returned by [`Strategy::indicator_state`]
+    ///
+    /// Fails if the indicators or their periods differ from the ones the state was taken with.
+    fn restore_indicator_state(&mut self, _state: serde_json::Value) -> Result<()> {
+        Err(anyhow::anyhow!("Strategy {} does not support hot reload", self.name()))
+    }
+}
+
+/// Resting limit order requested by a strategy
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitOrder {
+    pub side: OrderSide,
+    /// Limit price
+    pub price: f64,
+    /// Quantity in base currency
+    pub quantity: f64,
+    /// Tag recorded as the entry tag or exit tag of the fill
+    pub tag: Option<String>,
+}
+
+impl LimitOrder {
+    /// Create limit buy order
+    pub fn buy(price: f64, quantity: f64) -> Self {
+        Self {
+            side: OrderSide::Buy,
+            price,
+            quantity,
+            tag: None,
+        }
+    }
+
+    /// Create limit sell order
+    pub fn sell(price: f64, quantity: f64) -> Self {
+        Self {
+            side: OrderSide::Sell,
+            price,
+            quantity,
+            tag: None,
+        }
+    }
+
+    /// Set tag
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    /// Check if a price range reaches the limit price
+    pub fn is_hit(&self, low: f64, high: f64) -> bool {
+        match self.side {
+            OrderSide::Buy => low <= self.price,
+            OrderSide::Sell => high >= self.price,
+        }
+    }
+
+    /// Fill price when the market opens at `open`, orders gapped through fill at the open
+    pub fn fill_price(&self, open: f64) -> f64 {
+        match self.side {
+            OrderSide::Buy => self.price.min(open),
+            OrderSide::Sell => self.price.max(open),
+        }
+    }
 }
 
-/// Buy signal type
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Signal type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SignalType {
-    /// Buy/Long signal
-    Buy,
-    /// Sell/Short signal
-    Sell,
+    /// Open a long position
+    EnterLong,
+    /// Close long positions
+    ExitLong,
+    /// Open a short position
+    EnterShort,
+    /// Close short positions
+    ExitShort,
     /// Hold/No action
     Hold,
 }
 
+impl SignalType {
+    /// Check if the signal opens a position
+    pub fn is_entry(&self) -> bool {
+        matches!(self, SignalType::EnterLong | SignalType::EnterShort)
+    }
+
+    /// Check if the signal closes positions
+    pub fn is_exit(&self) -> bool {
+        matches!(self, SignalType::ExitLong | SignalType::ExitShort)
+    }
+}
+
 /// Trading signal
 #[derive(Debug, Clone)]
 pub struct Signal {
     /// Signal type
     pub signal_type: SignalType,
-    /// Entry price (if buy)
+    /// Entry price for entries, exit price for exits
     pub entry_price: Option<f64>,
     /// Stop loss price
     pub stop_loss: Option<f64>,
     /// Take profit price
     pub take_profit: Option<f64>,
+    /// Trailing stop distance (e.g., 0.02 = 2%)
+    pub trailing_stop: Option<f64>,
     /// Confidence level (0.0 to 1.0)
     pub confidence: f64,
     /// Reason for signal
     pub reason: String,
+    /// Entry tag recorded on the trade (e.g., "rsi_oversold")
+    pub enter_tag: Option<String>,
+    /// Exit tag recorded as the trade's exit reason
+    pub exit_tag: Option<String>,
 }
 
 impl Signal {
-    /// Create buy signal
-    pub fn buy(entry_price: f64, confidence: f64, reason: String) -> Self {
+    /// Create signal of the given type
+    pub fn new(signal_type: SignalType, price: Option<f64>, confidence: f64, reason: String) -> Self {
         Self {
-            signal_type: SignalType::Buy,
-            entry_price: Some(entry_price),
+            signal_type,
+            entry_price: price,
             stop_loss: None,
             take_profit: None,
+            trailing_stop: None,
             confidence,
             reason,
+            enter_tag: None,
+            exit_tag: None,
         }
     }
 
-    /// Create sell signal
-    pub fn sell(entry_price: f64, confidence: f64, reason: String) -> Self {
-        Self {
-            signal_type: SignalType::Sell,
-            entry_price: Some(entry_price),
-            stop_loss: None,
-            take_profit: None,
-            confidence,
-            reason,
-        }
+    /// Create enter long signal
+    pub fn enter_long(entry_price: f64, confidence: f64, reason: String) -> Self {
+        Self::new(SignalType::EnterLong, Some(entry_price), confidence, reason)
+    }
+
+    /// Create exit long signal
+    pub fn exit_long(exit_price: f64, confidence: f64, reason: String) -> Self {
+        Self::new(SignalType::ExitLong, Some(exit_price), confidence, reason)
+    }
+
+    /// Create enter short signal
+    pub fn enter_short(entry_price: f64, confidence: f64, reason: String) -> Self {
+        Self::new(SignalType::EnterShort, Some(entry_price), confidence, reason)
+    }
+
+    /// Create exit short signal
+    pub fn exit_short(exit_price: f64, confidence: f64, reason: String) -> Self {
+        Self::new(SignalType::ExitShort, Some(exit_price), confidence, reason)
+    }
+
+    /// Create buy signal (same as [`Signal::enter_long`])
+    pub fn buy(entry_price: f64, confidence: f64, reason: String) -> Self {
+        Self::enter_long(entry_price, confidence, reason)
+    }
+
+    /// Create sell signal (same as [`Signal::exit_long`])
+    pub fn sell(exit_price: f64, confidence: f64, reason: String) -> Self {
+        Self::exit_long(exit_price, confidence, reason)
     }
 
     /// Create hold signal
     pub fn hold(reason: String) -> Self {
-        Self {
-            signal_type: SignalType::Hold,
-            entry_price: None,
-            stop_loss: None,
-            take_profit: None,
-            confidence: 0.0,
-            reason,
-        }
+        Self::new(SignalType::Hold, None, 0.0, reason)
     }
 
     /// Set stop loss
@@ -94,5 +301,84 @@ impl Signal {
         self.take_profit = Some(take_profit);
         self
     }
+
+    /// Set trailing stop distance
+    pub fn with_trailing_stop(mut self, distance: f64) -> Self {
+        self.trailing_stop = Some(distance);
+        self
+    }
+
+    /// Set entry tag
+    pub fn with_enter_tag(mut self, tag: &str) -> Self {
+        self.enter_tag = Some(tag.to_string());
+        self
+    }
+
+    /// Set exit tag
+    pub fn with_exit_tag(mut self, tag: &str) -> Self {
+        self.exit_tag = Some(tag.to_string());
+        self
+    }
 }
 
+/// Why a position was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExitReason {
+    /// Strategy exit signal
+    Signal,
+    /// Stop loss hit
+    StopLoss,
+    /// Take profit (minimal ROI) hit
+    Roi,
+    /// Trailing stop hit after it moved the stop loss
+    TrailingStop,
+    /// Stop loss hit after the strategy's `custom_stoploss` callback moved it
+    CustomStoploss,
+    /// Strategy's `custom_exit` callback
+    CustomExit,
+    /// Exit by `adjust_trade_position` (partial, or full when it sells everything)
+    PartialExit,
+    /// Closed by the engine, e.g. at the end of the backtest
+    Forced,
+}
+
+impl ExitReason {
+    /// Check if `confirm_trade_exit` may veto this exit
+    pub fn is_vetoable(&self) -> bool {
+        !matches!(self, ExitReason::Forced)
+    }
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            ExitReason::Signal => "exit_signal",
+            ExitReason::StopLoss => "stop_loss",
+            ExitReason::Roi => "roi",
+            ExitReason::TrailingStop => "trailing_stop_loss",
+            ExitReason::CustomStoploss => "custom_stoploss",
+            ExitReason::CustomExit => "custom_exit",
+            ExitReason::PartialExit => "partial_exit",
+            ExitReason::Forced => "force_exit",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+impl FromStr for ExitReason {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "exit_signal" => Ok(ExitReason::Signal),
+            "stop_loss" => Ok(ExitReason::StopLoss),
+            "roi" => Ok(ExitReason::Roi),
+            "trailing_stop_loss" => Ok(ExitReason::TrailingStop),
+            "custom_stoploss" => Ok(ExitReason::CustomStoploss),
+            "custom_exit" => Ok(ExitReason::CustomExit),
+            "partial_exit" => Ok(ExitReason::PartialExit),
+            "force_exit" => Ok(ExitReason::Forced),
+            _ => Err(anyhow::anyhow!("Unknown exit reason: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,1883 @@
+//! Expression language for strategy buy / sell conditions
+//!
+//! Conditions such as `RSI < 30 AND close > EMA(200)` are parsed into an [`Expr`]
+//! tree, type checked, and evaluated on every closed candle against an
+//! [`IndicatorSet`] holding the indicators they reference. The same tree renders
+//! as pandas code for freqtrade strategy templates.
+//!
+//! Grammar (keywords and names are case-insensitive):
+//!
+//! ```text
+//! or      := and (("OR" | "||") and)*
+//! and     := not (("AND" | "&&") not)*
+//! not     := ("NOT" | "!") not | compare
+//! compare := sum (("<" | "<=" | ">" | ">=" | "==" | "=" | "!=") sum)?
+//! sum     := product (("+" | "-") product)*
+//! product := unary (("*" | "/") unary)*
+//! unary   := "-" unary | postfix
+//! postfix := primary ("[" integer "]")*
+//! primary := number | field | parameter | indicator ["(" args ")"]
+//!          | function "(" args ")" | "(" or ")"
+//! ```
+//!
+//! Fields are `open`, `high`, `low`, `close` (or `price`) and `volume`. Names that
+//! are neither fields nor indicators are looked up in the strategy parameters.
+//! Indicator arguments left out are taken from the parameters (`rsi_period`, then
+//! `period`) or the indicator's default; those keys only stand for values inside
+//! indicator arguments, e.g. `SMA(period)`, so `MACD > signal` is not `MACD > 9`.
+//!
+//! Functions look at previous candles: `crosses_above(a, b)` and `crosses_below(a, b)`
+//! hold on the candle `a` crosses `b`, `rising(x, n)` / `falling(x, n)` when `x` rose /
+//! fell on each of the last `n` candles, `highest(x, n)` / `lowest(x, n)` are taken over
+//! the last `n` candles and `x[n]` is the value of `x` `n` candles ago.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use ta::indicators::{
+    AverageTrueRange, BollingerBands, ExponentialMovingAverage, FastStochastic,
+    MovingAverageConvergenceDivergence, RelativeStrengthIndex, SimpleMovingAverage,
+};
+use ta::{DataItem, Next};
+
+/// Candle field usable in expressions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceField {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+}
+
+impl PriceField {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "open" => Some(Self::Open),
+            "high" => Some(Self::High),
+            "low" => Some(Self::Low),
+            "close" | "price" => Some(Self::Close),
+            "volume" => Some(Self::Volume),
+            _ => None,
+        }
+    }
+
+    /// Name of the field (and of its dataframe column)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::High => "high",
+            Self::Low => "low",
+            Self::Close => "close",
+            Self::Volume => "volume",
+        }
+    }
+}
+
+/// Indicator usable in expressions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndicatorKind {
+    Rsi,
+    Ema,
+    Sma,
+    Macd,
+    MacdSignal,
+    MacdHist,
+    BbUpper,
+    BbMiddle,
+    BbLower,
+    Atr,
+    Stoch,
+    Adx,
+}
+
+impl IndicatorKind {
+    /// Every indicator, in the order they are listed to users
+    pub const ALL: [IndicatorKind; 12] = [
+        Self::Rsi,
+        Self::Ema,
+        Self::Sma,
+        Self::Macd,
+        Self::MacdSignal,
+        Self::MacdHist,
+        Self::BbUpper,
+        Self::BbMiddle,
+        Self::BbLower,
+        Self::Atr,
+        Self::Stoch,
+        Self::Adx,
+    ];
+
+    /// Look up an indicator by name or alias (lowercase)
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rsi" => Some(Self::Rsi),
+            "ema" => Some(Self::Ema),
+            "sma" | "ma" => Some(Self::Sma),
+            "macd" => Some(Self::Macd),
+            "macd_signal" => Some(Self::MacdSignal),
+            "macd_hist" | "macd_histogram" => Some(Self::MacdHist),
+            "bb_upper" | "upperband" => Some(Self::BbUpper),
+            "bb_middle" | "middleband" => Some(Self::BbMiddle),
+            "bb_lower" | "lowerband" => Some(Self::BbLower),
+            "atr" => Some(Self::Atr),
+            "stoch" | "stochastic" => Some(Self::Stoch),
+            "adx" => Some(Self::Adx),
+            _ => None,
+        }
+    }
+
+    /// Canonical name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rsi => "RSI",
+            Self::Ema => "EMA",
+            Self::Sma => "SMA",
+            Self::Macd => "MACD",
+            Self::MacdSignal => "MACD_SIGNAL",
+            Self::MacdHist => "MACD_HIST",
+            Self::BbUpper => "BB_UPPER",
+            Self::BbMiddle => "BB_MIDDLE",
+            Self::BbLower => "BB_LOWER",
+            Self::Atr => "ATR",
+            Self::Stoch => "STOCH",
+            Self::Adx => "ADX",
+        }
+    }
+
+    /// Argument names, also the parameter keys their defaults are read from
+    pub fn arg_names(&self) -> &'static [&'static str] {
+        match self {
+            Self::Macd | Self::MacdSignal | Self::MacdHist => &["fast", "slow", "signal"],
+            Self::BbUpper | Self::BbMiddle | Self::BbLower => &["period", "std_dev"],
+            _ => &["period"],
+        }
+    }
+
+    /// Default argument values
+    fn defaults(&self) -> &'static [f64] {
+        match self {
+            Self::Rsi | Self::Atr | Self::Stoch | Self::Adx => &[14.0],
+            Self::Ema | Self::Sma => &[20.0],
+            Self::Macd | Self::MacdSignal | Self::MacdHist => &[12.0, 26.0, 9.0],
+            Self::BbUpper | Self::BbMiddle | Self::BbLower => &[20.0, 2.0],
+        }
+    }
+
+    /// Prefix of parameter keys specific to this indicator (`rsi_period`, `bb_std_dev`, ...)
+    fn param_prefix(&self) -> &'static str {
+        match self {
+            Self::Rsi => "rsi",
+            Self::Ema => "ema",
+            Self::Sma => "sma",
+            Self::Macd | Self::MacdSignal | Self::MacdHist => "macd",
+            Self::BbUpper | Self::BbMiddle | Self::BbLower => "bb",
+            Self::Atr => "atr",
+            Self::Stoch => "stoch",
+            Self::Adx => "adx",
+        }
+    }
+
+    /// Whether `name` (lowercase) is a parameter key indicator arguments are read from
+    fn is_arg_param(name: &str) -> bool {
+        Self::ALL.iter().any(|kind| {
+            kind.arg_names()
+                .iter()
+                .any(|arg| name == *arg || name == format!("{}_{}", kind.param_prefix(), arg))
+        })
+    }
+}
+
+/// Indicator with its arguments, e.g. `EMA(200)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndicatorCall {
+    pub kind: IndicatorKind,
+    pub args: Vec<f64>,
+}
+
+impl IndicatorCall {
+    /// Argument `i` as a period
+    fn period(&self, i: usize) -> usize {
+        self.args[i] as usize
+    }
+
+    /// Closed candles needed before the indicator has a value
+    pub fn lookback(&self) -> usize {
+        match self.kind {
+            IndicatorKind::Rsi => self.period(0) + 1,
+            IndicatorKind::Macd | IndicatorKind::MacdSignal | IndicatorKind::MacdHist => {
+                self.period(1) + self.period(2)
+            }
+            IndicatorKind::Adx => self.period(0) * 2,
+            _ => self.period(0),
+        }
+    }
+
+    /// Dataframe column of the indicator in generated freqtrade strategies, e.g. `ema_200`
+    pub fn column(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(|a| format_number(*a).replace('.', "_")).collect();
+        format!("{}_{}", self.kind.name().to_lowercase(), args.join("_"))
+    }
+
+    /// Python statement computing the indicator column with TA-Lib
+    pub fn to_python(&self) -> String {
+        let a: Vec<String> = self.args.iter().map(|a| format_number(*a)).collect();
+        let value = match self.kind {
+            IndicatorKind::Rsi => format!("ta.RSI(dataframe, timeperiod={})", a[0]),
+            IndicatorKind::Ema => format!("ta.EMA(dataframe, timeperiod={})", a[0]),
+            IndicatorKind::Sma => format!("ta.SMA(dataframe, timeperiod={})", a[0]),
+            IndicatorKind::Macd | IndicatorKind::MacdSignal | IndicatorKind::MacdHist => {
+                let output = match self.kind {
+                    IndicatorKind::Macd => "macd",
+                    IndicatorKind::MacdSignal => "macdsignal",
+                    _ => "macdhist",
+                };
+                format!(
+                    "ta.MACD(dataframe, fastperiod={}, slowperiod={}, signalperiod={})['{}']",
+                    a[0], a[1], a[2], output
+                )
+            }
+            IndicatorKind::BbUpper | IndicatorKind::BbMiddle | IndicatorKind::BbLower => {
+                let output = match self.kind {
+                    IndicatorKind::BbUpper => "upperband",
+                    IndicatorKind::BbMiddle => "middleband",
+                    _ => "lowerband",
+                };
+                format!(
+                    "ta.BBANDS(dataframe, timeperiod={}, nbdevup={}, nbdevdn={})['{}']",
+                    a[0], a[1], a[1], output
+                )
+            }
+            IndicatorKind::Atr => format!("ta.ATR(dataframe, timeperiod={})", a[0]),
+            IndicatorKind::Stoch => format!("ta.STOCHF(dataframe, fastk_period={}, fastd_period=3)['fastk']", a[0]),
+            IndicatorKind::Adx => format!("ta.ADX(dataframe, timeperiod={})", a[0]),
+        };
+        format!("dataframe['{}'] = {}", self.column(), value)
+    }
+}
+
+impl fmt::Display for IndicatorCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(|a| format_number(*a)).collect();
+        write!(f, "{}({})", self.kind.name(), args.join(", "))
+    }
+}
+
+/// Binary operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::And => "AND",
+            Self::Or => "OR",
+        }
+    }
+}
+
+/// Parsed expression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expr {
+    Number(f64),
+    Field(PriceField),
+    Indicator(IndicatorCall),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    /// Value `offset` candles ago, `close[1]`
+    Offset {
+        expr: Box<Expr>,
+        offset: usize,
+    },
+    /// Highest or lowest value over the last `length` candles
+    Window {
+        func: WindowFunc,
+        expr: Box<Expr>,
+        length: usize,
+    },
+    /// `left` crossed above (or below) `right` on this candle
+    Cross {
+        above: bool,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    /// `expr` rose (or fell) on each of the last `length` candles
+    Trend {
+        rising: bool,
+        expr: Box<Expr>,
+        length: usize,
+    },
+}
+
+/// Aggregate of [`Expr::Window`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowFunc {
+    Highest,
+    Lowest,
+}
+
+impl WindowFunc {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Highest => "highest",
+            Self::Lowest => "lowest",
+        }
+    }
+}
+
+impl Expr {
+    /// Numeric value, `None` while a referenced indicator has no value yet
+    pub fn value(&self, source: &dyn ValueSource) -> Option<f64> {
+        match self {
+            Expr::Number(n) => Some(*n),
+            Expr::Field(field) => source.field(*field),
+            Expr::Indicator(call) => source.indicator(call),
+            Expr::Neg(inner) => inner.value(source).map(|v| -v),
+            Expr::Binary { op, left, right } => {
+                let (l, r) = (left.value(source)?, right.value(source)?);
+                match op {
+                    BinaryOp::Add => Some(l + r),
+                    BinaryOp::Sub => Some(l - r),
+                    BinaryOp::Mul => Some(l * r),
+                    BinaryOp::Div => (r != 0.0).then(|| l / r),
+                    _ => None,
+                }
+            }
+            Expr::Offset { expr, offset } => source.past(expr, *offset),
+            Expr::Window { func, expr, length } => {
+                let values = (0..*length)
+                    .map(|i| source.past(expr, i))
+                    .collect::<Option<Vec<f64>>>()?;
+                Some(match func {
+                    WindowFunc::Highest => values.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                    WindowFunc::Lowest => values.into_iter().fold(f64::INFINITY, f64::min),
+                })
+            }
+            Expr::Not(_) | Expr::Cross { .. } | Expr::Trend { .. } => None,
+        }
+    }
+
+    /// Truth value, `None` while a referenced indicator has no value yet
+    pub fn holds(&self, source: &dyn ValueSource) -> Option<bool> {
+        match self {
+            Expr::Not(inner) => inner.holds(source).map(|v| !v),
+            Expr::Binary { op: BinaryOp::And, left, right } => {
+                Some(left.holds(source)? && right.holds(source)?)
+            }
+            Expr::Binary { op: BinaryOp::Or, left, right } => {
+                match (left.holds(source), right.holds(source)) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                }
+            }
+            Expr::Cross { above, left, right } => {
+                let (l, r) = (source.past(left, 0)?, source.past(right, 0)?);
+                let (prev_l, prev_r) = (source.past(left, 1)?, source.past(right, 1)?);
+                Some(if *above {
+                    l > r && prev_l <= prev_r
+                } else {
+                    l < r && prev_l >= prev_r
+                })
+            }
+            Expr::Trend { rising, expr, length } => {
+                for i in 0..*length {
+                    let (now, before) = (source.past(expr, i)?, source.past(expr, i + 1)?);
+                    if (*rising && now <= before) || (!*rising && now >= before) {
+                        return Some(false);
+                    }
+                }
+                Some(true)
+            }
+            Expr::Binary { op, left, right } => {
+                let (l, r) = (left.value(source)?, right.value(source)?);
+                let tolerance = 1e-9 * l.abs().max(r.abs()).max(1.0);
+                match op {
+                    BinaryOp::Lt => Some(l < r),
+                    BinaryOp::Le => Some(l <= r),
+                    BinaryOp::Gt => Some(l > r),
+                    BinaryOp::Ge => Some(l >= r),
+                    BinaryOp::Eq => Some((l - r).abs() <= tolerance),
+                    BinaryOp::Ne => Some((l - r).abs() > tolerance),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Evaluate a condition clause by clause: AND / OR chains and NOT split into their
+    /// operands, comparisons carry the values of both sides
+    pub fn explain(&self, source: &dyn ValueSource) -> ClauseResult {
+        let holds = self.holds(source);
+        match self {
+            Expr::Binary { op: op @ (BinaryOp::And | BinaryOp::Or), .. } => {
+                let mut operands = Vec::new();
+                self.collect_operands(*op, &mut operands);
+                ClauseResult {
+                    clause: op.symbol().to_string(),
+                    holds,
+                    left: None,
+                    right: None,
+                    children: operands.into_iter().map(|e| e.explain(source)).collect(),
+                }
+            }
+            Expr::Not(inner) => ClauseResult {
+                clause: "NOT".to_string(),
+                holds,
+                left: None,
+                right: None,
+                children: vec![inner.explain(source)],
+            },
+            Expr::Binary { op, left, right } => ClauseResult {
+                clause: format!("{} {} {}", left, op.symbol(), right),
+                holds,
+                left: left.value(source),
+                right: right.value(source),
+                children: Vec::new(),
+            },
+            _ => ClauseResult {
+                clause: self.to_string(),
+                holds,
+                left: None,
+                right: None,
+                children: Vec::new(),
+            },
+        }
+    }
+
+    /// Operands of a chain of the same AND / OR operator, `a AND b AND c` gives a, b, c
+    fn collect_operands<'a>(&'a self, chain_op: BinaryOp, operands: &mut Vec<&'a Expr>) {
+        match self {
+            Expr::Binary { op, left, right } if *op == chain_op => {
+                left.collect_operands(chain_op, operands);
+                right.collect_operands(chain_op, operands);
+            }
+            _ => operands.push(self),
+        }
+    }
+
+    /// Indicators referenced by the expression, without duplicates
+    pub fn indicators(&self) -> Vec<IndicatorCall> {
+        let mut calls = Vec::new();
+        self.collect_indicators(&mut calls);
+        calls
+    }
+
+    fn collect_indicators(&self, calls: &mut Vec<IndicatorCall>) {
+        match self {
+            Expr::Indicator(call) => {
+                if !calls.contains(call) {
+                    calls.push(call.clone());
+                }
+            }
+            Expr::Neg(inner)
+            | Expr::Not(inner)
+            | Expr::Offset { expr: inner, .. }
+            | Expr::Window { expr: inner, .. }
+            | Expr::Trend { expr: inner, .. } => inner.collect_indicators(calls),
+            Expr::Binary { left, right, .. } | Expr::Cross { left, right, .. } => {
+                left.collect_indicators(calls);
+                right.collect_indicators(calls);
+            }
+            Expr::Number(_) | Expr::Field(_) => {}
+        }
+    }
+
+    /// Closed candles needed before the expression has a value
+    pub fn lookback(&self) -> usize {
+        match self {
+            Expr::Number(_) => 0,
+            Expr::Field(_) => 1,
+            Expr::Indicator(call) => call.lookback(),
+            Expr::Neg(inner) | Expr::Not(inner) => inner.lookback(),
+            Expr::Binary { left, right, .. } => left.lookback().max(right.lookback()),
+            Expr::Offset { expr, offset } => expr.lookback() + offset,
+            Expr::Window { expr, length, .. } => expr.lookback() + length - 1,
+            Expr::Cross { left, right, .. } => left.lookback().max(right.lookback()) + 1,
+            Expr::Trend { expr, length, .. } => expr.lookback() + length,
+        }
+    }
+
+    /// Sub-expressions whose previous values are needed, with how many candles back,
+    /// inner ones first
+    fn collect_series(&self, series: &mut Vec<(Expr, usize)>) {
+        let need = |expr: &Expr, depth: usize, series: &mut Vec<(Expr, usize)>| {
+            expr.collect_series(series);
+            match series.iter_mut().find(|(e, _)| e == expr) {
+                Some((_, d)) => *d = (*d).max(depth),
+                None => series.push((expr.clone(), depth)),
+            }
+        };
+        match self {
+            Expr::Offset { expr, offset } => need(expr, *offset, series),
+            Expr::Window { expr, length, .. } => need(expr, length - 1, series),
+            Expr::Trend { expr, length, .. } => need(expr, *length, series),
+            Expr::Cross { left, right, .. } => {
+                need(left, 1, series);
+                need(right, 1, series);
+            }
+            Expr::Neg(inner) | Expr::Not(inner) => inner.collect_series(series),
+            Expr::Binary { left, right, .. } => {
+                left.collect_series(series);
+                right.collect_series(series);
+            }
+            Expr::Number(_) | Expr::Field(_) | Expr::Indicator(_) => {}
+        }
+    }
+
+    /// Pandas expression over a freqtrade dataframe
+    pub fn to_python(&self) -> String {
+        match self {
+            Expr::Number(n) => format_number(*n),
+            Expr::Field(field) => format!("dataframe['{}']", field.name()),
+            Expr::Indicator(call) => format!("dataframe['{}']", call.column()),
+            Expr::Neg(inner) => format!("(-{})", inner.to_python()),
+            Expr::Not(inner) => format!("(~{})", inner.to_python()),
+            Expr::Binary { op, left, right } => {
+                let op = match op {
+                    BinaryOp::And => "&",
+                    BinaryOp::Or => "|",
+                    op => op.symbol(),
+                };
+                format!("({} {} {})", left.to_python(), op, right.to_python())
+            }
+            Expr::Offset { expr, offset } => format!("{}.shift({})", expr.to_python(), offset),
+            Expr::Window { func, expr, length } => {
+                let agg = match func {
+                    WindowFunc::Highest => "max",
+                    WindowFunc::Lowest => "min",
+                };
+                format!("{}.rolling({}).{}()", expr.to_python(), length, agg)
+            }
+            Expr::Cross { above, left, right } => format!(
+                "qtpylib.crossed_{}({}, {})",
+                if *above { "above" } else { "below" },
+                left.to_python(),
+                right.to_python()
+            ),
+            Expr::Trend { rising, expr, length } => format!(
+                "(({}.diff() {} 0).rolling({}).sum() == {})",
+                expr.to_python(),
+                if *rising { ">" } else { "<" },
+                length,
+                length
+            ),
+        }
+    }
+
+    /// Value of an expression made only of numbers
+    fn constant(&self) -> Option<f64> {
+        match self {
+            Expr::Number(n) => Some(*n),
+            Expr::Neg(inner) => inner.constant().map(|v| -v),
+            Expr::Binary { op, left, right } => {
+                let (l, r) = (left.constant()?, right.constant()?);
+                match op {
+                    BinaryOp::Add => Some(l + r),
+                    BinaryOp::Sub => Some(l - r),
+                    BinaryOp::Mul => Some(l * r),
+                    BinaryOp::Div => (r != 0.0).then(|| l / r),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", format_number(*n)),
+            Expr::Field(field) => write!(f, "{}", field.name()),
+            Expr::Indicator(call) => write!(f, "{}", call),
+            Expr::Neg(inner) => write!(f, "-{}", inner),
+            Expr::Not(inner) => write!(f, "NOT {}", inner),
+            Expr::Binary { op, left, right } => write!(f, "({} {} {})", left, op.symbol(), right),
+            Expr::Offset { expr, offset } => write!(f, "{}[{}]", expr, offset),
+            Expr::Window { func, expr, length } => write!(f, "{}({}, {})", func.name(), expr, length),
+            Expr::Cross { above, left, right } => {
+                write!(f, "crosses_{}({}, {})", if *above { "above" } else { "below" }, left, right)
+            }
+            Expr::Trend { rising, expr, length } => {
+                write!(f, "{}({}, {})", if *rising { "rising" } else { "falling" }, expr, length)
+            }
+        }
+    }
+}
+
+/// Values an expression is evaluated against
+pub trait ValueSource {
+    /// Field of the current candle
+    fn field(&self, field: PriceField) -> Option<f64>;
+
+    /// Current value of an indicator, `None` until it has enough candles
+    fn indicator(&self, call: &IndicatorCall) -> Option<f64>;
+
+    /// Value of `expr` `offset` candles ago (0 is the current candle), `None` if not recorded
+    fn past(&self, expr: &Expr, offset: usize) -> Option<f64>;
+}
+
+/// Syntax or type error in an expression, with the character position it was found at
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExprError {
+    pub message: String,
+    /// Character offset into the source
+    pub position: usize,
+    source: String,
+}
+
+impl ExprError {
+    fn new(message: impl Into<String>, position: usize, source: &str) -> Self {
+        Self {
+            message: message.into(),
+            position,
+            source: source.to_string(),
+        }
+    }
+
+    /// The source with a caret under the error position
+    pub fn pointer(&self) -> String {
+        format!("{}\n{}^", self.source, " ".repeat(self.position))
+    }
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+/// Outcome of a condition clause on a candle, see [`Expr::explain`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClauseResult {
+    /// The clause (`RSI(14) < 30`), or `AND` / `OR` / `NOT` for its children
+    pub clause: String,
+    /// Whether the clause held, `None` while an indicator was warming up
+    pub holds: Option<bool>,
+    /// Value of the left side of a comparison
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub left: Option<f64>,
+    /// Value of the right side of a comparison
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub right: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ClauseResult>,
+}
+
+/// Buy or sell condition of a strategy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+}
+
+impl Condition {
+    /// Parse a condition, resolving parameter names and default indicator arguments from `params`
+    pub fn parse(source: &str, params: &serde_json::Value) -> std::result::Result<Self, ExprError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            source,
+            tokens,
+            index: 0,
+            params,
+            in_indicator_args: 0,
+        };
+        let (expr, ty, position) = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(parser.error(format!("Unexpected {}", token.kind.describe()), token.position));
+        }
+        if ty != Type::Bool {
+            return Err(ExprError::new(
+                format!("`{}` is a value, not a condition; compare it with <, >, <=, >=, == or != (e.g. `{} < 30`)", expr, expr),
+                position,
+                source,
+            ));
+        }
+
+        Ok(Self {
+            source: source.trim().to_string(),
+            expr,
+        })
+    }
+
+    /// The condition as written
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Parsed expression
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Indicators the condition needs
+    pub fn indicators(&self) -> Vec<IndicatorCall> {
+        self.expr.indicators()
+    }
+
+    /// Closed candles needed before every indicator of the condition has a value
+    pub fn lookback(&self) -> usize {
+        self.expr.lookback()
+    }
+
+    /// Check if the condition holds (false while indicators are warming up)
+    pub fn evaluate(&self, source: &dyn ValueSource) -> bool {
+        self.expr.holds(source).unwrap_or(false)
+    }
+
+    /// Evaluate the condition clause by clause, to explain why it held or not
+    pub fn explain(&self, source: &dyn ValueSource) -> ClauseResult {
+        self.expr.explain(source)
+    }
+
+    /// Pandas expression for freqtrade's `populate_entry_trend` / `populate_exit_trend`
+    pub fn to_python(&self) -> String {
+        self.expr.to_python()
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// Format a number without a trailing `.0`
+fn format_number(n: f64) -> String {
+    format!("{}", n)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Ident(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Op(BinaryOp),
+    Not,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Number(n) => format!("number {}", format_number(*n)),
+            TokenKind::Ident(name) => format!("`{}`", name),
+            TokenKind::LParen => "`(`".to_string(),
+            TokenKind::RParen => "`)`".to_string(),
+            TokenKind::LBracket => "`[`".to_string(),
+            TokenKind::RBracket => "`]`".to_string(),
+            TokenKind::Comma => "`,`".to_string(),
+            TokenKind::Op(op) => format!("`{}`", op.symbol()),
+            TokenKind::Not => "`NOT`".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn tokenize(source: &str) -> std::result::Result<Vec<Token>, ExprError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse::<f64>()
+                .map_err(|_| ExprError::new(format!("Invalid number `{}`", text), start, source))?;
+            TokenKind::Number(value)
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            match word.to_lowercase().as_str() {
+                "and" => TokenKind::Op(BinaryOp::And),
+                "or" => TokenKind::Op(BinaryOp::Or),
+                "not" => TokenKind::Not,
+                _ => TokenKind::Ident(word),
+            }
+        } else {
+            let next = chars.get(i + 1).copied();
+            let (kind, len) = match (c, next) {
+                ('<', Some('=')) => (TokenKind::Op(BinaryOp::Le), 2),
+                ('>', Some('=')) => (TokenKind::Op(BinaryOp::Ge), 2),
+                ('=', Some('=')) => (TokenKind::Op(BinaryOp::Eq), 2),
+                ('!', Some('=')) => (TokenKind::Op(BinaryOp::Ne), 2),
+                ('&', Some('&')) => (TokenKind::Op(BinaryOp::And), 2),
+                ('|', Some('|')) => (TokenKind::Op(BinaryOp::Or), 2),
+                ('<', _) => (TokenKind::Op(BinaryOp::Lt), 1),
+                ('>', _) => (TokenKind::Op(BinaryOp::Gt), 1),
+                ('=', _) => (TokenKind::Op(BinaryOp::Eq), 1),
+                ('!', _) => (TokenKind::Not, 1),
+                ('+', _) => (TokenKind::Op(BinaryOp::Add), 1),
+                ('-', _) => (TokenKind::Op(BinaryOp::Sub), 1),
+                ('*', _) => (TokenKind::Op(BinaryOp::Mul), 1),
+                ('/', _) => (TokenKind::Op(BinaryOp::Div), 1),
+                ('(', _) => (TokenKind::LParen, 1),
+                (')', _) => (TokenKind::RParen, 1),
+                ('[', _) => (TokenKind::LBracket, 1),
+                (']', _) => (TokenKind::RBracket, 1),
+                (',', _) => (TokenKind::Comma, 1),
+                _ => return Err(ExprError::new(format!("Unexpected character `{}`", c), start, source)),
+            };
+            i += len;
+            kind
+        };
+        tokens.push(Token { kind, position: start });
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Number,
+    Bool,
+}
+
+/// Expression with its type and the position it starts at
+type Typed = (Expr, Type, usize);
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    index: usize,
+    params: &'a serde_json::Value,
+    /// Depth of indicator argument lists, where argument parameters are values
+    in_indicator_args: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>, position: usize) -> ExprError {
+        ExprError::new(message, position, self.source)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    /// Position just past the last token (for "unexpected end" errors)
+    fn end_position(&self) -> usize {
+        self.source.trim_end().chars().count()
+    }
+
+    fn next_op(&self, ops: &[BinaryOp]) -> Option<BinaryOp> {
+        match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Op(op)) if ops.contains(op) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn expect_type(&self, operand: &Typed, expected: Type, op: BinaryOp) -> std::result::Result<(), ExprError> {
+        if operand.1 == expected {
+            return Ok(());
+        }
+        let message = match expected {
+            Type::Bool => format!("{} needs a condition on both sides, `{}` is a value", op.symbol(), operand.0),
+            Type::Number => format!("`{}` needs values on both sides, `{}` is a condition", op.symbol(), operand.0),
+        };
+        Err(self.error(message, operand.2))
+    }
+
+    fn parse_or(&mut self) -> std::result::Result<Typed, ExprError> {
+        let mut left = self.parse_and()?;
+        while self.next_op(&[BinaryOp::Or]).is_some() {
+            self.index += 1;
+            let right = self.parse_and()?;
+            self.expect_type(&left, Type::Bool, BinaryOp::Or)?;
+            self.expect_type(&right, Type::Bool, BinaryOp::Or)?;
+            left = (binary(BinaryOp::Or, left.0, right.0), Type::Bool, left.2);
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> std::result::Result<Typed, ExprError> {
+        let mut left = self.parse_not()?;
+        while self.next_op(&[BinaryOp::And]).is_some() {
+            self.index += 1;
+            let right = self.parse_not()?;
+            self.expect_type(&left, Type::Bool, BinaryOp::And)?;
+            self.expect_type(&right, Type::Bool, BinaryOp::And)?;
+            left = (binary(BinaryOp::And, left.0, right.0), Type::Bool, left.2);
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> std::result::Result<Typed, ExprError> {
+        if let Some(Token { kind: TokenKind::Not, position }) = self.peek().cloned() {
+            self.index += 1;
+            let inner = self.parse_not()?;
+            if inner.1 != Type::Bool {
+                return Err(self.error(format!("NOT needs a condition, `{}` is a value", inner.0), inner.2));
+            }
+            return Ok((Expr::Not(Box::new(inner.0)), Type::Bool, position));
+        }
+        self.parse_compare()
+    }
+
+    fn parse_compare(&mut self) -> std::result::Result<Typed, ExprError> {
+        const COMPARISONS: [BinaryOp; 6] = [
+            BinaryOp::Lt,
+            BinaryOp::Le,
+            BinaryOp::Gt,
+            BinaryOp::Ge,
+            BinaryOp::Eq,
+            BinaryOp::Ne,
+        ];
+
+        let left = self.parse_sum()?;
+        let Some(op) = self.next_op(&COMPARISONS) else {
+            return Ok(left);
+        };
+        self.index += 1;
+        let right = self.parse_sum()?;
+        self.expect_type(&left, Type::Number, op)?;
+        self.expect_type(&right, Type::Number, op)?;
+        if let Some(token) = self.peek().filter(|_| self.next_op(&COMPARISONS).is_some()) {
+            return Err(self.error("Comparisons cannot be chained, combine them with AND", token.position));
+        }
+        Ok((binary(op, left.0, right.0), Type::Bool, left.2))
+    }
+
+    fn parse_sum(&mut self) -> std::result::Result<Typed, ExprError> {
+        let mut left = self.parse_product()?;
+        while let Some(op) = self.next_op(&[BinaryOp::Add, BinaryOp::Sub]) {
+            self.index += 1;
+            let right = self.parse_product()?;
+            self.expect_type(&left, Type::Number, op)?;
+            self.expect_type(&right, Type::Number, op)?;
+            left = (binary(op, left.0, right.0), Type::Number, left.2);
+        }
+        Ok(left)
+    }
+
+    fn parse_product(&mut self) -> std::result::Result<Typed, ExprError> {
+        let mut left = self.parse_unary()?;
+        while let Some(op) = self.next_op(&[BinaryOp::Mul, BinaryOp::Div]) {
+            self.index += 1;
+            let right = self.parse_unary()?;
+            self.expect_type(&left, Type::Number, op)?;
+            self.expect_type(&right, Type::Number, op)?;
+            left = (binary(op, left.0, right.0), Type::Number, left.2);
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> std::result::Result<Typed, ExprError> {
+        if self.next_op(&[BinaryOp::Sub]).is_some() {
+            let position = self.tokens[self.index].position;
+            self.index += 1;
+            let inner = self.parse_unary()?;
+            if inner.1 != Type::Number {
+                return Err(self.error(format!("`-` needs a value, `{}` is a condition", inner.0), inner.2));
+            }
+            return Ok((Expr::Neg(Box::new(inner.0)), Type::Number, position));
+        }
+        self.parse_postfix()
+    }
+
+    fn parse_postfix(&mut self) -> std::result::Result<Typed, ExprError> {
+        let mut expr = self.parse_primary()?;
+        while let Some(Token { kind: TokenKind::LBracket, position: open }) = self.peek().cloned() {
+            self.index += 1;
+            if expr.1 != Type::Number {
+                return Err(self.error(format!("Only values can be offset, `{}` is a condition", expr.0), open));
+            }
+            let offset = self.parse_sum()?;
+            let offset = self.whole_number(&offset, "Offset", 0)?;
+            match self.peek().cloned() {
+                Some(Token { kind: TokenKind::RBracket, .. }) => self.index += 1,
+                Some(other) => {
+                    return Err(self.error(format!("Expected `]`, found {}", other.kind.describe()), other.position))
+                }
+                None => {
+                    return Err(self.error(
+                        format!("Missing `]` to close `[` at character {}", open + 1),
+                        self.end_position(),
+                    ))
+                }
+            }
+            expr.0 = self.series_arg(expr.0, expr.2)?;
+            if offset > 0 {
+                expr.0 = Expr::Offset { expr: Box::new(expr.0), offset };
+            }
+        }
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> std::result::Result<Typed, ExprError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("Unexpected end of condition, expected a value", self.end_position()));
+        };
+        self.index += 1;
+
+        match token.kind {
+            TokenKind::Number(n) => Ok((Expr::Number(n), Type::Number, token.position)),
+            TokenKind::LParen => {
+                let inner = self.parse_or()?;
+                match self.peek() {
+                    Some(Token { kind: TokenKind::RParen, .. }) => {
+                        self.index += 1;
+                        Ok((inner.0, inner.1, token.position))
+                    }
+                    Some(other) => Err(self.error(
+                        format!("Expected `)` to close `(` at character {}, found {}", token.position + 1, other.kind.describe()),
+                        other.position,
+                    )),
+                    None => Err(self.error(
+                        format!("Missing `)` to close `(` at character {}", token.position + 1),
+                        self.end_position(),
+                    )),
+                }
+            }
+            TokenKind::Ident(name) => self.parse_name(&name, token.position),
+            other => Err(self.error(format!("Unexpected {}, expected a value", other.describe()), token.position)),
+        }
+    }
+
+    fn parse_name(&mut self, name: &str, position: usize) -> std::result::Result<Typed, ExprError> {
+        let lower = name.to_lowercase();
+        let has_args = matches!(self.peek(), Some(Token { kind: TokenKind::LParen, .. }));
+
+        if has_args && FUNCTIONS.contains(&lower.as_str()) {
+            return self.parse_function(&lower, position);
+        }
+
+        if let Some(kind) = IndicatorKind::from_name(&lower) {
+            let args = if has_args { self.parse_args(kind)? } else { Vec::new() };
+            let call = self.resolve_call(kind, args, position)?;
+            return Ok((Expr::Indicator(call), Type::Number, position));
+        }
+        if has_args {
+            return Err(self.error(
+                format!(
+                    "Unknown indicator or function `{}`; indicators: {}; functions: {}",
+                    name,
+                    indicator_names(),
+                    FUNCTIONS.join(", ")
+                ),
+                position,
+            ));
+        }
+        if let Some(field) = PriceField::from_name(&lower) {
+            return Ok((Expr::Field(field), Type::Number, position));
+        }
+        if self.in_indicator_args == 0 && IndicatorKind::is_arg_param(&lower) {
+            return Err(self.error(
+                format!("`{}` sets indicator arguments and cannot be used as a value", name),
+                position,
+            ));
+        }
+        if let Some(value) = self.param(&[name.to_string(), lower.clone()]) {
+            return Ok((Expr::Number(value), Type::Number, position));
+        }
+
+        Err(self.error(
+            format!(
+                "Unknown name `{}`; use open, high, low, close, volume, an indicator ({}) or a numeric strategy parameter",
+                name,
+                indicator_names()
+            ),
+            position,
+        ))
+    }
+
+    /// Parse `(arg, ...)` after a function or indicator name
+    fn parse_call_args(&mut self, name: &str) -> std::result::Result<Vec<Typed>, ExprError> {
+        let open = self.tokens[self.index].position;
+        self.index += 1;
+        let mut args = Vec::new();
+        if matches!(self.peek(), Some(Token { kind: TokenKind::RParen, .. })) {
+            self.index += 1;
+            return Ok(args);
+        }
+
+        loop {
+            args.push(self.parse_or()?);
+            match self.peek().cloned() {
+                Some(Token { kind: TokenKind::Comma, .. }) => self.index += 1,
+                Some(Token { kind: TokenKind::RParen, .. }) => {
+                    self.index += 1;
+                    return Ok(args);
+                }
+                Some(other) => {
+                    return Err(self.error(
+                        format!("Expected `,` or `)` in the arguments of {}, found {}", name, other.kind.describe()),
+                        other.position,
+                    ))
+                }
+                None => {
+                    return Err(self.error(
+                        format!("Missing `)` to close the arguments of {} at character {}", name, open + 1),
+                        self.end_position(),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Parse indicator arguments, which must be constant
+    fn parse_args(&mut self, kind: IndicatorKind) -> std::result::Result<Vec<(f64, usize)>, ExprError> {
+        self.in_indicator_args += 1;
+        let args = self.parse_call_args(kind.name());
+        self.in_indicator_args -= 1;
+        args?
+            .into_iter()
+            .map(|arg| {
+                let value = arg.0.constant().filter(|_| arg.1 == Type::Number).ok_or_else(|| {
+                    self.error(format!("Arguments of {} must be numbers or strategy parameters", kind.name()), arg.2)
+                })?;
+                Ok((value, arg.2))
+            })
+            .collect()
+    }
+
+    /// Parse a call of one of [`FUNCTIONS`]
+    fn parse_function(&mut self, name: &str, position: usize) -> std::result::Result<Typed, ExprError> {
+        let args = self.parse_call_args(name)?;
+        let usage = match name {
+            "crosses_above" | "crosses_below" => "(a, b)",
+            _ => "(x, n)",
+        };
+        if args.len() != 2 {
+            return Err(self.error(format!("{} takes 2 arguments: {}{}", name, name, usage), position));
+        }
+        for arg in &args {
+            if arg.1 != Type::Number {
+                return Err(self.error(format!("Arguments of {} must be values, `{}` is a condition", name, arg.0), arg.2));
+            }
+        }
+        let mut args = args.into_iter();
+        let (first, second) = (args.next().expect("two arguments"), args.next().expect("two arguments"));
+
+        let expr = match name {
+            "crosses_above" | "crosses_below" => {
+                if first.0.constant().is_some() && second.0.constant().is_some() {
+                    return Err(self.error(format!("{} needs a price or indicator on at least one side", name), position));
+                }
+                Expr::Cross {
+                    above: name == "crosses_above",
+                    left: Box::new(first.0),
+                    right: Box::new(second.0),
+                }
+            }
+            "rising" | "falling" => Expr::Trend {
+                rising: name == "rising",
+                length: self.whole_number(&second, "Length", 1)?,
+                expr: Box::new(self.series_arg(first.0, first.2)?),
+            },
+            _ => Expr::Window {
+                func: if name == "highest" { WindowFunc::Highest } else { WindowFunc::Lowest },
+                length: self.whole_number(&second, "Length", 1)?,
+                expr: Box::new(self.series_arg(first.0, first.2)?),
+            },
+        };
+        let ty = if matches!(expr, Expr::Window { .. }) { Type::Number } else { Type::Bool };
+        Ok((expr, ty, position))
+    }
+
+    /// Check that a value looked at over several candles can change
+    fn series_arg(&self, expr: Expr, position: usize) -> std::result::Result<Expr, ExprError> {
+        if expr.constant().is_some() {
+            return Err(self.error(format!("`{}` never changes, use a price or indicator", expr), position));
+        }
+        Ok(expr)
+    }
+
+    /// Constant whole number argument (offsets and lengths)
+    fn whole_number(&self, arg: &Typed, what: &str, min: usize) -> std::result::Result<usize, ExprError> {
+        match arg.0.constant() {
+            Some(n) if n.fract() == 0.0 && n >= min as f64 && n <= MAX_HISTORY as f64 => Ok(n as usize),
+            _ => Err(self.error(
+                format!("{} must be a whole number from {} to {}", what, min, MAX_HISTORY),
+                arg.2,
+            )),
+        }
+    }
+
+    /// Fill missing arguments from the parameters or defaults and validate them
+    fn resolve_call(
+        &self,
+        kind: IndicatorKind,
+        args: Vec<(f64, usize)>,
+        position: usize,
+    ) -> std::result::Result<IndicatorCall, ExprError> {
+        let names = kind.arg_names();
+        if args.len() > names.len() {
+            return Err(self.error(
+                format!("{} takes at most {} argument(s): {}", kind.name(), names.len(), names.join(", ")),
+                args[names.len()].1,
+            ));
+        }
+
+        let mut values = Vec::with_capacity(names.len());
+        for (i, arg_name) in names.iter().enumerate() {
+            let (value, arg_position) = match args.get(i) {
+                Some(arg) => *arg,
+                None => {
+                    let value = self
+                        .param(&[format!("{}_{}", kind.param_prefix(), arg_name), arg_name.to_string()])
+                        .unwrap_or(kind.defaults()[i]);
+                    (value, position)
+                }
+            };
+            // Periods are capped like offsets, the indicators keep that many candles
+            let valid = if *arg_name == "std_dev" {
+                value > 0.0
+            } else {
+                value >= 1.0 && value <= MAX_HISTORY as f64 && value.fract() == 0.0
+            };
+            if !valid {
+                let expected = if *arg_name == "std_dev" {
+                    "a positive number".to_string()
+                } else {
+                    format!("a whole number from 1 to {}", MAX_HISTORY)
+                };
+                return Err(self.error(
+                    format!("{} {} must be {}, got {}", kind.name(), arg_name, expected, format_number(value)),
+                    arg_position,
+                ));
+            }
+            values.push(value);
+        }
+
+        if names.first() == Some(&"fast") && values[0] >= values[1] {
+            return Err(self.error(format!("{} fast period must be below the slow period", kind.name()), position));
+        }
+        Ok(IndicatorCall { kind, args: values })
+    }
+
+    /// First numeric parameter found under one of `keys`
+    fn param(&self, keys: &[String]) -> Option<f64> {
+        keys.iter().find_map(|key| self.params.get(key).and_then(|v| v.as_f64()))
+    }
+}
+
+/// Functions over previous candles
+const FUNCTIONS: [&str; 6] = ["crosses_above", "crosses_below", "rising", "falling", "highest", "lowest"];
+
+/// Largest offset, length or indicator period accepted
+const MAX_HISTORY: usize = 1000;
+
+fn binary(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+    Expr::Binary {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+fn indicator_names() -> String {
+    IndicatorKind::ALL.iter().map(|k| k.name()).collect::<Vec<_>>().join(", ")
+}
+
+/// Running state of one indicator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum IndicatorState {
+    Rsi(RelativeStrengthIndex),
+    Ema(ExponentialMovingAverage),
+    Sma(SimpleMovingAverage),
+    Macd(MovingAverageConvergenceDivergence),
+    Bollinger(BollingerBands),
+    Atr(AverageTrueRange),
+    Stoch(FastStochastic),
+    Adx(AdxState),
+    /// Value supplied by the caller (see [`IndicatorSet::add_external`])
+    External,
+}
+
+impl IndicatorState {
+    fn new(call: &IndicatorCall) -> Self {
+        let p = call.period(0);
+        match call.kind {
+            IndicatorKind::Rsi => Self::Rsi(RelativeStrengthIndex::new(p).expect("validated period")),
+            IndicatorKind::Ema => Self::Ema(ExponentialMovingAverage::new(p).expect("validated period")),
+            IndicatorKind::Sma => Self::Sma(SimpleMovingAverage::new(p).expect("validated period")),
+            IndicatorKind::Macd | IndicatorKind::MacdSignal | IndicatorKind::MacdHist => Self::Macd(
+                MovingAverageConvergenceDivergence::new(p, call.period(1), call.period(2)).expect("validated periods"),
+            ),
+            IndicatorKind::BbUpper | IndicatorKind::BbMiddle | IndicatorKind::BbLower => {
+                Self::Bollinger(BollingerBands::new(p, call.args[1]).expect("validated arguments"))
+            }
+            IndicatorKind::Atr => Self::Atr(AverageTrueRange::new(p).expect("validated period")),
+            IndicatorKind::Stoch => Self::Stoch(FastStochastic::new(p).expect("validated period")),
+            IndicatorKind::Adx => Self::Adx(AdxState::new(p)),
+        }
+    }
+
+    fn next(&mut self, kind: IndicatorKind, bar: &Bar) -> Option<f64> {
+        let item = || {
+            DataItem::builder()
+                .open(bar.open.clamp(bar.low, bar.high))
+                .high(bar.high)
+                .low(bar.low)
+                .close(bar.close)
+                .volume(bar.volume.max(0.0))
+                .build()
+                .ok()
+        };
+        match self {
+            Self::Rsi(rsi) => Some(rsi.next(bar.close)),
+            Self::Ema(ema) => Some(ema.next(bar.close)),
+            Self::Sma(sma) => Some(sma.next(bar.close)),
+            Self::Macd(macd) => {
+                let output = macd.next(bar.close);
+                Some(match kind {
+                    IndicatorKind::MacdSignal => output.signal,
+                    IndicatorKind::MacdHist => output.histogram,
+                    _ => output.macd,
+                })
+            }
+            Self::Bollinger(bb) => {
+                let output = bb.next(bar.close);
+                Some(match kind {
+                    IndicatorKind::BbUpper => output.upper,
+                    IndicatorKind::BbLower => output.lower,
+                    _ => output.average,
+                })
+            }
+            Self::Atr(atr) => item().map(|item| atr.next(&item)),
+            Self::Stoch(stoch) => item().map(|item| stoch.next(&item)),
+            Self::Adx(adx) => adx.next(bar),
+            Self::External => None,
+        }
+    }
+}
+
+/// Wilder's Average Directional Index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AdxState {
+    period: usize,
+    previous: Option<Bar>,
+    /// Bars with a directional movement seen so far
+    count: usize,
+    tr: f64,
+    plus_dm: f64,
+    minus_dm: f64,
+    dx_sum: f64,
+    adx: Option<f64>,
+}
+
+impl AdxState {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            previous: None,
+            count: 0,
+            tr: 0.0,
+            plus_dm: 0.0,
+            minus_dm: 0.0,
+            dx_sum: 0.0,
+            adx: None,
+        }
+    }
+
+    fn next(&mut self, bar: &Bar) -> Option<f64> {
+        let previous = self.previous.replace(*bar)?;
+        let tr = (bar.high - bar.low)
+            .max((bar.high - previous.close).abs())
+            .max((bar.low - previous.close).abs());
+        let up = bar.high - previous.high;
+        let down = previous.low - bar.low;
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+
+        let period = self.period as f64;
+        self.count += 1;
+        if self.count <= self.period {
+            self.tr += tr;
+            self.plus_dm += plus_dm;
+            self.minus_dm += minus_dm;
+            if self.count < self.period {
+                return None;
+            }
+        } else {
+            self.tr += tr - self.tr / period;
+            self.plus_dm += plus_dm - self.plus_dm / period;
+            self.minus_dm += minus_dm - self.minus_dm / period;
+        }
+
+        let (plus_di, minus_di) = if self.tr > 0.0 {
+            (100.0 * self.plus_dm / self.tr, 100.0 * self.minus_dm / self.tr)
+        } else {
+            (0.0, 0.0)
+        };
+        let dx = if plus_di + minus_di > 0.0 {
+            100.0 * (plus_di - minus_di).abs() / (plus_di + minus_di)
+        } else {
+            0.0
+        };
+
+        // The first ADX is the mean of `period` DX values, then Wilder smoothing
+        self.adx = match self.adx {
+            Some(adx) => Some((adx * (period - 1.0) + dx) / period),
+            None => {
+                self.dx_sum += dx;
+                (self.count == 2 * self.period - 1).then(|| self.dx_sum / period)
+            }
+        };
+        self.adx
+    }
+}
+
+/// OHLCV of one candle
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Bar {
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndicatorEntry {
+    call: IndicatorCall,
+    state: IndicatorState,
+    updates: usize,
+    value: Option<f64>,
+}
+
+/// Recent values of a sub-expression used by a function over previous candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SeriesEntry {
+    expr: Expr,
+    /// Candles back the value is needed for
+    depth: usize,
+    /// Most recent first
+    values: VecDeque<Option<f64>>,
+}
+
+/// Indicators referenced by a strategy's conditions, updated once per closed candle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndicatorSet {
+    entries: Vec<IndicatorEntry>,
+    series: Vec<SeriesEntry>,
+    last: Option<Bar>,
+}
+
+impl IndicatorSet {
+    /// Indicator set for every indicator of the given conditions
+    pub fn for_conditions(conditions: &[&Condition]) -> Self {
+        let mut set = Self::default();
+        for condition in conditions {
+            set.track(condition);
+        }
+        set
+    }
+
+    /// Track the indicators and the history a condition needs
+    pub fn track(&mut self, condition: &Condition) {
+        for call in condition.indicators() {
+            self.add(call);
+        }
+        let mut needed = Vec::new();
+        condition.expr().collect_series(&mut needed);
+        for (expr, depth) in needed {
+            match self.series.iter_mut().find(|s| s.expr == expr) {
+                Some(entry) => entry.depth = entry.depth.max(depth),
+                None => self.series.push(SeriesEntry {
+                    expr,
+                    depth,
+                    values: VecDeque::new(),
+                }),
+            }
+        }
+    }
+
+    /// Track an indicator computed by the caller, who sets it with [`Self::set_external`]
+    /// before each [`Self::update`]
+    pub fn add_external(&mut self, call: IndicatorCall) {
+        self.entries.retain(|e| e.call != call);
+        self.entries.push(IndicatorEntry {
+            call,
+            state: IndicatorState::External,
+            updates: 0,
+            value: None,
+        });
+    }
+
+    /// Set the current value of an indicator added with [`Self::add_external`]
+    pub fn set_external(&mut self, call: &IndicatorCall, value: Option<f64>) {
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|e| &e.call == call && matches!(e.state, IndicatorState::External))
+        {
+            entry.value = value.filter(|v| v.is_finite());
+        }
+    }
+
+    /// Track an indicator (ignored if already tracked)
+    pub fn add(&mut self, call: IndicatorCall) {
+        if self.entries.iter().any(|e| e.call == call) {
+            return;
+        }
+        self.entries.push(IndicatorEntry {
+            state: IndicatorState::new(&call),
+            call,
+            updates: 0,
+            value: None,
+        });
+    }
+
+    /// Drop all candles seen, keeping the tracked indicators
+    pub fn reset(&mut self) {
+        for entry in &mut self.entries {
+            entry.state = IndicatorState::new(&entry.call);
+            entry.updates = 0;
+            entry.value = None;
+        }
+        for series in &mut self.series {
+            series.values.clear();
+        }
+        self.last = None;
+    }
+
+    /// Take over the candles `warm` has seen, e.g. to reconfigure a running strategy
+    ///
+    /// Every indicator of this set has to be tracked by `warm` with the same arguments;
+    /// histories `warm` does not keep start empty.
+    pub fn resume_from(&mut self, warm: &IndicatorSet) -> crate::Result<()> {
+        let mut entries = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            let source = warm
+                .entries
+                .iter()
+                .find(|e| e.call == entry.call)
+                .ok_or_else(|| anyhow::anyhow!("{} is not warmed up", entry.call))?;
+            entries.push(source.clone());
+        }
+        self.entries = entries;
+        for series in &mut self.series {
+            series.values = warm
+                .series
+                .iter()
+                .find(|s| s.expr == series.expr)
+                .map(|s| s.values.iter().take(series.depth + 1).copied().collect())
+                .unwrap_or_default();
+        }
+        self.last = warm.last;
+        Ok(())
+    }
+
+    /// Closed candles needed before every tracked indicator and history has a value
+    pub fn lookback(&self) -> usize {
+        let indicators = self.entries.iter().map(|e| e.call.lookback());
+        let series = self.series.iter().map(|s| s.expr.lookback() + s.depth);
+        indicators.chain(series).max().unwrap_or(0)
+    }
+
+    /// Feed a closed candle
+    pub fn update(&mut self, open: f64, high: f64, low: f64, close: f64, volume: f64) {
+        let bar = Bar {
+            open,
+            high: high.max(open).max(close),
+            low: low.min(open).min(close),
+            close,
+            volume,
+        };
+        for entry in &mut self.entries {
+            if matches!(entry.state, IndicatorState::External) {
+                continue;
+            }
+            let value = entry.state.next(entry.call.kind, &bar);
+            entry.updates += 1;
+            entry.value = value.filter(|v| v.is_finite() && entry.updates >= entry.call.lookback());
+        }
+        self.last = Some(bar);
+
+        // Inner series come first, so nested ones see this candle's values
+        for i in 0..self.series.len() {
+            let value = self.series[i].expr.value(self).filter(|v| v.is_finite());
+            let series = &mut self.series[i];
+            series.values.push_front(value);
+            series.values.truncate(series.depth + 1);
+        }
+    }
+
+    /// Feed a closed candle
+    pub fn update_candle(&mut self, candle: &crate::data::Candle) {
+        self.update(candle.open, candle.high, candle.low, candle.close, candle.volume);
+    }
+
+    /// Current values of the tracked indicators that are ready, labelled like `EMA(200)`
+    pub fn values(&self) -> Vec<(String, f64)> {
+        self.entries
+            .iter()
+            .filter_map(|e| e.value.map(|v| (e.call.to_string(), v)))
+            .collect()
+    }
+}
+
+impl ValueSource for IndicatorSet {
+    fn field(&self, field: PriceField) -> Option<f64> {
+        let bar = self.last.as_ref()?;
+        Some(match field {
+            PriceField::Open => bar.open,
+            PriceField::High => bar.high,
+            PriceField::Low => bar.low,
+            PriceField::Close => bar.close,
+            PriceField::Volume => bar.volume,
+        })
+    }
+
+    fn indicator(&self, call: &IndicatorCall) -> Option<f64> {
+        self.entries.iter().find(|e| &e.call == call).and_then(|e| e.value)
+    }
+
+    fn past(&self, expr: &Expr, offset: usize) -> Option<f64> {
+        let series = self.series.iter().find(|s| &s.expr == expr)?;
+        series.values.get(offset).copied().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(source: &str) -> std::result::Result<Condition, ExprError> {
+        Condition::parse(source, &json!({ "rsi_period": 7, "threshold": 25 }))
+    }
+
+    #[test]
+    fn test_parse_precedence_and_parameters() {
+        let condition = parse("RSI < threshold + 5 AND close > EMA(200) OR NOT (volume == 0)").unwrap();
+        assert_eq!(
+            condition.expr().to_string(),
+            "(((RSI(7) < (25 + 5)) AND (close > EMA(200))) OR NOT (volume == 0))"
+        );
+        assert_eq!(condition.indicators().len(), 2);
+        assert_eq!(condition.lookback(), 200);
+
+        let macd = parse("MACD(8, 21) > MACD_SIGNAL").unwrap();
+        assert_eq!(
+            macd.indicators(),
+            vec![
+                IndicatorCall { kind: IndicatorKind::Macd, args: vec![8.0, 21.0, 9.0] },
+                IndicatorCall { kind: IndicatorKind::MacdSignal, args: vec![12.0, 26.0, 9.0] },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("RSI", 0, "is a value"),
+            ("RSI < 30 AND", 12, "Unexpected end"),
+            ("RSI < 30 AND close", 13, "is a value"),
+            ("(RSI < 30", 9, "Missing `)`"),
+            ("RSI < 30 > 20", 9, "cannot be chained"),
+            ("EMA(0) < close", 4, "whole number"),
+            ("SMA(100000000000) < close", 4, "whole number from 1 to 1000"),
+            ("EMA(20, 3) < close", 8, "at most 1"),
+            ("foo > 1", 0, "Unknown name `foo`"),
+            ("MACD > Signal", 7, "sets indicator arguments"),
+            ("close > rsi_period", 8, "sets indicator arguments"),
+            ("KAMA(10) > 1", 0, "Unknown indicator"),
+            ("close > 1 $", 10, "Unexpected character"),
+            ("rising(close)", 0, "takes 2 arguments"),
+            ("highest(close, 0) > 1", 15, "whole number from 1"),
+            ("falling(30, 3)", 8, "never changes"),
+            ("crosses_above(1, 2)", 0, "at least one side"),
+            ("close[1.5] > 1", 6, "whole number from 0"),
+            ("crosses_above(close > 1, 2)", 14, "is a condition"),
+        ];
+        for (source, position, message) in cases {
+            let error = parse(source).unwrap_err();
+            assert_eq!(error.position, position, "{}: {}", source, error);
+            assert!(error.message.contains(message), "{}: {}", source, error);
+        }
+        assert_eq!(parse("(RSI < 30").unwrap_err().pointer(), "(RSI < 30\n         ^");
+    }
+
+    #[test]
+    fn test_evaluate_on_indicator_set() {
+        let buy = parse("close > SMA(3) AND NOT (RSI(2) > 99)").unwrap();
+        let mut set = IndicatorSet::for_conditions(&[&buy]);
+        assert_eq!(set.lookback(), 3);
+
+        set.update(10.0, 10.0, 10.0, 10.0, 1.0);
+        assert!(!buy.evaluate(&set), "indicators are still warming up");
+        set.update(10.0, 11.0, 9.0, 9.0, 1.0);
+        set.update(9.0, 12.0, 9.0, 12.0, 1.0);
+        // SMA(3) = 10.33, RSI(2) < 99 after the dip
+        assert!(buy.evaluate(&set));
+        assert_eq!(set.values().len(), 2);
+
+        set.update(12.0, 12.0, 8.0, 8.0, 1.0);
+        assert!(!buy.evaluate(&set));
+    }
+
+    #[test]
+    fn test_explain_clauses() {
+        let buy = parse("close > SMA(2) AND RSI(2) < 50 AND NOT crosses_above(close, 20)").unwrap();
+        let mut set = IndicatorSet::for_conditions(&[&buy]);
+        set.update(10.0, 10.0, 10.0, 10.0, 1.0);
+        let result = buy.explain(&set);
+        assert_eq!(result.clause, "AND");
+        assert_eq!(result.holds, None, "SMA(2) is still warming up");
+        assert_eq!(result.children.len(), 3);
+
+        for close in [12.0, 11.0] {
+            set.update(close, close, close, close, 1.0);
+        }
+        // close 11 > SMA(2) 11.5 fails, RSI(2) fell below 50, close stayed under 20
+        let result = buy.explain(&set);
+        assert_eq!(result.holds, Some(false));
+        let first = &result.children[0];
+        assert_eq!(first.clause, "close > SMA(2)");
+        assert_eq!((first.holds, first.left, first.right), (Some(false), Some(11.0), Some(11.5)));
+        assert_eq!(result.children[1].holds, Some(true));
+        assert_eq!(result.children[2].clause, "NOT");
+        assert_eq!(result.children[2].holds, Some(true));
+        assert_eq!(result.children[2].children[0].clause, "crosses_above(close, 20)");
+    }
+
+    #[test]
+    fn test_series_functions() {
+        let cross = parse("crosses_above(close, SMA(2))").unwrap();
+        let trend = parse("rising(close, 2) AND close[2] < lowest(close, 2)").unwrap();
+        let high = parse("highest(close, 3)[1] == 12").unwrap();
+        let mut set = IndicatorSet::for_conditions(&[&cross, &trend, &high]);
+        assert_eq!(cross.lookback(), 3);
+        assert_eq!(trend.lookback(), 3);
+        assert_eq!(high.lookback(), 4);
+        assert_eq!(set.lookback(), 4);
+
+        let mut results = Vec::new();
+        for close in [12.0, 10.0, 9.0, 11.0, 13.0, 12.0] {
+            set.update(close, close, close, close, 1.0);
+            results.push((cross.evaluate(&set), trend.evaluate(&set), high.evaluate(&set)));
+        }
+        // 11 crosses above SMA(2) = 10 after being below it, 9 -> 11 -> 13 keeps rising
+        // with 9 below the last two closes, and the highest of 12, 10, 9 is 12 one candle later
+        assert_eq!(
+            results,
+            vec![
+                (false, false, false),
+                (false, false, false),
+                (false, false, false),
+                (true, false, true),
+                (false, true, false),
+                (false, false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_external_indicator() {
+        let condition = parse("crosses_below(RSI, 30)").unwrap();
+        let rsi = condition.indicators()[0].clone();
+        let mut set = IndicatorSet::default();
+        set.add_external(rsi.clone());
+        set.track(&condition);
+
+        for value in [40.0, 25.0] {
+            set.set_external(&rsi, Some(value));
+            set.update(1.0, 1.0, 1.0, 1.0, 1.0);
+        }
+        assert!(condition.evaluate(&set));
+    }
+
+    #[test]
+    fn test_adx_and_stochastic_warm_up() {
+        let condition = parse("ADX(3) > 0 AND STOCH(3) >= 0").unwrap();
+        let mut set = IndicatorSet::for_conditions(&[&condition]);
+        for i in 0..6 {
+            let close = 100.0 + i as f64 * 2.0;
+            set.update(close - 1.0, close + 1.0, close - 2.0, close, 10.0);
+            assert_eq!(condition.evaluate(&set), i == 5, "candle {}", i);
+        }
+    }
+
+    #[test]
+    fn test_to_python() {
+        let condition = parse("RSI < 30 AND -close / 2 != BB_LOWER(20, 2.5)").unwrap();
+        assert_eq!(
+            condition.to_python(),
+            "((dataframe['rsi_7'] < 30) & (((-dataframe['close']) / 2) != dataframe['bb_lower_20_2_5']))"
+        );
+        let series = parse("crosses_above(MACD, MACD_SIGNAL) AND falling(close[1], 3) OR highest(high, 5) < 2").unwrap();
+        assert_eq!(
+            series.to_python(),
+            "((qtpylib.crossed_above(dataframe['macd_12_26_9'], dataframe['macd_signal_12_26_9']) & \
+             ((dataframe['close'].shift(1).diff() < 0).rolling(3).sum() == 3)) | (dataframe['high'].rolling(5).max() < 2))"
+        );
+        assert_eq!(
+            condition.indicators()[1].to_python(),
+            "dataframe['bb_lower_20_2_5'] = ta.BBANDS(dataframe, timeperiod=20, nbdevup=2.5, nbdevdn=2.5)['lowerband']"
+        );
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let condition = parse("close > EMA(3)").unwrap();
+        let mut set = IndicatorSet::for_conditions(&[&condition]);
+        for close in [1.0, 2.0, 3.0, 4.0] {
+            set.update(close, close, close, close, 0.0);
+        }
+        let restored: IndicatorSet = serde_json::from_value(serde_json::to_value(&set).unwrap()).unwrap();
+        let condition_restored: Condition = serde_json::from_value(serde_json::to_value(&condition).unwrap()).unwrap();
+        assert_eq!(condition_restored, condition);
+        assert_eq!(restored.values(), set.values());
+    }
+}
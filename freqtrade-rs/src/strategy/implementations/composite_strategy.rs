@@ -0,0 +1,493 @@
+//! Composite Strategy implementation
+
+use crate::data::{Candle, InformativeData, InformativeSpec};
+use crate::strategy::{ClauseResult, Signal, SignalType, Strategy};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tracing::info;
+
+/// How the member signals combine into the composite signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositeMode {
+    /// Every member signals on the same candle
+    #[default]
+    Unanimous,
+    /// More than half of the members signal on the same candle
+    Majority,
+    /// Weighted confidence of the signalling members reaches the threshold
+    Weighted,
+    /// Each member confirms the next: the last member triggers while every earlier
+    /// member signalled, in order, within the confirmation window
+    Sequence,
+}
+
+impl fmt::Display for CompositeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self {
+            CompositeMode::Unanimous => "unanimous",
+            CompositeMode::Majority => "majority",
+            CompositeMode::Weighted => "weighted",
+            CompositeMode::Sequence => "sequence",
+        };
+        write!(f, "{}", mode)
+    }
+}
+
+fn default_threshold() -> f64 {
+    0.5
+}
+
+fn default_confirm_window() -> usize {
+    3
+}
+
+/// Composite strategy configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompositeStrategyConfig {
+    #[serde(default)]
+    pub mode: CompositeMode,
+    /// Weighted mode: share of the total weight the signalling members need (0 to 1)
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+    /// Sequence mode: candles an earlier member's signal stays valid
+    #[serde(default = "default_confirm_window")]
+    pub confirm_window: usize,
+}
+
+impl Default for CompositeStrategyConfig {
+    fn default() -> Self {
+        Self {
+            mode: CompositeMode::default(),
+            threshold: default_threshold(),
+            confirm_window: default_confirm_window(),
+        }
+    }
+}
+
+/// Strategy wrapped by a composite strategy
+pub struct CompositeMember {
+    /// Name used in the combined reasons and indicator values
+    pub name: String,
+    /// Weight in weighted mode
+    pub weight: f64,
+    pub strategy: Box<dyn Strategy + Send + Sync>,
+}
+
+impl CompositeMember {
+    /// Create member with weight 1
+    pub fn new(name: &str, strategy: Box<dyn Strategy + Send + Sync>) -> Self {
+        Self {
+            name: name.to_string(),
+            weight: 1.0,
+            strategy,
+        }
+    }
+
+    /// Set weight
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+impl fmt::Debug for CompositeMember {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompositeMember")
+            .field("name", &self.name)
+            .field("weight", &self.weight)
+            .field("strategy", &self.strategy.name())
+            .finish()
+    }
+}
+
+/// A member's last entry and exit signals: candles since and reason
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MemberHistory {
+    entry: Option<(usize, String)>,
+    exit: Option<(usize, String)>,
+}
+
+impl MemberHistory {
+    fn record(&mut self, signal: &Signal) {
+        for last in [&mut self.entry, &mut self.exit].into_iter().flatten() {
+            last.0 += 1;
+        }
+        match signal.signal_type {
+            SignalType::EnterLong => self.entry = Some((0, signal.reason.clone())),
+            SignalType::ExitLong => self.exit = Some((0, signal.reason.clone())),
+            _ => {}
+        }
+    }
+
+    fn last(&self, signal_type: SignalType) -> Option<&(usize, String)> {
+        match signal_type {
+            SignalType::EnterLong => self.entry.as_ref(),
+            _ => self.exit.as_ref(),
+        }
+    }
+
+    fn since(&self, signal_type: SignalType) -> Option<usize> {
+        self.last(signal_type).map(|(since, _)| *since)
+    }
+}
+
+/// Strategy trading on the combined signals of other strategies
+///
+/// Entries and exits combine by the same mode. The combined signal carries the
+/// reasons of the members that contributed to it. Only member signals are used;
+/// their stop, exit and order callbacks are not.
+#[derive(Debug)]
+pub struct CompositeStrategy {
+    config: CompositeStrategyConfig,
+    members: Vec<CompositeMember>,
+    history: Vec<MemberHistory>,
+    /// Latest signal of each member
+    signals: Vec<Signal>,
+}
+
+impl CompositeStrategy {
+    /// Create new composite strategy
+    pub fn new(config: CompositeStrategyConfig, members: Vec<CompositeMember>) -> Result<Self> {
+        if members.is_empty() {
+            return Err(anyhow::anyhow!("Composite strategy needs at least one member"));
+        }
+        if config.mode == CompositeMode::Sequence && members.len() < 2 {
+            return Err(anyhow::anyhow!("Sequence mode needs at least two members"));
+        }
+        if config.threshold <= 0.0 || config.threshold > 1.0 {
+            return Err(anyhow::anyhow!("Threshold must be between 0 and 1, got {}", config.threshold));
+        }
+        if let Some(member) = members.iter().find(|m| !m.weight.is_finite() || m.weight <= 0.0) {
+            return Err(anyhow::anyhow!("Weight of {} must be positive, got {}", member.name, member.weight));
+        }
+
+        Ok(Self {
+            history: vec![MemberHistory::default(); members.len()],
+            signals: Vec::new(),
+            config,
+            members,
+        })
+    }
+
+    /// Members in order
+    pub fn members(&self) -> &[CompositeMember] {
+        &self.members
+    }
+
+    /// Indices and confidence of the members agreeing on `signal_type`, `None` if the mode rejects it
+    fn combine(&self, signal_type: SignalType) -> Option<(Vec<usize>, f64)> {
+        let agreeing: Vec<usize> = (0..self.members.len())
+            .filter(|&i| self.signals[i].signal_type == signal_type)
+            .collect();
+        let average = |indices: &[usize]| {
+            indices.iter().map(|&i| self.signals[i].confidence).sum::<f64>() / indices.len().max(1) as f64
+        };
+
+        match self.config.mode {
+            CompositeMode::Unanimous => {
+                (agreeing.len() == self.members.len()).then(|| (agreeing.clone(), average(&agreeing)))
+            }
+            CompositeMode::Majority => {
+                (agreeing.len() * 2 > self.members.len()).then(|| (agreeing.clone(), average(&agreeing)))
+            }
+            CompositeMode::Weighted => {
+                let total: f64 = self.members.iter().map(|m| m.weight).sum();
+                let score = agreeing.iter()
+                    .map(|&i| self.members[i].weight * self.signals[i].confidence)
+                    .sum::<f64>() / total;
+                (!agreeing.is_empty() && score >= self.config.threshold).then_some((agreeing, score))
+            }
+            CompositeMode::Sequence => {
+                let since: Vec<Option<usize>> = self.history.iter().map(|h| h.since(signal_type)).collect();
+                let trigger = since.len() - 1;
+                let confirmed = since[trigger] == Some(0)
+                    && since.iter().all(|s| s.is_some_and(|n| n <= self.config.confirm_window))
+                    && since.windows(2).all(|pair| pair[0] >= pair[1]);
+                confirmed.then(|| ((0..self.members.len()).collect(), self.signals[trigger].confidence))
+            }
+        }
+    }
+
+    /// Contributing member reasons, e.g. `majority 2/3: RSI: Buy condition met: RSI < 30; ...`
+    fn reason(&self, indices: &[usize], signal_type: SignalType) -> String {
+        let reasons: Vec<String> = indices.iter()
+            .filter_map(|&i| self.history[i].last(signal_type)
+                .map(|(_, reason)| format!("{}: {}", self.members[i].name, reason)))
+            .collect();
+        format!("{} {}/{}: {}", self.config.mode, indices.len(), self.members.len(), reasons.join("; "))
+    }
+}
+
+impl Strategy for CompositeStrategy {
+    fn name(&self) -> &str {
+        "Composite Strategy"
+    }
+
+    fn initialize(&mut self, candles: &[Candle]) -> Result<()> {
+        info!(
+            "Initializing Composite Strategy ({}, {} members)",
+            self.config.mode,
+            self.members.len()
+        );
+        for member in &mut self.members {
+            member.strategy.initialize(candles)?;
+        }
+        Ok(())
+    }
+
+    fn process(&mut self, candle: &Candle) -> Result<Signal> {
+        self.process_with_informative(candle, &InformativeData::default())
+    }
+
+    fn process_with_informative(
+        &mut self,
+        candle: &Candle,
+        informative: &InformativeData,
+    ) -> Result<Signal> {
+        self.signals.clear();
+        for (member, history) in self.members.iter_mut().zip(&mut self.history) {
+            let signal = member.strategy.process_with_informative(candle, informative)?;
+            history.record(&signal);
+            self.signals.push(signal);
+        }
+
+        if !self.is_ready() {
+            return Ok(Signal::hold("Members not ready".to_string()));
+        }
+
+        if let Some((indices, confidence)) = self.combine(SignalType::EnterLong) {
+            Ok(Signal::enter_long(candle.close, confidence.clamp(0.0, 1.0), self.reason(&indices, SignalType::EnterLong))
+                .with_enter_tag(&format!("mix_{}", self.config.mode)))
+        } else if let Some((indices, confidence)) = self.combine(SignalType::ExitLong) {
+            Ok(Signal::exit_long(candle.close, confidence.clamp(0.0, 1.0), self.reason(&indices, SignalType::ExitLong))
+                .with_exit_tag(&format!("mix_{}", self.config.mode)))
+        } else {
+            Ok(Signal::hold("Members do not agree".to_string()))
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        self.members.iter().all(|m| m.strategy.is_ready())
+    }
+
+    fn indicator_values(&self) -> Vec<(String, f64)> {
+        self.members.iter()
+            .flat_map(|m| m.strategy.indicator_values().into_iter()
+                .map(move |(name, value)| (format!("{}.{}", m.name, name), value)))
+            .collect()
+    }
+
+    fn explain_conditions(&self) -> Vec<(String, ClauseResult)> {
+        self.members.iter()
+            .flat_map(|m| m.strategy.explain_conditions().into_iter()
+                .map(move |(label, result)| (format!("{}: {}", m.name, label), result)))
+            .collect()
+    }
+
+    fn informative(&self) -> Vec<InformativeSpec> {
+        let mut specs: Vec<InformativeSpec> = Vec::new();
+        for spec in self.members.iter().flat_map(|m| m.strategy.informative()) {
+            if !specs.contains(&spec) {
+                specs.push(spec);
+            }
+        }
+        specs
+    }
+
+    fn startup_candle_count(&self) -> usize {
+        self.members.iter().map(|m| m.strategy.startup_candle_count()).max().unwrap_or(0)
+    }
+
+    fn snapshot(&self) -> Option<serde_json::Value> {
+        let members = self.members.iter()
+            .map(|m| m.strategy.snapshot())
+            .collect::<Option<Vec<_>>>()?;
+        Some(serde_json::json!({
+            "members": members,
+            "history": serde_json::to_value(&self.history).ok()?,
+        }))
+    }
+
+    fn restore(&mut self, state: serde_json::Value) -> Result<()> {
+        let members = state.get("members")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Composite snapshot has no members"))?;
+        if members.len() != self.members.len() {
+            return Err(anyhow::anyhow!(
+                "Composite snapshot has {} members, the strategy has {}",
+                members.len(),
+                self.members.len()
+            ));
+        }
+        let history: Vec<MemberHistory> = serde_json::from_value(state["history"].clone())?;
+
+        for (member, snapshot) in self.members.iter_mut().zip(members) {
+            member.strategy.restore(snapshot.clone())?;
+        }
+        self.history = history;
+        Ok(())
+    }
+
+    fn indicator_state(&self) -> Option<serde_json::Value> {
+        let members = self.members.iter()
+            .map(|m| m.strategy.indicator_state())
+            .collect::<Option<Vec<_>>>()?;
+        Some(serde_json::json!({
+            "members": members,
+            "history": serde_json::to_value(&self.history).ok()?,
+        }))
+    }
+
+    fn restore_indicator_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let members = state.get("members")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Composite indicator state has no members"))?;
+        if members.len() != self.members.len() {
+            return Err(anyhow::anyhow!(
+                "Composite indicator state has {} members, the strategy has {}",
+                members.len(),
+                self.members.len()
+            ));
+        }
+        let history: Vec<MemberHistory> = serde_json::from_value(state["history"].clone())?;
+
+        for (member, member_state) in self.members.iter_mut().zip(members) {
+            member.strategy.restore_indicator_state(member_state.clone())?;
+        }
+        self.history = history;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{ExpressionStrategy, ExpressionStrategyConfig};
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+
+    fn member(name: &str, buy: &str, sell: &str) -> CompositeMember {
+        let strategy = ExpressionStrategy::new(ExpressionStrategyConfig {
+            buy_condition: buy.to_string(),
+            sell_condition: sell.to_string(),
+            parameters: json!({}),
+        })
+        .unwrap();
+        CompositeMember::new(name, Box::new(strategy))
+    }
+
+    fn run(strategy: &mut CompositeStrategy, closes: &[f64]) -> Vec<Signal> {
+        strategy.initialize(&[]).unwrap();
+        let base = Utc::now();
+        closes.iter().enumerate().map(|(i, close)| {
+            let candle = Candle::new(*close, *close, *close, *close, 1.0, base + Duration::minutes(i as i64), "BTC/USDT".to_string(), "1m".to_string());
+            strategy.process(&candle).unwrap()
+        }).collect()
+    }
+
+    fn config(mode: CompositeMode) -> CompositeStrategyConfig {
+        CompositeStrategyConfig { mode, ..Default::default() }
+    }
+
+    #[test]
+    fn test_composite_unanimous_and_majority() {
+        let members = || vec![
+            member("low", "close < 90", "close > 110"),
+            member("lower", "close < 80", "close > 120"),
+            member("dip", "close < 90", "close > 130"),
+        ];
+        let closes = [85.0, 75.0, 115.0];
+
+        let mut unanimous = CompositeStrategy::new(config(CompositeMode::Unanimous), members()).unwrap();
+        let signals: Vec<SignalType> = run(&mut unanimous, &closes).iter().map(|s| s.signal_type).collect();
+        assert_eq!(signals, vec![SignalType::Hold, SignalType::EnterLong, SignalType::Hold]);
+
+        let mut majority = CompositeStrategy::new(config(CompositeMode::Majority), members()).unwrap();
+        let signals = run(&mut majority, &closes);
+        assert_eq!(signals[0].signal_type, SignalType::EnterLong);
+        assert!(signals[0].reason.starts_with("majority 2/3: low: Buy condition met"));
+        assert!(signals[0].reason.contains("dip: Buy condition met"));
+        assert_eq!(signals[0].enter_tag.as_deref(), Some("mix_majority"));
+        assert_eq!(signals[2].signal_type, SignalType::Hold);
+    }
+
+    #[test]
+    fn test_composite_weighted() {
+        let members = vec![
+            member("heavy", "close < 90", "close > 110").with_weight(3.0),
+            member("light", "close < 80", "close > 120"),
+        ];
+        let config = CompositeStrategyConfig {
+            mode: CompositeMode::Weighted,
+            threshold: 0.7,
+            ..Default::default()
+        };
+        let mut strategy = CompositeStrategy::new(config, members).unwrap();
+        let signals = run(&mut strategy, &[85.0, 115.0]);
+
+        assert_eq!(signals[0].signal_type, SignalType::EnterLong);
+        assert!((signals[0].confidence - 0.75).abs() < 1e-9);
+        assert_eq!(signals[1].signal_type, SignalType::ExitLong);
+    }
+
+    #[test]
+    fn test_composite_sequence() {
+        let members = || vec![
+            member("setup", "close < 90", "close > 200"),
+            member("trigger", "close > 100", "close > 200"),
+        ];
+        let config = CompositeStrategyConfig {
+            mode: CompositeMode::Sequence,
+            confirm_window: 2,
+            ..Default::default()
+        };
+
+        // The setup confirms a trigger within two candles
+        let mut strategy = CompositeStrategy::new(config.clone(), members()).unwrap();
+        let signals: Vec<SignalType> = run(&mut strategy, &[85.0, 95.0, 105.0]).iter().map(|s| s.signal_type).collect();
+        assert_eq!(signals, vec![SignalType::Hold, SignalType::Hold, SignalType::EnterLong]);
+
+        // A stale setup does not
+        let mut strategy = CompositeStrategy::new(config.clone(), members()).unwrap();
+        let signals = run(&mut strategy, &[85.0, 95.0, 95.0, 95.0, 105.0]);
+        assert_eq!(signals[4].signal_type, SignalType::Hold);
+
+        // Nor does a trigger before the setup
+        let mut strategy = CompositeStrategy::new(config, members()).unwrap();
+        let signals = run(&mut strategy, &[105.0, 95.0, 85.0]);
+        assert!(signals.iter().all(|s| s.signal_type == SignalType::Hold));
+    }
+
+    #[test]
+    fn test_composite_snapshot_restore() {
+        let members = || vec![
+            member("setup", "close < 90", "close > 200"),
+            member("trigger", "close > 100", "close > 200"),
+        ];
+        let config = config(CompositeMode::Sequence);
+        let mut strategy = CompositeStrategy::new(config.clone(), members()).unwrap();
+        run(&mut strategy, &[85.0]);
+
+        let mut restored = CompositeStrategy::new(config, members()).unwrap();
+        restored.restore(strategy.snapshot().unwrap()).unwrap();
+        let signals = run(&mut restored, &[105.0]);
+        assert_eq!(signals[0].signal_type, SignalType::EnterLong);
+    }
+
+    #[test]
+    fn test_composite_rejects_invalid_config() {
+        assert!(CompositeStrategy::new(config(CompositeMode::Majority), Vec::new()).is_err());
+        assert!(CompositeStrategy::new(
+            config(CompositeMode::Sequence),
+            vec![member("only", "close < 90", "close > 110")],
+        )
+        .is_err());
+        assert!(CompositeStrategy::new(
+            config(CompositeMode::Weighted),
+            vec![member("zero", "close < 90", "close > 110").with_weight(0.0)],
+        )
+        .is_err());
+    }
+}
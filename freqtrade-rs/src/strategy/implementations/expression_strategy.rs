@@ -0,0 +1,252 @@
+//! Expression Strategy implementation
+
+use crate::data::Candle;
+use crate::strategy::{ClauseResult, Condition, IndicatorSet, Signal, Strategy};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// Expression strategy configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpressionStrategyConfig {
+    /// Entry condition, e.g. `RSI < 30 AND close > EMA(200)`
+    pub buy_condition: String,
+    /// Exit condition
+    pub sell_condition: String,
+    /// Parameters the conditions can reference by name
+    #[serde(default)]
+    pub parameters: serde_json::Value,
+}
+
+/// Strategy entering and exiting on user-written conditions (see [`crate::strategy::expression`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpressionStrategy {
+    buy: Condition,
+    sell: Condition,
+    indicators: IndicatorSet,
+    candles_seen: usize,
+    is_initialized: bool,
+}
+
+impl ExpressionStrategy {
+    /// Create new expression strategy, failing on syntax errors in either condition
+    pub fn new(config: ExpressionStrategyConfig) -> Result<Self> {
+        let buy = Condition::parse(&config.buy_condition, &config.parameters)
+            .map_err(|e| anyhow::anyhow!("Invalid buy condition: {}", e))?;
+        let sell = Condition::parse(&config.sell_condition, &config.parameters)
+            .map_err(|e| anyhow::anyhow!("Invalid sell condition: {}", e))?;
+        let indicators = IndicatorSet::for_conditions(&[&buy, &sell]);
+
+        Ok(Self {
+            buy,
+            sell,
+            indicators,
+            candles_seen: 0,
+            is_initialized: false,
+        })
+    }
+
+    /// Entry condition
+    pub fn buy_condition(&self) -> &Condition {
+        &self.buy
+    }
+
+    /// Exit condition
+    pub fn sell_condition(&self) -> &Condition {
+        &self.sell
+    }
+
+    /// Closed candles needed before both conditions can be evaluated
+    pub fn lookback(&self) -> usize {
+        self.indicators.lookback()
+    }
+}
+
+impl Strategy for ExpressionStrategy {
+    fn name(&self) -> &str {
+        "Expression Strategy"
+    }
+
+    fn initialize(&mut self, candles: &[Candle]) -> Result<()> {
+        // Indicators are only fed through `process` so a backtest never sees
+        // candles ahead of the one being evaluated
+        info!(
+            "Initializing Expression Strategy (buy: {}, sell: {}), lookback {} of {} candles",
+            self.buy,
+            self.sell,
+            self.lookback(),
+            candles.len()
+        );
+        self.is_initialized = true;
+        Ok(())
+    }
+
+    fn process(&mut self, candle: &Candle) -> Result<Signal> {
+        self.indicators.update_candle(candle);
+        self.candles_seen += 1;
+
+        if !self.is_ready() {
+            debug!("Indicators not ready yet ({}/{})", self.candles_seen, self.lookback());
+            return Ok(Signal::hold("Indicators not ready".to_string()));
+        }
+
+        if self.buy.evaluate(&self.indicators) {
+            Ok(Signal::enter_long(candle.close, 1.0, format!("Buy condition met: {}", self.buy))
+                .with_enter_tag("expression"))
+        } else if self.sell.evaluate(&self.indicators) {
+            Ok(Signal::exit_long(candle.close, 1.0, format!("Sell condition met: {}", self.sell))
+                .with_exit_tag("expression"))
+        } else {
+            Ok(Signal::hold("No condition met".to_string()))
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_initialized && self.candles_seen >= self.lookback()
+    }
+
+    fn indicator_values(&self) -> Vec<(String, f64)> {
+        self.indicators.values()
+    }
+
+    fn explain_conditions(&self) -> Vec<(String, ClauseResult)> {
+        vec![
+            ("buy".to_string(), self.buy.explain(&self.indicators)),
+            ("sell".to_string(), self.sell.explain(&self.indicators)),
+        ]
+    }
+
+    fn startup_candle_count(&self) -> usize {
+        self.lookback()
+    }
+
+    fn snapshot(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self).ok()
+    }
+
+    fn restore(&mut self, state: serde_json::Value) -> Result<()> {
+        *self = serde_json::from_value(state)?;
+        Ok(())
+    }
+
+    fn indicator_state(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "indicators": serde_json::to_value(&self.indicators).ok()?,
+            "candles_seen": self.candles_seen,
+        }))
+    }
+
+    fn restore_indicator_state(&mut self, state: serde_json::Value) -> Result<()> {
+        let warm: IndicatorSet = serde_json::from_value(state.get("indicators").cloned().unwrap_or_default())?;
+        let candles_seen = state.get("candles_seen").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        self.indicators.resume_from(&warm)?;
+        self.candles_seen = candles_seen;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::SignalType;
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+
+    #[test]
+    fn test_expression_strategy_signals() {
+        let mut strategy = ExpressionStrategy::new(ExpressionStrategyConfig {
+            buy_condition: "close < SMA(period) - 5".to_string(),
+            sell_condition: "close > SMA(period) + 5".to_string(),
+            parameters: json!({ "period": 3 }),
+        })
+        .unwrap();
+        assert_eq!(strategy.lookback(), 3);
+        strategy.initialize(&[]).unwrap();
+
+        let base = Utc::now();
+        let mut signals = Vec::new();
+        for (i, close) in [100.0, 100.0, 100.0, 85.0, 100.0, 120.0].iter().enumerate() {
+            let candle = Candle::new(*close, *close, *close, *close, 1.0, base + Duration::minutes(i as i64), "BTC/USDT".to_string(), "1m".to_string());
+            signals.push(strategy.process(&candle).unwrap().signal_type);
+        }
+
+        assert_eq!(signals[2], SignalType::Hold);
+        assert_eq!(signals[3], SignalType::EnterLong);
+        assert_eq!(signals[5], SignalType::ExitLong);
+
+        // The exit on 120 is explained by the sell condition holding
+        let explained = strategy.explain_conditions();
+        assert_eq!(explained[0].0, "buy");
+        assert_eq!(explained[0].1.holds, Some(false));
+        assert_eq!(explained[1].1.clause, "close > (SMA(3) + 5)");
+        assert_eq!(explained[1].1.holds, Some(true));
+        assert_eq!(explained[1].1.left, Some(120.0));
+    }
+
+    #[test]
+    fn test_expression_strategy_snapshot_restore() {
+        let config = ExpressionStrategyConfig {
+            buy_condition: "crosses_above(close, SMA(3))".to_string(),
+            sell_condition: "crosses_below(close, SMA(3))".to_string(),
+            parameters: json!({}),
+        };
+        let mut strategy = ExpressionStrategy::new(config.clone()).unwrap();
+        strategy.initialize(&[]).unwrap();
+        assert_eq!(strategy.startup_candle_count(), 4);
+
+        let base = Utc::now();
+        let candle = |i: usize, close: f64| {
+            Candle::new(close, close, close, close, 1.0, base + Duration::minutes(i as i64), "BTC/USDT".to_string(), "1m".to_string())
+        };
+        for (i, close) in [100.0, 100.0, 100.0, 95.0].iter().enumerate() {
+            strategy.process(&candle(i, *close)).unwrap();
+        }
+
+        let mut restored = ExpressionStrategy::new(config).unwrap();
+        restored.restore(strategy.snapshot().unwrap()).unwrap();
+        assert!(restored.is_ready());
+        assert_eq!(restored.process(&candle(4, 110.0)).unwrap().signal_type, SignalType::EnterLong);
+    }
+
+    #[test]
+    fn test_expression_strategy_hot_reload() {
+        let config = |threshold: f64, period: u64| ExpressionStrategyConfig {
+            buy_condition: "close < SMA(period) - threshold".to_string(),
+            sell_condition: "close > SMA(period) + threshold".to_string(),
+            parameters: json!({ "period": period, "threshold": threshold }),
+        };
+        let mut strategy = ExpressionStrategy::new(config(20.0, 3)).unwrap();
+        strategy.initialize(&[]).unwrap();
+
+        let base = Utc::now();
+        let candle = |i: usize, close: f64| {
+            Candle::new(close, close, close, close, 1.0, base + Duration::minutes(i as i64), "BTC/USDT".to_string(), "1m".to_string())
+        };
+        for (i, close) in [100.0, 100.0, 100.0].iter().enumerate() {
+            strategy.process(&candle(i, *close)).unwrap();
+        }
+
+        // A new threshold resumes from the warm indicators
+        let mut reloaded = ExpressionStrategy::new(config(5.0, 3)).unwrap();
+        reloaded.initialize(&[]).unwrap();
+        reloaded.restore_indicator_state(strategy.indicator_state().unwrap()).unwrap();
+        assert!(reloaded.is_ready());
+        assert_eq!(reloaded.process(&candle(3, 85.0)).unwrap().signal_type, SignalType::EnterLong);
+
+        // A new period needs another warm-up
+        let mut reloaded = ExpressionStrategy::new(config(5.0, 4)).unwrap();
+        let error = reloaded.restore_indicator_state(strategy.indicator_state().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("not warmed up"));
+    }
+
+    #[test]
+    fn test_expression_strategy_rejects_invalid_condition() {
+        let error = ExpressionStrategy::new(ExpressionStrategyConfig {
+            buy_condition: "RSI <".to_string(),
+            sell_condition: "RSI > 70".to_string(),
+            parameters: json!({}),
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("Invalid buy condition"));
+    }
+}
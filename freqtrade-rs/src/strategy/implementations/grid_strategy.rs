@@ -0,0 +1,310 @@
+//! Grid Strategy implementation
+
+use crate::data::Candle;
+use crate::exchange::OrderSide;
+use crate::strategy::{LimitOrder, Signal, Strategy};
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// How grid levels are spaced between the bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridSpacing {
+    /// Equal price distance between levels
+    #[default]
+    Arithmetic,
+    /// Equal percentage distance between levels
+    Geometric,
+}
+
+/// Grid strategy configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GridStrategyConfig {
+    /// Lowest grid level
+    pub lower_price: f64,
+    /// Highest grid level
+    pub upper_price: f64,
+    /// Number of grid intervals (levels = grid_count + 1)
+    pub grid_count: usize,
+    #[serde(default)]
+    pub spacing: GridSpacing,
+    /// Quote currency amount bought at each level
+    pub order_size: f64,
+    /// Shift the grid up when the price breaks above the upper bound
+    #[serde(default)]
+    pub trailing: bool,
+}
+
+/// Grid trading strategy
+///
+/// Rests a limit buy at every level below the price. When a buy at a level fills, a
+/// limit sell for the same quantity rests one level up; when that sells, the buy is
+/// placed again. The price range is fixed unless trailing is enabled, in which case
+/// the grid moves up level by level once every sell above has filled.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GridStrategy {
+    config: GridStrategyConfig,
+    levels: Vec<f64>,
+    /// Quantity bought at the lower level of each interval, waiting to be sold
+    holdings: Vec<Option<f64>>,
+    last_close: Option<f64>,
+    is_initialized: bool,
+}
+
+impl GridStrategy {
+    /// Create new grid strategy
+    pub fn new(config: GridStrategyConfig) -> Result<Self> {
+        if config.grid_count == 0 {
+            return Err(anyhow::anyhow!("Grid count must be at least 1"));
+        }
+        if config.lower_price <= 0.0 || config.upper_price <= config.lower_price {
+            return Err(anyhow::anyhow!(
+                "Grid bounds must satisfy 0 < lower ({}) < upper ({})",
+                config.lower_price,
+                config.upper_price
+            ));
+        }
+        if config.order_size <= 0.0 {
+            return Err(anyhow::anyhow!("Grid order size must be positive"));
+        }
+
+        let count = config.grid_count;
+        let levels = (0..=count)
+            .map(|i| {
+                let fraction = i as f64 / count as f64;
+                match config.spacing {
+                    GridSpacing::Arithmetic => {
+                        config.lower_price + (config.upper_price - config.lower_price) * fraction
+                    }
+                    GridSpacing::Geometric => {
+                        config.lower_price * (config.upper_price / config.lower_price).powf(fraction)
+                    }
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            config,
+            levels,
+            holdings: vec![None; count],
+            last_close: None,
+            is_initialized: false,
+        })
+    }
+
+    /// Grid configuration
+    pub fn config(&self) -> &GridStrategyConfig {
+        &self.config
+    }
+
+    /// Current grid levels, ascending
+    pub fn levels(&self) -> &[f64] {
+        &self.levels
+    }
+
+    /// Quantity held from filled buys
+    pub fn inventory(&self) -> f64 {
+        self.holdings.iter().flatten().sum()
+    }
+
+    /// Move every level up by one grid step, dropping the lowest interval
+    fn shift_up(&mut self) {
+        let top = self.levels[self.levels.len() - 1];
+        let next = match self.config.spacing {
+            GridSpacing::Arithmetic => top + (self.levels[1] - self.levels[0]),
+            GridSpacing::Geometric => top * (self.levels[1] / self.levels[0]),
+        };
+        self.levels.remove(0);
+        self.levels.push(next);
+        self.holdings.remove(0);
+        self.holdings.push(None);
+    }
+
+    /// Interval whose level `offset` matches `price`
+    fn interval_at(&self, price: f64, offset: usize) -> Option<usize> {
+        (0..self.holdings.len()).find(|&i| (self.levels[i + offset] - price).abs() <= price * 1e-9)
+    }
+}
+
+impl Strategy for GridStrategy {
+    fn name(&self) -> &str {
+        "Grid Strategy"
+    }
+
+    fn initialize(&mut self, _candles: &[Candle]) -> Result<()> {
+        info!(
+            "Initializing Grid Strategy: {} levels from {:.4} to {:.4}",
+            self.levels.len(),
+            self.levels[0],
+            self.levels[self.levels.len() - 1]
+        );
+        self.is_initialized = true;
+        Ok(())
+    }
+
+    fn process(&mut self, candle: &Candle) -> Result<Signal> {
+        self.last_close = Some(candle.close);
+
+        // Every sell above has filled once the price is past the top level
+        while self.config.trailing
+            && candle.close > self.levels[self.levels.len() - 1]
+            && self.holdings[0].is_none()
+        {
+            self.shift_up();
+            debug!(
+                "Grid trailed up to {:.4} - {:.4}",
+                self.levels[0],
+                self.levels[self.levels.len() - 1]
+            );
+        }
+
+        Ok(Signal::hold(format!(
+            "Grid holding {:.8} across {} levels",
+            self.inventory(),
+            self.levels.len()
+        )))
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn limit_orders(&self) -> Vec<LimitOrder> {
+        let Some(close) = self.last_close else {
+            return Vec::new();
+        };
+
+        self.holdings
+            .iter()
+            .enumerate()
+            .filter_map(|(i, holding)| match holding {
+                Some(quantity) => Some(LimitOrder::sell(self.levels[i + 1], *quantity).with_tag("grid_sell")),
+                None if self.levels[i] < close => Some(
+                    LimitOrder::buy(self.levels[i], self.config.order_size / self.levels[i]).with_tag("grid_buy"),
+                ),
+                None => None,
+            })
+            .collect()
+    }
+
+    fn on_order_filled(&mut self, order: &LimitOrder, fill_price: f64, _time: DateTime<Utc>) {
+        match order.side {
+            OrderSide::Buy => {
+                if let Some(i) = self.interval_at(order.price, 0) {
+                    self.holdings[i] = Some(order.quantity);
+                }
+            }
+            OrderSide::Sell => {
+                if let Some(i) = self.interval_at(order.price, 1) {
+                    self.holdings[i] = None;
+                }
+            }
+        }
+        debug!("Grid {:?} filled at {:.4}", order.side, fill_price);
+    }
+    fn snapshot(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self).ok()
+    }
+
+    fn restore(&mut self, state: serde_json::Value) -> Result<()> {
+        *self = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn config(spacing: GridSpacing, trailing: bool) -> GridStrategyConfig {
+        GridStrategyConfig {
+            lower_price: 90.0,
+            upper_price: 110.0,
+            grid_count: 4,
+            spacing,
+            order_size: 100.0,
+            trailing,
+        }
+    }
+
+    fn candle(close: f64) -> Candle {
+        Candle::new(
+            close,
+            close,
+            close,
+            close,
+            1000.0,
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            "BTC/USDT".to_string(),
+            "1h".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_grid_levels() {
+        let grid = GridStrategy::new(config(GridSpacing::Arithmetic, false)).unwrap();
+        assert_eq!(grid.levels(), &[90.0, 95.0, 100.0, 105.0, 110.0]);
+
+        let grid = GridStrategy::new(config(GridSpacing::Geometric, false)).unwrap();
+        let ratio = grid.levels()[1] / grid.levels()[0];
+        assert!((grid.levels()[4] / grid.levels()[3] - ratio).abs() < 1e-12);
+        assert!((grid.levels()[4] - 110.0).abs() < 1e-9);
+
+        assert!(GridStrategy::new(GridStrategyConfig { grid_count: 0, ..config(GridSpacing::Arithmetic, false) }).is_err());
+    }
+
+    #[test]
+    fn test_grid_orders_follow_fills() {
+        let mut grid = GridStrategy::new(config(GridSpacing::Arithmetic, false)).unwrap();
+        grid.initialize(&[]).unwrap();
+        grid.process(&candle(101.0)).unwrap();
+
+        let orders = grid.limit_orders();
+        assert_eq!(orders.iter().map(|o| o.price).collect::<Vec<_>>(), vec![90.0, 95.0, 100.0]);
+        assert!(orders.iter().all(|o| o.side == OrderSide::Buy));
+
+        let buy = orders[2].clone();
+        grid.on_order_filled(&buy, 100.0, Utc::now());
+        grid.process(&candle(99.0)).unwrap();
+        let orders = grid.limit_orders();
+        let sell = orders.iter().find(|o| o.side == OrderSide::Sell).unwrap();
+        assert_eq!(sell.price, 105.0);
+        assert_eq!(sell.quantity, 1.0);
+        assert_eq!(grid.inventory(), 1.0);
+
+        let sell = sell.clone();
+        grid.on_order_filled(&sell, 105.0, Utc::now());
+        assert_eq!(grid.inventory(), 0.0);
+    }
+
+    #[test]
+    fn test_grid_state_roundtrip() {
+        let mut grid = GridStrategy::new(config(GridSpacing::Geometric, false)).unwrap();
+        grid.initialize(&[]).unwrap();
+        grid.process(&candle(101.0)).unwrap();
+        let buy = grid.limit_orders()[1].clone();
+        grid.on_order_filled(&buy, buy.price, Utc::now());
+
+        let restored: GridStrategy = serde_json::from_value(serde_json::to_value(&grid).unwrap()).unwrap();
+        assert_eq!(restored.levels(), grid.levels());
+        assert_eq!(restored.inventory(), grid.inventory());
+        assert_eq!(restored.limit_orders(), grid.limit_orders());
+        assert!(restored.is_ready());
+    }
+
+    #[test]
+    fn test_grid_trails_up() {
+        let mut grid = GridStrategy::new(config(GridSpacing::Arithmetic, true)).unwrap();
+        grid.initialize(&[]).unwrap();
+        grid.process(&candle(121.0)).unwrap();
+        assert_eq!(grid.levels(), &[105.0, 110.0, 115.0, 120.0, 125.0]);
+
+        let mut fixed = GridStrategy::new(config(GridSpacing::Arithmetic, false)).unwrap();
+        fixed.initialize(&[]).unwrap();
+        fixed.process(&candle(121.0)).unwrap();
+        assert_eq!(fixed.levels()[4], 110.0);
+    }
+}
@@ -2,9 +2,9 @@
 
 use crate::data::Candle;
 use crate::indicators::{MACD, Indicator};
-use crate::strategy::{Strategy, Signal, SignalType};
+use crate::strategy::{Strategy, Signal};
 use crate::Result;
-use tracing::{debug, info, warn};
+use tracing::{debug, info};
 
 /// MACD Strategy configuration
 #[derive(Debug, Clone)]
@@ -156,11 +156,12 @@ impl Strategy for MACDStrategy {
                     if confidence >= self.config.min_confidence
                         && histogram.abs() >= self.config.min_histogram
                     {
-                        let signal = Signal::buy(
+                        let signal = Signal::enter_long(
                             candle.close,
                             confidence,
                             format!("MACD bullish crossover: histogram={:.4}", histogram),
                         )
+                        .with_enter_tag("macd_bullish_cross")
                         .with_stop_loss(candle.close * 0.95) // 5% stop loss
                         .with_take_profit(candle.close * 1.05); // 5% take profit
 
@@ -176,11 +177,12 @@ impl Strategy for MACDStrategy {
                     if confidence >= self.config.min_confidence
                         && histogram.abs() >= self.config.min_histogram
                     {
-                        let signal = Signal::sell(
+                        let signal = Signal::exit_long(
                             candle.close,
                             confidence,
                             format!("MACD bearish crossover: histogram={:.4}", histogram),
-                        );
+                        )
+                        .with_exit_tag("macd_bearish_cross");
 
                         info!(
                             "SELL signal generated: price={:.2}, MACD={:.4}, histogram={:.4}, confidence={:.2}",
@@ -203,6 +205,17 @@ impl Strategy for MACDStrategy {
     fn is_ready(&self) -> bool {
         self.is_initialized && self.macd.is_ready()
     }
+
+    fn indicator_values(&self) -> Vec<(String, f64)> {
+        [
+            ("macd", self.macd.macd()),
+            ("macd_signal", self.macd.signal()),
+            ("macd_histogram", self.macd.histogram()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|v| (name.to_string(), v)))
+        .collect()
+    }
 }
 
 #[cfg(test)]
@@ -2,7 +2,13 @@
 
 pub mod rsi_strategy;
 pub mod macd_strategy;
+pub mod grid_strategy;
+pub mod expression_strategy;
+pub mod composite_strategy;
 
 pub use rsi_strategy::*;
 pub use macd_strategy::*;
+pub use grid_strategy::*;
+pub use expression_strategy::*;
+pub use composite_strategy::*;
 
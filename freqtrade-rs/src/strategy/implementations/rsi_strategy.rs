@@ -2,9 +2,9 @@
 
 use crate::data::Candle;
 use crate::indicators::{RSI, Indicator};
-use crate::strategy::{Strategy, Signal, SignalType};
+use crate::strategy::{Strategy, Signal};
 use crate::Result;
-use tracing::{debug, info, warn};
+use tracing::{debug, info};
 
 /// RSI Strategy configuration
 #[derive(Debug, Clone)]
@@ -121,11 +121,12 @@ impl Strategy for RSIStrategy {
         // Generate signal based on RSI
         if rsi_value < self.config.rsi_oversold && confidence >= self.config.min_confidence {
             // RSI is oversold - buy signal
-            let signal = Signal::buy(
+            let signal = Signal::enter_long(
                 candle.close,
                 confidence,
                 format!("RSI oversold: {:.2} < {}", rsi_value, self.config.rsi_oversold),
             )
+            .with_enter_tag("rsi_oversold")
             .with_stop_loss(candle.close * 0.95) // 5% stop loss
             .with_take_profit(candle.close * 1.05); // 5% take profit
 
@@ -137,11 +138,12 @@ impl Strategy for RSIStrategy {
             Ok(signal)
         } else if rsi_value > self.config.rsi_overbought && confidence >= self.config.min_confidence {
             // RSI is overbought - sell signal
-            let signal = Signal::sell(
+            let signal = Signal::exit_long(
                 candle.close,
                 confidence,
                 format!("RSI overbought: {:.2} > {}", rsi_value, self.config.rsi_overbought),
-            );
+            )
+            .with_exit_tag("rsi_overbought");
 
             info!(
                 "SELL signal generated: price={:.2}, RSI={:.2}, confidence={:.2}",
@@ -161,12 +163,17 @@ impl Strategy for RSIStrategy {
     fn is_ready(&self) -> bool {
         self.is_initialized && self.rsi.is_ready()
     }
+
+    fn indicator_values(&self) -> Vec<(String, f64)> {
+        self.rsi.value().map(|v| ("rsi".to_string(), v)).into_iter().collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::data::Candle;
+    use crate::strategy::SignalType;
     use chrono::Utc;
 
     fn create_test_candle(close: f64, timestamp: chrono::DateTime<Utc>) -> Candle {
@@ -209,7 +216,7 @@ mod tests {
         // Should generate buy signal if RSI is oversold
         // Note: Actual RSI calculation depends on price movement pattern
         match signal.signal_type {
-            SignalType::Buy | SignalType::Hold => {
+            SignalType::EnterLong | SignalType::Hold => {
                 // Either is acceptable depending on actual RSI value
             }
             _ => panic!("Expected Buy or Hold signal"),
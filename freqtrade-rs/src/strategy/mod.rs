@@ -5,10 +5,14 @@
 pub mod base;
 pub mod signal;
 pub mod validator;
+pub mod trend_filter;
+pub mod expression;
 pub mod implementations;
 
 pub use base::*;
 pub use signal::*;
 pub use validator::*;
+pub use trend_filter::*;
+pub use expression::*;
 pub use implementations::*;
 
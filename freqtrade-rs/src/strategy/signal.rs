@@ -9,11 +9,14 @@ impl SignalValidator {
     /// Validate signal
     pub fn validate(signal: &Signal) -> bool {
         match signal.signal_type {
-            SignalType::Buy | SignalType::Sell => {
+            SignalType::EnterLong | SignalType::EnterShort => {
                 signal.entry_price.is_some()
                     && signal.confidence >= 0.0
                     && signal.confidence <= 1.0
             }
+            SignalType::ExitLong | SignalType::ExitShort => {
+                signal.confidence >= 0.0 && signal.confidence <= 1.0
+            }
             SignalType::Hold => true,
         }
     }
@@ -0,0 +1,63 @@
+//! Informative trend filters
+//!
+//! A [`TrendFilter`] gates entries on a higher timeframe or another pair, e.g. a
+//! 1h EMA trend filter on a 5m strategy or a BTC regime filter on altcoins.
+
+use crate::data::{InformativeData, InformativeSpec};
+use crate::indicators::calculate_ema;
+use serde::{Deserialize, Serialize};
+
+/// Entry filter: informative close must be above its EMA
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendFilter {
+    /// Symbol (e.g., "BTC/USDT"), `None` for the strategy's own pair
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// Informative timeframe (e.g., "1h")
+    pub timeframe: String,
+    /// EMA period on the informative timeframe
+    #[serde(default = "default_ema_period")]
+    pub ema_period: usize,
+}
+
+impl TrendFilter {
+    /// Create new trend filter
+    pub fn new(spec: InformativeSpec, ema_period: usize) -> Self {
+        Self {
+            symbol: spec.symbol,
+            timeframe: spec.timeframe,
+            ema_period,
+        }
+    }
+
+    /// Informative series the filter reads
+    pub fn spec(&self) -> InformativeSpec {
+        InformativeSpec {
+            symbol: self.symbol.clone(),
+            timeframe: self.timeframe.clone(),
+        }
+    }
+
+    /// Check the filter; `None` until enough informative candles are closed
+    pub fn is_bullish(&self, data: &InformativeData, base_symbol: &str) -> Option<bool> {
+        if self.ema_period == 0 {
+            return None;
+        }
+        let closes: Vec<f64> = data
+            .candles_for(&self.spec(), base_symbol)
+            .iter()
+            .map(|c| c.close)
+            .collect();
+        let ema = calculate_ema(&closes, self.ema_period).last().copied().flatten()?;
+        closes.last().map(|close| *close > ema)
+    }
+
+    /// Check whether entries are allowed (blocked while warming up)
+    pub fn allows_entry(&self, data: &InformativeData, base_symbol: &str) -> bool {
+        self.is_bullish(data, base_symbol).unwrap_or(false)
+    }
+}
+
+fn default_ema_period() -> usize {
+    50
+}
@@ -2,10 +2,10 @@
 
 use freqtrade_rs::data::{Candle, CandleSeries};
 use freqtrade_rs::indicators::{RSI, MACD, EMA, SMA, Indicator};
-use freqtrade_rs::strategy::{Strategy, SignalType};
+use freqtrade_rs::strategy::Strategy;
 use freqtrade_rs::strategy::implementations::{RSIStrategy, RSIStrategyConfig};
 use freqtrade_rs::portfolio::{Balance, Position, PositionSide};
-use freqtrade_rs::backtest::{BacktestEngine, BacktestResult};
+use freqtrade_rs::backtest::BacktestEngine;
 use chrono::Utc;
 
 /// Helper function to create test candles
@@ -181,8 +181,8 @@ fn test_rsi_strategy() {
     // Signal type depends on RSI value, but should be valid
     assert!(matches!(
         signal.signal_type,
-        freqtrade_rs::strategy::SignalType::Buy
-            | freqtrade_rs::strategy::SignalType::Sell
+        freqtrade_rs::strategy::SignalType::EnterLong
+            | freqtrade_rs::strategy::SignalType::ExitLong
             | freqtrade_rs::strategy::SignalType::Hold
     ));
 }
@@ -66,7 +66,7 @@ mod tests {
         let value = rsi.value();
         assert!(value.is_some());
         if let Some(v) = value {
-            assert!(v >= 0.0 && v <= 100.0);
+            assert!((0.0..=100.0).contains(&v));
         }
     }
 
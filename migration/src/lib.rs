@@ -8,6 +8,11 @@ mod m20251107_000001_create_live_trading_orders;
 mod m20251108_000001_create_positions_and_trades;
 mod m20251109_000001_rename_live_trading_orders_to_signals;
 mod m20251110_000001_create_live_trading_sessions;
+mod m20251111_000001_create_position_orders;
+mod m20251112_000001_create_protection_locks;
+mod m20251113_000001_create_strategy_snapshots;
+mod m20251114_000001_add_session_id_to_signals;
+mod m20251115_000001_add_pause_and_reloads_to_sessions;
 
 pub struct Migrator;
 
@@ -23,6 +28,11 @@ impl MigratorTrait for Migrator {
                 Box::new(m20251108_000001_create_positions_and_trades::Migration),
                 Box::new(m20251109_000001_rename_live_trading_orders_to_signals::Migration),
                 Box::new(m20251110_000001_create_live_trading_sessions::Migration),
+                Box::new(m20251111_000001_create_position_orders::Migration),
+                Box::new(m20251112_000001_create_protection_locks::Migration),
+                Box::new(m20251113_000001_create_strategy_snapshots::Migration),
+                Box::new(m20251114_000001_add_session_id_to_signals::Migration),
+                Box::new(m20251115_000001_add_pause_and_reloads_to_sessions::Migration),
         ]
     }
 }
@@ -0,0 +1,101 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // P&L realized by partial exits of a still-open position
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Positions::Table)
+                    .add_column(ColumnDef::new(Positions::RealizedPnl).decimal_len(20, 8).not_null().default(0.0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create position_orders table (entry/exit fills of a position: DCA entries, partial exits)
+        manager
+            .create_table(
+                Table::create()
+                    .table(PositionOrders::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PositionOrders::Id).big_unsigned().auto_increment().primary_key())
+                    .col(ColumnDef::new(PositionOrders::PositionId).big_unsigned().not_null())
+                    .col(ColumnDef::new(PositionOrders::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(PositionOrders::SignalId).big_unsigned().null()) // Reference to live_trading_signals
+                    .col(ColumnDef::new(PositionOrders::Kind).string().not_null()) // "entry" or "exit"
+                    .col(ColumnDef::new(PositionOrders::Price).decimal_len(20, 8).not_null())
+                    .col(ColumnDef::new(PositionOrders::Quantity).decimal_len(20, 8).not_null())
+                    .col(ColumnDef::new(PositionOrders::Value).decimal_len(20, 8).not_null()) // price * quantity
+                    .col(ColumnDef::new(PositionOrders::RealizedPnl).decimal_len(20, 8).not_null().default(0.0)) // Exits only
+                    .col(ColumnDef::new(PositionOrders::CreatedAt).timestamp().default(Expr::cust("CURRENT_TIMESTAMP")))
+                    .index(
+                        Index::create()
+                            .name("idx_position_orders_position")
+                            .table(PositionOrders::Table)
+                            .col(PositionOrders::PositionId)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_position_orders_position")
+                            .from(PositionOrders::Table, PositionOrders::PositionId)
+                            .to(Positions::Table, Positions::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_position_orders_user")
+                            .from(PositionOrders::Table, PositionOrders::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PositionOrders::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Positions::Table)
+                    .drop_column(Positions::RealizedPnl)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PositionOrders {
+    Table,
+    Id,
+    PositionId,
+    UserId,
+    SignalId,
+    Kind,
+    Price,
+    Quantity,
+    Value,
+    RealizedPnl,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Positions {
+    Table,
+    Id,
+    RealizedPnl,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Exit reason of a closed trade (read by protections such as StoplossGuard)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Trades::Table)
+                    .add_column(ColumnDef::new(Trades::ExitReason).string().null()) // "exit_signal", "stop_loss", ...
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create protection_locks table (entry locks created by protections, honored after restarts)
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProtectionLocks::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ProtectionLocks::Id).big_unsigned().auto_increment().primary_key())
+                    .col(ColumnDef::new(ProtectionLocks::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(ProtectionLocks::Pair).string().null()) // NULL locks all pairs
+                    .col(ColumnDef::new(ProtectionLocks::Protection).string().not_null()) // "CooldownPeriod", "StoplossGuard", ...
+                    .col(ColumnDef::new(ProtectionLocks::Reason).text().not_null())
+                    .col(ColumnDef::new(ProtectionLocks::LockUntil).timestamp().not_null())
+                    .col(ColumnDef::new(ProtectionLocks::CreatedAt).timestamp().default(Expr::cust("CURRENT_TIMESTAMP")))
+                    .index(
+                        Index::create()
+                            .name("idx_protection_locks_user_until")
+                            .table(ProtectionLocks::Table)
+                            .col(ProtectionLocks::UserId)
+                            .col(ProtectionLocks::LockUntil)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_protection_locks_user")
+                            .from(ProtectionLocks::Table, ProtectionLocks::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProtectionLocks::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Trades::Table)
+                    .drop_column(Trades::ExitReason)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProtectionLocks {
+    Table,
+    Id,
+    UserId,
+    Pair,
+    Protection,
+    Reason,
+    LockUntil,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Trades {
+    Table,
+    ExitReason,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create strategy_snapshots table (checkpointed strategy state, resumed after restarts)
+        manager
+            .create_table(
+                Table::create()
+                    .table(StrategySnapshots::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(StrategySnapshots::Id).big_unsigned().auto_increment().primary_key())
+                    .col(ColumnDef::new(StrategySnapshots::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(StrategySnapshots::Pair).string().not_null())
+                    .col(ColumnDef::new(StrategySnapshots::StrategyType).string().not_null())
+                    .col(ColumnDef::new(StrategySnapshots::State).custom(Alias::new("LONGTEXT")).not_null()) // JSON, price buffers can exceed TEXT
+                    .col(ColumnDef::new(StrategySnapshots::UpdatedAt).timestamp().not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_strategy_snapshots_user_pair")
+                            .table(StrategySnapshots::Table)
+                            .col(StrategySnapshots::UserId)
+                            .col(StrategySnapshots::Pair)
+                            .unique()
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_strategy_snapshots_user")
+                            .from(StrategySnapshots::Table, StrategySnapshots::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StrategySnapshots::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum StrategySnapshots {
+    Table,
+    Id,
+    UserId,
+    Pair,
+    StrategyType,
+    State,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
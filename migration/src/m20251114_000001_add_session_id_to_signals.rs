@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Link signals to the live session that raised them (users can run several sessions at once)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LiveTradingSignals::Table)
+                    .add_column(ColumnDef::new(LiveTradingSignals::SessionId).big_unsigned().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_live_trading_signals_session")
+                    .table(LiveTradingSignals::Table)
+                    .col(LiveTradingSignals::SessionId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_live_trading_signals_session")
+                    .table(LiveTradingSignals::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LiveTradingSignals::Table)
+                    .drop_column(LiveTradingSignals::SessionId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LiveTradingSignals {
+    Table,
+    SessionId,
+}
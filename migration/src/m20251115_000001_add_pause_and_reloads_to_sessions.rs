@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Paused sessions keep running without emitting signals; reloads record the changed parameters
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LiveTradingSessions::Table)
+                    .add_column(ColumnDef::new(LiveTradingSessions::PausedAt).timestamp().null())
+                    .add_column(ColumnDef::new(LiveTradingSessions::ConfigChanges).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LiveTradingSessions::Table)
+                    .drop_column(LiveTradingSessions::PausedAt)
+                    .drop_column(LiveTradingSessions::ConfigChanges)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LiveTradingSessions {
+    Table,
+    PausedAt,
+    ConfigChanges,
+}
@@ -18,6 +18,14 @@ pub struct Config {
     pub gemini_model_name: String,
     pub gemini_base_url: String,
     pub gemini_timeout_secs: u64,
+    /// Replay recorded market data from this directory instead of live exchange streams
+    pub market_replay_dir: Option<String>,
+    /// Recorded time replayed per real time (60 replays a minute per second)
+    pub market_replay_speed: f64,
+    /// Record the trades and closed candles of the market streams into this directory
+    pub market_record_dir: Option<String>,
+    /// Days of recordings kept
+    pub market_record_retention_days: u32,
 }
 
 impl Config {
@@ -64,6 +72,16 @@ impl Config {
                 .unwrap_or_else(|_| "60".to_string())
                 .parse()
                 .unwrap_or(60),
+            market_replay_dir: std::env::var("MARKET_REPLAY_DIR").ok(),
+            market_replay_speed: std::env::var("MARKET_REPLAY_SPEED")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .unwrap_or(1.0),
+            market_record_dir: std::env::var("MARKET_RECORD_DIR").ok(),
+            market_record_retention_days: std::env::var("MARKET_RECORD_RETENTION_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
         })
     }
 }
@@ -21,6 +21,9 @@ pub struct Model {
     pub stopped_at: Option<DateTimeUtc>,
     pub created_at: Option<DateTimeUtc>,
     pub updated_at: Option<DateTimeUtc>,
+    pub paused_at: Option<DateTimeUtc>, // Set while signals are paused
+    #[sea_orm(column_type = "Text", nullable)]
+    pub config_changes: Option<String>, // JSON array of {"at", "changes"} applied by hot reloads
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
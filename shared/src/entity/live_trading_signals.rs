@@ -37,11 +37,13 @@ pub struct Model {
     // New fields for better signal tracking
     pub candle_timestamp: Option<DateTimeUtc>, // Timestamp of the candle that generated this signal
     #[sea_orm(column_type = "Text", nullable)]
-    pub indicator_values: Option<String>, // JSON string storing indicator values at signal time (e.g., {"rsi": 30.5, "macd": 0.02})
+    pub indicator_values: Option<String>, // JSON snapshot of the indicator values, evaluated conditions and candle at signal time
     #[sea_orm(column_type = "BigInteger", nullable)]
     pub telegram_message_id: Option<i64>, // Telegram message ID that sent this signal
     #[sea_orm(column_type = "BigUnsigned", nullable)]
     pub related_signal_id: Option<u64>, // Link to previous signal in sequence (for signal chain tracking)
+    #[sea_orm(column_type = "BigUnsigned", nullable)]
+    pub session_id: Option<u64>, // Live trading session that raised the signal
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -5,7 +5,10 @@ pub mod prelude;
 pub mod exchange_tokens;
 pub mod live_trading_signals;
 pub mod live_trading_sessions;
+pub mod position_orders;
 pub mod positions;
+pub mod protection_locks;
 pub mod strategies;
+pub mod strategy_snapshots;
 pub mod trades;
 pub mod users;
@@ -0,0 +1,45 @@
+//! `SeaORM` Entity, @generated manually
+
+use sea_orm::entity::prelude::*;
+use rust_decimal::Decimal;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "position_orders")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: u64,
+    pub position_id: u64,
+    pub user_id: i64,
+    #[sea_orm(column_type = "BigUnsigned", nullable)]
+    pub signal_id: Option<u64>,
+    pub kind: String, // "entry" or "exit"
+    #[sea_orm(column_type = "Decimal(Some((20, 8)))")]
+    pub price: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((20, 8)))")]
+    pub quantity: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((20, 8)))")]
+    pub value: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((20, 8)))")]
+    pub realized_pnl: Decimal,
+    pub created_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::positions::Entity",
+        from = "Column::PositionId",
+        to = "super::positions::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Positions,
+}
+
+impl Related<super::positions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Positions.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
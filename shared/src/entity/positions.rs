@@ -30,6 +30,9 @@ pub struct Model {
     pub unrealized_pnl: Decimal,
     #[sea_orm(column_type = "Decimal(Some((10, 4)))")]
     pub unrealized_pnl_percent: Decimal,
+    /// P&L realized by partial exits while the position is open
+    #[sea_orm(column_type = "Decimal(Some((20, 8)))")]
+    pub realized_pnl: Decimal,
     pub status: String, // "open", "closed"
     pub entry_time: Option<DateTimeUtc>,
     pub close_time: Option<DateTimeUtc>,
@@ -0,0 +1,37 @@
+//! `SeaORM` Entity, @generated manually
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "protection_locks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: u64,
+    pub user_id: i64,
+    pub pair: Option<String>, // None locks all pairs
+    pub protection: String,
+    #[sea_orm(column_type = "Text")]
+    pub reason: String,
+    pub lock_until: DateTimeUtc,
+    pub created_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Users,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -38,6 +38,7 @@ pub struct Model {
     pub exit_time: Option<DateTimeUtc>,
     #[sea_orm(column_type = "BigInteger", nullable)]
     pub duration: Option<i64>, // Duration in seconds
+    pub exit_reason: Option<String>, // "exit_signal", "stop_loss", "roi", ...
     pub created_at: Option<DateTimeUtc>,
 }
 